@@ -1,5 +1,6 @@
+use std::io;
 use hex::FromHex;
-use ser::{deserialize};
+use ser::{deserialize, Error, Reader};
 use {BlockHeader, Transaction};
 
 #[cfg(any(test, feature = "test-helpers"))]
@@ -24,6 +25,11 @@ impl Block {
 		Block { block_header: header, transactions: transactions }
 	}
 
+	/// Reads block from the stream, without buffering the whole block in memory.
+	pub fn read_from<R: io::Read>(reader: &mut Reader<R>) -> Result<Self, Error> {
+		reader.read()
+	}
+
 	/// Returns block's merkle root.
 	#[cfg(any(test, feature = "test-helpers"))]
 	pub fn merkle_root(&self) -> H256 {
@@ -47,9 +53,10 @@ impl Block {
 
 #[cfg(test)]
 mod tests {
+	use std::io;
 	use hex::FromHex;
 	use hash::H256;
-	use ser::{serialize, deserialize};
+	use ser::{serialize, deserialize, Reader};
 	use super::Block;
 
 	#[test]
@@ -85,6 +92,11 @@ mod tests {
 			let serialized = serialize(&parsed).take();
 			assert_eq!(origin_block, serialized);
 
+			// check that block is read from the stream
+			let mut reader = Reader::from_read(io::Cursor::new(&origin_block));
+			assert_eq!(Block::read_from(&mut reader).unwrap(), parsed);
+			assert!(reader.is_finished());
+
 			// check that block hash is equal to original
 			let origin_block_hash = H256::from_reversed_str(origin_block_hash);
 			assert_eq!(origin_block_hash, parsed.hash());
@@ -1,7 +1,7 @@
 use std::fmt;
 use hex::FromHex;
-use ser::{deserialize, serialize};
-use crypto::dhash256;
+use ser::deserialize;
+use hash_serializable::hash_serializable;
 use compact::Compact;
 use hash::H256;
 use primitives::bytes::Bytes;
@@ -62,7 +62,9 @@ impl From<&'static str> for BlockHeader {
 
 /// Compute hash of the block header.
 pub(crate) fn block_header_hash(block_header: &BlockHeader) -> H256 {
-	dhash256(&serialize(block_header))
+	#[cfg(test)]
+	::HASH_COMPUTATIONS.with(|counter| counter.set(counter.get() + 1));
+	hash_serializable(block_header)
 }
 
 #[cfg(test)]
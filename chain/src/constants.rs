@@ -23,3 +23,15 @@ pub const LOCKTIME_THRESHOLD: u32 = 500000000; // Tue Nov  5 00:53:20 1985 UTC
 
 /// Number of Satoshis in single coin
 pub const SATOSHIS_IN_COIN: u64 = 100_000_000;
+
+/// Maximal size of the serialized transaction (for all network upgrades)
+pub const MAX_TRANSACTION_SIZE: usize = 2_000_000;
+
+/// Maximal number of JoinSplit descriptions in the transaction (each Groth16 description is 1698 bytes long)
+pub const MAX_JOIN_SPLIT_DESCRIPTIONS: usize = MAX_TRANSACTION_SIZE / 1698;
+
+/// Maximal number of Sapling spend descriptions in the transaction (each description is 384 bytes long)
+pub const MAX_SAPLING_SPENDS: usize = MAX_TRANSACTION_SIZE / 384;
+
+/// Maximal number of Sapling output descriptions in the transaction (each description is 948 bytes long)
+pub const MAX_SAPLING_OUTPUTS: usize = MAX_TRANSACTION_SIZE / 948;
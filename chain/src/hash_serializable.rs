@@ -0,0 +1,31 @@
+use std::io;
+use crypto::{Digest, DHash256};
+use hash::H256;
+use ser::{Serializable, Stream};
+
+/// Writer adapter that feeds written data into the digest.
+struct DigestWriter<'a, D: 'a>(&'a mut D);
+
+impl<'a, D: Digest> io::Write for DigestWriter<'a, D> {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.0.input(buf);
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		Ok(())
+	}
+}
+
+/// Double SHA-256 of serialized value.
+///
+/// Serialized data is hashed as it is produced, so the whole serialization is never allocated.
+pub fn hash_serializable<T: Serializable>(t: &T) -> H256 {
+	let mut hasher = DHash256::new();
+	{
+		let mut writer = DigestWriter(&mut hasher);
+		let mut stream = Stream::with_writer(&mut writer);
+		stream.append(t);
+	}
+	hasher.finish()
+}
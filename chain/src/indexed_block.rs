@@ -1,10 +1,13 @@
 use std::cmp;
+use std::collections::HashMap;
 use hash::H256;
 use hex::FromHex;
-use ser::{Serializable, serialized_list_size, deserialize};
+use bytes::Bytes;
+use ser::{Serializable, Stream, Reader, CompactInteger, Error as ReaderError, serialized_list_size, deserialize};
 use block::Block;
-use transaction::Transaction;
-use merkle_root::merkle_root;
+use block_header::{BlockHeader, block_header_hash};
+use transaction::{Transaction, transaction_hash};
+use merkle_root::{merkle_root, merkle_root_with_mutation};
 use indexed_header::IndexedBlockHeader;
 use indexed_transaction::IndexedTransaction;
 
@@ -49,6 +52,66 @@ impl IndexedBlock {
 		&self.header.hash
 	}
 
+	/// Position of the transaction with given hash within the block.
+	pub fn transaction_position(&self, hash: &H256) -> Option<usize> {
+		self.transactions.iter().position(|tx| tx.hash == *hash)
+	}
+
+	/// Finds block transaction by its hash.
+	///
+	/// Performs linear search => use `build_hash_index` for repeated lookups.
+	pub fn transaction_by_hash(&self, hash: &H256) -> Option<&IndexedTransaction> {
+		self.transaction_position(hash).map(|position| &self.transactions[position])
+	}
+
+	/// Builds map of transaction hash => transaction position within the block.
+	pub fn build_hash_index(&self) -> HashMap<&H256, usize> {
+		self.transactions.iter()
+			.enumerate()
+			.map(|(position, tx)| (&tx.hash, position))
+			.collect()
+	}
+
+	/// Serializes block together with precomputed header and transactions hashes.
+	pub fn to_indexed_bytes(&self) -> Bytes {
+		let mut stream = Stream::new();
+		stream
+			.append(&self.header.hash)
+			.append(&self.header.raw)
+			.append(&CompactInteger::from(self.transactions.len()));
+		for tx in &self.transactions {
+			stream
+				.append(&tx.hash)
+				.append(&tx.raw);
+		}
+		stream.out()
+	}
+
+	/// Deserializes block, serialized with `to_indexed_bytes`.
+	///
+	/// Stored hashes are used as is, without rehashing block header + transactions.
+	pub fn from_indexed_bytes(bytes: &[u8]) -> Result<Self, ReaderError> {
+		let mut reader = Reader::new(bytes);
+		let header_hash: H256 = reader.read()?;
+		let header: BlockHeader = reader.read()?;
+		debug_assert_eq!(header_hash, block_header_hash(&header));
+
+		let transactions_count: usize = reader.read::<CompactInteger>()?.into();
+		let mut transactions = Vec::new();
+		for _ in 0..transactions_count {
+			let tx_hash: H256 = reader.read()?;
+			let tx: Transaction = reader.read()?;
+			debug_assert_eq!(tx_hash, transaction_hash(&tx));
+			transactions.push(IndexedTransaction::new(tx_hash, tx));
+		}
+
+		if !reader.is_finished() {
+			return Err(ReaderError::UnreadData);
+		}
+
+		Ok(Self::new(IndexedBlockHeader::new(header_hash, header), transactions))
+	}
+
 	pub fn to_raw_block(self) -> Block {
 		Block::new(self.header.raw, self.transactions.into_iter().map(|tx| tx.raw).collect())
 	}
@@ -64,6 +127,16 @@ impl IndexedBlock {
 		merkle_root(&self.transactions.iter().map(|tx| &tx.hash).collect::<Vec<&H256>>())
 	}
 
+	/// Checks if merkle root of (cached) transactions hashes matches the one in the header.
+	///
+	/// Returns (root matches, tree is mutated). Mutated tree (CVE-2012-2459) has the same root
+	/// as the tree without duplicated transactions, so the block must be rejected even if roots match.
+	pub fn merkle_root_matches(&self) -> (bool, bool) {
+		let hashes = self.transactions.iter().map(|tx| &tx.hash).collect::<Vec<&H256>>();
+		let (merkle_root, mutated) = merkle_root_with_mutation(&hashes);
+		(merkle_root == self.header.raw.merkle_root_hash, mutated)
+	}
+
 	pub fn is_final(&self, height: u32) -> bool {
 		self.transactions.iter().all(|tx| tx.raw.is_final_in_block(height, self.header.raw.time))
 	}
@@ -74,3 +147,77 @@ impl From<&'static str> for IndexedBlock {
 		deserialize(&s.from_hex::<Vec<u8>>().unwrap() as &[u8]).unwrap()
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use HASH_COMPUTATIONS;
+	use transaction::Transaction;
+	use indexed_transaction::IndexedTransaction;
+	use super::IndexedBlock;
+
+	// https://zcash.blockexplorer.com/block/00040fe8ec8471911baa1db1266ea15dd06b4a8a5c453883c000b031973dce08
+	const GENESIS: &'static str = "040000000000000000000000000000000000000000000000000000000000000000000000db4d7a85b768123f1dff1d4c4cece70083b2d27e117b4ac2e31d087988a5eac4000000000000000000000000000000000000000000000000000000000000000090041358ffff071f5712000000000000000000000000000000000000000000000000000000000000fd4005000a889f00854b8665cd555f4656f68179d31ccadc1b1f7fb0952726313b16941da348284d67add4686121d4e3d930160c1348d8191c25f12b267a6a9c131b5031cbf8af1f79c9d513076a216ec87ed045fa966e01214ed83ca02dc1797270a454720d3206ac7d931a0a680c5c5e099057592570ca9bdf6058343958b31901fce1a15a4f38fd347750912e14004c73dfe588b903b6c03166582eeaf30529b14072a7b3079e3a684601b9b3024054201f7440b0ee9eb1a7120ff43f713735494aa27b1f8bab60d7f398bca14f6abb2adbf29b04099121438a7974b078a11635b594e9170f1086140b4173822dd697894483e1c6b4e8b8dcd5cb12ca4903bc61e108871d4d915a9093c18ac9b02b6716ce1013ca2c1174e319c1a570215bc9ab5f7564765f7be20524dc3fdf8aa356fd94d445e05ab165ad8bb4a0db096c097618c81098f91443c719416d39837af6de85015dca0de89462b1d8386758b2cf8a99e00953b308032ae44c35e05eb71842922eb69797f68813b59caf266cb6c213569ae3280505421a7e3a0a37fdf8e2ea354fc5422816655394a9454bac542a9298f176e211020d63dee6852c40de02267e2fc9d5e1ff2ad9309506f02a1a71a0501b16d0d36f70cdfd8de78116c0c506ee0b8ddfdeb561acadf31746b5a9dd32c21930884397fb1682164cb565cc14e089d66635a32618f7eb05fe05082b8a3fae620571660a6b89886eac53dec109d7cbb6930ca698a168f301a950be152da1be2b9e07516995e20baceebecb5579d7cdbc16d09f3a50cb3c7dffe33f26686d4ff3f8946ee6475e98cf7b3cf9062b6966e838f865ff3de5fb064a37a21da7bb8dfd2501a29e184f207caaba364f36f2329a77515dcb710e29ffbf73e2bbd773fab1f9a6b005567affff605c132e4e4dd69f36bd201005458cfbd2c658701eb2a700251cefd886b1e674ae816d3f719bac64be649c172ba27a4fd55947d95d53ba4cbc73de97b8af5ed4840b659370c556e7376457f51e5ebb66018849923db82c1c9a819f173cccdb8f3324b239609a300018d0fb094adf5bd7cbb3834c69e6d0b3798065c525b20f040e965e1a161af78ff7561cd874f5f1b75aa0bc77f720589e1b810f831eac5073e6dd46d00a2793f70f7427f0f798f2f53a67e615e65d356e66fe40609a958a05edb4c175bcc383ea0530e67ddbe479a898943c6e3074c6fcc252d6014de3a3d292b03f0d88d312fe221be7be7e3c59d07fa0f2f4029e364f1f355c5d01fa53770d0cd76d82bf7e60f6903bc1beb772e6fde4a70be51d9c7e03c8d6d8dfb361a234ba47c470fe630820bbd920715621b9fbedb49fcee165ead0875e6c2b1af16f50b5d6140cc981122fcbcf7c5a4e3772b3661b628e08380abc545957e59f634705b1bbde2f0b4e055a5ec5676d859be77e20962b645e051a880fddb0180b4555789e1f9344a436a84dc5579e2553f1e5fb0a599c137be36cabbed0319831fea3fddf94ddc7971e4bcf02cdc93294a9aab3e3b13e3b058235b4f4ec06ba4ceaa49d675b4ba80716f3bc6976b1fbf9c8bf1f3e3a4dc1cd83ef9cf816667fb94f1e923ff63fef072e6a19321e4812f96cb0ffa864da50ad74deb76917a336f31dce03ed5f0303aad5e6a83634f9fcc371096f8288b8f02ddded5ff1bb9d49331e4a84dbe1543164438fde9ad71dab024779dcdde0b6602b5ae0a6265c14b94edd83b37403f4b78fcd2ed555b596402c28ee81d87a909c4e8722b30c71ecdd861b05f61f8b1231795c76adba2fdefa451b283a5d527955b9f3de1b9828e7b2e74123dd47062ddcc09b05e7fa13cb2212a6fdbc65d7e852cec463ec6fd929f5b8483cf3052113b13dac91b69f49d1b7d1aec01c4a68e41ce1570101000000010000000000000000000000000000000000000000000000000000000000000000ffffffff4d04ffff071f0104455a6361736830623963346565663862376363343137656535303031653335303039383462366665613335363833613763616331343161303433633432303634383335643334ffffffff010000000000000000434104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac00000000";
+
+	#[test]
+	fn indexed_block_bytes_roundtrip_preserves_hashes() {
+		let block: IndexedBlock = GENESIS.into();
+		let bytes = block.to_indexed_bytes();
+
+		HASH_COMPUTATIONS.with(|counter| counter.set(0));
+		let restored = IndexedBlock::from_indexed_bytes(&bytes).unwrap();
+		assert_eq!(restored.hash(), block.hash());
+		assert_eq!(restored.transactions[0].hash, block.transactions[0].hash);
+		assert_eq!(restored.header.raw, block.header.raw);
+
+		// hashes are only recomputed by debug assertions
+		let expected_computations = if cfg!(debug_assertions) { 1 + block.transactions.len() } else { 0 };
+		assert_eq!(HASH_COMPUTATIONS.with(|counter| counter.get()), expected_computations);
+
+		assert!(IndexedBlock::from_indexed_bytes(&bytes[..bytes.len() - 1]).is_err());
+	}
+
+	#[test]
+	fn merkle_root_matches_works() {
+		let block: IndexedBlock = GENESIS.into();
+		assert_eq!(block.merkle_root_matches(), (true, false));
+
+		let mut tampered = block.clone();
+		tampered.header.raw.merkle_root_hash = Default::default();
+		assert_eq!(tampered.merkle_root_matches(), (false, false));
+
+		let mut tampered = block.clone();
+		tampered.transactions[0].hash = Default::default();
+		assert_eq!(tampered.merkle_root_matches(), (false, false));
+
+		// duplicating the last of odd number of transactions doesn't change the root, but mutates the tree
+		let mut mutated = block;
+		let mut transaction = mutated.transactions[0].clone();
+		transaction.hash = 1.into();
+		mutated.transactions.push(transaction.clone());
+		transaction.hash = 2.into();
+		mutated.transactions.push(transaction.clone());
+		mutated.header.raw.merkle_root_hash = mutated.merkle_root();
+		assert_eq!(mutated.merkle_root_matches(), (true, false));
+		mutated.transactions.push(transaction);
+		assert_eq!(mutated.merkle_root_matches(), (true, true));
+	}
+
+	#[test]
+	fn transaction_by_hash_works() {
+		let genesis: IndexedBlock = GENESIS.into();
+		let transactions: Vec<IndexedTransaction> = (0..3)
+			.map(|lock_time| IndexedTransaction::from_raw(Transaction { lock_time: lock_time, ..Default::default() }))
+			.collect();
+		let block = IndexedBlock::new(genesis.header, transactions.clone());
+
+		for (position, tx) in transactions.iter().enumerate() {
+			assert_eq!(block.transaction_by_hash(&tx.hash), Some(tx));
+			assert_eq!(block.transaction_position(&tx.hash), Some(position));
+		}
+		assert_eq!(block.transaction_by_hash(&Default::default()), None);
+
+		let index = block.build_hash_index();
+		assert_eq!(index.len(), 3);
+		assert_eq!(index.get(&transactions[2].hash), Some(&2));
+	}
+}
@@ -1,7 +1,7 @@
 use std::{cmp, io, fmt};
 use hash::H256;
 use heapsize::HeapSizeOf;
-use ser::{Deserializable, Reader, Error as ReaderError};
+use ser::{Serializable, Deserializable, Reader, Error as ReaderError};
 use transaction::{Transaction, transaction_hash};
 use read_and_hash::ReadAndHash;
 
@@ -9,6 +9,10 @@ use read_and_hash::ReadAndHash;
 pub struct IndexedTransaction {
 	pub hash: H256,
 	pub raw: Transaction,
+	/// Cached result of `raw.is_coinbase()`, computed at construction.
+	is_coinbase: bool,
+	/// Serialized size of `raw`, known when the transaction has been deserialized.
+	size: Option<usize>,
 }
 
 impl fmt::Debug for IndexedTransaction {
@@ -37,7 +41,9 @@ impl IndexedTransaction {
 	pub fn new(hash: H256, transaction: Transaction) -> Self {
 		IndexedTransaction {
 			hash: hash,
+			is_coinbase: transaction.is_coinbase(),
 			raw: transaction,
+			size: None,
 		}
 	}
 
@@ -48,6 +54,23 @@ impl IndexedTransaction {
 		let transaction = Transaction::from(transaction);
 		Self::new(transaction_hash(&transaction), transaction)
 	}
+
+	/// Returns true if it is a coinbase transaction.
+	///
+	/// The value is computed once, when the transaction is constructed.
+	pub fn is_coinbase(&self) -> bool {
+		self.is_coinbase
+	}
+
+	/// Returns size of the serialized transaction.
+	///
+	/// The value is cached when transaction is deserialized. Otherwise the transaction size is computed.
+	pub fn serialized_size(&self) -> usize {
+		match self.size {
+			Some(size) => size,
+			None => self.raw.serialized_size(),
+		}
+	}
 }
 
 impl cmp::PartialEq for IndexedTransaction {
@@ -59,12 +82,36 @@ impl cmp::PartialEq for IndexedTransaction {
 impl Deserializable for IndexedTransaction {
 	fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, ReaderError> where T: io::Read {
 		let data = try!(reader.read_and_hash::<Transaction>());
-		// TODO: use len
-		let tx = IndexedTransaction {
-			raw: data.data,
-			hash: data.hash,
+		let mut transaction = IndexedTransaction::new(data.hash, data.data);
+		transaction.size = Some(data.size);
+		Ok(transaction)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use ser::{Serializable, serialize, deserialize};
+	use transaction::{Transaction, TransactionInput, TransactionOutput};
+	use super::IndexedTransaction;
+
+	#[test]
+	fn test_indexed_transaction_serialized_size() {
+		let raw_tx = Transaction {
+			version: 1,
+			inputs: vec![TransactionInput::coinbase(vec![1, 2, 3].into())],
+			outputs: vec![TransactionOutput { value: 50, script_pubkey: vec![0x51].into() }],
+			..Default::default()
 		};
+		let raw_size = serialize(&raw_tx).len();
+
+		let tx: IndexedTransaction = deserialize(serialize(&raw_tx).as_ref()).unwrap();
+		assert_eq!(tx.size, Some(raw_size));
+		assert_eq!(tx.size, Some(serialize(&tx.raw).len()));
+		assert_eq!(tx.serialized_size(), raw_size);
 
-		Ok(tx)
+		let tx = IndexedTransaction::from_raw(raw_tx.clone());
+		assert_eq!(tx.size, None);
+		assert_eq!(tx.serialized_size(), raw_size);
+		assert_eq!(tx.serialized_size(), raw_tx.serialized_size());
 	}
 }
@@ -3,6 +3,7 @@ use hash::{H256, H512};
 use hex::ToHex;
 use ser::{Error, Stream, Reader, CompactInteger, Serializable};
 use crypto::Groth16Proof;
+use constants::MAX_JOIN_SPLIT_DESCRIPTIONS;
 
 #[derive(Clone)]
 pub enum JoinSplitProof {
@@ -64,6 +65,24 @@ impl Serializable for JoinSplitDescription {
 		};
 		stream.append(&self.ciphertexts);
 	}
+
+	fn serialized_size(&self) -> usize {
+		let zkproof_size = match self.zkproof {
+			JoinSplitProof::PHGR(ref proof) => proof.serialized_size(),
+			JoinSplitProof::Groth(_) => 192,
+		};
+
+		self.value_pub_old.serialized_size() +
+			self.value_pub_new.serialized_size() +
+			self.anchor.serialized_size() +
+			self.nullifiers.serialized_size() +
+			self.commitments.serialized_size() +
+			self.ephemeral_key.serialized_size() +
+			self.random_seed.serialized_size() +
+			self.macs.serialized_size() +
+			zkproof_size +
+			self.ciphertexts.serialized_size()
+	}
 }
 
 impl fmt::Debug for JoinSplitDescription {
@@ -146,11 +165,31 @@ pub fn serialize_join_split(stream: &mut Stream, join_split: &Option<JoinSplit>)
 	}
 }
 
+pub fn serialized_join_split_size(join_split: &Option<JoinSplit>) -> usize {
+	let len = join_split.as_ref()
+		.map(|join_split| join_split.descriptions.len())
+		.unwrap_or_default();
+	let mut size = CompactInteger::from(len).serialized_size();
+
+	if let &Some(ref join_split) = join_split {
+		if !join_split.descriptions.is_empty() {
+			size += join_split.descriptions.iter().map(Serializable::serialized_size).sum::<usize>();
+			size += join_split.pubkey.serialized_size();
+			size += join_split.sig.serialized_size();
+		}
+	}
+
+	size
+}
+
 pub fn deserialize_join_split<T>(reader: &mut Reader<T>, use_groth: bool) -> Result<Option<JoinSplit>, Error> where T: io::Read {
 	let len: usize = reader.read::<CompactInteger>()?.into();
 	if len == 0 {
 		return Ok(None);
 	}
+	if len > MAX_JOIN_SPLIT_DESCRIPTIONS {
+		return Err(Error::InvalidFormat(format!("Too many JoinSplit descriptions: {}", len)));
+	}
 
 	let descriptions = (0..len)
 		.map(|_| deserialize_join_split_description(reader, use_groth))
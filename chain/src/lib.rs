@@ -10,6 +10,7 @@ pub mod constants;
 
 mod block;
 mod block_header;
+mod hash_serializable;
 mod solution;
 mod join_split;
 mod merkle_root;
@@ -29,11 +30,13 @@ pub use transaction::{OVERWINTER_TX_VERSION_GROUP_ID, SAPLING_TX_VERSION_GROUP_I
 
 pub use block::Block;
 pub use block_header::BlockHeader;
+pub use hash_serializable::hash_serializable;
 pub use solution::EquihashSolution;
 pub use join_split::{JoinSplit, JoinSplitDescription, JoinSplitProof};
-pub use merkle_root::{merkle_root, merkle_node_hash};
+pub use merkle_root::{merkle_root, merkle_root_with_mutation, merkle_node_hash, merkle_branch, verify_merkle_branch};
 pub use sapling::{Sapling, SaplingSpendDescription, SaplingOutputDescription};
-pub use transaction::{Transaction, TransactionInput, TransactionOutput, OutPoint};
+pub use transaction::{Transaction, TransactionInput, TransactionOutput, OutPoint, ShieldedBalance, ShieldedBalanceError,
+	RelativeLocktime};
 
 pub use read_and_hash::{ReadAndHash, HashedData};
 pub use indexed_block::IndexedBlock;
@@ -41,3 +44,9 @@ pub use indexed_header::IndexedBlockHeader;
 pub use indexed_transaction::IndexedTransaction;
 
 pub type ShortTransactionID = hash::H48;
+
+#[cfg(test)]
+thread_local! {
+	/// Number of header and transaction hashes computed from raw data by this thread.
+	static HASH_COMPUTATIONS: ::std::cell::Cell<usize> = ::std::cell::Cell::new(0);
+}
@@ -32,15 +32,86 @@ pub fn merkle_root<T>(hashes: &[T]) -> H256 where T: AsRef<H256> {
 	merkle_root(&row)
 }
 
+/// Calculates the root of the merkle tree and checks if the tree has been mutated (CVE-2012-2459).
+///
+/// Tree is mutated when any of its rows contains two equal nodes that are hashed together. Such tree
+/// has the same root as the tree where the duplicated subtree is only present once.
+pub fn merkle_root_with_mutation<T>(hashes: &[T]) -> (H256, bool) where T: AsRef<H256> {
+	if hashes.len() == 1 {
+		return (hashes[0].as_ref().clone(), false);
+	}
+
+	let mut mutated = false;
+	let mut row = Vec::with_capacity((hashes.len() + 1) / 2);
+	let mut i = 0;
+	while i + 1 < hashes.len() {
+		mutated |= hashes[i].as_ref() == hashes[i + 1].as_ref();
+		row.push(merkle_node_hash(&hashes[i], &hashes[i + 1]));
+		i += 2
+	}
+
+	// duplicate the last element if len is not even, which is not a mutation
+	if hashes.len() % 2 == 1 {
+		let last = &hashes[hashes.len() - 1];
+		row.push(merkle_node_hash(last, last));
+	}
+
+	let (root, row_mutated) = merkle_root_with_mutation(&row);
+	(root, mutated || row_mutated)
+}
+
 /// Calculate merkle tree node hash
 pub fn merkle_node_hash<T>(left: T, right: T) -> H256 where T: AsRef<H256> {
 	dhash256(&*concat(left, right))
 }
 
+/// Calculates merkle branch (siblings of all nodes on the path from the leaf to the root)
+/// of the leaf with given index.
+///
+/// Returns None if there's no leaf with given index.
+pub fn merkle_branch<T>(hashes: &[T], index: usize) -> Option<Vec<H256>> where T: AsRef<H256> {
+	if index >= hashes.len() {
+		return None;
+	}
+
+	let mut branch = Vec::new();
+	let mut row: Vec<H256> = hashes.iter().map(|h| h.as_ref().clone()).collect();
+	let mut index = index;
+	while row.len() > 1 {
+		// the last element is its own sibling if len is not even
+		let sibling = if index ^ 1 < row.len() { index ^ 1 } else { index };
+		branch.push(row[sibling].clone());
+
+		row = row.chunks(2)
+			.map(|pair| merkle_node_hash(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+			.collect();
+		index /= 2;
+	}
+
+	Some(branch)
+}
+
+/// Checks that the merkle branch of the leaf with given index leads to the root.
+pub fn verify_merkle_branch(leaf: &H256, branch: &[H256], index: usize, root: &H256) -> bool {
+	let mut hash = leaf.clone();
+	let mut index = index;
+	for sibling in branch {
+		hash = if index & 1 == 0 {
+			merkle_node_hash(&hash, sibling)
+		} else {
+			merkle_node_hash(sibling, &hash)
+		};
+		index /= 2;
+	}
+
+	index == 0 && hash == *root
+}
+
 #[cfg(test)]
 mod tests {
-	use hash::H256;
-	use super::merkle_root;
+	use crypto::dhash256;
+	use hash::{H256, H512};
+	use super::{merkle_root, merkle_root_with_mutation, merkle_node_hash, merkle_branch, verify_merkle_branch};
 
 	// block 80_000
 	// https://blockchain.info/block/000000000043a8c0fd1d6f726790caa2a406010d19efd2780db27bdbbd93baf6
@@ -55,4 +126,67 @@ mod tests {
 		assert_eq!(result, expected);
 		assert_eq!(result2, expected);
 	}
+
+	#[test]
+	fn test_merkle_root_with_mutation() {
+		let h = |n: u8| H256::from(n);
+		let is_mutated = |hashes: &[H256]| {
+			let (root, mutated) = merkle_root_with_mutation(hashes);
+			assert_eq!(root, merkle_root(hashes));
+			mutated
+		};
+
+		// odd rows are padded by hashing last node with itself, which is not a mutation
+		assert!(!is_mutated(&[h(1)]));
+		assert!(!is_mutated(&[h(1), h(2), h(3)]));
+		assert!(!is_mutated(&[h(1), h(2), h(3), h(4), h(5)]));
+
+		// duplicated last transaction
+		assert_eq!(merkle_root(&[h(1), h(2), h(3)]), merkle_root(&[h(1), h(2), h(3), h(3)]));
+		assert!(is_mutated(&[h(1), h(2), h(3), h(3)]));
+
+		// duplicated subtree
+		assert_eq!(merkle_root(&[h(1), h(2), h(3), h(4), h(5), h(6)]),
+			merkle_root(&[h(1), h(2), h(3), h(4), h(5), h(6), h(5), h(6)]));
+		assert!(is_mutated(&[h(1), h(2), h(3), h(4), h(5), h(6), h(5), h(6)]));
+	}
+
+	#[test]
+	fn test_merkle_node_hash_is_ordered_double_sha256() {
+		let left = H256::from(1);
+		let right = H256::from(2);
+
+		let mut concatenated = H512::default();
+		concatenated[0..32].copy_from_slice(&*left);
+		concatenated[32..64].copy_from_slice(&*right);
+
+		assert_eq!(merkle_node_hash(&left, &right), dhash256(&*concatenated));
+		assert!(merkle_node_hash(&left, &right) != merkle_node_hash(&right, &left));
+	}
+
+	#[test]
+	fn test_merkle_branch() {
+		let h = |n: u8| H256::from(n);
+
+		for leaves in &[vec![h(1), h(2), h(3), h(4)], vec![h(1), h(2), h(3), h(4), h(5)]] {
+			let root = merkle_root(leaves);
+			for (index, leaf) in leaves.iter().enumerate() {
+				let branch = merkle_branch(leaves, index).unwrap();
+				assert_eq!(branch.len(), if leaves.len() == 4 { 2 } else { 3 });
+				assert!(verify_merkle_branch(leaf, &branch, index, &root));
+				// wrong leaf or wrong index (unless the leaf is paired with itself)
+				if index ^ 1 < leaves.len() {
+					assert!(!verify_merkle_branch(leaf, &branch, index ^ 1, &root));
+				}
+				assert!(!verify_merkle_branch(&h(0), &branch, index, &root));
+			}
+		}
+
+		// the last (odd) leaf is paired with itself
+		let leaves = [h(1), h(2), h(3), h(4), h(5)];
+		assert_eq!(merkle_branch(&leaves, 4).unwrap()[0], h(5));
+
+		assert_eq!(merkle_branch(&leaves, 5), None);
+		assert_eq!(merkle_branch::<H256>(&[], 0), None);
+	}
 }
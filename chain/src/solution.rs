@@ -1,12 +1,17 @@
 use std::{fmt, io};
 use hex::ToHex;
-use ser::{Error, Serializable, Deserializable, Stream, Reader};
+use ser::{Error, Serializable, Deserializable, Stream, Reader, CompactInteger};
 
-/// Equihash solution size.
+/// Equihash solution size for on-chain (N = 200, K = 9) parameters.
+///
+/// This is also the maximal solution size for all supported parameters.
 pub const SOLUTION_SIZE: usize = 1344;
 
+/// Equihash solution.
+///
+/// The length of solution depends on Equihash parameters and is checked during header verification.
 #[derive(Clone)]
-pub struct EquihashSolution([u8; SOLUTION_SIZE]);
+pub struct EquihashSolution(Vec<u8>);
 
 impl AsRef<[u8]> for EquihashSolution {
 	fn as_ref(&self) -> &[u8] {
@@ -14,15 +19,21 @@ impl AsRef<[u8]> for EquihashSolution {
 	}
 }
 
+impl From<Vec<u8>> for EquihashSolution {
+	fn from(solution: Vec<u8>) -> Self {
+		EquihashSolution(solution)
+	}
+}
+
 impl Default for EquihashSolution {
 	fn default() -> Self {
-		EquihashSolution([0; SOLUTION_SIZE])
+		EquihashSolution(vec![0; SOLUTION_SIZE])
 	}
 }
 
 impl PartialEq<EquihashSolution> for EquihashSolution {
 	fn eq(&self, other: &EquihashSolution) -> bool {
-		self.0.as_ref() == other.0.as_ref()
+		self.0 == other.0
 	}
 }
 
@@ -36,13 +47,63 @@ impl Serializable for EquihashSolution {
 	fn serialize(&self, stream: &mut Stream) {
 		stream.append_list(&self.0);
 	}
+
+	fn serialized_size(&self) -> usize {
+		CompactInteger::from(self.0.len()).serialized_size() + self.0.len()
+	}
 }
 
 impl Deserializable for EquihashSolution {
 	fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, Error> where Self: Sized, T: io::Read {
-		let v = reader.read_list_exact(SOLUTION_SIZE)?;
-		let mut sol = [0; SOLUTION_SIZE];
-		sol.copy_from_slice(&v);
-		Ok(EquihashSolution(sol))
+		// check length before allocating anything
+		let len: usize = reader.read::<CompactInteger>()?.into();
+		if len > SOLUTION_SIZE {
+			return Err(Error::InvalidFormat(format!("Equihash solution is too large: {} bytes, maximum is {} bytes",
+				len, SOLUTION_SIZE)));
+		}
+
+		let mut solution = vec![0; len];
+		reader.read_slice(&mut solution)?;
+		Ok(EquihashSolution(solution))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use ser::{Error, Stream, CompactInteger, deserialize, serialize};
+	use super::{EquihashSolution, SOLUTION_SIZE};
+
+	#[test]
+	fn equihash_solution_roundtrip() {
+		let solution = EquihashSolution::from(vec![7; 400]);
+		let serialized = serialize(&solution);
+		assert_eq!(deserialize::<_, EquihashSolution>(&*serialized as &[u8]).unwrap(), solution);
+
+		let solution = EquihashSolution::default();
+		let serialized = serialize(&solution);
+		assert_eq!(deserialize::<_, EquihashSolution>(&*serialized as &[u8]).unwrap(), solution);
+	}
+
+	#[test]
+	fn equihash_solution_with_absurd_length_prefix() {
+		let mut stream = Stream::default();
+		stream.append(&CompactInteger::from(::std::u64::MAX));
+		stream.append_slice(&[0; 16]);
+		let serialized = stream.out();
+
+		match deserialize::<_, EquihashSolution>(&*serialized as &[u8]) {
+			Err(Error::InvalidFormat(_)) => (),
+			result => panic!("unexpected result: {:?}", result),
+		}
+
+		let mut stream = Stream::default();
+		stream.append(&CompactInteger::from(SOLUTION_SIZE + 1));
+		stream.append_slice(&[0; SOLUTION_SIZE + 1]);
+		let serialized = stream.out();
+
+		match deserialize::<_, EquihashSolution>(&*serialized as &[u8]) {
+			Err(Error::InvalidFormat(_)) => (),
+			result => panic!("unexpected result: {:?}", result),
+		}
 	}
 }
@@ -2,16 +2,18 @@
 //! https://en.bitcoin.it/wiki/Protocol_documentation#tx
 
 use std::io;
+use std::cmp::Ordering;
 use heapsize::HeapSizeOf;
 use hex::FromHex;
 use bytes::Bytes;
-use ser::{deserialize, serialize};
-use crypto::dhash256;
+use ser::deserialize;
+use hash_serializable::hash_serializable;
 use hash::H256;
-use constants::{SEQUENCE_FINAL, LOCKTIME_THRESHOLD};
-use join_split::{JoinSplit, deserialize_join_split, serialize_join_split};
-use sapling::Sapling;
-use ser::{Error, Serializable, Deserializable, Stream, Reader};
+use constants::{SEQUENCE_FINAL, SEQUENCE_LOCKTIME_DISABLE_FLAG, SEQUENCE_LOCKTIME_TYPE_FLAG, SEQUENCE_LOCKTIME_MASK,
+	LOCKTIME_THRESHOLD, MAX_SAPLING_SPENDS, MAX_SAPLING_OUTPUTS};
+use join_split::{JoinSplit, deserialize_join_split, serialize_join_split, serialized_join_split_size};
+use sapling::{Sapling, SaplingSpendDescription, SaplingOutputDescription};
+use ser::{Error, Serializable, Deserializable, Stream, Reader, serialized_list_size};
 
 /// Original bitcoin transaction version.
 pub const BTC_TX_VERSION: i32 = 1;
@@ -27,12 +29,47 @@ pub const OVERWINTER_TX_VERSION_GROUP_ID: u32 = 0x03C48270;
 /// Sapling version group id.
 pub const SAPLING_TX_VERSION_GROUP_ID: u32 = 0x892F2085;
 
+/// Balance of the transaction shielded value pools.
+#[derive(Debug, PartialEq, Default, Clone, Copy)]
+pub struct ShieldedBalance {
+	/// Sum of `value_pub_old` of all join split descriptions (value that enters the sprout pool).
+	pub sprout_in: u64,
+	/// Sum of `value_pub_new` of all join split descriptions (value that leaves the sprout pool).
+	pub sprout_out: u64,
+	/// Sapling balancing value (positive value leaves the sapling pool).
+	pub sapling_net: i64,
+}
+
+/// Shielded balance computation error.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ShieldedBalanceError {
+	/// Sum of join split `value_pub_old` overflows.
+	SproutInOverflow,
+	/// Sum of join split `value_pub_new` overflows.
+	SproutOutOverflow,
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Default, Serializable, Deserializable, Hash)]
 pub struct OutPoint {
 	pub hash: H256,
 	pub index: u32,
 }
 
+/// Outpoints are ordered by hash, then by index. Hashes are compared in their
+/// displayed (reversed) byte order, as BIP69 requires.
+impl Ord for OutPoint {
+	fn cmp(&self, other: &Self) -> Ordering {
+		self.hash.reversed().cmp(&other.hash.reversed())
+			.then_with(|| self.index.cmp(&other.index))
+	}
+}
+
+impl PartialOrd for OutPoint {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
 impl OutPoint {
 	pub fn null() -> Self {
 		OutPoint {
@@ -46,6 +83,16 @@ impl OutPoint {
 	}
 }
 
+/// Relative lock-time of the transaction input (BIP68).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum RelativeLocktime {
+	/// Input can't be included until given number of blocks are mined on top of the prevout block.
+	Blocks(u32),
+	/// Input can't be included until given number of seconds (multiple of 512) has passed since the
+	/// median time past of the block, preceding the prevout block.
+	Seconds(u32),
+}
+
 #[derive(Debug, PartialEq, Default, Clone)]
 pub struct TransactionInput {
 	pub previous_output: OutPoint,
@@ -65,6 +112,28 @@ impl TransactionInput {
 	pub fn is_final(&self) -> bool {
 		self.sequence == SEQUENCE_FINAL
 	}
+
+	/// Decodes relative lock-time from the sequence field.
+	///
+	/// Returns None if relative lock-time is disabled for this input. Note that sequence
+	/// isn't interpreted as relative lock-time in transactions with version < 2.
+	pub fn relative_locktime(&self) -> Option<RelativeLocktime> {
+		if self.sequence & SEQUENCE_LOCKTIME_DISABLE_FLAG != 0 {
+			return None;
+		}
+
+		let value = self.sequence & SEQUENCE_LOCKTIME_MASK;
+		if self.sequence & SEQUENCE_LOCKTIME_TYPE_FLAG != 0 {
+			Some(RelativeLocktime::Seconds(value << 9))
+		} else {
+			Some(RelativeLocktime::Blocks(value))
+		}
+	}
+
+	/// Returns true if input spends null outpoint, like coinbase input does.
+	pub fn is_coinbase_like(&self) -> bool {
+		self.previous_output.is_null()
+	}
 }
 
 impl HeapSizeOf for TransactionInput {
@@ -88,6 +157,16 @@ impl Default for TransactionOutput {
 	}
 }
 
+impl TransactionOutput {
+	/// Returns true if output is provably unspendable, i.e. its script starts with OP_RETURN.
+	pub fn is_unspendable(&self) -> bool {
+		// OP_RETURN opcode (chain doesn't depend on script crate)
+		const OP_RETURN: u8 = 0x6a;
+
+		self.script_pubkey.first() == Some(&OP_RETURN)
+	}
+}
+
 impl HeapSizeOf for TransactionOutput {
 	fn heap_size_of_children(&self) -> usize {
 		self.script_pubkey.heap_size_of_children()
@@ -129,6 +208,36 @@ impl Transaction {
 		version
 	}
 
+	/// Returns true if this is Overwinter (v3) transaction.
+	pub fn is_overwinter(&self) -> bool {
+		is_overwinter_version(self.overwintered, self.version, self.version_group_id)
+	}
+
+	/// Returns true if this is Sapling (v4) transaction.
+	pub fn is_sapling(&self) -> bool {
+		is_sapling_version(self.overwintered, self.version, self.version_group_id)
+	}
+
+	/// Returns true if this is overwintered transaction with Overwinter version group id.
+	pub fn has_overwinter_version_group(&self) -> bool {
+		self.overwintered && self.version_group_id == OVERWINTER_TX_VERSION_GROUP_ID
+	}
+
+	/// Returns true if this is overwintered transaction with Sapling version group id.
+	pub fn has_sapling_version_group(&self) -> bool {
+		self.overwintered && self.version_group_id == SAPLING_TX_VERSION_GROUP_ID
+	}
+
+	/// Returns true if transaction has JoinSplit or Sapling descriptions.
+	pub fn is_shielded(&self) -> bool {
+		self.join_split.is_some() || self.sapling.is_some()
+	}
+
+	/// Reads transaction from the stream, without buffering the whole transaction in memory.
+	pub fn deserialize_from<R: io::Read>(reader: &mut Reader<R>) -> Result<Self, Error> {
+		reader.read()
+	}
+
 	#[cfg(any(test, feature = "test-helpers"))]
 	pub fn hash(&self) -> H256 {
 		transaction_hash(self)
@@ -147,11 +256,45 @@ impl Transaction {
 	}
 
 	pub fn is_null(&self) -> bool {
-		self.inputs.iter().any(|input| input.previous_output.is_null())
+		self.inputs.iter().any(TransactionInput::is_coinbase_like)
 	}
 
 	pub fn is_coinbase(&self) -> bool {
-		self.inputs.len() == 1 && self.inputs[0].previous_output.is_null()
+		self.inputs.len() == 1 && self.inputs[0].is_coinbase_like()
+	}
+
+	/// Previous outputs spent by this transaction. Null (coinbase) outpoints are skipped.
+	pub fn spent_outpoints(&self) -> impl Iterator<Item=&OutPoint> {
+		self.inputs.iter()
+			.map(|input| &input.previous_output)
+			.filter(|outpoint| !outpoint.is_null())
+	}
+
+	/// Outputs created by this transaction, with `txid` being the hash of this transaction.
+	pub fn created_outpoints(&self, txid: &H256) -> impl Iterator<Item=(OutPoint, &TransactionOutput)> {
+		let txid = txid.clone();
+		self.outputs.iter()
+			.enumerate()
+			.map(move |(index, output)| (OutPoint {
+				hash: txid.clone(),
+				index: index as u32,
+			}, output))
+	}
+
+	/// Returns true if inputs are sorted by previous outpoints and outputs are sorted by
+	/// (value, script_pubkey), as BIP69 suggests. This is a policy (not consensus) rule.
+	pub fn is_bip69_sorted(&self) -> bool {
+		let inputs_sorted = self.inputs.windows(2)
+			.all(|pair| pair[0].previous_output <= pair[1].previous_output);
+		let outputs_sorted = self.outputs.windows(2)
+			.all(|pair| bip69_output_key(&pair[0]) <= bip69_output_key(&pair[1]));
+		inputs_sorted && outputs_sorted
+	}
+
+	/// Sorts inputs and outputs in BIP69 order. Invalidates signatures of signed inputs.
+	pub fn bip69_sort(&mut self) {
+		self.inputs.sort_by(|a, b| a.previous_output.cmp(&b.previous_output));
+		self.outputs.sort_by(|a, b| bip69_output_key(a).cmp(&bip69_output_key(b)));
 	}
 
 	pub fn is_final(&self) -> bool {
@@ -182,15 +325,34 @@ impl Transaction {
 		self.inputs.iter().all(TransactionInput::is_final)
 	}
 
+	/// Sum of all outputs values. Saturates to `u64::max_value()` on overflow.
 	pub fn total_spends(&self) -> u64 {
-		let mut result = 0u64;
-		for output in self.outputs.iter() {
-			if u64::max_value() - result < output.value {
-				return u64::max_value();
+		self.checked_total_spends().unwrap_or(u64::max_value())
+	}
+
+	/// Sum of all outputs values. Returns None on overflow.
+	pub fn checked_total_spends(&self) -> Option<u64> {
+		self.outputs.iter()
+			.try_fold(0u64, |total, output| total.checked_add(output.value))
+	}
+
+	/// Summarizes values that are moved to/from shielded pools by this transaction.
+	pub fn shielded_value_balance(&self) -> Result<ShieldedBalance, ShieldedBalanceError> {
+		let mut balance = ShieldedBalance::default();
+		if let Some(ref join_split) = self.join_split {
+			for desc in &join_split.descriptions {
+				balance.sprout_in = balance.sprout_in.checked_add(desc.value_pub_old)
+					.ok_or(ShieldedBalanceError::SproutInOverflow)?;
+				balance.sprout_out = balance.sprout_out.checked_add(desc.value_pub_new)
+					.ok_or(ShieldedBalanceError::SproutOutOverflow)?;
 			}
-			result += output.value;
 		}
-		result
+
+		if let Some(ref sapling) = self.sapling {
+			balance.sapling_net = sapling.balancing_value;
+		}
+
+		Ok(balance)
 	}
 }
 
@@ -201,6 +363,12 @@ impl Serializable for TransactionInput {
 			.append(&self.script_sig)
 			.append(&self.sequence);
 	}
+
+	fn serialized_size(&self) -> usize {
+		self.previous_output.serialized_size() +
+			self.script_sig.serialized_size() +
+			self.sequence.serialized_size()
+	}
 }
 
 impl Deserializable for TransactionInput {
@@ -244,6 +412,39 @@ impl Serializable for Transaction {
 			}
 		}
 	}
+
+	fn serialized_size(&self) -> usize {
+		let mut size = self.serialized_version().serialized_size();
+		if self.overwintered {
+			size += self.version_group_id.serialized_size();
+		}
+
+		size += serialized_list_size::<TransactionInput, TransactionInput>(&self.inputs);
+		size += serialized_list_size::<TransactionOutput, TransactionOutput>(&self.outputs);
+		size += self.lock_time.serialized_size();
+
+		if self.overwintered {
+			size += self.expiry_height.serialized_size();
+		}
+
+		if let Some(sapling) = self.sapling.as_ref() {
+			size += sapling.balancing_value.serialized_size();
+			size += serialized_list_size::<SaplingSpendDescription, SaplingSpendDescription>(&sapling.spends);
+			size += serialized_list_size::<SaplingOutputDescription, SaplingOutputDescription>(&sapling.outputs);
+		}
+
+		if self.version >= SPROUT_TX_VERSION {
+			size += serialized_join_split_size(&self.join_split);
+		}
+
+		if let Some(sapling) = self.sapling.as_ref() {
+			if !sapling.spends.is_empty() || !sapling.outputs.is_empty() {
+				size += sapling.binding_sig.serialized_size();
+			}
+		}
+
+		size
+	}
 }
 
 impl Deserializable for Transaction {
@@ -270,9 +471,14 @@ impl Deserializable for Transaction {
 			0
 		};
 
+		// distinguish transactions from the future from malformed transactions
+		if overwintered && version > SAPLING_TX_VERSION {
+			return Err(Error::UnsupportedTransactionVersion(version));
+		}
+
 		// reject overwintered transactions of unknown versions
-		let is_overwinter_tx = overwintered && version == OVERWINTER_TX_VERSION && version_group_id == OVERWINTER_TX_VERSION_GROUP_ID;
-		let is_sapling_tx = overwintered && version == SAPLING_TX_VERSION && version_group_id == SAPLING_TX_VERSION_GROUP_ID;
+		let is_overwinter_tx = is_overwinter_version(overwintered, version, version_group_id);
+		let is_sapling_tx = is_sapling_version(overwintered, version, version_group_id);
 		if overwintered && !is_overwinter_tx && !is_sapling_tx {
 			return Err(Error::InvalidFormat(format!("Invalid overwinter transaction version: {}, version group: {}",
 				version, version_group_id)));
@@ -290,8 +496,8 @@ impl Deserializable for Transaction {
 
 		let mut sapling = if is_sapling_tx {
 			let balancing_value = reader.read()?;
-			let spends = reader.read_list()?;
-			let outputs = reader.read_list()?;
+			let spends = reader.read_list_max_named(MAX_SAPLING_SPENDS, "Sapling spends")?;
+			let outputs = reader.read_list_max_named(MAX_SAPLING_OUTPUTS, "Sapling outputs")?;
 			Some(Sapling {
 				balancing_value,
 				spends,
@@ -329,16 +535,71 @@ impl Deserializable for Transaction {
 	}
 }
 
+fn is_overwinter_version(overwintered: bool, version: i32, version_group_id: u32) -> bool {
+	overwintered && version == OVERWINTER_TX_VERSION && version_group_id == OVERWINTER_TX_VERSION_GROUP_ID
+}
+
+fn is_sapling_version(overwintered: bool, version: i32, version_group_id: u32) -> bool {
+	overwintered && version == SAPLING_TX_VERSION && version_group_id == SAPLING_TX_VERSION_GROUP_ID
+}
+
+fn bip69_output_key(output: &TransactionOutput) -> (u64, &[u8]) {
+	(output.value, output.script_pubkey.as_ref())
+}
+
 pub(crate) fn transaction_hash(transaction: &Transaction) -> H256 {
-	dhash256(&serialize(transaction))
+	#[cfg(test)]
+	::HASH_COMPUTATIONS.with(|counter| counter.set(counter.get() + 1));
+	hash_serializable(transaction)
 }
 
 #[cfg(test)]
 mod tests {
-	use hex::ToHex;
+	use std::io;
+	use hex::{FromHex, ToHex};
 	use hash::H256;
-	use ser::{Serializable, serialize};
-	use super::Transaction;
+	use ser::{Serializable, Stream, Reader, Error as ReaderError, CompactInteger, List, serialize, serialize_with_capacity,
+		serialize_list, deserialize, deserialize_at, deserialize_iterator};
+	use crypto::dhash256;
+	use hash_serializable::hash_serializable;
+	use join_split::{JoinSplit, JoinSplitDescription};
+	use super::{Transaction, TransactionInput, TransactionOutput, OutPoint, ShieldedBalance, ShieldedBalanceError,
+		RelativeLocktime};
+
+	#[test]
+	fn test_optional_transaction_output_roundtrip() {
+		let output = Some(TransactionOutput {
+			value: 10,
+			script_pubkey: "76a914".into(),
+		});
+
+		let mut stream = Stream::default();
+		stream.append_option(&output).append_option(&None::<TransactionOutput>);
+		let buffer = stream.out();
+		assert_eq!(buffer.len(), 1 + output.as_ref().unwrap().serialized_size() + 1);
+
+		let mut reader = Reader::new(&buffer);
+		assert_eq!(reader.read_option::<TransactionOutput>().unwrap(), output);
+		assert_eq!(reader.read_option::<TransactionOutput>().unwrap(), None);
+		assert!(reader.is_finished());
+	}
+
+	fn assert_serialized_with_capacity(t: &Transaction) {
+		let serialized = serialize_with_capacity(t);
+		assert_eq!(serialized, serialize(t));
+
+		let buffer = serialized.take();
+		assert_eq!(buffer.len(), t.serialized_size());
+		assert_eq!(buffer.capacity(), t.serialized_size());
+	}
+
+	fn deserialize_from_stream(hex: &str) -> Transaction {
+		let bytes = hex.from_hex::<Vec<u8>>().unwrap();
+		let mut reader = Reader::from_read(io::Cursor::new(bytes));
+		let t = Transaction::deserialize_from(&mut reader).unwrap();
+		assert!(reader.is_finished());
+		t
+	}
 
 	// real transaction from Zcash block 30003
 	// https://zcash.blockexplorer.com/api/rawtx/54c8acf69271dad83e9faa34284cda725caa5bea7378db92acf35becd0989463
@@ -355,6 +616,7 @@ mod tests {
 		assert_eq!(t.expiry_height, 0);
 		assert_eq!(t.inputs.len(), 3);
 		assert_eq!(t.outputs.len(), 2);
+		assert_eq!(t.shielded_value_balance(), Ok(ShieldedBalance::default()));
 		let tx_input = &t.inputs[0];
 		assert_eq!(tx_input.sequence, 4294967294);
 		assert_eq!(tx_input.script_sig, "47304402201380ad195adf528b05e6c78322434d40b0cd08f676611bf86733179c2851229102202f7ebeceffead9fe62e36126d1f15acf8c577558fff43a09aa7373c367465e7c012102ec25f8fb5efcac5b6424fd16faafdb0c24b71d7b21695dc020e1665c98da74d4".into());
@@ -372,6 +634,12 @@ mod tests {
 		assert_eq!(tx_output.script_pubkey, "76a9148061115677d41cd5661b86a6f9c288fbeb9d8e1f88ac".into());
 		assert!(t.join_split.is_none());
 		assert!(t.sapling.is_none());
+		assert!(!t.is_overwinter());
+		assert!(!t.is_sapling());
+		assert!(!t.is_shielded());
+
+		// serialize with pre-allocated buffer && check tx
+		assert_serialized_with_capacity(&t);
 
 		// serialize && check tx
 		let t: String = serialize(&t).to_hex();
@@ -395,6 +663,19 @@ mod tests {
 		assert_eq!(t.outputs.len(), 0);
 		assert!(t.join_split.is_some());
 		assert!(t.sapling.is_none());
+		assert!(!t.is_overwinter());
+		assert!(!t.is_sapling());
+		assert!(t.is_shielded());
+		assert_eq!(t.shielded_value_balance(), Ok(ShieldedBalance { sprout_in: 14250000, sprout_out: 0, sapling_net: 0 }));
+
+		// deserialize from stream && check tx
+		assert_eq!(deserialize_from_stream(hex), t);
+
+		// serialize with pre-allocated buffer && check tx
+		assert_serialized_with_capacity(&t);
+
+		// hash without intermediate serialization && check hash
+		assert_eq!(hash_serializable(&t), dhash256(&serialize(&t)));
 
 		// serialize && check tx
 		let t: String = serialize(&t).to_hex();
@@ -418,6 +699,20 @@ mod tests {
 		assert_eq!(t.outputs.len(), 2);
 		assert!(t.join_split.is_some());
 		assert!(t.sapling.is_some());
+		assert!(!t.is_overwinter());
+		assert!(t.is_sapling());
+		assert!(t.is_shielded());
+		assert!(!t.has_overwinter_version_group());
+		assert!(t.has_sapling_version_group());
+
+		// deserialize from stream && check tx
+		assert_eq!(deserialize_from_stream(hex), t);
+
+		// serialize with pre-allocated buffer && check tx
+		assert_serialized_with_capacity(&t);
+
+		// hash without intermediate serialization && check hash
+		assert_eq!(hash_serializable(&t), dhash256(&serialize(&t)));
 
 		// serialize && check tx
 		let t: String = serialize(&t).to_hex();
@@ -436,6 +731,12 @@ mod tests {
 		assert!(t.sapling.is_some());
 		assert_eq!(t.sapling.as_ref().unwrap().spends.len(), 1);
 		assert_eq!(t.sapling.as_ref().unwrap().outputs.len(), 1);
+		assert!(t.is_sapling());
+		assert!(t.is_shielded());
+		assert_eq!(t.shielded_value_balance(), Ok(ShieldedBalance { sprout_in: 0, sprout_out: 0, sapling_net: 10000 }));
+
+		// serialize with pre-allocated buffer && check tx
+		assert_serialized_with_capacity(&t);
 	}
 
 	#[test]
@@ -451,4 +752,293 @@ mod tests {
 		let tx: Transaction = raw_tx.into();
 		assert_eq!(tx.serialized_size(), raw_tx.len() / 2);
 	}
+
+	#[test]
+	fn test_transaction_deserialize_at() {
+		let t1: Transaction = "0100000001a6b97044d03da79c005b20ea9c0e1a6d9dc12d9f7b91a5911c9030a439eed8f5000000004948304502206e21798a42fae0e854281abd38bacd1aeed3ee3738d9e1446618c4571d1090db022100e2ac980643b0b82c0e88ffdfec6b64e3e6ba35e7ba5fdd7d5d6cc8d25c6b241501ffffffff0100f2052a010000001976a914404371705fa9bd789a2fcd52d2c580b65d35549d88ac00000000".into();
+		let t2: Transaction = "0400008085202f8900000000000072da060010270000000000000148b1c0668fce604361fbb1b89bbd76f8fee09b51a9dc0fdfcf6c6720cd596083d970234fcc0e9a70fdfed82d32fbb9ca92c9c5c3bad5daad9ac62b5bf4255817ee5bc95a9af453bb9cc7e2c544aa29efa20011a65b624998369c849aa8f0bc83d60e7902a3cfe6eeaeb8d583a491de5982c5ded29e64cd8f8fac594a5bb4f2838e6c30876e36a18d8d935238815c8d9205a4f1f523ff76b51f614bff1064d1c5fa0a27ec0c43c8a6c2714e7234d32e9a8934a3e9c0f74f1fdac2ddf6be3b13bc933b0478cae556a2d387cc23b05e8b0bd53d9e838ad2d2cb31daccefe256087511b044dfae665f0af0fa968edeea4cbb437a8099724159471adf7946eec434cccc1129f4d1e31d7f3f8be524226c65f28897d3604c14efb64bea6a889b2705617432927229dfa382e78c0ace31cc158fbf3ec1597242955e45af1ee5cfaffd789cc80dc53d6b18d42033ec2c327170e2811fe8ec00feadeb1033eb48ab24a6dce2480ad428be57c4619466fc3181ece69b914fed30566ff853250ef19ef7370601f4c24b0125e4059eec61f63ccbe277363172f2bdee384412ea073c5aca06b94e402ba3a43e15bd9c65bbfb194c561c24a031dec43be95c59eb6b568c176b1038d5b7b057dc032488335284adebfb6607e6a995b7fa418f13c8a61b343e5df44faa1050d9d76550748d9efebe01da97ade5937afd5f007ed26e0af03f283611655e91bc6a4857f66a57a1584ff687c4baf725f4a1b32fae53a3e6e8b98bca319bb1badb704c9c1a04f401f33d813d605eef6943c2c52dbc85ab7081d1f8f69d3202aae281bf42336a949a12a7dbbd22abdd6e92996282ebd69033c22cb0539d97f83636d6a8232209a7411e8b03bef180d83e608563ea2d0becff56dc996c2049df054961bfb21b7cbef5049a7dacc18f2c977aa1b2d48291abc19c3c8ea25d2e61901048354b17ce952f6f2248cf3a0eb54c19b507b41d7281c3d227e2b142ff695d8b925a4bb942ed9492a73a17468a8332a367fd16295420bdca6c04d380271f40440709998fce3a3af3e1e505f5402e5dd464dd179cb0eede3d494a95b84d2fb2eb5abb425cf2c712af999c65259c4782a5ec97388324c67738908a5ba43b6db62a10f50cddf9b5039123437c74165921ac8cf4f13292a216baef9d00bd544106b52755986c98a462ade1149f69367e926d88eb92798c0e56cd19a1bcf264fd93293033b758da65c7901eb5b4a17ee265a3312dbc477868da0057e1b3cbf47726dead6ecfcc8e1044c6f311ff0fc83192dc2f75a89626ba33364dac747b63ff3c8337e00332c8783ba9c8dc13cdf0750d7adc3926fbe1279017d50adba35c38c5b810f73abe5d759cd7fb650f6b0a1f78dc1f62fd017090ff4de4cf54c883752ddda68083d4617ed2c38bab8da313965dd3f7b755aec23a2d9e2965d08d2134827a72ffb3bd65b1fd5410da105bfba7a74ddff0928a654aca1ee211ac9dce8019ddcbb52263ce44b2544a314355c1e8c8543f3ed3e883e7a7a8f9e3c7c11f41ab9069854fb21e9b3660a860df19d289d54b29d82522b32d187cde6261eb0a429c3994dff6f37b9ab9102281223e3cd584790a909e05ba0ea1a2d9aef8e571986e98e09312dccaf8e739d718a1edd217dc4c8a5c8a650015405b592a7c674a451d7d1686c7ea6d93e74a8fe4ade12b679ac780457f08a79bfbf96dcf7eefe9a39b99f1ae39d2c5f86aadf156b7d5ce4b2733f307cfe1e1ff6de0ff2006d9cba535b0c40dfb7a98399cdff8e681fc38c7b9aa94ee5eb89432e28d94ee27f238776ba964a87caf58eddbb64771e64de094305a8eb848d2d9ad6373903687d22170f48f1ae8d714514034ee2733857af4747312bb006e6ce3918ede8c730bacc7821b81c1b93bb50b219e79e8e0d74531ed18c1145632d9847d38783b49141ac5353aaa7d125fb2934e681467e16b28090978e74e0b".into();
+
+		let mut raw = serialize(&t1).take();
+		raw.extend_from_slice(&*serialize(&t2));
+
+		let (d1, consumed1) = deserialize_at::<Transaction>(&raw).unwrap();
+		assert_eq!(d1, t1);
+		assert_eq!(consumed1, t1.serialized_size());
+
+		let (d2, consumed2) = deserialize_at::<Transaction>(&raw[consumed1..]).unwrap();
+		assert_eq!(d2, t2);
+		assert_eq!(consumed2, t2.serialized_size());
+		assert_eq!(consumed1 + consumed2, raw.len());
+	}
+
+	#[test]
+	fn test_spent_and_created_outpoints() {
+		let coinbase = Transaction {
+			inputs: vec![TransactionInput::coinbase(vec![1, 2].into())],
+			outputs: vec![TransactionOutput { value: 10, script_pubkey: Default::default() }],
+			..Default::default()
+		};
+		assert_eq!(coinbase.spent_outpoints().count(), 0);
+
+		let coinbase_hash = coinbase.hash();
+		let created = coinbase.created_outpoints(&coinbase_hash).collect::<Vec<_>>();
+		assert_eq!(created, vec![(OutPoint { hash: coinbase_hash.clone(), index: 0 }, &coinbase.outputs[0])]);
+
+		let first = OutPoint { hash: H256::from(1), index: 0 };
+		let second = OutPoint { hash: H256::from(2), index: 3 };
+		let tx = Transaction {
+			inputs: vec![
+				TransactionInput { previous_output: first.clone(), ..Default::default() },
+				TransactionInput { previous_output: second.clone(), ..Default::default() },
+			],
+			outputs: vec![
+				TransactionOutput { value: 1, script_pubkey: Default::default() },
+				TransactionOutput { value: 2, script_pubkey: Default::default() },
+			],
+			..Default::default()
+		};
+		assert_eq!(tx.spent_outpoints().collect::<Vec<_>>(), vec![&first, &second]);
+
+		let tx_hash = tx.hash();
+		let created = tx.created_outpoints(&tx_hash).collect::<Vec<_>>();
+		assert_eq!(created, vec![
+			(OutPoint { hash: tx_hash.clone(), index: 0 }, &tx.outputs[0]),
+			(OutPoint { hash: tx_hash.clone(), index: 1 }, &tx.outputs[1]),
+		]);
+	}
+
+	#[test]
+	fn test_transaction_output_is_unspendable() {
+		let null_data = TransactionOutput {
+			value: 0,
+			script_pubkey: "6a0b68656c6c6f20776f726c64".into(),
+		};
+		assert!(null_data.is_unspendable());
+
+		let p2pkh = TransactionOutput {
+			value: 10,
+			script_pubkey: "76a914c8e90996c7c6080ee06284600c684ed904d14c5c88ac".into(),
+		};
+		assert!(!p2pkh.is_unspendable());
+
+		// OP_RETURN in the middle of the script doesn't make it unspendable
+		let op_return_inside = TransactionOutput {
+			value: 10,
+			script_pubkey: "516a".into(),
+		};
+		assert!(!op_return_inside.is_unspendable());
+
+		let empty = TransactionOutput {
+			value: 10,
+			script_pubkey: Default::default(),
+		};
+		assert!(!empty.is_unspendable());
+	}
+
+	#[test]
+	fn test_transaction_bip69_sorting() {
+		let input = |hash: u8, index: u32| TransactionInput {
+			previous_output: OutPoint { hash: H256::from(hash), index: index },
+			..Default::default()
+		};
+		let output = |value: u64, script_pubkey: &'static str| TransactionOutput {
+			value: value,
+			script_pubkey: script_pubkey.into(),
+		};
+
+		let sorted = Transaction {
+			inputs: vec![input(1, 1), input(1, 2), input(2, 0)],
+			outputs: vec![output(1, "51"), output(2, "51"), output(2, "52")],
+			..Default::default()
+		};
+		assert!(sorted.is_bip69_sorted());
+		assert!(Transaction::default().is_bip69_sorted());
+
+		let unsorted = Transaction {
+			inputs: vec![input(2, 0), input(1, 2), input(1, 1)],
+			outputs: vec![output(2, "52"), output(1, "51"), output(2, "51")],
+			..Default::default()
+		};
+		assert!(!unsorted.is_bip69_sorted());
+
+		let mut resorted = unsorted.clone();
+		resorted.bip69_sort();
+		assert_eq!(resorted, sorted);
+
+		// only outputs are unsorted
+		let unsorted_outputs = Transaction {
+			outputs: vec![output(2, "52"), output(2, "51")],
+			..sorted.clone()
+		};
+		assert!(!unsorted_outputs.is_bip69_sorted());
+	}
+
+	#[test]
+	fn test_transaction_bip69_compares_displayed_hashes() {
+		let input = |hash: &'static str| TransactionInput {
+			previous_output: OutPoint { hash: H256::from_reversed_str(hash), index: 0 },
+			..Default::default()
+		};
+
+		// first inputs of the BIP69 example transaction: sorted by displayed hashes,
+		// but not by their raw bytes
+		let first = "0e53ec5dfb2cb8a71fec32dc9a634a35b7e24799295ddd5278217822e0b31f57";
+		let second = "26aa6e6d8b9e49bb0630aac301db6757c02e3619feb4ee0eea81eb1672947024";
+		let third = "28e0fdd185542f2c6ea19030b0796051e7772b6026dd5ddccd7a2f93b73e6fc2";
+		let sorted = Transaction {
+			inputs: vec![input(first), input(second), input(third)],
+			..Default::default()
+		};
+		assert!(sorted.inputs[0].previous_output.hash > sorted.inputs[1].previous_output.hash);
+		assert!(sorted.is_bip69_sorted());
+
+		let mut resorted = Transaction {
+			inputs: vec![input(third), input(first), input(second)],
+			..Default::default()
+		};
+		assert!(!resorted.is_bip69_sorted());
+		resorted.bip69_sort();
+		assert_eq!(resorted, sorted);
+	}
+
+	#[test]
+	fn test_transaction_checked_total_spends() {
+		let output = |value: u64| TransactionOutput {
+			value: value,
+			script_pubkey: Default::default(),
+		};
+
+		let tx = Transaction {
+			outputs: vec![output(10), output(20)],
+			..Default::default()
+		};
+		assert_eq!(tx.checked_total_spends(), Some(30));
+		assert_eq!(tx.total_spends(), 30);
+
+		let tx = Transaction {
+			outputs: vec![output(u64::max_value() / 2 + 1), output(u64::max_value() / 2 + 1)],
+			..Default::default()
+		};
+		assert_eq!(tx.checked_total_spends(), None);
+		assert_eq!(tx.total_spends(), u64::max_value());
+	}
+
+	#[test]
+	fn test_transaction_shielded_value_balance_overflow() {
+		let join_split = |value_pub_old: u64, value_pub_new: u64| JoinSplit {
+			descriptions: vec![
+				JoinSplitDescription { value_pub_old: value_pub_old, value_pub_new: value_pub_new, ..Default::default() },
+				JoinSplitDescription { value_pub_old: value_pub_old, value_pub_new: value_pub_new, ..Default::default() },
+			],
+			..Default::default()
+		};
+
+		let tx = Transaction {
+			join_split: Some(join_split(10, 20)),
+			..Default::default()
+		};
+		assert_eq!(tx.shielded_value_balance(), Ok(ShieldedBalance { sprout_in: 20, sprout_out: 40, sapling_net: 0 }));
+
+		let tx = Transaction {
+			join_split: Some(join_split(u64::max_value() / 2 + 1, 0)),
+			..Default::default()
+		};
+		assert_eq!(tx.shielded_value_balance(), Err(ShieldedBalanceError::SproutInOverflow));
+
+		let tx = Transaction {
+			join_split: Some(join_split(0, u64::max_value() / 2 + 1)),
+			..Default::default()
+		};
+		assert_eq!(tx.shielded_value_balance(), Err(ShieldedBalanceError::SproutOutOverflow));
+	}
+
+	#[test]
+	fn test_transaction_huge_inputs_count_is_rejected() {
+		// version 1 transaction, followed by 0x0fffffff inputs count
+		let bytes: Vec<u8> = "01000000feffffff0f".from_hex().unwrap();
+		let result: Result<Transaction, _> = deserialize(&bytes as &[u8]);
+		assert_eq!(result, Err(ReaderError::ListTooLong(0x0fffffff)));
+	}
+
+	#[test]
+	fn test_transaction_unsupported_version_is_rejected() {
+		let deserialize_tx = |hex: &str| {
+			let bytes: Vec<u8> = hex.from_hex().unwrap();
+			deserialize::<_, Transaction>(&bytes as &[u8])
+		};
+
+		// overwintered version 5 transaction with sapling version group
+		assert_eq!(deserialize_tx("0500008085202f89"), Err(ReaderError::UnsupportedTransactionVersion(5)));
+		// overwintered version 5 transaction with unknown version group
+		assert_eq!(deserialize_tx("0500008001020304"), Err(ReaderError::UnsupportedTransactionVersion(5)));
+		// overwintered version 4 transaction with unknown version group is malformed
+		match deserialize_tx("0400008001020304") {
+			Err(ReaderError::InvalidFormat(_)) => (),
+			result => panic!("unexpected result: {:?}", result),
+		}
+	}
+
+	#[test]
+	fn test_transaction_huge_shielded_counts_are_rejected() {
+		let deserialize_tx = |hex: &str| {
+			let bytes: Vec<u8> = hex.from_hex().unwrap();
+			deserialize::<_, Transaction>(&bytes as &[u8])
+		};
+		let assert_invalid_format = |result: Result<Transaction, ReaderError>| match result {
+			Err(ReaderError::InvalidFormat(_)) => (),
+			result => panic!("unexpected result: {:?}", result),
+		};
+
+		// sprout transaction without inputs and outputs, followed by 0xffffffff JoinSplits count
+		assert_invalid_format(deserialize_tx("02000000000000000000feffffffff"));
+		// sapling transaction without inputs and outputs, followed by 0xffffffff spends count
+		assert_invalid_format(deserialize_tx("0400008085202f89000000000000000000000000000000000000feffffffff"));
+		// sapling transaction without inputs, outputs and spends, followed by 0xffffffff outputs count
+		assert_invalid_format(deserialize_tx("0400008085202f8900000000000000000000000000000000000000feffffffff"));
+	}
+
+	#[test]
+	fn test_transaction_input_relative_locktime() {
+		let input = |sequence| TransactionInput { sequence: sequence, ..Default::default() };
+
+		// disable flag is set
+		assert_eq!(input(0xffffffff).relative_locktime(), None);
+		assert_eq!(input(1 << 31).relative_locktime(), None);
+		assert_eq!(input((1 << 31) | (1 << 22) | 10).relative_locktime(), None);
+
+		// block-based lock-time
+		assert_eq!(input(0).relative_locktime(), Some(RelativeLocktime::Blocks(0)));
+		assert_eq!(input(10).relative_locktime(), Some(RelativeLocktime::Blocks(10)));
+		// bits outside of the mask are ignored
+		assert_eq!(input(0x0001_0000 | 0xffff).relative_locktime(), Some(RelativeLocktime::Blocks(0xffff)));
+
+		// time-based lock-time
+		assert_eq!(input((1 << 22) | 10).relative_locktime(), Some(RelativeLocktime::Seconds(10 * 512)));
+		assert_eq!(input((1 << 22) | 0xffff).relative_locktime(), Some(RelativeLocktime::Seconds(0xffff * 512)));
+	}
+
+	#[test]
+	fn test_transactions_list_write_streaming() {
+		let transactions: Vec<Transaction> = (0..1000u32).map(|index| Transaction {
+			version: 1,
+			inputs: vec![TransactionInput {
+				previous_output: OutPoint { hash: H256::from(index as u8), index: index },
+				script_sig: Default::default(),
+				sequence: index,
+			}],
+			outputs: vec![TransactionOutput { value: index as u64, script_pubkey: Default::default() }],
+			..Default::default()
+		}).collect();
+
+		let mut buffer = Vec::new();
+		List::<Transaction>::write_streaming(&mut buffer, transactions.len(), transactions.iter()).unwrap();
+		assert_eq!(buffer, serialize_list::<Transaction, Transaction>(&transactions).take());
+
+		let (count, count_size): (CompactInteger, _) = deserialize_at(&buffer).unwrap();
+		assert_eq!(usize::from(count), transactions.len());
+		let read_transactions = deserialize_iterator(&buffer[count_size..])
+			.collect::<Result<Vec<Transaction>, _>>()
+			.unwrap();
+		assert_eq!(read_transactions, transactions);
+	}
 }
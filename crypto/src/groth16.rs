@@ -2,8 +2,11 @@ use std::fmt;
 
 use hex::ToHex;
 
-use pairing::bls12_381::Bls12;
-use bellman::groth16::Proof as BellmanProof;
+use rand::{thread_rng, Rand};
+use pairing::{Engine, CurveAffine, CurveProjective, Field, PrimeField};
+use pairing::bls12_381::{Bls12, Fr, G1};
+use bellman::SynthesisError;
+use bellman::groth16::{Proof as BellmanProof, VerifyingKey};
 
 #[derive(Clone)]
 pub struct Proof([u8; 192]);
@@ -55,3 +58,93 @@ impl Proof {
 			.map_err(|_| /* only invalid point data possible, length is always ok */ Error::InvalidData)
 	}
 }
+
+/// Groth16 proofs batch verifier.
+///
+/// All proofs are checked at once, using random linear combination of verification equations:
+/// prod(e(r_i * A_i, B_i)) * e(sum(r_i * IC_i), -gamma) * e(sum(r_i * C_i), -delta) == e(alpha, beta) ^ sum(r_i)
+/// This costs single final exponentiation instead of one per proof.
+pub struct BatchVerifier<'a> {
+	vk: &'a VerifyingKey<Bls12>,
+	items: Vec<(BellmanProof<Bls12>, Vec<Fr>)>,
+}
+
+impl<'a> BatchVerifier<'a> {
+	pub fn new(vk: &'a VerifyingKey<Bls12>) -> Self {
+		BatchVerifier {
+			vk: vk,
+			items: Vec::new(),
+		}
+	}
+
+	/// Add proof and its public input to the batch.
+	pub fn push(&mut self, proof: BellmanProof<Bls12>, public_input: Vec<Fr>) {
+		self.items.push((proof, public_input));
+	}
+
+	/// Proofs in the batch.
+	pub fn items(&self) -> &[(BellmanProof<Bls12>, Vec<Fr>)] {
+		&self.items
+	}
+
+	pub fn len(&self) -> usize {
+		self.items.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.items.is_empty()
+	}
+
+	/// Verify all proofs in the batch. Returns Ok(true) only if every proof is valid.
+	pub fn verify(&self) -> Result<bool, SynthesisError> {
+		if self.items.is_empty() {
+			return Ok(true);
+		}
+
+		let mut rng = thread_rng();
+		let mut acc_r = Fr::zero();
+		let mut acc_ic = G1::zero();
+		let mut acc_c = G1::zero();
+		let mut prepared = Vec::with_capacity(self.items.len());
+
+		for &(ref proof, ref public_input) in &self.items {
+			if public_input.len() + 1 != self.vk.ic.len() {
+				return Err(SynthesisError::MalformedVerifyingKey);
+			}
+
+			let r = Fr::rand(&mut rng);
+			acc_r.add_assign(&r);
+
+			let mut ic = self.vk.ic[0].into_projective();
+			for (input, base) in public_input.iter().zip(self.vk.ic.iter().skip(1)) {
+				ic.add_assign(&base.mul(input.into_repr()));
+			}
+			ic.mul_assign(r.into_repr());
+			acc_ic.add_assign(&ic);
+
+			acc_c.add_assign(&proof.c.mul(r.into_repr()));
+
+			let a = proof.a.mul(r.into_repr()).into_affine();
+			prepared.push((a.prepare(), proof.b.prepare()));
+		}
+
+		let mut neg_gamma = self.vk.gamma_g2;
+		neg_gamma.negate();
+		let neg_gamma = neg_gamma.prepare();
+		let mut neg_delta = self.vk.delta_g2;
+		neg_delta.negate();
+		let neg_delta = neg_delta.prepare();
+		let acc_ic = acc_ic.into_affine().prepare();
+		let acc_c = acc_c.into_affine().prepare();
+
+		let mut terms: Vec<_> = prepared.iter().map(|&(ref a, ref b)| (a, b)).collect();
+		terms.push((&acc_ic, &neg_gamma));
+		terms.push((&acc_c, &neg_delta));
+
+		let lhs = Bls12::final_exponentiation(&Bls12::miller_loop(terms.iter()))
+			.ok_or(SynthesisError::UnexpectedIdentity)?;
+		let rhs = Bls12::pairing(self.vk.alpha_g1, self.vk.beta_g2).pow(acc_r.into_repr());
+
+		Ok(lhs == rhs)
+	}
+}
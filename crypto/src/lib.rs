@@ -1,28 +1,44 @@
 extern crate blake2_rfc;
 extern crate crypto as rcrypto;
 extern crate primitives;
-extern crate serde_json;
 extern crate siphasher;
-extern crate bn;
-extern crate serde;
 extern crate rustc_hex as hex;
 extern crate ed25519_dalek as ed25519;
+#[cfg(feature = "zk")]
+extern crate serde_json;
+#[cfg(feature = "zk")]
+extern crate bn;
+#[cfg(feature = "zk")]
+extern crate serde;
+#[cfg(feature = "zk")]
+extern crate rand;
 
+#[cfg(feature = "zk")]
 pub extern crate bellman;
+#[cfg(feature = "zk")]
 pub extern crate pairing;
+#[cfg(feature = "zk")]
 pub extern crate sapling_crypto;
 pub extern crate blake2b_simd as blake2;
 
+#[cfg(feature = "zk")]
 #[macro_use] extern crate lazy_static;
+#[cfg(feature = "zk")]
 #[macro_use] extern crate serde_derive;
 
+#[cfg(feature = "zk")]
 pub mod json;
+#[cfg(feature = "zk")]
 mod pghr13;
+#[cfg(feature = "zk")]
 mod groth16;
+mod midstate;
 
 pub use rcrypto::digest::Digest;
 pub use blake2_rfc::blake2b::Blake2b;
+pub use midstate::{Sha256Midstate, sha256_midstate, sha256_resume};
 
+#[cfg(feature = "zk")]
 lazy_static! {
 	pub static ref JUBJUB: sapling_crypto::jubjub::JubjubBls12 =
 		{ sapling_crypto::jubjub::JubjubBls12::new() };
@@ -35,17 +51,23 @@ use rcrypto::ripemd160::Ripemd160;
 use siphasher::sip::SipHasher24;
 use primitives::hash::{H32, H160, H256};
 
+#[cfg(feature = "zk")]
 pub use json::groth16::{
 	load_sapling_spend_verifying_key, load_sapling_output_verifying_key, load_joinsplit_groth16_verifying_key,
 };
 
-pub use pghr13::{VerifyingKey as Pghr13VerifyingKey, Proof as Pghr13Proof, verify as pghr13_verify};
+#[cfg(feature = "zk")]
+pub use pghr13::{VerifyingKey as Pghr13VerifyingKey, Proof as Pghr13Proof, VerifyError as Pghr13VerifyError,
+	verify as pghr13_verify, verify_detailed as pghr13_verify_detailed};
 
+#[cfg(feature = "zk")]
 pub use groth16::{
 	Proof as Groth16Proof,
 	Error as Groth16Error,
+	BatchVerifier as Groth16BatchVerifier,
 };
 
+#[cfg(feature = "zk")]
 pub mod curve {
 	pub mod bn {
 		pub use pghr13::{G1, G2, Fr, Group, U256};
@@ -56,18 +78,47 @@ pub mod curve {
 	}
 }
 
-pub struct Groth16VerifyingKey(pub bellman::groth16::PreparedVerifyingKey<pairing::bls12_381::Bls12>);
+/// Groth16 verifying key. Prepared form is used to verify single proofs, raw form is used for batch verification.
+#[cfg(feature = "zk")]
+pub struct Groth16VerifyingKey(
+	pub bellman::groth16::PreparedVerifyingKey<pairing::bls12_381::Bls12>,
+	pub bellman::groth16::VerifyingKey<pairing::bls12_381::Bls12>,
+);
+
+#[cfg(feature = "zk")]
+impl From<bellman::groth16::VerifyingKey<pairing::bls12_381::Bls12>> for Groth16VerifyingKey {
+	fn from(vk: bellman::groth16::VerifyingKey<pairing::bls12_381::Bls12>) -> Self {
+		Groth16VerifyingKey(bellman::groth16::prepare_verifying_key(&vk), vk)
+	}
+}
+
+/// Hash function that is used as a building block of `DHash160` and `DHash256`.
+pub trait InnerDigest: Digest {
+	fn new_inner() -> Self;
+}
+
+impl InnerDigest for Sha256 {
+	fn new_inner() -> Self {
+		Sha256::new()
+	}
+}
+
+impl InnerDigest for Ripemd160 {
+	fn new_inner() -> Self {
+		Ripemd160::new()
+	}
+}
 
-pub struct DHash160 {
-	sha256: Sha256,
-	ripemd: Ripemd160,
+pub struct DHash160<H1 = Sha256, H2 = Ripemd160> {
+	first: H1,
+	second: H2,
 }
 
-impl Default for DHash160 {
+impl<H1: InnerDigest, H2: InnerDigest> Default for DHash160<H1, H2> {
 	fn default() -> Self {
 		DHash160 {
-			sha256: Sha256::new(),
-			ripemd: Ripemd160::new(),
+			first: H1::new_inner(),
+			second: H2::new_inner(),
 		}
 	}
 }
@@ -78,21 +129,22 @@ impl DHash160 {
 	}
 }
 
-impl Digest for DHash160 {
+impl<H1: InnerDigest, H2: InnerDigest> Digest for DHash160<H1, H2> {
 	fn input(&mut self, d: &[u8]) {
-		self.sha256.input(d)
+		self.first.input(d)
 	}
 
 	fn result(&mut self, out: &mut [u8]) {
 		let mut tmp = [0u8; 32];
-		self.sha256.result(&mut tmp);
-		self.ripemd.input(&tmp);
-		self.ripemd.result(out);
-		self.ripemd.reset();
+		let tmp = &mut tmp[..self.first.output_bytes()];
+		self.first.result(tmp);
+		self.second.input(tmp);
+		self.second.result(out);
+		self.second.reset();
 	}
 
 	fn reset(&mut self) {
-		self.sha256.reset();
+		self.first.reset();
 	}
 
 	fn output_bits(&self) -> usize {
@@ -104,14 +156,14 @@ impl Digest for DHash160 {
 	}
 }
 
-pub struct DHash256 {
-	hasher: Sha256,
+pub struct DHash256<H = Sha256> {
+	hasher: H,
 }
 
-impl Default for DHash256 {
+impl<H: InnerDigest> Default for DHash256<H> {
 	fn default() -> Self {
 		DHash256 {
-			hasher: Sha256::new(),
+			hasher: H::new_inner(),
 		}
 	}
 }
@@ -120,7 +172,9 @@ impl DHash256 {
 	pub fn new() -> Self {
 		DHash256::default()
 	}
+}
 
+impl<H: InnerDigest> DHash256<H> {
 	pub fn finish(mut self) -> H256 {
 		let mut result = H256::default();
 		self.result(&mut *result);
@@ -128,7 +182,7 @@ impl DHash256 {
 	}
 }
 
-impl Digest for DHash256 {
+impl<H: InnerDigest> Digest for DHash256<H> {
 	fn input(&mut self, d: &[u8]) {
 		self.hasher.input(d)
 	}
@@ -234,6 +288,7 @@ pub fn blake2b_personal(personalization: &[u8], input: &[u8]) -> H256 {
 }
 
 /// "Uncommitted" note value.
+#[cfg(feature = "zk")]
 #[inline]
 pub fn pedersen_uncommitted() -> H256 {
 	use pairing::{PrimeField, PrimeFieldRepr, bls12_381::Bls12};
@@ -247,6 +302,7 @@ pub fn pedersen_uncommitted() -> H256 {
 }
 
 /// Computes a Pedersen hash for merkle tree at given depth.
+#[cfg(feature = "zk")]
 pub fn pedersen_hash(left: &[u8; 32], right: &[u8; 32], depth: usize) -> H256 {
 	use pairing::{PrimeField, PrimeFieldRepr, BitIterator};
 	use pairing::bls12_381::{Bls12, Fr, FrRepr};
@@ -282,6 +338,7 @@ pub fn checksum(data: &[u8]) -> H32 {
 	result
 }
 
+#[cfg(feature = "zk")]
 impl ::std::fmt::Debug for Groth16VerifyingKey {
 	fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
 		f.write_str("Groth16VerifyingKey")
@@ -308,7 +365,50 @@ pub fn verify_ed25519(msg: &[u8], public_key: &[u8; 32], signature: &[u8; 64]) -
 mod tests {
 	use primitives::bytes::Bytes;
 	use primitives::hash::H256;
-	use super::{ripemd160, sha1, sha256, dhash160, dhash256, siphash24, checksum, sha256_compress, pedersen_hash};
+	use std::cell::Cell;
+	use super::{ripemd160, sha1, sha256, dhash160, dhash256, siphash24, checksum, sha256_compress, blake2b_personal,
+		Digest, InnerDigest, DHash160, DHash256};
+	#[cfg(feature = "zk")]
+	use super::pedersen_hash;
+
+	thread_local! {
+		static MOCK_DIGEST_CALLS: Cell<(usize, usize)> = Cell::new((0, 0));
+	}
+
+	/// Digest that outputs number of bytes it has been fed with && counts (input, result) calls.
+	struct MockDigest(usize);
+
+	impl InnerDigest for MockDigest {
+		fn new_inner() -> Self {
+			MockDigest(0)
+		}
+	}
+
+	impl Digest for MockDigest {
+		fn input(&mut self, d: &[u8]) {
+			MOCK_DIGEST_CALLS.with(|calls| calls.set((calls.get().0 + 1, calls.get().1)));
+			self.0 += d.len();
+		}
+
+		fn result(&mut self, out: &mut [u8]) {
+			MOCK_DIGEST_CALLS.with(|calls| calls.set((calls.get().0, calls.get().1 + 1)));
+			for byte in out.iter_mut() {
+				*byte = self.0 as u8;
+			}
+		}
+
+		fn reset(&mut self) {
+			self.0 = 0;
+		}
+
+		fn output_bits(&self) -> usize {
+			256
+		}
+
+		fn block_size(&self) -> usize {
+			64
+		}
+	}
 
 	#[test]
 	fn test_ripemd160() {
@@ -350,6 +450,25 @@ mod tests {
 		assert_eq!(result, expected);
 	}
 
+	#[test]
+	fn test_dhash_applies_inner_digest_twice() {
+		MOCK_DIGEST_CALLS.with(|calls| calls.set((0, 0)));
+		let mut hasher = DHash256::<MockDigest>::default();
+		hasher.input(b"hello");
+		let result = hasher.finish();
+		assert_eq!(MOCK_DIGEST_CALLS.with(|calls| calls.get()), (2, 2));
+		// second application hashes 32-byte output of the first one
+		assert_eq!(result, H256::from([32u8; 32]));
+
+		MOCK_DIGEST_CALLS.with(|calls| calls.set((0, 0)));
+		let mut hasher = DHash160::<MockDigest, MockDigest>::default();
+		let mut result = [0u8; 20];
+		hasher.input(b"hello");
+		hasher.result(&mut result);
+		assert_eq!(MOCK_DIGEST_CALLS.with(|calls| calls.get()), (2, 2));
+		assert_eq!(result, [32u8; 20]);
+	}
+
 	#[test]
 	fn test_siphash24() {
 		let expected = 0x74f839c593dc67fd_u64;
@@ -441,6 +560,14 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn test_blake2b_personal() {
+		let expected = "d53a633bbecf82fe9e9484d8a0e727c73bb9e68c96e72dec30144f6a84afa136".into();
+		let result = blake2b_personal(b"ZcashPrevoutHash", &[]);
+		assert_eq!(result, expected);
+	}
+
+	#[cfg(feature = "zk")]
 	#[test]
 	fn test_pedersen_hash() {
 		// ofiginal test case:
@@ -0,0 +1,162 @@
+//! SHA-256 midstate.
+//!
+//! This is a standalone SHA-256 implementation that exposes internal state after hashing whole
+//! blocks of the input. It is only used to hand out partially-computed hashes (e.g. to external
+//! miners) and must not be used by consensus code.
+
+use primitives::hash::H256;
+
+/// SHA-256 block size.
+const BLOCK_SIZE: usize = 64;
+
+/// SHA-256 initial state.
+const INITIAL_STATE: [u32; 8] = [
+	0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// SHA-256 round constants.
+const K: [u32; 64] = [
+	0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+	0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+	0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+	0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+	0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+	0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+	0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+	0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// SHA-256 state after hashing whole number of blocks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sha256Midstate {
+	/// Internal state of the hasher.
+	pub state: [u32; 8],
+	/// Number of bytes that have been hashed to get the state. Always multiple of 64.
+	pub bytes_hashed: u64,
+}
+
+/// Computes SHA-256 midstate of the prefix.
+///
+/// Returns None if prefix length is not a multiple of 64 bytes.
+pub fn sha256_midstate(prefix: &[u8]) -> Option<Sha256Midstate> {
+	if prefix.len() % BLOCK_SIZE != 0 {
+		return None;
+	}
+
+	let mut state = INITIAL_STATE;
+	for block in prefix.chunks(BLOCK_SIZE) {
+		compress(&mut state, block);
+	}
+
+	Some(Sha256Midstate {
+		state: state,
+		bytes_hashed: prefix.len() as u64,
+	})
+}
+
+/// Finishes SHA-256 hash computation, started with `sha256_midstate`.
+pub fn sha256_resume(midstate: &Sha256Midstate, suffix: &[u8]) -> H256 {
+	let mut state = midstate.state;
+	let whole_blocks_len = suffix.len() - suffix.len() % BLOCK_SIZE;
+	for block in suffix[..whole_blocks_len].chunks(BLOCK_SIZE) {
+		compress(&mut state, block);
+	}
+
+	// pad the rest: 0x80, zeroes and 64-bit big-endian length (in bits)
+	let rest = &suffix[whole_blocks_len..];
+	let total_bits = (midstate.bytes_hashed + suffix.len() as u64) * 8;
+	let mut tail = [0u8; BLOCK_SIZE * 2];
+	tail[..rest.len()].copy_from_slice(rest);
+	tail[rest.len()] = 0x80;
+	let tail_len = if rest.len() + 1 + 8 <= BLOCK_SIZE { BLOCK_SIZE } else { BLOCK_SIZE * 2 };
+	for i in 0..8 {
+		tail[tail_len - 1 - i] = (total_bits >> (8 * i)) as u8;
+	}
+	for block in tail[..tail_len].chunks(BLOCK_SIZE) {
+		compress(&mut state, block);
+	}
+
+	let mut result = H256::default();
+	for (i, word) in state.iter().enumerate() {
+		result[i * 4] = (word >> 24) as u8;
+		result[i * 4 + 1] = (word >> 16) as u8;
+		result[i * 4 + 2] = (word >> 8) as u8;
+		result[i * 4 + 3] = *word as u8;
+	}
+	result
+}
+
+/// SHA-256 compression function.
+fn compress(state: &mut [u32; 8], block: &[u8]) {
+	debug_assert_eq!(block.len(), BLOCK_SIZE);
+
+	let mut w = [0u32; 64];
+	for i in 0..16 {
+		w[i] = (block[i * 4] as u32) << 24
+			| (block[i * 4 + 1] as u32) << 16
+			| (block[i * 4 + 2] as u32) << 8
+			| block[i * 4 + 3] as u32;
+	}
+	for i in 16..64 {
+		let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+		let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+		w[i] = w[i - 16].wrapping_add(s0).wrapping_add(w[i - 7]).wrapping_add(s1);
+	}
+
+	let (mut a, mut b, mut c, mut d) = (state[0], state[1], state[2], state[3]);
+	let (mut e, mut f, mut g, mut h) = (state[4], state[5], state[6], state[7]);
+	for i in 0..64 {
+		let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+		let ch = (e & f) ^ (!e & g);
+		let t1 = h.wrapping_add(s1).wrapping_add(ch).wrapping_add(K[i]).wrapping_add(w[i]);
+		let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+		let maj = (a & b) ^ (a & c) ^ (b & c);
+		let t2 = s0.wrapping_add(maj);
+
+		h = g;
+		g = f;
+		f = e;
+		e = d.wrapping_add(t1);
+		d = c;
+		c = b;
+		b = a;
+		a = t1.wrapping_add(t2);
+	}
+
+	state[0] = state[0].wrapping_add(a);
+	state[1] = state[1].wrapping_add(b);
+	state[2] = state[2].wrapping_add(c);
+	state[3] = state[3].wrapping_add(d);
+	state[4] = state[4].wrapping_add(e);
+	state[5] = state[5].wrapping_add(f);
+	state[6] = state[6].wrapping_add(g);
+	state[7] = state[7].wrapping_add(h);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{sha256_midstate, sha256_resume};
+	use sha256;
+
+	#[test]
+	fn sha256_resume_from_midstate_works() {
+		let input: Vec<u8> = (0..200u32).map(|i| (i * 7) as u8).collect();
+		for &split in &[0, 64, 128, 192] {
+			let midstate = sha256_midstate(&input[..split]).unwrap();
+			assert_eq!(midstate.bytes_hashed, split as u64);
+			assert_eq!(sha256_resume(&midstate, &input[split..]), sha256(&input));
+		}
+
+		// suffix lengths around padding boundary
+		for &len in &[0, 55, 56, 63, 64, 119, 120] {
+			let input = &input[..64 + len];
+			assert_eq!(sha256_resume(&sha256_midstate(&input[..64]).unwrap(), &input[64..]), sha256(input));
+		}
+	}
+
+	#[test]
+	fn sha256_midstate_requires_whole_blocks() {
+		assert_eq!(sha256_midstate(&[0u8; 63]), None);
+		assert_eq!(sha256_midstate(&[0u8; 65]), None);
+	}
+}
@@ -81,7 +81,27 @@ impl Proof {
 	}
 }
 
+/// Failed check of the proof verification.
+#[derive(Debug, PartialEq)]
+pub enum VerifyError {
+	/// Knowledge commitment for A is invalid.
+	KnowledgeCommitmentA,
+	/// Knowledge commitment for B is invalid.
+	KnowledgeCommitmentB,
+	/// Knowledge commitment for C is invalid.
+	KnowledgeCommitmentC,
+	/// Different coefficients were used for A, B, C.
+	CoefficientCheck,
+	/// QAP divisibility check has failed.
+	QapDivisibility,
+}
+
 pub fn verify(vk: &VerifyingKey, primary_input: &[Fr], proof: &Proof) -> bool {
+	verify_detailed(vk, primary_input, proof).is_ok()
+}
+
+/// Same as `verify`, but reports which check has failed.
+pub fn verify_detailed(vk: &VerifyingKey, primary_input: &[Fr], proof: &Proof) -> Result<(), VerifyError> {
 	let p2 = G2::one();
 
 	// 1. compute accumulated input circuit (evaluate the polynomial)
@@ -92,15 +112,28 @@ pub fn verify(vk: &VerifyingKey, primary_input: &[Fr], proof: &Proof) -> bool {
 	acc = acc + vk.ic[0];
 
 	// 2. check validity of knowledge commitments for A, B, C:
-	pairing(proof.a, vk.a) == pairing(proof.a_prime, p2) &&
-	pairing(vk.b, proof.b) == pairing(proof.b_prime, p2) &&
-	pairing(proof.c, vk.c) == pairing(proof.c_prime, p2) &&
+	if pairing(proof.a, vk.a) != pairing(proof.a_prime, p2) {
+		return Err(VerifyError::KnowledgeCommitmentA);
+	}
+	if pairing(vk.b, proof.b) != pairing(proof.b_prime, p2) {
+		return Err(VerifyError::KnowledgeCommitmentB);
+	}
+	if pairing(proof.c, vk.c) != pairing(proof.c_prime, p2) {
+		return Err(VerifyError::KnowledgeCommitmentC);
+	}
 
 	// 3. check same coefficients were used:
-	pairing(proof.k, vk.gamma) ==
-		pairing(acc + proof.a + proof.c, vk.gamma_beta_2) * pairing(vk.gamma_beta_1, proof.b) &&
-		// 4. check QAP divisibility
-		pairing(acc + proof.a, proof.b) == pairing(proof.h, vk.z) * pairing(proof.c, p2)
+	if pairing(proof.k, vk.gamma) !=
+		pairing(acc + proof.a + proof.c, vk.gamma_beta_2) * pairing(vk.gamma_beta_1, proof.b) {
+		return Err(VerifyError::CoefficientCheck);
+	}
+
+	// 4. check QAP divisibility
+	if pairing(acc + proof.a, proof.b) != pairing(proof.h, vk.z) * pairing(proof.c, p2) {
+		return Err(VerifyError::QapDivisibility);
+	}
+
+	Ok(())
 }
 
 #[cfg(test)]
@@ -129,6 +162,20 @@ mod tests {
 		pgh13_proof("022cbbb59465c880f50d42d0d49d6422197b5f823c2b3ffdb341869b98ed2eb2fd031b271702bda61ff885788363a7cf980a134c09a24c9911dc94cbe970bd613b700b0891fe8b8b05d9d2e7e51df9d6959bdf0a3f2310164afb197a229486a0e8e3808d76c75662b568839ebac7fbf740db9d576523282e6cdd1adf8b0f9c183ae95b0301fa1146d35af869cc47c51cfd827b7efceeca3c55884f54a68e38ee7682b5d102131b9b1198ed371e7e3da9f5a8b9ad394ab5a29f67a1d9b6ca1b8449862c69a5022e5d671e6989d33c182e0a6bbbe4a9da491dbd93ca3c01490c8f74a780479c7c031fb473670cacde779713dcd8cbdad802b8d418e007335919837becf46a3b1d0e02120af9d926bed2b28ed8a2b8307b3da2a171b3ee1bc1e6196773b570407df6b4")
 	}
 
+	fn sample_primary_input() -> Vec<Fr> {
+		vec![
+			Fr::from_str("11893887518801564238850113243068155191401763535822078310914655246254174921707").unwrap(),
+			Fr::from_str("9039742628274832857146315176202079824763880684544058044764009859702372701908").unwrap(),
+			Fr::from_str("7864248849999267529324215987921491632294157863019983191999113732927809771441").unwrap(),
+			Fr::from_str("2886983623257678406932083534975273655277211437585781522465101031866117927530").unwrap(),
+			Fr::from_str("1639613592978633992206850322587892881255594351774222883941421746126476816445").unwrap(),
+			Fr::from_str("5902043119256669211364401966461491601894820710756687540191805850512824202436").unwrap(),
+			Fr::from_str("13692185839566206949758987046107079401517252355870659294323573892338548513162").unwrap(),
+			Fr::from_str("213567272714802366240312308317683913515756890632602759628885800370159516315").unwrap(),
+			Fr::from_str("170484577853289").unwrap(),
+		]
+	}
+
 	#[test]
 	fn proof_decode() {
 		let proof = Proof::from_raw(&sample_pghr_proof()).unwrap();
@@ -245,17 +292,7 @@ mod tests {
 			).expect("valid proof.a_prime").into(),
 		};
 
-		let primary_input = vec![
-			Fr::from_str("11893887518801564238850113243068155191401763535822078310914655246254174921707").unwrap(),
-			Fr::from_str("9039742628274832857146315176202079824763880684544058044764009859702372701908").unwrap(),
-			Fr::from_str("7864248849999267529324215987921491632294157863019983191999113732927809771441").unwrap(),
-			Fr::from_str("2886983623257678406932083534975273655277211437585781522465101031866117927530").unwrap(),
-			Fr::from_str("1639613592978633992206850322587892881255594351774222883941421746126476816445").unwrap(),
-			Fr::from_str("5902043119256669211364401966461491601894820710756687540191805850512824202436").unwrap(),
-			Fr::from_str("13692185839566206949758987046107079401517252355870659294323573892338548513162").unwrap(),
-			Fr::from_str("213567272714802366240312308317683913515756890632602759628885800370159516315").unwrap(),
-			Fr::from_str("170484577853289").unwrap(),
-		];
+		let primary_input = sample_primary_input();
 
 		assert!(verify(&vk, &primary_input[..], &proof));
 	}
@@ -318,4 +355,15 @@ mod tests {
 
 		assert!(verify(&vk, &primary_input[..], &proof));
 	}
+
+	#[test]
+	fn verification_detailed() {
+		let vk = vkey();
+		let mut proof = Proof::from_raw(&sample_pghr_proof()).unwrap();
+		assert_eq!(verify_detailed(&vk, &sample_primary_input(), &proof), Ok(()));
+
+		proof.h = proof.h + G1::one();
+		assert_eq!(verify_detailed(&vk, &sample_primary_input(), &proof), Err(VerifyError::QapDivisibility));
+		assert!(!verify(&vk, &sample_primary_input(), &proof));
+	}
 }
@@ -1,12 +1,14 @@
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
+use std::ops::Range;
 use parking_lot::RwLock;
 use hash::H256;
+use primitives::bigint::U256;
 use bytes::Bytes;
 use chain::{
 	IndexedBlock, IndexedBlockHeader, IndexedTransaction,
-	OutPoint, TransactionOutput,
+	OutPoint, Transaction, TransactionOutput,
 };
 use ser::{
 	deserialize, serialize, List
@@ -26,15 +28,22 @@ use storage::{
 	TransactionMetaProvider, TransactionProvider, TransactionOutputProvider, BlockChain, Store,
 	SideChainOrigin, ForkChain, Forkable, CanonStore, BestBlock, NullifierTracker,
 	EpochTag, EpochRef, SproutTreeState, SaplingTreeState, TreeStateProvider,
+	ShieldedPools, ShieldedPoolProvider,
 };
 
 const KEY_BEST_BLOCK_NUMBER: &'static str = "best_block_number";
 const KEY_BEST_BLOCK_HASH: &'static str = "best_block_hash";
+const KEY_SIDE_CHAIN_TIPS: &'static str = "side_chain_tips";
+const KEY_SHIELDED_POOLS_BACKFILLED: &'static str = "shielded_pools_backfilled";
 
 const MAX_FORK_ROUTE_PRESET: usize = 2048;
+/// Maximal number of side chain tips to remember. When there are more tips, the lowest are forgotten.
+const MAX_SIDE_CHAIN_TIPS: usize = 64;
 
 pub struct BlockChainDatabase<T> where T: KeyValueDatabase {
 	best_block: RwLock<BestBlock>,
+	/// Tips of side chains. Tips that are more than `MAX_FORK_ROUTE_PRESET` blocks below the best block are forgotten.
+	side_chain_tips: RwLock<Vec<BestBlock>>,
 	db: T,
 }
 
@@ -131,10 +140,14 @@ impl<T> BlockChainDatabase<CacheDatabase<AutoFlushingOverlayDatabase<T>>> where
 	pub fn open_with_cache(db: T) -> Self {
 		let db = CacheDatabase::new(AutoFlushingOverlayDatabase::new(db, 50));
 		let best_block = Self::read_best_block(&db).unwrap_or_default();
-		BlockChainDatabase {
+		let side_chain_tips = Self::read_side_chain_tips(&db);
+		let store = BlockChainDatabase {
 			best_block: RwLock::new(best_block),
+			side_chain_tips: RwLock::new(side_chain_tips),
 			db: db,
-		}
+		};
+		store.backfill().expect("Failed to upgrade database");
+		store
 	}
 }
 
@@ -153,20 +166,150 @@ impl<T> BlockChainDatabase<T> where T: KeyValueDatabase {
 		}
 	}
 
+	fn read_side_chain_tips(db: &T) -> Vec<BestBlock> {
+		db.get(&Key::Meta(KEY_SIDE_CHAIN_TIPS))
+			.expect("db value to be fine")
+			.into_option()
+			.and_then(Value::as_meta)
+			.map(|tips| deserialize::<_, List<BestBlock>>(&*tips).expect("Inconsistent DB. Invalid side chain tips.").into())
+			.unwrap_or_default()
+	}
+
 	pub fn open(db: T) -> Self {
 		let best_block = Self::read_best_block(&db).unwrap_or_default();
+		let side_chain_tips = Self::read_side_chain_tips(&db);
 		BlockChainDatabase {
 			best_block: RwLock::new(best_block),
+			side_chain_tips: RwLock::new(side_chain_tips),
 			db: db,
 		}
 	}
 
+	/// Fills values that weren't stored by previous versions of the database.
+	pub fn backfill(&self) -> Result<(), Error> {
+		self.backfill_chain_work()?;
+		self.backfill_root_heights()?;
+		self.backfill_shielded_pools()
+	}
+
+	/// Computes heights of tree roots of canon blocks, inserted by previous versions of the database.
+	fn backfill_root_heights(&self) -> Result<(), Error> {
+		let best_block = self.best_block();
+		if best_block.hash.is_zero() {
+			return Ok(());
+		}
+
+		let best_header = self.block_header(best_block.hash.into())
+			.ok_or_else(|| Error::DatabaseError(format!("Missing canon block header {}", best_block.number)))?;
+		if self.block_root_keys(&best_header)?.iter().all(|root_key| self.get(Key::RootHeight(*root_key)).is_some()) {
+			return Ok(());
+		}
+
+		info!(target: "db", "Computing heights of tree roots of {} blocks", best_block.number + 1);
+
+		for number in 0..best_block.number + 1 {
+			let header = self.block_header(number.into())
+				.ok_or_else(|| Error::DatabaseError(format!("Missing canon block header {}", number)))?;
+
+			// trees only grow => the first canon block with the root is the block where it appears
+			let mut update = DBTransaction::new();
+			for root_key in &self.block_root_keys(&header)? {
+				if self.get(Key::RootHeight(*root_key)).is_none() {
+					update.insert(KeyValue::RootHeight(*root_key, number));
+				}
+			}
+
+			if !update.operations.is_empty() {
+				self.db.write(update).map_err(Error::DatabaseError)?;
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Computes chain work of canon blocks, inserted by previous versions of the database.
+	fn backfill_chain_work(&self) -> Result<(), Error> {
+		let best_block = self.best_block();
+		if best_block.hash.is_zero() || self.get(Key::ChainWork(best_block.hash)).is_some() {
+			return Ok(());
+		}
+
+		info!(target: "db", "Computing chain work of {} blocks", best_block.number + 1);
+
+		let mut chain_work = U256::zero();
+		for number in 0..best_block.number + 1 {
+			let block_hash = self.block_hash(number)
+				.ok_or_else(|| Error::DatabaseError(format!("Missing canon block {}", number)))?;
+			if let Some(known_work) = self.get(Key::ChainWork(block_hash)).and_then(Value::as_chain_work) {
+				chain_work = known_work;
+				continue;
+			}
+
+			let header = self.block_header(block_hash.into())
+				.ok_or_else(|| Error::DatabaseError(format!("Missing canon block header {}", number)))?;
+			chain_work = chain_work + header.raw.bits.to_work();
+
+			let mut update = DBTransaction::new();
+			update.insert(KeyValue::ChainWork(block_hash, chain_work));
+			self.db.write(update).map_err(Error::DatabaseError)?;
+		}
+
+		Ok(())
+	}
+
+	/// Computes shielded pools balances of canon blocks, inserted by previous versions of the database.
+	///
+	/// Runs at most once per database: completion is recorded in the meta column, so that the chain
+	/// isn't replayed on every open (even if balances of some canon block can't be computed).
+	fn backfill_shielded_pools(&self) -> Result<(), Error> {
+		if self.get(Key::Meta(KEY_SHIELDED_POOLS_BACKFILLED)).is_some() {
+			return Ok(());
+		}
+
+		let best_block = self.best_block();
+		if !best_block.hash.is_zero() && self.get(Key::ShieldedPools(best_block.hash)).is_none() {
+			info!(target: "db", "Computing shielded pools balances of {} blocks", best_block.number + 1);
+
+			let mut pools = ShieldedPools::default();
+			for number in 0..best_block.number + 1 {
+				let block_hash = self.block_hash(number)
+					.ok_or_else(|| Error::DatabaseError(format!("Missing canon block {}", number)))?;
+				if let Some(known_pools) = self.get(Key::ShieldedPools(block_hash)).and_then(Value::as_shielded_pools) {
+					pools = known_pools;
+					continue;
+				}
+
+				let transactions = self.block_transactions(block_hash.into());
+				pools = match pools.apply_block(transactions.iter().map(|tx| &tx.raw)) {
+					Ok(pools) => pools,
+					Err(epoch) => {
+						warn!(target: "db", "{:?} pool is negative at block {}", epoch, number);
+						break;
+					},
+				};
+
+				let mut update = DBTransaction::new();
+				update.insert(KeyValue::ShieldedPools(block_hash, pools));
+				self.db.write(update).map_err(Error::DatabaseError)?;
+			}
+		}
+
+		let mut update = DBTransaction::new();
+		update.insert(KeyValue::Meta(KEY_SHIELDED_POOLS_BACKFILLED, serialize(&true)));
+		self.db.write(update).map_err(Error::DatabaseError)
+	}
+
 	pub fn best_block(&self) -> BestBlock {
 		self.best_block.read().clone()
 	}
 
+	pub fn side_chain_tips(&self) -> Vec<BestBlock> {
+		self.side_chain_tips.read().clone()
+	}
+
 	pub fn fork(&self, side_chain: SideChainOrigin) -> Result<ForkChainDatabase<T>, Error> {
 		let overlay = BlockChainDatabase::open(OverlayDatabase::new(&self.db));
+		*overlay.side_chain_tips.write() = self.side_chain_tips();
 
 		for hash in side_chain.decanonized_route.into_iter().rev() {
 			let decanonized_hash = overlay.decanonize()?;
@@ -187,6 +330,7 @@ impl<T> BlockChainDatabase<T> where T: KeyValueDatabase {
 	pub fn switch_to_fork(&self, fork: ForkChainDatabase<T>) -> Result<(), Error> {
 		let mut best_block = self.best_block.write();
 		*best_block = fork.blockchain.best_block.read().clone();
+		*self.side_chain_tips.write() = fork.blockchain.side_chain_tips();
 		fork.blockchain.db.flush().map_err(Error::DatabaseError)
 	}
 
@@ -241,6 +385,25 @@ impl<T> BlockChainDatabase<T> where T: KeyValueDatabase {
 		Err(Error::AncientFork)
 	}
 
+	/// Returns number of the side chain block with given parent, or None if block
+	/// is going to extend the canon chain or its route to the canon chain is unknown.
+	fn side_chain_block_number(&self, parent_hash: &H256) -> Option<u32> {
+		if parent_hash.is_zero() || *parent_hash == self.best_block.read().hash {
+			return None;
+		}
+
+		let mut next_hash = parent_hash.clone();
+		for fork_len in 0..MAX_FORK_ROUTE_PRESET {
+			if let Some(number) = self.block_number(&next_hash) {
+				return Some(number + fork_len as u32 + 1);
+			}
+
+			next_hash = self.block_header(next_hash.into())?.raw.previous_header_hash;
+		}
+
+		None
+	}
+
 	pub fn insert(&self, block: IndexedBlock) -> Result<(), Error> {
 		if self.contains_block(block.hash().clone().into()) {
 			return Ok(())
@@ -251,6 +414,12 @@ impl<T> BlockChainDatabase<T> where T: KeyValueDatabase {
 			return Err(Error::UnknownParent);
 		}
 
+		let side_chain_tip = self.side_chain_block_number(&parent_hash)
+			.map(|number| BestBlock {
+				number: number,
+				hash: *block.hash(),
+			});
+
 		let mut sprout_tree_state = if parent_hash.is_zero() {
 			SproutTreeState::new()
 		} else {
@@ -265,8 +434,26 @@ impl<T> BlockChainDatabase<T> where T: KeyValueDatabase {
 				.expect(&format!("Corrupted database - no sapling root for block {}", parent_hash))
 		};
 
+		// pools balances are unknown if they are unknown for the parent block
+		// or if block moves more value out of some pool than it holds
+		let shielded_pools = if parent_hash.is_zero() {
+			Some(ShieldedPools::default())
+		} else {
+			self.shielded_pools_at_block(&parent_hash)
+		}.and_then(|pools| pools.apply_block(block.transactions.iter().map(|tx| &tx.raw)).ok());
+
+		// chain work is unknown if the parent block is unknown
+		let chain_work = if parent_hash.is_zero() {
+			Some(U256::zero())
+		} else {
+			self.block_chain_work(&parent_hash)
+		}.map(|parent_work| parent_work + block.header.raw.bits.to_work());
+
 		let sapling_tree_root = block.header.raw.final_sapling_root;
 		let mut update = DBTransaction::new();
+		if let Some(chain_work) = chain_work {
+			update.insert(KeyValue::ChainWork(block.header.hash, chain_work));
+		}
 		update.insert(KeyValue::BlockHeader(*block.hash(), block.header.raw));
 		let tx_hashes = block.transactions.iter().map(|tx| tx.hash).collect::<Vec<_>>();
 		update.insert(KeyValue::BlockTransactions(block.header.hash, List::from(tx_hashes)));
@@ -296,6 +483,10 @@ impl<T> BlockChainDatabase<T> where T: KeyValueDatabase {
 			update.insert(KeyValue::Transaction(tx.hash, tx.raw));
 		}
 
+		if let Some(shielded_pools) = shielded_pools {
+			update.insert(KeyValue::ShieldedPools(block.header.hash, shielded_pools));
+		}
+
 		let sprout_tree_root = sprout_tree_state.root();
 		update.insert(KeyValue::SproutBlockRoot(block.header.hash, sprout_tree_root));
 		update.insert(KeyValue::SproutTreeState(sprout_tree_root, sprout_tree_state));
@@ -303,7 +494,19 @@ impl<T> BlockChainDatabase<T> where T: KeyValueDatabase {
 		// TODO: possible optimization is not to store sapling trees until sapling is activated
 		update.insert(KeyValue::SaplingTreeState(sapling_tree_root, sapling_tree_state));
 
-		self.db.write(update).map_err(Error::DatabaseError)
+		let side_chain_tips = side_chain_tip.map(|side_chain_tip|
+			self.update_side_chain_tips(&mut update, self.best_block().number, |tips| {
+				tips.retain(|tip| tip.hash != parent_hash);
+				tips.push(side_chain_tip);
+			}));
+
+		self.db.write(update).map_err(Error::DatabaseError)?;
+
+		if let Some(side_chain_tips) = side_chain_tips {
+			*self.side_chain_tips.write() = side_chain_tips;
+		}
+
+		Ok(())
 	}
 
 	/// Rollbacks single best block.
@@ -322,8 +525,11 @@ impl<T> BlockChainDatabase<T> where T: KeyValueDatabase {
 		for tx_hash in tx_to_decanonize {
 			update.delete(Key::Transaction(tx_hash));
 		}
+		let side_chain_tips = self.update_side_chain_tips(&mut update, self.best_block().number,
+			|tips| tips.retain(|tip| tip.hash != decanonized_hash));
 
 		self.db.write(update).map_err(Error::DatabaseError)?;
+		*self.side_chain_tips.write() = side_chain_tips;
 
 		Ok(self.best_block().hash)
 	}
@@ -367,17 +573,27 @@ impl<T> BlockChainDatabase<T> where T: KeyValueDatabase {
 		let mut update = DBTransaction::new();
 		update.insert(KeyValue::BlockHash(new_best_block.number, new_best_block.hash.clone()));
 		update.insert(KeyValue::BlockNumber(new_best_block.hash.clone(), new_best_block.number));
+
+		// trees only grow => canonized block is the first block with its root, unless root is already known
+		for root_key in &self.block_root_keys(&block.header)? {
+			if self.get(Key::RootHeight(*root_key)).is_none() {
+				update.insert(KeyValue::RootHeight(*root_key, new_best_block.number));
+			}
+		}
 		update.insert(KeyValue::Meta(KEY_BEST_BLOCK_HASH, serialize(&new_best_block.hash)));
 		update.insert(KeyValue::Meta(KEY_BEST_BLOCK_NUMBER, serialize(&new_best_block.number)));
 
 		let mut modified_meta: HashMap<H256, TransactionMeta> = HashMap::new();
 		if let Some(tx) = block.transactions.first() {
-			let meta = TransactionMeta::new_coinbase(new_best_block.number, tx.raw.outputs.len());
+			let mut meta = TransactionMeta::new_coinbase(new_best_block.number, tx.raw.outputs.len());
+			meta.denote_unspendable(&tx.raw.outputs);
 			modified_meta.insert(tx.hash.clone(), meta);
 		}
 
 		for tx in block.transactions.iter().skip(1) {
-			modified_meta.insert(tx.hash.clone(), TransactionMeta::new(new_best_block.number, tx.raw.outputs.len()));
+			let mut meta = TransactionMeta::new(new_best_block.number, tx.raw.outputs.len());
+			meta.denote_unspendable(&tx.raw.outputs);
+			modified_meta.insert(tx.hash.clone(), meta);
 
 			if let Some(ref js) = tx.raw.join_split {
 				for js_descriptor in js.descriptions.iter() {
@@ -440,8 +656,12 @@ impl<T> BlockChainDatabase<T> where T: KeyValueDatabase {
 			update.insert(KeyValue::TransactionMeta(hash, meta));
 		}
 
+		let side_chain_tips = self.update_side_chain_tips(&mut update, new_best_block.number,
+			|tips| tips.retain(|tip| tip.hash != *hash));
+
 		self.db.write(update).map_err(Error::DatabaseError)?;
 		*best_block = new_best_block;
+		*self.side_chain_tips.write() = side_chain_tips;
 		Ok(())
 	}
 
@@ -472,6 +692,12 @@ impl<T> BlockChainDatabase<T> where T: KeyValueDatabase {
 		let mut update = DBTransaction::new();
 		update.delete(Key::BlockHash(block_number));
 		update.delete(Key::BlockNumber(block_hash.clone()));
+
+		for root_key in &self.block_root_keys(&block.header)? {
+			if self.get(Key::RootHeight(*root_key)).and_then(Value::as_root_height) == Some(block_number) {
+				update.delete(Key::RootHeight(*root_key));
+			}
+		}
 		update.insert(KeyValue::Meta(KEY_BEST_BLOCK_HASH, serialize(&new_best_block.hash)));
 		update.insert(KeyValue::Meta(KEY_BEST_BLOCK_NUMBER, serialize(&new_best_block.number)));
 
@@ -542,8 +768,23 @@ impl<T> BlockChainDatabase<T> where T: KeyValueDatabase {
 			update.delete(Key::TransactionMeta(tx.hash));
 		}
 
+		// decanonized block becomes a side chain tip, unless it is a parent of other tip
+		let side_chain_tips = self.update_side_chain_tips(&mut update, new_best_block.number, |tips| {
+			let is_tip_parent = tips.iter()
+				.filter_map(|tip| self.block_header(tip.hash.into()))
+				.any(|header| header.raw.previous_header_hash == block_hash);
+			if !is_tip_parent {
+				tips.push(BestBlock {
+					number: block_number,
+					hash: block_hash.clone(),
+				});
+			}
+		});
+
 		self.db.write(update).map_err(Error::DatabaseError)?;
 		*best_block = new_best_block;
+		*self.side_chain_tips.write() = side_chain_tips;
+
 		Ok(block_hash)
 	}
 
@@ -551,6 +792,43 @@ impl<T> BlockChainDatabase<T> where T: KeyValueDatabase {
 		self.db.get(&key).expect("db value to be fine").into_option()
 	}
 
+	fn get_many(&self, keys: &[Key]) -> Vec<Option<Value>> {
+		self.db.get_many(keys).expect("db values to be fine")
+			.into_iter()
+			.map(KeyState::into_option)
+			.collect()
+	}
+
+	/// Applies modification to the side chain tips, prunes stale tips and adds the result to the update.
+	///
+	/// Returns new side chain tips that should be set after the update is written.
+	fn update_side_chain_tips<F>(&self, update: &mut DBTransaction, best_block_number: u32, modify: F) -> Vec<BestBlock>
+		where F: FnOnce(&mut Vec<BestBlock>)
+	{
+		let mut side_chain_tips = self.side_chain_tips();
+		modify(&mut side_chain_tips);
+
+		// too deep forks could not become canon => forget their tips
+		side_chain_tips.retain(|tip| tip.number + MAX_FORK_ROUTE_PRESET as u32 > best_block_number);
+		if side_chain_tips.len() > MAX_SIDE_CHAIN_TIPS {
+			side_chain_tips.sort_by(|a, b| b.number.cmp(&a.number));
+			side_chain_tips.truncate(MAX_SIDE_CHAIN_TIPS);
+		}
+
+		update.insert(KeyValue::Meta(KEY_SIDE_CHAIN_TIPS, serialize(&List::from(side_chain_tips.clone()))));
+		side_chain_tips
+	}
+
+	/// Returns keys of sprout and sapling tree roots after given block.
+	fn block_root_keys(&self, header: &IndexedBlockHeader) -> Result<[EpochRef; 2], Error> {
+		let sprout_root = self.sprout_block_root(&header.hash)
+			.ok_or_else(|| Error::DatabaseError(format!("Missing sprout root for block {}", header.hash.reversed())))?;
+		Ok([
+			EpochRef::new(EpochTag::Sprout, sprout_root),
+			EpochRef::new(EpochTag::Sapling, header.raw.final_sapling_root),
+		])
+	}
+
 	fn resolve_hash(&self, block_ref: BlockRef) -> Option<H256> {
 		match block_ref {
 			BlockRef::Number(n) => self.block_hash(n),
@@ -572,6 +850,23 @@ impl<T> BlockHeaderProvider for BlockChainDatabase<T> where T: KeyValueDatabase
 					.map(|header| IndexedBlockHeader::new(block_hash, header))
 			})
 	}
+
+	fn block_headers_range(&self, range: Range<u32>) -> Vec<IndexedBlockHeader> {
+		let hash_keys = range.map(Key::BlockHash).collect::<Vec<_>>();
+		let hashes = self.get_many(&hash_keys).into_iter()
+			.map(|value| value.and_then(Value::as_block_hash))
+			.take_while(Option::is_some)
+			.filter_map(|hash| hash)
+			.collect::<Vec<_>>();
+
+		let header_keys = hashes.iter().cloned().map(Key::BlockHeader).collect::<Vec<_>>();
+		hashes.into_iter()
+			.zip(self.get_many(&header_keys))
+			.map(|(hash, value)| value.and_then(Value::as_block_header).map(|header| IndexedBlockHeader::new(hash, header)))
+			.take_while(Option::is_some)
+			.filter_map(|header| header)
+			.collect()
+	}
 }
 
 impl<T> BlockProvider for BlockChainDatabase<T> where T: KeyValueDatabase {
@@ -618,6 +913,26 @@ impl<T> BlockProvider for BlockChainDatabase<T> where T: KeyValueDatabase {
 				.map(|tx| IndexedTransaction::new(hash, tx)))
 			.collect()
 	}
+
+	fn block_chain_work(&self, hash: &H256) -> Option<U256> {
+		// chain work of side chain blocks, inserted by previous versions of the database,
+		// is computed from the closest ancestor with known work
+		let mut work = U256::zero();
+		let mut next_hash = *hash;
+		loop {
+			if next_hash.is_zero() {
+				return Some(work);
+			}
+
+			if let Some(known_work) = self.get(Key::ChainWork(next_hash)).and_then(Value::as_chain_work) {
+				return Some(known_work + work);
+			}
+
+			let header = self.block_header(next_hash.into())?.raw;
+			work = work + header.bits.to_work();
+			next_hash = header.previous_header_hash;
+		}
+	}
 }
 
 impl<T> TransactionMetaProvider for BlockChainDatabase<T> where T: KeyValueDatabase {
@@ -625,6 +940,23 @@ impl<T> TransactionMetaProvider for BlockChainDatabase<T> where T: KeyValueDatab
 		self.get(Key::TransactionMeta(hash.clone()))
 			.and_then(Value::as_transaction_meta)
 	}
+
+	fn transaction_is_coinbase(&self, hash: &H256) -> Option<bool> {
+		self.get(Key::TransactionMetaSummary(hash.clone()))
+			.and_then(Value::as_transaction_meta_summary)
+			.map(|(_, is_coinbase)| is_coinbase)
+	}
+
+	fn transaction_height(&self, hash: &H256) -> Option<u32> {
+		self.get(Key::TransactionMetaSummary(hash.clone()))
+			.and_then(Value::as_transaction_meta_summary)
+			.map(|(height, _)| height)
+	}
+
+	fn transaction_block_hash(&self, hash: &H256) -> Option<H256> {
+		self.transaction_height(hash)
+			.and_then(|height| self.block_hash(height))
+	}
 }
 
 impl<T> TransactionProvider for BlockChainDatabase<T> where T: KeyValueDatabase {
@@ -647,6 +979,22 @@ impl<T> TransactionOutputProvider for BlockChainDatabase<T> where T: KeyValueDat
 			.and_then(|tx| tx.raw.outputs.into_iter().nth(prevout.index as usize))
 	}
 
+	fn outputs_for_transaction(&self, tx: &Transaction, _transaction_index: usize) -> Vec<Option<TransactionOutput>> {
+		// every previous transaction is read once, even if it is spent by several inputs
+		let mut prev_txs: HashMap<H256, Option<Transaction>> = HashMap::new();
+		tx.inputs.iter()
+			.map(|input| {
+				let prevout = &input.previous_output;
+				prev_txs.entry(prevout.hash.clone())
+					.or_insert_with(|| self.transaction_meta(&prevout.hash)
+						.and_then(|_| self.transaction(&prevout.hash))
+						.map(|tx| tx.raw))
+					.as_ref()
+					.and_then(|tx| tx.outputs.get(prevout.index as usize).cloned())
+			})
+			.collect()
+	}
+
 	fn is_spent(&self, prevout: &OutPoint) -> bool {
 		self.transaction_meta(&prevout.hash)
 			.and_then(|meta| meta.is_spent(prevout.index as usize))
@@ -658,6 +1006,14 @@ impl<T> NullifierTracker for BlockChainDatabase<T> where T: KeyValueDatabase {
 	fn contains_nullifier(&self, nullifier: EpochRef) -> bool {
 		self.get(Key::Nullifier(nullifier)).is_some()
 	}
+
+	fn contains_any(&self, nullifiers: &[EpochRef]) -> Option<EpochRef> {
+		let keys = nullifiers.iter().cloned().map(Key::Nullifier).collect::<Vec<_>>();
+		nullifiers.iter()
+			.zip(self.get_many(&keys))
+			.find(|&(_, ref value)| value.is_some())
+			.map(|(nullifier, _)| *nullifier)
+	}
 }
 
 impl<T> TreeStateProvider for BlockChainDatabase<T> where T: KeyValueDatabase {
@@ -677,6 +1033,51 @@ impl<T> TreeStateProvider for BlockChainDatabase<T> where T: KeyValueDatabase {
 		self.block_header(BlockRef::Hash(*block_hash))
 			.map(|header| header.raw.final_sapling_root)
 	}
+
+	fn root_at_height(&self, epoch: EpochTag, height: u32) -> Option<H256> {
+		let block_hash = self.block_hash(height)?;
+		match epoch {
+			EpochTag::Sprout => self.sprout_block_root(&block_hash),
+			EpochTag::Sapling => self.sapling_block_root(&block_hash),
+		}
+	}
+
+	fn height_of_root(&self, epoch: EpochTag, root: &H256) -> Option<u32> {
+		self.get(Key::RootHeight(EpochRef::new(epoch, *root))).and_then(Value::as_root_height)
+	}
+}
+
+impl<T> ShieldedPoolProvider for BlockChainDatabase<T> where T: KeyValueDatabase {
+	fn shielded_pools_at_block(&self, block_hash: &H256) -> Option<ShieldedPools> {
+		// balances of side chain blocks, inserted by previous versions of the database,
+		// are computed from the closest ancestor with known balances
+		let mut route = Vec::new();
+		let mut next_hash = *block_hash;
+		let mut pools = loop {
+			if next_hash.is_zero() {
+				break ShieldedPools::default();
+			}
+
+			if let Some(pools) = self.get(Key::ShieldedPools(next_hash)).and_then(Value::as_shielded_pools) {
+				break pools;
+			}
+
+			if route.len() == MAX_FORK_ROUTE_PRESET {
+				return None;
+			}
+
+			let parent_hash = self.block_header(next_hash.into())?.raw.previous_header_hash;
+			route.push(next_hash);
+			next_hash = parent_hash;
+		};
+
+		for hash in route.into_iter().rev() {
+			let transactions = self.block_transactions(hash.into());
+			pools = pools.apply_block(transactions.iter().map(|tx| &tx.raw)).ok()?;
+		}
+
+		Some(pools)
+	}
 }
 
 impl<T> BlockChain for BlockChainDatabase<T> where T: KeyValueDatabase {
@@ -713,6 +1114,7 @@ impl<T> Forkable for BlockChainDatabase<T> where T: KeyValueDatabase {
 	fn switch_to_fork<'a>(&self, fork: Box<ForkChain + 'a>) -> Result<(), Error> {
 		let mut best_block = self.best_block.write();
 		*best_block = fork.store().best_block();
+		*self.side_chain_tips.write() = fork.store().side_chain_tips();
 		fork.flush()
 	}
 }
@@ -732,4 +1134,8 @@ impl<T> Store for BlockChainDatabase<T> where T: KeyValueDatabase {
 	fn best_header(&self) -> IndexedBlockHeader {
 		self.block_header(self.best_block().hash.into()).expect("best block header should be in db; qed")
 	}
+
+	fn side_chain_tips(&self) -> Vec<BestBlock> {
+		BlockChainDatabase::side_chain_tips(self)
+	}
 }
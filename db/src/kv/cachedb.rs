@@ -44,4 +44,37 @@ impl<T> KeyValueDatabase for CacheDatabase<T> where T: KeyValueDatabase {
 		}
 		self.db.get(key)
 	}
+
+	fn get_many(&self, keys: &[Key]) -> Result<Vec<KeyState<Value>>, String> {
+		let mut values = Vec::with_capacity(keys.len());
+		let mut uncached = Vec::new();
+		{
+			let mut header = self.header.lock();
+			for (index, key) in keys.iter().enumerate() {
+				let cached = match *key {
+					Key::BlockHeader(ref hash) => header.get_mut(hash).map(|state| state.clone().map(Value::BlockHeader)),
+					_ => None,
+				};
+
+				match cached {
+					Some(value) => values.push(value),
+					None => {
+						values.push(KeyState::Unknown);
+						uncached.push(index);
+					},
+				}
+			}
+		}
+
+		if uncached.is_empty() {
+			return Ok(values);
+		}
+
+		let uncached_keys = uncached.iter().map(|index| keys[*index].clone()).collect::<Vec<_>>();
+		for (index, value) in uncached.into_iter().zip(self.db.get_many(&uncached_keys)?) {
+			values[index] = value;
+		}
+
+		Ok(values)
+	}
 }
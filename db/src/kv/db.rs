@@ -4,4 +4,9 @@ pub trait KeyValueDatabase: Send + Sync {
 	fn write(&self, tx: Transaction) -> Result<(), String>;
 
 	fn get(&self, key: &Key) -> Result<KeyState<Value>, String>;
+
+	/// Reads values of all given keys at once. Values are returned in the same order as keys.
+	fn get_many(&self, keys: &[Key]) -> Result<Vec<KeyState<Value>>, String> {
+		keys.iter().map(|key| self.get(key)).collect()
+	}
 }
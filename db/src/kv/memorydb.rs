@@ -3,11 +3,12 @@ use std::sync::Arc;
 use std::mem::replace;
 use parking_lot::RwLock;
 use hash::H256;
+use primitives::bigint::U256;
 use bytes::Bytes;
 use ser::List;
 use chain::{Transaction as ChainTransaction, BlockHeader};
 use kv::{Transaction, Key, KeyState, Operation, Value, KeyValueDatabase, KeyValue};
-use storage::{TransactionMeta, EpochTag, EpochRef, SproutTreeState, SaplingTreeState};
+use storage::{TransactionMeta, EpochTag, EpochRef, SproutTreeState, SaplingTreeState, ShieldedPools};
 
 #[derive(Default, Debug)]
 struct InnerDatabase {
@@ -24,6 +25,10 @@ struct InnerDatabase {
 	sapling_nullifiers: HashMap<H256, KeyState<()>>,
 	sprout_tree_state: HashMap<H256, KeyState<SproutTreeState>>,
 	sapling_tree_state: HashMap<H256, KeyState<SaplingTreeState>>,
+	shielded_pools: HashMap<H256, KeyState<ShieldedPools>>,
+	chain_work: HashMap<H256, KeyState<U256>>,
+	sprout_root_heights: HashMap<H256, KeyState<u32>>,
+	sapling_root_heights: HashMap<H256, KeyState<u32>>,
 }
 
 #[derive(Default, Debug)]
@@ -89,6 +94,24 @@ impl MemoryDatabase {
 					KeyValue::SaplingTreeState,
 					|k| Key::TreeRoot(EpochRef::new(EpochTag::Sapling, k))));
 
+		let shielded_pools = replace(&mut db.shielded_pools, HashMap::default()).into_iter()
+			.flat_map(|(key, state)| state.into_operation(key, KeyValue::ShieldedPools, Key::ShieldedPools));
+
+		let chain_work = replace(&mut db.chain_work, HashMap::default()).into_iter()
+			.flat_map(|(key, state)| state.into_operation(key, KeyValue::ChainWork, Key::ChainWork));
+
+		let sprout_root_heights = replace(&mut db.sprout_root_heights, HashMap::default()).into_iter()
+			.flat_map(|(key, state)|
+				state.into_operation(key,
+					|k, v| KeyValue::RootHeight(EpochRef::new(EpochTag::Sprout, k), v),
+					|k| Key::RootHeight(EpochRef::new(EpochTag::Sprout, k))));
+
+		let sapling_root_heights = replace(&mut db.sapling_root_heights, HashMap::default()).into_iter()
+			.flat_map(|(key, state)|
+				state.into_operation(key,
+					|k, v| KeyValue::RootHeight(EpochRef::new(EpochTag::Sapling, k), v),
+					|k| Key::RootHeight(EpochRef::new(EpochTag::Sapling, k))));
+
 		Transaction {
 			operations: meta
 				.chain(block_hash)
@@ -103,6 +126,10 @@ impl MemoryDatabase {
 				.chain(sprout_block_root)
 				.chain(sprout_nullifiers)
 				.chain(sapling_nullifiers)
+				.chain(shielded_pools)
+				.chain(chain_work)
+				.chain(sprout_root_heights)
+				.chain(sapling_root_heights)
 				.collect()
 		}
 	}
@@ -129,6 +156,12 @@ impl KeyValueDatabase for MemoryDatabase {
 					KeyValue::SproutTreeState(key, value) => { db.sprout_tree_state.insert(key, KeyState::Insert(value)); },
 					KeyValue::SaplingTreeState(key, value) => { db.sapling_tree_state.insert(key, KeyState::Insert(value)); },
 					KeyValue::SproutBlockRoot(key, value) => { db.sprout_block_root.insert(key, KeyState::Insert(value)); },
+					KeyValue::ShieldedPools(key, value) => { db.shielded_pools.insert(key, KeyState::Insert(value)); },
+					KeyValue::ChainWork(key, value) => { db.chain_work.insert(key, KeyState::Insert(value)); },
+					KeyValue::RootHeight(key, value) => match key.epoch() {
+						EpochTag::Sprout => { db.sprout_root_heights.insert(*key.hash(), KeyState::Insert(value)); },
+						EpochTag::Sapling => { db.sapling_root_heights.insert(*key.hash(), KeyState::Insert(value)); },
+					},
 				},
 				Operation::Delete(delete) => match delete {
 					Key::Meta(key) => { db.meta.insert(key, KeyState::Delete); }
@@ -137,6 +170,7 @@ impl KeyValueDatabase for MemoryDatabase {
 					Key::BlockTransactions(key) => { db.block_transactions.insert(key, KeyState::Delete); }
 					Key::Transaction(key) => { db.transaction.insert(key, KeyState::Delete); }
 					Key::TransactionMeta(key) => { db.transaction_meta.insert(key, KeyState::Delete); }
+					Key::TransactionMetaSummary(key) => { db.transaction_meta.insert(key, KeyState::Delete); }
 					Key::BlockNumber(key) => { db.block_number.insert(key, KeyState::Delete); }
 					Key::Configuration(key) => { db.configuration.insert(key, KeyState::Delete); }
 					Key::Nullifier(key) => match key.epoch() {
@@ -148,6 +182,12 @@ impl KeyValueDatabase for MemoryDatabase {
 						EpochTag::Sapling => { db.sapling_tree_state.insert(*key.hash(), KeyState::Delete); },
 					},
 					Key::SproutBlockRoot(key) => { db.sprout_block_root.insert(key, KeyState::Delete); },
+					Key::ShieldedPools(key) => { db.shielded_pools.insert(key, KeyState::Delete); },
+					Key::ChainWork(key) => { db.chain_work.insert(key, KeyState::Delete); },
+					Key::RootHeight(key) => match key.epoch() {
+						EpochTag::Sprout => { db.sprout_root_heights.insert(*key.hash(), KeyState::Delete); },
+						EpochTag::Sapling => { db.sapling_root_heights.insert(*key.hash(), KeyState::Delete); },
+					},
 				},
 			}
 		}
@@ -155,14 +195,26 @@ impl KeyValueDatabase for MemoryDatabase {
 	}
 
 	fn get(&self, key: &Key) -> Result<KeyState<Value>, String> {
+		Ok(Self::get_locked(&self.db.read(), key))
+	}
+
+	fn get_many(&self, keys: &[Key]) -> Result<Vec<KeyState<Value>>, String> {
 		let db = self.db.read();
-		let result = match *key {
+		Ok(keys.iter().map(|key| Self::get_locked(&db, key)).collect())
+	}
+}
+
+impl MemoryDatabase {
+	fn get_locked(db: &InnerDatabase, key: &Key) -> KeyState<Value> {
+		match *key {
 			Key::Meta(ref key) => db.meta.get(key).cloned().unwrap_or_default().map(Value::Meta),
 			Key::BlockHash(ref key) => db.block_hash.get(key).cloned().unwrap_or_default().map(Value::BlockHash),
 			Key::BlockHeader(ref key) => db.block_header.get(key).cloned().unwrap_or_default().map(Value::BlockHeader),
 			Key::BlockTransactions(ref key) => db.block_transactions.get(key).cloned().unwrap_or_default().map(Value::BlockTransactions),
 			Key::Transaction(ref key) => db.transaction.get(key).cloned().unwrap_or_default().map(Value::Transaction),
 			Key::TransactionMeta(ref key) => db.transaction_meta.get(key).cloned().unwrap_or_default().map(Value::TransactionMeta),
+			Key::TransactionMetaSummary(ref key) => db.transaction_meta.get(key).cloned().unwrap_or_default()
+				.map(|meta| Value::TransactionMetaSummary(meta.height(), meta.is_coinbase())),
 			Key::BlockNumber(ref key) => db.block_number.get(key).cloned().unwrap_or_default().map(Value::BlockNumber),
 			Key::Configuration(ref key) => db.configuration.get(key).cloned().unwrap_or_default().map(Value::Configuration),
 			Key::Nullifier(ref key) => match key.epoch() {
@@ -174,9 +226,13 @@ impl KeyValueDatabase for MemoryDatabase {
 				EpochTag::Sapling => db.sapling_tree_state.get(key.hash()).cloned().unwrap_or_default().map(Value::SaplingTreeState),
 			},
 			Key::SproutBlockRoot(ref key) => db.sprout_block_root.get(key).cloned().unwrap_or_default().map(Value::SproutTreeRoot),
-		};
-
-		Ok(result)
+			Key::ShieldedPools(ref key) => db.shielded_pools.get(key).cloned().unwrap_or_default().map(Value::ShieldedPools),
+			Key::ChainWork(ref key) => db.chain_work.get(key).cloned().unwrap_or_default().map(Value::ChainWork),
+			Key::RootHeight(ref key) => match key.epoch() {
+				EpochTag::Sprout => db.sprout_root_heights.get(key.hash()).cloned().unwrap_or_default().map(Value::RootHeight),
+				EpochTag::Sapling => db.sapling_root_heights.get(key.hash()).cloned().unwrap_or_default().map(Value::RootHeight),
+			},
+		}
 	}
 }
 
@@ -209,4 +265,8 @@ impl KeyValueDatabase for SharedMemoryDatabase {
 	fn get(&self, key: &Key) -> Result<KeyState<Value>, String> {
 		self.db.get(key)
 	}
+
+	fn get_many(&self, keys: &[Key]) -> Result<Vec<KeyState<Value>>, String> {
+		self.db.get_many(keys)
+	}
 }
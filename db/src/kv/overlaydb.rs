@@ -30,6 +30,10 @@ impl<'a, T> KeyValueDatabase for OverlayDatabase<'a, T> where T: 'a + KeyValueDa
 			exists => Ok(exists)
 		}
 	}
+
+	fn get_many(&self, keys: &[Key]) -> Result<Vec<KeyState<Value>>, String> {
+		get_many_with_overlay(&self.overlay, self.db, keys)
+	}
 }
 
 pub struct AutoFlushingOverlayDatabase<T> where T: KeyValueDatabase {
@@ -72,6 +76,10 @@ impl<T> KeyValueDatabase for AutoFlushingOverlayDatabase<T> where T: KeyValueDat
 			exists => Ok(exists)
 		}
 	}
+
+	fn get_many(&self, keys: &[Key]) -> Result<Vec<KeyState<Value>>, String> {
+		get_many_with_overlay(&self.overlay, &self.db, keys)
+	}
 }
 
 impl<T> Drop for AutoFlushingOverlayDatabase<T> where T: KeyValueDatabase {
@@ -79,3 +87,28 @@ impl<T> Drop for AutoFlushingOverlayDatabase<T> where T: KeyValueDatabase {
 		self.flush().expect("Failed to save database");
 	}
 }
+
+/// Reads values from the overlay, falling back to the single batched read from the database.
+fn get_many_with_overlay<T>(overlay: &MemoryDatabase, db: &T, keys: &[Key]) -> Result<Vec<KeyState<Value>>, String>
+	where T: KeyValueDatabase
+{
+	let mut values = overlay.get_many(keys)?;
+	let unknown = values.iter()
+		.enumerate()
+		.filter(|&(_, value)| match *value {
+			KeyState::Unknown => true,
+			_ => false,
+		})
+		.map(|(index, _)| index)
+		.collect::<Vec<_>>();
+	if unknown.is_empty() {
+		return Ok(values);
+	}
+
+	let unknown_keys = unknown.iter().map(|index| keys[*index].clone()).collect::<Vec<_>>();
+	for (index, value) in unknown.into_iter().zip(db.get_many(&unknown_keys)?) {
+		values[index] = value;
+	}
+
+	Ok(values)
+}
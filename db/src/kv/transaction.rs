@@ -1,8 +1,9 @@
 use bytes::Bytes;
 use hash::H256;
+use primitives::bigint::U256;
 use ser::{serialize, List, deserialize};
 use chain::{Transaction as ChainTransaction, BlockHeader};
-use storage::{TransactionMeta, EpochTag, EpochRef, SproutTreeState, SaplingTreeState};
+use storage::{TransactionMeta, EpochTag, EpochRef, SproutTreeState, SaplingTreeState, ShieldedPools};
 
 pub const COL_COUNT: u32 = 16;
 pub const COL_META: u32 = 0;
@@ -17,6 +18,10 @@ pub const COL_SAPLING_NULLIFIERS: u32 = 8;
 pub const COL_SPROUT_BLOCK_ROOTS: u32 = 9;
 pub const COL_TREE_STATES: u32 = 10;
 pub const COL_CONFIGURATION: u32 = 11;
+pub const COL_SHIELDED_POOLS: u32 = 12;
+pub const COL_CHAIN_WORK: u32 = 13;
+pub const COL_SPROUT_ROOT_HEIGHTS: u32 = 14;
+pub const COL_SAPLING_ROOT_HEIGHTS: u32 = 15;
 
 #[derive(Debug)]
 pub enum Operation {
@@ -38,9 +43,12 @@ pub enum KeyValue {
 	SproutTreeState(H256, SproutTreeState),
 	SaplingTreeState(H256, SaplingTreeState),
 	SproutBlockRoot(H256, H256),
+	ShieldedPools(H256, ShieldedPools),
+	ChainWork(H256, U256),
+	RootHeight(EpochRef, u32),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Key {
 	Meta(&'static str),
 	BlockHash(u32),
@@ -48,11 +56,16 @@ pub enum Key {
 	BlockTransactions(H256),
 	Transaction(H256),
 	TransactionMeta(H256),
+	/// Block height and coinbase flag of the transaction meta.
+	TransactionMetaSummary(H256),
 	BlockNumber(H256),
 	Configuration(&'static str),
 	Nullifier(EpochRef),
 	TreeRoot(EpochRef),
 	SproutBlockRoot(H256),
+	ShieldedPools(H256),
+	ChainWork(H256),
+	RootHeight(EpochRef),
 }
 
 #[derive(Debug, Clone)]
@@ -63,12 +76,16 @@ pub enum Value {
 	BlockTransactions(List<H256>),
 	Transaction(ChainTransaction),
 	TransactionMeta(TransactionMeta),
+	TransactionMetaSummary(u32, bool),
 	BlockNumber(u32),
 	Configuration(Bytes),
 	Empty,
 	SproutTreeState(SproutTreeState),
 	SaplingTreeState(SaplingTreeState),
 	SproutTreeRoot(H256),
+	ShieldedPools(ShieldedPools),
+	ChainWork(U256),
+	RootHeight(u32),
 }
 
 impl Value {
@@ -80,6 +97,8 @@ impl Value {
 			Key::BlockTransactions(_) => deserialize(bytes).map(Value::BlockTransactions),
 			Key::Transaction(_) => deserialize(bytes).map(Value::Transaction),
 			Key::TransactionMeta(_) => deserialize(bytes).map(Value::TransactionMeta),
+			Key::TransactionMetaSummary(_) => TransactionMeta::read_height_and_coinbase(bytes)
+				.map(|(height, is_coinbase)| Value::TransactionMetaSummary(height, is_coinbase)),
 			Key::BlockNumber(_) => deserialize(bytes).map(Value::BlockNumber),
 			Key::Configuration(_) => deserialize(bytes).map(Value::Configuration),
 			Key::Nullifier(_) => Ok(Value::Empty),
@@ -88,6 +107,9 @@ impl Value {
 				EpochTag::Sapling => deserialize(bytes).map(Value::SaplingTreeState),
 			},
 			Key::SproutBlockRoot(_) => deserialize(bytes).map(Value::SproutTreeRoot),
+			Key::ShieldedPools(_) => deserialize(bytes).map(Value::ShieldedPools),
+			Key::ChainWork(_) => deserialize(bytes).map(Value::ChainWork),
+			Key::RootHeight(_) => deserialize(bytes).map(Value::RootHeight),
 		}.map_err(|e| format!("{:?}", e))
 	}
 
@@ -133,6 +155,13 @@ impl Value {
 		}
 	}
 
+	pub fn as_transaction_meta_summary(self) -> Option<(u32, bool)> {
+		match self {
+			Value::TransactionMetaSummary(height, is_coinbase) => Some((height, is_coinbase)),
+			_ => None,
+		}
+	}
+
 	pub fn as_block_number(self) -> Option<u32> {
 		match self {
 			Value::BlockNumber(number) => Some(number),
@@ -167,6 +196,27 @@ impl Value {
 			_ => None,
 		}
 	}
+
+	pub fn as_shielded_pools(self) -> Option<ShieldedPools> {
+		match self {
+			Value::ShieldedPools(pools) => Some(pools),
+			_ => None,
+		}
+	}
+
+	pub fn as_chain_work(self) -> Option<U256> {
+		match self {
+			Value::ChainWork(work) => Some(work),
+			_ => None,
+		}
+	}
+
+	pub fn as_root_height(self) -> Option<u32> {
+		match self {
+			Value::RootHeight(height) => Some(height),
+			_ => None,
+		}
+	}
 }
 
 #[derive(Debug, Clone)]
@@ -277,6 +327,12 @@ impl<'a> From<&'a KeyValue> for RawKeyValue {
 			KeyValue::SaplingTreeState(ref key, ref value) => (COL_TREE_STATES, serialize(key), serialize(value)),
 			KeyValue::SproutBlockRoot(ref key, ref value) => (COL_SPROUT_BLOCK_ROOTS, serialize(key), serialize(value)),
 			KeyValue::Configuration(ref key, ref value) => (COL_CONFIGURATION, serialize(key), serialize(value)),
+			KeyValue::ShieldedPools(ref key, ref value) => (COL_SHIELDED_POOLS, serialize(key), serialize(value)),
+			KeyValue::ChainWork(ref key, ref value) => (COL_CHAIN_WORK, serialize(key), serialize(value)),
+			KeyValue::RootHeight(ref key, ref value) => match key.epoch() {
+				EpochTag::Sprout => (COL_SPROUT_ROOT_HEIGHTS, serialize(key.hash()), serialize(value)),
+				EpochTag::Sapling => (COL_SAPLING_ROOT_HEIGHTS, serialize(key.hash()), serialize(value)),
+			},
 		};
 
 		RawKeyValue {
@@ -310,6 +366,7 @@ impl<'a> From<&'a Key> for RawKey {
 			Key::BlockTransactions(ref key) => (COL_BLOCK_TRANSACTIONS, serialize(key)),
 			Key::Transaction(ref key) => (COL_TRANSACTIONS, serialize(key)),
 			Key::TransactionMeta(ref key) => (COL_TRANSACTIONS_META, serialize(key)),
+			Key::TransactionMetaSummary(ref key) => (COL_TRANSACTIONS_META, serialize(key)),
 			Key::Nullifier(ref key) => match key.epoch() {
 				EpochTag::Sprout => (COL_SPROUT_NULLIFIERS, serialize(key.hash())),
 				EpochTag::Sapling => (COL_SAPLING_NULLIFIERS, serialize(key.hash())),
@@ -318,6 +375,12 @@ impl<'a> From<&'a Key> for RawKey {
 			Key::BlockNumber(ref key) => (COL_BLOCK_NUMBERS, serialize(key)),
 			Key::SproutBlockRoot(ref key) => (COL_SPROUT_BLOCK_ROOTS, serialize(key)),
 			Key::Configuration(ref key) => (COL_CONFIGURATION, serialize(key)),
+			Key::ShieldedPools(ref key) => (COL_SHIELDED_POOLS, serialize(key)),
+			Key::ChainWork(ref key) => (COL_CHAIN_WORK, serialize(key)),
+			Key::RootHeight(ref key) => match key.epoch() {
+				EpochTag::Sprout => (COL_SPROUT_ROOT_HEIGHTS, serialize(key.hash())),
+				EpochTag::Sapling => (COL_SAPLING_ROOT_HEIGHTS, serialize(key.hash())),
+			},
 		};
 
 		RawKey {
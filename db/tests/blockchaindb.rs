@@ -3,8 +3,12 @@ extern crate storage;
 extern crate db;
 extern crate test_data;
 
-use chain::IndexedBlock;
-use storage::{ForkChain, BlockProvider, SideChainOrigin};
+use chain::{IndexedBlock, Transaction, Sapling, SaplingSpendDescription, JoinSplit, JoinSplitDescription};
+use chain::hash::H256;
+use chain::bigint::U256;
+use storage::{ForkChain, BlockProvider, BlockHeaderProvider, SideChainOrigin, NullifierTracker, EpochRef, EpochTag,
+	ShieldedPools, ShieldedPoolProvider, BlockAncestors, BlockRef, TransactionMetaProvider, BestBlock, TreeStateProvider,
+	TransactionOutputProvider};
 use db::BlockChainDatabase;
 use db::kv::{MemoryDatabase, SharedMemoryDatabase};
 
@@ -128,3 +132,465 @@ fn switch_to_simple_fork() {
 	assert_eq!(store.best_block().hash, store.block_hash(2).unwrap());
 
 }
+
+#[test]
+fn contains_any_nullifier() {
+	let store = BlockChainDatabase::init_test_chain(vec![test_data::genesis().into()]);
+
+	let spend = |nullifier: u8| SaplingSpendDescription {
+		nullifier: [nullifier; 32],
+		..Default::default()
+	};
+	let nullifier = |nullifier: u8| EpochRef::new(EpochTag::Sapling, H256::from([nullifier; 32]));
+
+	let tx: Transaction = test_data::TransactionBuilder::with_sapling(Sapling {
+		spends: vec![spend(2), spend(4)],
+		..Default::default()
+	}).into();
+	let block: IndexedBlock = test_data::block_builder()
+		.header().parent(test_data::genesis().hash()).build()
+		.transaction().coinbase().build()
+		.with_transaction(tx)
+		.build()
+		.into();
+	store.insert(block.clone()).unwrap();
+	store.canonize(block.hash()).unwrap();
+
+	// when none of nullifiers is known
+	assert_eq!(store.contains_any(&[]), None);
+	assert_eq!(store.contains_any(&[nullifier(1), nullifier(3)]), None);
+
+	// when some nullifiers are known, the first known is returned
+	assert_eq!(store.contains_any(&[nullifier(1), nullifier(4), nullifier(3), nullifier(2)]), Some(nullifier(4)));
+	assert_eq!(store.contains_any(&[nullifier(2), nullifier(4)]), Some(nullifier(2)));
+
+	// when nullifier is known, but in other epoch
+	assert_eq!(store.contains_any(&[EpochRef::new(EpochTag::Sprout, H256::from([2; 32]))]), None);
+}
+
+#[test]
+fn shielded_pools_tracking() {
+	let store = BlockChainDatabase::init_test_chain(vec![test_data::genesis().into()]);
+	assert_eq!(store.shielded_pools_at_block(&test_data::genesis().hash()), Some(ShieldedPools::default()));
+
+	let join_split = |value_pub_old: u64, value_pub_new: u64| JoinSplit {
+		descriptions: vec![JoinSplitDescription {
+			value_pub_old: value_pub_old,
+			value_pub_new: value_pub_new,
+			..Default::default()
+		}],
+		..Default::default()
+	};
+
+	// when value is moved to shielded pools
+	let block1: IndexedBlock = test_data::block_builder()
+		.header().parent(test_data::genesis().hash()).build()
+		.transaction().coinbase().build()
+		.with_transaction(test_data::TransactionBuilder::with_join_split(join_split(100, 0)).into())
+		.with_transaction(test_data::TransactionBuilder::with_sapling(Sapling {
+			balancing_value: -50,
+			..Default::default()
+		}).into())
+		.build()
+		.into();
+	store.insert(block1.clone()).unwrap();
+	assert_eq!(store.shielded_pools_at_block(block1.hash()), Some(ShieldedPools { sprout: 100, sapling: 50 }));
+
+	// when block moves more value out of the pool than it holds
+	let block2: IndexedBlock = test_data::block_builder()
+		.header().parent(*block1.hash()).build()
+		.transaction().coinbase().build()
+		.with_transaction(test_data::TransactionBuilder::with_join_split(join_split(0, 101)).into())
+		.build()
+		.into();
+	store.insert(block2.clone()).unwrap();
+	assert_eq!(store.shielded_pools_at_block(block2.hash()), None);
+}
+
+#[test]
+fn shielded_pools_backfill() {
+	let shared_database = SharedMemoryDatabase::default();
+	let block1: IndexedBlock = test_data::block_builder()
+		.header().parent(test_data::genesis().hash()).build()
+		.transaction().coinbase().build()
+		.with_transaction(test_data::TransactionBuilder::with_sapling(Sapling {
+			balancing_value: -50,
+			..Default::default()
+		}).into())
+		.build()
+		.into();
+	let block2: IndexedBlock = test_data::block_builder()
+		.header().parent(*block1.hash()).build()
+		.transaction().coinbase().build()
+		.build()
+		.into();
+
+	{
+		let store = BlockChainDatabase::open(shared_database.clone());
+		for block in vec![test_data::genesis().into(), block1.clone(), block2.clone()] {
+			let hash = *block.hash();
+			store.insert(block).unwrap();
+			store.canonize(&hash).unwrap();
+		}
+	}
+
+	// when database has been created by version that hasn't tracked shielded pools
+	let mut update = db::kv::Transaction::new();
+	update.delete(db::kv::Key::ShieldedPools(test_data::genesis().hash()));
+	update.delete(db::kv::Key::ShieldedPools(*block1.hash()));
+	update.delete(db::kv::Key::ShieldedPools(*block2.hash()));
+	db::kv::KeyValueDatabase::write(&shared_database, update).unwrap();
+
+	// missing balances are computed from ancestors
+	let store = BlockChainDatabase::open(shared_database.clone());
+	assert_eq!(store.shielded_pools_at_block(block2.hash()), Some(ShieldedPools { sprout: 0, sapling: 50 }));
+
+	// missing balances of canon blocks are stored by backfill
+	store.backfill().unwrap();
+	let stored_pools = |hash: &H256| db::kv::KeyValueDatabase::get(&shared_database, &db::kv::Key::ShieldedPools(*hash))
+		.unwrap()
+		.into_option()
+		.and_then(db::kv::Value::as_shielded_pools);
+	assert_eq!(stored_pools(&test_data::genesis().hash()), Some(ShieldedPools::default()));
+	assert_eq!(stored_pools(block1.hash()), Some(ShieldedPools { sprout: 0, sapling: 50 }));
+	assert_eq!(stored_pools(block2.hash()), Some(ShieldedPools { sprout: 0, sapling: 50 }));
+
+	// backfill is only performed once
+	let mut update = db::kv::Transaction::new();
+	update.delete(db::kv::Key::ShieldedPools(*block2.hash()));
+	db::kv::KeyValueDatabase::write(&shared_database, update).unwrap();
+	store.backfill().unwrap();
+	assert_eq!(stored_pools(block2.hash()), None);
+}
+
+#[test]
+fn block_headers_range() {
+	let blocks = test_data::build_n_empty_blocks_from_genesis(5, 1);
+	let mut chain: Vec<IndexedBlock> = vec![test_data::genesis().into()];
+	chain.extend(blocks.into_iter().map(Into::into));
+	let store = BlockChainDatabase::init_test_chain(chain);
+
+	let expected = (1u32..4)
+		.map(|number| store.block_header(number.into()).unwrap().hash)
+		.collect::<Vec<_>>();
+	let actual = store.block_headers_range(1..4).into_iter()
+		.map(|header| header.hash)
+		.collect::<Vec<_>>();
+	assert_eq!(actual, expected);
+
+	// range is truncated at the first unknown block
+	assert_eq!(store.block_headers_range(4..10).len(), 2);
+	assert!(store.block_headers_range(10..20).is_empty());
+}
+
+#[test]
+fn block_ancestors_stop_at_genesis() {
+	let blocks = test_data::build_n_empty_blocks_from_genesis(2, 1);
+	let mut chain: Vec<IndexedBlock> = vec![test_data::genesis().into()];
+	chain.extend(blocks.into_iter().map(Into::into));
+	let store = BlockChainDatabase::init_test_chain(chain);
+
+	let b2 = store.block_header(BlockRef::Number(2)).unwrap();
+	let mut ancestors = BlockAncestors::new(b2.raw.previous_header_hash.clone().into(), &store);
+	assert_eq!(ancestors.next().map(|header| header.hash), store.block_hash(1));
+	assert_eq!(ancestors.next().map(|header| header.hash), store.block_hash(0));
+	assert_eq!(ancestors.next(), None);
+	assert_eq!(ancestors.next(), None);
+
+	assert_eq!(BlockAncestors::new(BlockRef::Number(2), &store).ancestors_until_genesis_count(), 3);
+	assert_eq!(BlockAncestors::new(BlockRef::Number(0), &store).ancestors_until_genesis_count(), 1);
+}
+
+#[test]
+fn transaction_block_hash() {
+	let store = BlockChainDatabase::open(MemoryDatabase::default());
+	let b0: IndexedBlock = test_data::block_h0().into();
+	let b1: IndexedBlock = test_data::block_h1().into();
+	let b2: IndexedBlock = test_data::block_h2().into();
+
+	store.insert(b0.clone()).unwrap();
+	store.insert(b1.clone()).unwrap();
+	store.insert(b2.clone()).unwrap();
+	store.canonize(b0.hash()).unwrap();
+	store.canonize(b1.hash()).unwrap();
+	store.canonize(b2.hash()).unwrap();
+
+	assert_eq!(store.transaction_block_hash(&b1.transactions[0].hash), store.block_hash(1));
+	assert_eq!(store.transaction_block_hash(&b1.transactions[0].hash), Some(b1.hash().clone()));
+	assert_eq!(store.transaction_block_hash(&b2.transactions[0].hash), Some(b2.hash().clone()));
+
+	// transactions of decanonized block are not in the canon chain anymore
+	store.decanonize().unwrap();
+	assert_eq!(store.transaction_block_hash(&b2.transactions[0].hash), None);
+	assert_eq!(store.transaction_block_hash(&H256::default()), None);
+}
+
+#[test]
+fn outputs_for_transaction() {
+	let b0 = test_data::block_builder().header().nonce(1.into()).build()
+		.transaction()
+			.output().value(10).build()
+			.output().value(20).build()
+			.build()
+		.build();
+	let tx0_hash = b0.transactions[0].hash();
+	let tx1: Transaction = test_data::TransactionBuilder::default()
+		.add_input(&b0.transactions[0], 1)
+		.add_input(&Transaction::default(), 0)
+		.add_input(&b0.transactions[0], 0)
+		.add_input(&b0.transactions[0], 2)
+		.into();
+	assert_eq!(tx1.inputs[0].previous_output.hash, tx0_hash);
+
+	let store = BlockChainDatabase::init_test_chain(vec![b0.clone().into()]);
+	let outputs = store.outputs_for_transaction(&tx1, ::std::usize::MAX);
+
+	assert_eq!(outputs, vec![
+		Some(b0.transactions[0].outputs[1].clone()),
+		None,
+		Some(b0.transactions[0].outputs[0].clone()),
+		None,
+	]);
+	let one_by_one: Vec<_> = tx1.inputs.iter()
+		.map(|input| store.transaction_output(&input.previous_output, ::std::usize::MAX))
+		.collect();
+	assert_eq!(outputs, one_by_one);
+}
+
+#[test]
+fn side_chain_tips_are_tracked() {
+	let store = BlockChainDatabase::init_test_chain(vec![test_data::block_h0().into(), test_data::block_h1().into()]);
+	let b1: IndexedBlock = test_data::block_h1().into();
+
+	// side chain of two blocks, forked at genesis
+	let s1: IndexedBlock = test_data::block_builder()
+		.header().parent(test_data::block_h0().hash()).build()
+		.transaction().coinbase().build()
+		.build()
+		.into();
+	let s2: IndexedBlock = test_data::block_builder()
+		.header().parent(*s1.hash()).build()
+		.transaction().coinbase().build()
+		.build()
+		.into();
+
+	store.insert(s1.clone()).unwrap();
+	assert_eq!(store.side_chain_tips(), vec![BestBlock { number: 1, hash: *s1.hash() }]);
+
+	store.insert(s2.clone()).unwrap();
+	assert_eq!(store.side_chain_tips(), vec![BestBlock { number: 2, hash: *s2.hash() }]);
+
+	// switch to the side chain => previous best block becomes side chain tip
+	let side_chain_origin = SideChainOrigin {
+		ancestor: 0,
+		canonized_route: vec![*s1.hash()],
+		decanonized_route: vec![*b1.hash()],
+		block_number: 2,
+	};
+	let fork = store.fork(side_chain_origin).unwrap();
+	fork.store().canonize(s2.hash()).unwrap();
+	store.switch_to_fork(fork).unwrap();
+
+	assert_eq!(store.best_block(), BestBlock { number: 2, hash: *s2.hash() });
+	assert_eq!(store.side_chain_tips(), vec![BestBlock { number: 1, hash: *b1.hash() }]);
+}
+
+#[test]
+fn side_chain_tips_are_persisted() {
+	let shared_database = SharedMemoryDatabase::default();
+	let b0: IndexedBlock = test_data::block_h0().into();
+	let b1: IndexedBlock = test_data::block_h1().into();
+	let side_block = |nonce: u8| -> IndexedBlock {
+		test_data::block_builder()
+			.header().parent(*b0.hash()).nonce(nonce.into()).build()
+			.transaction().coinbase().build()
+			.build()
+			.into()
+	};
+
+	{
+		let store = BlockChainDatabase::open(shared_database.clone());
+		store.insert(b0.clone()).unwrap();
+		store.canonize(b0.hash()).unwrap();
+		store.insert(b1.clone()).unwrap();
+		store.canonize(b1.hash()).unwrap();
+		store.insert(side_block(0)).unwrap();
+	}
+
+	let store = BlockChainDatabase::open(shared_database);
+	assert_eq!(store.side_chain_tips(), vec![BestBlock { number: 1, hash: *side_block(0).hash() }]);
+
+	// number of remembered tips is limited
+	for nonce in 1..100 {
+		store.insert(side_block(nonce)).unwrap();
+	}
+	assert_eq!(store.side_chain_tips().len(), 64);
+}
+
+#[test]
+fn tree_roots_by_height() {
+	let root1 = H256::from([1; 32]);
+	let root2 = H256::from([2; 32]);
+	let block = |parent: H256, root: H256| -> IndexedBlock {
+		test_data::block_builder()
+			.header().parent(parent).final_sapling_root(root).build()
+			.transaction().coinbase().build()
+			.build()
+			.into()
+	};
+	let b1 = block(test_data::genesis().hash(), root1);
+	let b2 = block(*b1.hash(), root1);
+	let b3 = block(*b2.hash(), root2);
+	let store = BlockChainDatabase::init_test_chain(vec![test_data::genesis().into(), b1, b2, b3]);
+
+	assert_eq!(store.root_at_height(EpochTag::Sapling, 1), Some(root1));
+	assert_eq!(store.root_at_height(EpochTag::Sapling, 2), Some(root1));
+	assert_eq!(store.root_at_height(EpochTag::Sapling, 3), Some(root2));
+	assert_eq!(store.root_at_height(EpochTag::Sapling, 4), None);
+
+	// root is reported at the first block that has produced it
+	assert_eq!(store.height_of_root(EpochTag::Sapling, &root1), Some(1));
+	assert_eq!(store.height_of_root(EpochTag::Sapling, &root2), Some(3));
+	assert_eq!(store.height_of_root(EpochTag::Sapling, &H256::from([3; 32])), None);
+
+	// sprout tree is empty in all blocks
+	let sprout_root = store.root_at_height(EpochTag::Sprout, 0).unwrap();
+	assert_eq!(store.root_at_height(EpochTag::Sprout, 3), Some(sprout_root));
+	assert_eq!(store.height_of_root(EpochTag::Sprout, &sprout_root), Some(0));
+	assert_eq!(store.height_of_root(EpochTag::Sapling, &sprout_root), None);
+
+	// roots of decanonized blocks are forgotten, unless they're produced by canon blocks
+	store.decanonize().unwrap();
+	assert_eq!(store.height_of_root(EpochTag::Sapling, &root2), None);
+	store.decanonize().unwrap();
+	assert_eq!(store.height_of_root(EpochTag::Sapling, &root1), Some(1));
+	assert_eq!(store.height_of_root(EpochTag::Sprout, &sprout_root), Some(0));
+	store.decanonize().unwrap();
+	assert_eq!(store.height_of_root(EpochTag::Sapling, &root1), None);
+}
+
+#[test]
+fn tree_root_heights_backfill() {
+	let shared_database = SharedMemoryDatabase::default();
+	let root1 = H256::from([1; 32]);
+	let root2 = H256::from([2; 32]);
+	let block = |parent: H256, root: H256| -> IndexedBlock {
+		test_data::block_builder()
+			.header().parent(parent).final_sapling_root(root).build()
+			.transaction().coinbase().build()
+			.build()
+			.into()
+	};
+	let b1 = block(test_data::genesis().hash(), root1);
+	let b2 = block(*b1.hash(), root1);
+	let b3 = block(*b2.hash(), root2);
+
+	let sprout_root = {
+		let store = BlockChainDatabase::open(shared_database.clone());
+		for block in vec![test_data::genesis().into(), b1, b2, b3] {
+			let hash = *block.hash();
+			store.insert(block).unwrap();
+			store.canonize(&hash).unwrap();
+		}
+		store.root_at_height(EpochTag::Sprout, 0).unwrap()
+	};
+
+	// when database has been created by version that hasn't indexed tree roots
+	let mut update = db::kv::Transaction::new();
+	update.delete(db::kv::Key::RootHeight(EpochRef::new(EpochTag::Sprout, sprout_root)));
+	update.delete(db::kv::Key::RootHeight(EpochRef::new(EpochTag::Sapling, H256::default())));
+	update.delete(db::kv::Key::RootHeight(EpochRef::new(EpochTag::Sapling, root1)));
+	update.delete(db::kv::Key::RootHeight(EpochRef::new(EpochTag::Sapling, root2)));
+	db::kv::KeyValueDatabase::write(&shared_database, update).unwrap();
+
+	let store = BlockChainDatabase::open(shared_database);
+	assert_eq!(store.height_of_root(EpochTag::Sapling, &root1), None);
+
+	// missing heights of canon roots are stored by backfill
+	store.backfill().unwrap();
+	assert_eq!(store.height_of_root(EpochTag::Sprout, &sprout_root), Some(0));
+	assert_eq!(store.height_of_root(EpochTag::Sapling, &H256::default()), Some(0));
+	assert_eq!(store.height_of_root(EpochTag::Sapling, &root1), Some(1));
+	assert_eq!(store.height_of_root(EpochTag::Sapling, &root2), Some(3));
+}
+
+#[test]
+fn chain_work_accumulation() {
+	let b0: IndexedBlock = test_data::block_h0().into();
+	let b1: IndexedBlock = test_data::block_h1().into();
+	let store = BlockChainDatabase::init_test_chain(vec![b0.clone(), b1.clone()]);
+
+	let b0_work = b0.header.raw.bits.to_work();
+	let b1_work = b1.header.raw.bits.to_work();
+	assert_eq!(store.block_chain_work(b0.hash()), Some(b0_work));
+	assert_eq!(store.block_chain_work(b1.hash()), Some(b0_work + b1_work));
+	assert_eq!(store.block_chain_work(b1.hash()), Some(U256::from(0x4000)));
+	assert_eq!(store.block_chain_work(&H256::from(1)), None);
+}
+
+#[test]
+fn chain_work_backfill() {
+	let shared_database = SharedMemoryDatabase::default();
+	let b0: IndexedBlock = test_data::block_h0().into();
+	let b1: IndexedBlock = test_data::block_h1().into();
+	let b2: IndexedBlock = test_data::block_h2().into();
+
+	{
+		let store = BlockChainDatabase::open(shared_database.clone());
+		for block in vec![b0.clone(), b1.clone(), b2.clone()] {
+			let hash = *block.hash();
+			store.insert(block).unwrap();
+			store.canonize(&hash).unwrap();
+		}
+	}
+
+	// when database has been created by version that hasn't tracked chain work
+	let mut update = db::kv::Transaction::new();
+	update.delete(db::kv::Key::ChainWork(*b0.hash()));
+	update.delete(db::kv::Key::ChainWork(*b1.hash()));
+	update.delete(db::kv::Key::ChainWork(*b2.hash()));
+	db::kv::KeyValueDatabase::write(&shared_database, update).unwrap();
+
+	// missing chain work is computed from ancestors
+	let store = BlockChainDatabase::open(shared_database.clone());
+	assert_eq!(store.block_chain_work(b2.hash()), Some(U256::from(0x6000)));
+
+	// missing chain work of canon blocks is stored by backfill
+	store.backfill().unwrap();
+	let stored_work = |hash: &H256| db::kv::KeyValueDatabase::get(&shared_database, &db::kv::Key::ChainWork(*hash))
+		.unwrap()
+		.into_option()
+		.and_then(db::kv::Value::as_chain_work);
+	assert_eq!(stored_work(b0.hash()), Some(U256::from(0x2000)));
+	assert_eq!(stored_work(b1.hash()), Some(U256::from(0x4000)));
+	assert_eq!(stored_work(b2.hash()), Some(U256::from(0x6000)));
+}
+
+#[test]
+fn transaction_is_coinbase_and_height() {
+	let b0 = test_data::block_builder().header().nonce(1.into()).build()
+		.transaction().coinbase().output().value(10).build().build()
+		.build();
+	let b1 = test_data::block_builder().header().nonce(2.into()).parent(b0.hash()).build()
+		.transaction().coinbase().output().value(20).build().build()
+		.transaction().input().hash(b0.transactions[0].hash()).build().output().value(10).build().build()
+		.build();
+
+	let store = BlockChainDatabase::init_test_chain(vec![b0.clone().into(), b1.clone().into()]);
+
+	for tx in b0.transactions.iter().chain(b1.transactions.iter()) {
+		let hash = tx.hash();
+		let meta = store.transaction_meta(&hash).unwrap();
+		assert_eq!(store.transaction_is_coinbase(&hash), Some(meta.is_coinbase()));
+		assert_eq!(store.transaction_height(&hash), Some(meta.height()));
+	}
+
+	assert_eq!(store.transaction_is_coinbase(&b1.transactions[0].hash()), Some(true));
+	assert_eq!(store.transaction_is_coinbase(&b1.transactions[1].hash()), Some(false));
+	assert_eq!(store.transaction_height(&b1.transactions[1].hash()), Some(1));
+
+	let unknown = H256::from(1);
+	assert!(store.transaction_meta(&unknown).is_none());
+	assert_eq!(store.transaction_is_coinbase(&unknown), None);
+	assert_eq!(store.transaction_height(&unknown), None);
+}
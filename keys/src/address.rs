@@ -56,9 +56,9 @@ impl DisplayLayout for Address {
 
 		result[..2].copy_from_slice(&match (self.network, self.kind) {
 			(Network::Mainnet, Type::P2PKH) => [0x1C, 0xB8],
-			(Network::Testnet, Type::P2PKH) => [0x1D, 0x25],
+			(Network::Testnet, Type::P2PKH) | (Network::Regtest, Type::P2PKH) => [0x1D, 0x25],
 			(Network::Mainnet, Type::P2SH) => [0x1C, 0xBD],
-			(Network::Testnet, Type::P2SH) => [0x1C, 0xBA],
+			(Network::Testnet, Type::P2SH) | (Network::Regtest, Type::P2SH) => [0x1C, 0xBA],
 		});
 
 		result[2..22].copy_from_slice(&*self.hash);
@@ -77,6 +77,7 @@ impl DisplayLayout for Address {
 			return Err(Error::InvalidChecksum);
 		}
 
+		// regtest prefixes are the same as testnet => regtest addresses are parsed as testnet
 		let (network, kind) = match (data[0], data[1]) {
 			(0x1C, 0xB8) => (Network::Mainnet, Type::P2PKH),
 			(0x1C, 0xBD) => (Network::Mainnet, Type::P2SH),
@@ -145,4 +146,18 @@ mod tests {
 
 		assert_eq!(address, "t1h8SqgtM3QM5e2M8EzhhT1yL2PXXtA6oqe".into());
 	}
+
+	#[test]
+	fn test_regtest_address_is_parsed_as_testnet() {
+		for &kind in &[Type::P2PKH, Type::P2SH] {
+			let address = Address {
+				kind: kind,
+				network: Network::Regtest,
+				hash: "ff197b14e502ab41f3bc8ccb48c4abac9eab35bc".into(),
+			};
+
+			let parsed: Address = address.to_string().parse().unwrap();
+			assert_eq!(parsed, Address { network: Network::Testnet, ..address });
+		}
+	}
 }
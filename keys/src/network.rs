@@ -2,4 +2,7 @@
 pub enum Network {
 	Mainnet,
 	Testnet,
+	/// Regtest network. Uses the same address prefixes as the testnet, so regtest
+	/// addresses and private keys are parsed back as testnet ones.
+	Regtest,
 }
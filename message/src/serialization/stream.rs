@@ -9,7 +9,7 @@ pub fn serialize_payload<T>(t: &T, version: u32) -> MessageResult<Bytes> where T
 }
 
 pub struct PayloadStream {
-	stream: Stream,
+	stream: Stream<'static>,
 	version: u32,
 }
 
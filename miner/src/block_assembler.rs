@@ -186,6 +186,19 @@ impl<'a, T> TransactionOutputProvider for FittingTransactionsIterator<'a, T> whe
 			})
 	}
 
+	fn outputs_for_transaction(&self, tx: &Transaction, transaction_index: usize) -> Vec<Option<TransactionOutput>> {
+		tx.inputs.iter()
+			.zip(self.store.outputs_for_transaction(tx, transaction_index))
+			.map(|(input, output)| output.or_else(|| {
+				let prevout = &input.previous_output;
+				self.previous_entries.iter()
+					.find(|e| e.hash == prevout.hash)
+					.and_then(|e| e.transaction.outputs.iter().nth(prevout.index as usize))
+					.cloned()
+			}))
+			.collect()
+	}
+
 	fn is_spent(&self, _outpoint: &OutPoint) -> bool {
 		unimplemented!();
 	}
@@ -206,7 +219,7 @@ impl<'a, T> Iterator for FittingTransactionsIterator<'a, T> where T: Iterator<It
 
 			let transaction_size = entry.size as u32;
 			let bip16_active = true;
-			let sigops_count = transaction_sigops(&entry.transaction, self, bip16_active) as u32;
+			let sigops_count = transaction_sigops(&entry.transaction, self, bip16_active, false) as u32;
 
 			let size_step = self.block_size.decide(transaction_size);
 			let sigops_step = self.sigops.decide(sigops_count);
@@ -17,6 +17,7 @@ use std::collections::VecDeque;
 use std::hash::{Hash, Hasher};
 use ser::{Serializable, serialize};
 use heapsize::HeapSizeOf;
+use verification::{AncestorsSummary, ConflictsSummary};
 use fee::MemoryPoolFeeCalculator;
 
 /// Transactions ordering strategy
@@ -751,6 +752,30 @@ impl MemoryPool {
 		self.storage.is_output_spent(prevout)
 	}
 
+	/// Returns summary of in-pool ancestors of the given transaction.
+	pub fn ancestors_summary(&self, t: &Transaction) -> AncestorsSummary {
+		self.get_ancestors(t).iter()
+			.filter_map(|hash| self.storage.get_by_hash(hash))
+			.fold(AncestorsSummary::default(), |summary, entry| AncestorsSummary {
+				count: summary.count + 1,
+				size: summary.size + entry.size,
+			})
+	}
+
+	/// Returns summary of in-pool transactions, spending the same outputs as the given transaction.
+	pub fn conflicts_summary(&self, t: &Transaction) -> ConflictsSummary {
+		t.inputs.iter()
+			.filter_map(|input| self.storage.by_previous_output.get(&input.previous_output.clone().into()))
+			.collect::<HashSet<_>>()
+			.into_iter()
+			.filter_map(|hash| self.storage.get_by_hash(hash))
+			.fold(ConflictsSummary::default(), |summary, entry| ConflictsSummary {
+				count: summary.count + 1,
+				fee: summary.fee + entry.miner_fee,
+				size: summary.size + entry.size,
+			})
+	}
+
 	fn make_entry<FC: MemoryPoolFeeCalculator>(&mut self, t: IndexedTransaction, fc: &FC) -> Option<Entry> {
 		let ancestors = self.get_ancestors(&t.raw);
 		let size = self.get_transaction_size(&t.raw);
@@ -952,6 +977,47 @@ pub mod tests {
 		assert_eq!(pool.remove_n_with_strategy(100, OrderingStrategy::ByTimestamp), vec![]);
 	}
 
+	#[test]
+	fn test_memory_pool_ancestors_summary() {
+		let chain = &mut ChainBuilder::new();
+		TransactionBuilder::with_output(100).store(chain)
+			.into_input(0).add_output(90).store(chain)
+			.into_input(0).add_output(80).store(chain)
+			.into_input(0).add_output(70).store(chain);
+		let mut pool = MemoryPool::new();
+		pool.insert_verified(chain.at(0).into(), &NonZeroFeeCalculator);
+		pool.insert_verified(chain.at(1).into(), &NonZeroFeeCalculator);
+		pool.insert_verified(chain.at(2).into(), &NonZeroFeeCalculator);
+
+		// transaction with a chain of 3 unconfirmed parents
+		let summary = pool.ancestors_summary(&chain.at(3));
+		assert_eq!(summary.count, 3);
+		assert_eq!(summary.size, chain.size(0) + chain.size(1) + chain.size(2));
+
+		// transaction without unconfirmed parents
+		assert_eq!(pool.ancestors_summary(&chain.at(0)), Default::default());
+	}
+
+	#[test]
+	fn test_memory_pool_conflicts_summary() {
+		let chain = &mut ChainBuilder::new();
+		TransactionBuilder::with_output(10).add_output(10).store(chain)				// t0
+			.reset().set_input(&chain.at(0), 0).add_output(20).lock().store(chain)	// nonfinal: t0[0] -> t1
+			.reset().set_input(&chain.at(0), 1).add_output(30).lock().store(chain)	// nonfinal: t0[1] -> t2
+			.reset().set_input(&chain.at(0), 0).add_output(40).store(chain);		// replacement: t0[0] -> t3
+		let mut pool = MemoryPool::new();
+		pool.insert_verified(chain.at(1).into(), &NonZeroFeeCalculator);
+		pool.insert_verified(chain.at(2).into(), &NonZeroFeeCalculator);
+
+		let summary = pool.conflicts_summary(&chain.at(3));
+		assert_eq!(summary.count, 1);
+		assert_eq!(summary.fee, 100_000_020);
+		assert_eq!(summary.size, chain.size(1));
+
+		// transaction without conflicts
+		assert_eq!(pool.conflicts_summary(&chain.at(0)), Default::default());
+	}
+
 	#[test]
 	fn test_memory_pool_remove_by_hash() {
 		let mut pool = MemoryPool::new();
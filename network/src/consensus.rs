@@ -1,6 +1,18 @@
-use keys::Address;
+use std::cmp::Ordering;
+use keys::{Address, Type as AddressType};
+use primitives::bytes::Bytes;
+use chain::constants::MAX_TRANSACTION_SIZE;
 use {Network, Magic, Deployment, crypto};
 
+/// Consensus branch id of Sprout (there were no network upgrades before Overwinter).
+pub const SPROUT_BRANCH_ID: u32 = 0;
+/// Consensus branch id of Overwinter (ZIP 201).
+pub const OVERWINTER_BRANCH_ID: u32 = 0x5ba81b19;
+/// Consensus branch id of Sapling (ZIP 205).
+pub const SAPLING_BRANCH_ID: u32 = 0x76b809bb;
+/// Consensus branch id of Blossom (ZIP 206). Blossom activation isn't supported yet.
+pub const BLOSSOM_BRANCH_ID: u32 = 0x2bb40e60;
+
 lazy_static! {
 	static ref SAPLING_SPEND_VK: crypto::Groth16VerifyingKey = crypto::load_sapling_spend_verifying_key()
 		.expect("hardcoded value should load without errors");
@@ -72,6 +84,14 @@ pub struct ConsensusParams {
 	/// Equihash (N, K) parameters.
 	pub equihash_params: Option<(u32, u32)>,
 
+	/// Maximal size of the input scriptSig for transactions accepted to the memory pool.
+	/// This is the policy rule, not enforced for transactions in blocks.
+	pub max_script_sig_size: usize,
+	/// Maximal number of in-pool ancestors of the transaction accepted to the memory pool.
+	pub max_ancestors_count: usize,
+	/// Maximal total size of in-pool ancestors of the transaction accepted to the memory pool.
+	pub max_ancestors_size: usize,
+
 	/// Active key for pghr13 joinsplit verification
 	pub joinsplit_verification_key: crypto::Pghr13VerifyingKey,
 
@@ -212,6 +232,9 @@ impl ConsensusParams {
 				],
 
 				equihash_params: Some((200, 9)),
+				max_script_sig_size: 1650,
+				max_ancestors_count: 25,
+				max_ancestors_size: 101_000,
 
 				joinsplit_verification_key: mainnet_pghr_verification_key(),
 				joinsplit_groth16_verification_key: &JOINSPLIT_GROTH16_VK,
@@ -292,6 +315,9 @@ impl ConsensusParams {
 				],
 
 				equihash_params: Some((200, 9)),
+				max_script_sig_size: 1650,
+				max_ancestors_count: 25,
+				max_ancestors_size: 101_000,
 
 				joinsplit_verification_key: testnet_pghr_verification_key(),
 				joinsplit_groth16_verification_key: &JOINSPLIT_GROTH16_VK,
@@ -326,6 +352,9 @@ impl ConsensusParams {
 				],
 
 				equihash_params: Some((200, 9)),
+				max_script_sig_size: 1650,
+				max_ancestors_count: 25,
+				max_ancestors_size: 101_000,
 
 				joinsplit_verification_key: regtest_pghr_verification_key(),
 				joinsplit_groth16_verification_key: &JOINSPLIT_GROTH16_VK,
@@ -359,6 +388,9 @@ impl ConsensusParams {
 				],
 
 				equihash_params: None,
+				max_script_sig_size: 1650,
+				max_ancestors_count: 25,
+				max_ancestors_size: 101_000,
 
 				joinsplit_verification_key: unitest_pghr_verification_key(),
 				joinsplit_groth16_verification_key: &JOINSPLIT_GROTH16_VK,
@@ -397,17 +429,35 @@ impl ConsensusParams {
 		20_000
 	}
 
+	/// Maximal number of seconds the block timestamp could be ahead of the current time.
+	pub fn max_future_block_time(&self) -> u32 {
+		2 * 60 * 60
+	}
+
+	/// Number of blocks that must be mined on top of the coinbase transaction block before it could be spent.
+	pub fn coinbase_maturity(&self) -> u32 {
+		match self.network {
+			Network::Mainnet | Network::Testnet | Network::Other(_) => 100,
+			Network::Regtest | Network::Unitest => 10,
+		}
+	}
+
+	/// Total money supply (in zatoshi).
+	pub fn max_money(&self) -> u64 {
+		21_000_000 * 100_000_000
+	}
+
 	pub fn max_transaction_value(&self) -> i64 {
-		21_000_000 * 100_000_000 // No amount larger than this (in satoshi) is valid
+		self.max_money() as i64 // No amount larger than this (in satoshi) is valid
 	}
 
 	pub fn absolute_max_transaction_size(&self) -> usize {
-		2_000_000
+		MAX_TRANSACTION_SIZE
 	}
 
 	pub fn max_transaction_size(&self, height: u32) -> usize {
 		if height >= self.sapling_height {
-			2_000_000
+			MAX_TRANSACTION_SIZE
 		} else {
 			100_000
 		}
@@ -473,19 +523,49 @@ impl ConsensusParams {
 		Some(self.founders_addresses[address_index as usize].clone())
 	}
 
-	pub fn consensus_branch_id(&self, height: u32) -> u32 {
-		// sapling upgrade
-		if height >= self.sapling_height {
-			return 0x76b809bb;
-		}
+	/// Script of the coinbase output, where founders reward goes at given height.
+	pub fn founder_address_script(&self, height: u32) -> Option<Bytes> {
+		self.founder_address(height).map(|address| {
+			let mut script = Vec::with_capacity(25);
+			match address.kind {
+				AddressType::P2PKH => {
+					// OP_DUP OP_HASH160 <hash> OP_EQUALVERIFY OP_CHECKSIG
+					script.extend_from_slice(&[0x76, 0xa9, 0x14]);
+					script.extend_from_slice(&*address.hash);
+					script.extend_from_slice(&[0x88, 0xac]);
+				},
+				AddressType::P2SH => {
+					// OP_HASH160 <hash> OP_EQUAL
+					script.extend_from_slice(&[0xa9, 0x14]);
+					script.extend_from_slice(&*address.hash);
+					script.push(0x87);
+				},
+			}
+			script.into()
+		})
+	}
 
-		// overwinter upgrade
-		if height >= self.overwinter_height {
-			return 0x5ba81b19;
-		}
+	/// Returns (activation_height, consensus_branch_id) pairs of all network upgrades, sorted by height.
+	pub fn network_upgrades(&self) -> [(u32, u32); 3] {
+		[
+			(0, SPROUT_BRANCH_ID),
+			(self.overwinter_height, OVERWINTER_BRANCH_ID),
+			(self.sapling_height, SAPLING_BRANCH_ID),
+		]
+	}
+
+	pub fn consensus_branch_id(&self, height: u32) -> u32 {
+		// find the number of upgrades, activated at or before given height
+		// (comparator never returns Equal, so that upgrades with the same height are handled properly)
+		let network_upgrades = self.network_upgrades();
+		let activated = network_upgrades.binary_search_by(|&(activation_height, _)| if activation_height <= height {
+			Ordering::Less
+		} else {
+			Ordering::Greater
+		}).unwrap_err();
 
-		// sprout
-		0
+		// sprout is always active
+		network_upgrades[activated - 1].1
 	}
 }
 
@@ -493,6 +573,14 @@ impl ConsensusParams {
 mod tests {
 	use super::*;
 
+	#[test]
+	fn coinbase_maturity() {
+		assert_eq!(ConsensusParams::new(Network::Mainnet).coinbase_maturity(), 100);
+		assert_eq!(ConsensusParams::new(Network::Testnet).coinbase_maturity(), 100);
+		assert_eq!(ConsensusParams::new(Network::Regtest).coinbase_maturity(), 10);
+		assert_eq!(ConsensusParams::new(Network::Unitest).coinbase_maturity(), 10);
+	}
+
 	#[test]
 	fn block_reward() {
 		let consensus = ConsensusParams::new(Network::Mainnet);
@@ -506,4 +594,81 @@ mod tests {
 		assert_eq!(consensus.block_reward(20_000_000), 149);
 		assert_eq!(consensus.block_reward(30_000_000), 0);
 	}
+
+	#[test]
+	fn block_reward_at_slow_start_and_first_halving() {
+		let consensus = ConsensusParams::new(Network::Mainnet);
+
+		// slow start: linear ramp, shifted by one block in the second half
+		assert_eq!(consensus.block_reward(0), 0);
+		assert_eq!(consensus.block_reward(9_999), 624_937_500);
+		assert_eq!(consensus.block_reward(10_000), 625_062_500);
+		assert_eq!(consensus.block_reward(19_999), 1_250_000_000);
+		assert_eq!(consensus.block_reward(20_000), 1_250_000_000);
+
+		// first halving happens `subsidy_halving_interval` blocks after the slow start midpoint
+		assert_eq!(consensus.block_reward(849_999), 1_250_000_000);
+		assert_eq!(consensus.block_reward(850_000), 625_000_000);
+	}
+
+	#[test]
+	fn founder_address_script() {
+		let consensus = ConsensusParams::new(Network::Mainnet);
+		let first: Bytes = "a9147d46a730d31f97b1930d3368a967c309bd4d136a87".into();
+		let second: Bytes = "a914c2e5af6fa0864ceb5ab93d88813772cebe04c62087".into();
+		let last: Bytes = "a9143770b379a58159d907f6b83868ca65e1a450753a87".into();
+
+		assert_eq!(consensus.founder_address_script(0), None);
+		assert_eq!(consensus.founder_address_script(1), Some(first.clone()));
+		assert_eq!(consensus.founder_address_script(17_708), Some(first));
+		assert_eq!(consensus.founder_address_script(17_709), Some(second));
+		assert_eq!(consensus.founder_address_script(849_999), Some(last));
+		assert_eq!(consensus.founder_address_script(850_000), None);
+	}
+
+	#[test]
+	fn network_upgrades() {
+		let consensus = ConsensusParams::new(Network::Mainnet);
+		assert_eq!(consensus.network_upgrades(), [
+			(0, SPROUT_BRANCH_ID),
+			(347_500, OVERWINTER_BRANCH_ID),
+			(419_200, SAPLING_BRANCH_ID),
+		]);
+
+		// upgrades follow activation heights
+		let mut consensus = ConsensusParams::new(Network::Unitest);
+		consensus.overwinter_height = 10;
+		consensus.sapling_height = 20;
+		assert_eq!(consensus.consensus_branch_id(9), SPROUT_BRANCH_ID);
+		assert_eq!(consensus.consensus_branch_id(10), OVERWINTER_BRANCH_ID);
+		assert_eq!(consensus.consensus_branch_id(20), SAPLING_BRANCH_ID);
+	}
+
+	#[test]
+	fn consensus_branch_id() {
+		let consensus = ConsensusParams::new(Network::Mainnet);
+		assert_eq!(consensus.consensus_branch_id(0), 0);
+		assert_eq!(consensus.consensus_branch_id(347_499), 0);
+		assert_eq!(consensus.consensus_branch_id(347_500), 0x5ba81b19);
+		assert_eq!(consensus.consensus_branch_id(419_199), 0x5ba81b19);
+		assert_eq!(consensus.consensus_branch_id(419_200), 0x76b809bb);
+		assert_eq!(consensus.consensus_branch_id(::std::u32::MAX), 0x76b809bb);
+
+		// upgrades are never activated on regtest
+		let consensus = ConsensusParams::new(Network::Regtest);
+		assert_eq!(consensus.consensus_branch_id(1_000_000), SPROUT_BRANCH_ID);
+	}
+
+	#[test]
+	fn consensus_branch_ids() {
+		// https://zips.z.cash/zip-0200
+		assert_eq!(SPROUT_BRANCH_ID, 0);
+		assert_eq!(OVERWINTER_BRANCH_ID, 0x5ba81b19);
+		assert_eq!(SAPLING_BRANCH_ID, 0x76b809bb);
+		assert_eq!(BLOSSOM_BRANCH_ID, 0x2bb40e60);
+
+		let consensus = ConsensusParams::new(Network::Mainnet);
+		assert_eq!(consensus.consensus_branch_id(consensus.overwinter_height), OVERWINTER_BRANCH_ID);
+		assert_eq!(consensus.consensus_branch_id(consensus.sapling_height), SAPLING_BRANCH_ID);
+	}
 }
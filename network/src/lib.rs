@@ -14,6 +14,6 @@ mod network;
 
 pub use primitives::{hash, compact};
 
-pub use consensus::ConsensusParams;
+pub use consensus::{ConsensusParams, SPROUT_BRANCH_ID, OVERWINTER_BRANCH_ID, SAPLING_BRANCH_ID, BLOSSOM_BRANCH_ID};
 pub use deployments::Deployment;
 pub use network::{Magic, Network};
@@ -1,6 +1,7 @@
 //! Bitcoin network
 //! https://www.anintegratedworld.com/unravelling-the-mysterious-block-chain-magic-number/
 
+use std::str;
 use compact::Compact;
 use chain::IndexedBlock;
 use primitives::hash::H256;
@@ -34,7 +35,31 @@ pub enum Network {
 	Other(u32),
 }
 
+impl str::FromStr for Network {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"main" => Ok(Network::Mainnet),
+			"test" => Ok(Network::Testnet),
+			"regtest" => Ok(Network::Regtest),
+			"unitest" => Ok(Network::Unitest),
+			_ => Err(format!("Unknown network: {}", s)),
+		}
+	}
+}
+
 impl Network {
+	/// Network that is using given magic. Regtest and unitest share the same magic => regtest is returned.
+	pub fn from_magic(magic: Magic) -> Option<Network> {
+		match magic {
+			ZCASH_MAGIC_MAINNET => Some(Network::Mainnet),
+			ZCASH_MAGIC_TESTNET => Some(Network::Testnet),
+			ZCASH_MAGIC_REGTEST => Some(Network::Regtest),
+			_ => None,
+		}
+	}
+
 	pub fn magic(&self) -> Magic {
 		match *self {
 			Network::Mainnet => ZCASH_MAGIC_MAINNET,
@@ -77,6 +102,16 @@ impl Network {
 		}
 	}
 
+	/// Network that is used to encode addresses && private keys.
+	pub fn address_network(&self) -> keys::Network {
+		match *self {
+			Network::Mainnet => keys::Network::Mainnet,
+			Network::Regtest | Network::Unitest => keys::Network::Regtest,
+			// there's no correct choice for other networks => let's just use Testnet
+			Network::Testnet | Network::Other(_) => keys::Network::Testnet,
+		}
+	}
+
 	pub fn default_verification_edge(&self) -> H256 {
 		match *self {
 			// block #410100, best checkpoint of zcashd as of 12.03.2019
@@ -88,5 +123,46 @@ impl Network {
 
 #[cfg(test)]
 mod tests {
-	// TODO: tests
+	use keys::{Address, Type};
+	use super::{Network, ZCASH_MAGIC_MAINNET, ZCASH_MAGIC_TESTNET, ZCASH_MAGIC_REGTEST};
+
+	#[test]
+	fn network_from_str_works() {
+		assert_eq!("main".parse::<Network>(), Ok(Network::Mainnet));
+		assert_eq!("test".parse::<Network>(), Ok(Network::Testnet));
+		assert_eq!("regtest".parse::<Network>(), Ok(Network::Regtest));
+		assert_eq!("unitest".parse::<Network>(), Ok(Network::Unitest));
+		assert!("mainnet".parse::<Network>().is_err());
+	}
+
+	#[test]
+	fn network_magic_roundtrip() {
+		assert_eq!(Network::from_magic(ZCASH_MAGIC_MAINNET), Some(Network::Mainnet));
+		assert_eq!(Network::from_magic(ZCASH_MAGIC_TESTNET), Some(Network::Testnet));
+		assert_eq!(Network::from_magic(ZCASH_MAGIC_REGTEST), Some(Network::Regtest));
+		assert_eq!(Network::from_magic(0x01020304), None);
+
+		for network in &[Network::Mainnet, Network::Testnet, Network::Regtest] {
+			assert_eq!(Network::from_magic(network.magic()), Some(*network));
+		}
+		assert_eq!(Network::from_magic(Network::Unitest.magic()), Some(Network::Regtest));
+		assert_eq!(Network::from_magic(Network::Unitest.magic()).map(|n| n.magic()), Some(Network::Unitest.magic()));
+	}
+
+	#[test]
+	fn address_network_works() {
+		let address = |network: Network, kind: Type| Address {
+			kind: kind,
+			network: network.address_network(),
+			hash: "ff197b14e502ab41f3bc8ccb48c4abac9eab35bc".into(),
+		}.to_string();
+
+		assert_eq!(address(Network::Mainnet, Type::P2PKH), "t1h8SqgtM3QM5e2M8EzhhT1yL2PXXtA6oqe");
+		assert_eq!(address(Network::Mainnet, Type::P2SH), "t3hpTmENnbJfTjC3ZN6NHseLGAupFWYLAiK");
+		assert_eq!(address(Network::Testnet, Type::P2PKH), "tmYyCAXNkS4ranGYZuj1SJge5dNcMN6xDC7");
+		assert_eq!(address(Network::Testnet, Type::P2SH), "t2VoeouUvTmGqGtdJHqNLRGWuHQ3RLyBEg4");
+		assert_eq!(address(Network::Regtest, Type::P2PKH), "tmYyCAXNkS4ranGYZuj1SJge5dNcMN6xDC7");
+		assert_eq!(address(Network::Regtest, Type::P2SH), "t2VoeouUvTmGqGtdJHqNLRGWuHQ3RLyBEg4");
+		assert_eq!(address(Network::Unitest, Type::P2PKH), "tmYyCAXNkS4ranGYZuj1SJge5dNcMN6xDC7");
+	}
 }
@@ -54,17 +54,40 @@ impl Compact {
 		};
 
 		let is_negative = word != 0 && (self.0 & 0x00800000) != 0;
-		let is_overflow = (word != 0 && size > 34) ||
-				(word > 0xff && size > 33) ||
-				(word > 0xffff && size > 32);
 
-		if is_negative || is_overflow {
+		if is_negative || self.is_overflow() {
 			Err(result)
 		} else {
 			Ok(result)
 		}
 	}
 
+	/// Same as `to_u256`, but returns None if target overflows or is negative.
+	pub fn to_u256_checked(&self) -> Option<U256> {
+		self.to_u256().ok()
+	}
+
+	/// Returns true if represented value doesn't fit into `U256`.
+	pub fn is_overflow(&self) -> bool {
+		let size = self.0 >> 24;
+		let word = self.0 & 0x007fffff;
+		(word != 0 && size > 34) ||
+			(word > 0xff && size > 33) ||
+			(word > 0xffff && size > 32)
+	}
+
+	/// Returns expected number of hashes required to find the hash below represented target,
+	/// i.e. `2^256 / (target + 1)`. Returns zero if target overflows, is negative or zero.
+	pub fn to_work(&self) -> U256 {
+		let target = match self.to_u256_checked() {
+			Some(ref target) if !target.is_zero() => *target,
+			_ => return U256::zero(),
+		};
+
+		// 2^256 doesn't fit into U256, but 2^256 / (target + 1) == (2^256 - target - 1) / (target + 1) + 1
+		(!target / (target + U256::one())) + U256::one()
+	}
+
 	pub fn from_u256(val: U256) -> Self {
 		let mut size = (val.bits() + 7) / 8;
 		let mut compact = if size <= 3 {
@@ -98,6 +121,21 @@ impl Compact {
 		}
 		diff
 	}
+
+	/// Same as `to_f64`, but returns None if result isn't a finite number.
+	/// Subnormal results are flushed to zero.
+	pub fn to_f64_checked(&self, limit: Compact) -> Option<f64> {
+		let diff = self.to_f64(limit);
+		if !diff.is_finite() {
+			return None;
+		}
+
+		if diff.is_normal() {
+			Some(diff)
+		} else {
+			Some(0.0)
+		}
+	}
 }
 
 #[cfg(test)]
@@ -116,6 +154,18 @@ mod tests {
 		assert_eq!(Compact::new(0x04123456).to_u256(), Ok(0x12345600u64.into()));
 	}
 
+	#[test]
+	fn test_compact_overflow() {
+		assert!(!Compact::new(0x1d00ffff).is_overflow());
+		assert!(!Compact::new(0x2100ffff).is_overflow());
+		assert!(Compact::new(0x2101ffff).is_overflow());
+		assert!(Compact::new(0x23000001).is_overflow());
+		assert_eq!(Compact::new(0x23000001).to_u256_checked(), None);
+		// negative
+		assert_eq!(Compact::new(0x04923456).to_u256_checked(), None);
+		assert_eq!(Compact::new(0x04123456).to_u256_checked(), Some(0x12345600u64.into()));
+	}
+
 	#[test]
 	fn test_from_u256() {
 		let test1 = U256::from(1000u64);
@@ -156,4 +206,35 @@ mod tests {
 		assert!(compare_f64(Compact::new(0x1cf88f6f).to_f64(limit), 1.029916));
 		assert!(compare_f64(Compact::new(0x12345678).to_f64(limit), 5913134931067755359633408.0));
 	}
+
+	#[test]
+	fn difficulty_checked() {
+		// zcash mainnet max bits
+		let limit = Compact::new(0x1f07ffff);
+		assert_eq!(limit.to_f64_checked(limit), Some(1.0));
+
+		// degenerate, but still finite
+		let diff = Compact::new(0x00000001).to_f64_checked(limit).unwrap();
+		assert!(diff.is_normal());
+		assert!(diff > 1.0);
+
+		// zero mantissa => division by zero
+		assert_eq!(Compact::new(0x1f000000).to_f64_checked(limit), None);
+		assert_eq!(Compact::new(0x00000000).to_f64_checked(limit), None);
+
+		// too small to be represented as normal f64
+		assert_eq!(Compact::new(0x7e7fffff).to_f64_checked(Compact::new(0x00000001)), Some(0.0));
+	}
+
+	#[test]
+	fn test_compact_to_work() {
+		// zcash mainnet genesis bits
+		assert_eq!(Compact::new(0x1f07ffff).to_work(), U256::from(0x2000));
+		// bitcoin mainnet genesis bits
+		assert_eq!(Compact::new(0x1d00ffff).to_work(), U256::from(0x100010001u64));
+		// overflow, negative and zero targets
+		assert_eq!(Compact::new(0x23000001).to_work(), U256::zero());
+		assert_eq!(Compact::new(0x04923456).to_work(), U256::zero());
+		assert_eq!(Compact::new(0).to_work(), U256::zero());
+	}
 }
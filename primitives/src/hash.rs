@@ -122,6 +122,14 @@ macro_rules! impl_hash {
 			}
 		}
 
+		impl cmp::Ord for $name {
+			fn cmp(&self, other: &Self) -> cmp::Ordering {
+				let self_ref: &[u8] = &self.0;
+				let other_ref: &[u8] = &other.0;
+				self_ref.cmp(other_ref)
+			}
+		}
+
 
 		impl Hash for $name {
 			fn hash<H>(&self, state: &mut H) where H: Hasher {
@@ -150,6 +158,15 @@ macro_rules! impl_hash {
 			pub fn is_zero(&self) -> bool {
 				self.0.iter().all(|b| *b == 0)
 			}
+
+			/// Compares hashes in constant time (i.e. the time doesn't depend on
+			/// the position of the first differing byte).
+			pub fn ct_eq(&self, other: &Self) -> bool {
+				let diff = self.0.iter()
+					.zip(other.0.iter())
+					.fold(0u8, |diff, (a, b)| diff | (a ^ b));
+				diff == 0
+			}
 		}
 	}
 }
@@ -171,8 +188,84 @@ impl H256 {
 		H256::from(s).reversed()
 	}
 
+	#[inline]
+	pub fn from_reversed_slice(slc: &[u8]) -> Self {
+		H256::from(slc).reversed()
+	}
+
 	#[inline]
 	pub fn to_reversed_str(&self) -> String {
 		self.reversed().to_string()
 	}
+
+	/// Returns number of leading zero bits of the hash, interpreted as little-endian number
+	/// (i.e. the way it is compared to the proof-of-work target).
+	pub fn leading_zeros(&self) -> u32 {
+		let mut zeros = 0;
+		for byte in self.0.iter().rev() {
+			zeros += byte.leading_zeros();
+			if *byte != 0 {
+				break;
+			}
+		}
+		zeros
+	}
+}
+
+/// Displays hash in reversed byte order without allocating.
+///
+/// Formats to the same string as `H256::to_reversed_str`.
+pub struct DisplayReversed<'a>(pub &'a H256);
+
+impl<'a> fmt::Display for DisplayReversed<'a> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		for byte in self.0.iter().rev() {
+			write!(f, "{:02x}", byte)?;
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{H256, DisplayReversed};
+
+	#[test]
+	fn display_reversed() {
+		let hash = H256::from("00000000000000000000000000000000000000000000000000000000000a0b0c");
+		assert_eq!(format!("{}", DisplayReversed(&hash)), hash.to_reversed_str());
+		assert_eq!(format!("{}", DisplayReversed(&hash)), "0c0b0a0000000000000000000000000000000000000000000000000000000000");
+	}
+
+	#[test]
+	fn from_reversed_slice() {
+		let hash = H256::from("00000000000000000000000000000000000000000000000000000000000a0b0c");
+		assert_eq!(H256::from_reversed_slice(&*hash.reversed()), hash);
+	}
+
+	#[test]
+	fn leading_zeros() {
+		assert_eq!(H256::default().leading_zeros(), 256);
+		assert_eq!(H256::from_reversed_str("8000000000000000000000000000000000000000000000000000000000000000").leading_zeros(), 0);
+		assert_eq!(H256::from_reversed_str("00000000839a8e6886ab5951d76f411475428afc90947ee320161bbf18eb6048").leading_zeros(), 32);
+		assert_eq!(H256::from_reversed_str("0007bc227e1c57a4a70e237cad00e7b7ce565155ab49166bc57397a26d339283").leading_zeros(), 13);
+		assert_eq!(H256::from_reversed_str("0000000000000000000000000000000000000000000000000000000000000001").leading_zeros(), 255);
+	}
+
+	#[test]
+	fn ct_eq() {
+		let hashes = [
+			H256::default(),
+			H256::from(1),
+			H256::from_reversed_str("0000000000000000000000000000000000000000000000000000000000000001"),
+			H256::from_reversed_str("00000000839a8e6886ab5951d76f411475428afc90947ee320161bbf18eb6048"),
+			H256::from_reversed_str("00000000839a8e6886ab5951d76f411475428afc90947ee320161bbf18eb6049"),
+		];
+
+		for a in &hashes {
+			for b in &hashes {
+				assert_eq!(a.ct_eq(b), a == b);
+			}
+		}
+	}
 }
@@ -121,6 +121,7 @@ pub fn start(cfg: config::Config) -> Result<(), String> {
 	let rpc_deps = rpc::Dependencies {
 		consensus: cfg.consensus,
 		storage: cfg.db,
+		memory_pool: local_sync_node.memory_pool(),
 		local_sync_node: local_sync_node,
 		p2p_context: p2p.context().clone(),
 		miner_address: cfg.miner_address,
@@ -13,6 +13,7 @@ pub struct Dependencies {
 	pub consensus: ConsensusParams,
 	pub local_sync_node: sync::LocalNodeRef,
 	pub storage: storage::SharedStore,
+	pub memory_pool: sync::MemoryPoolRef,
 	pub p2p_context: Arc<p2p::Context>,
 	pub miner_address: Option<Address>,
 }
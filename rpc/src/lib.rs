@@ -9,6 +9,7 @@ extern crate jsonrpc_core;
 extern crate jsonrpc_derive;
 extern crate jsonrpc_http_server;
 extern crate time;
+extern crate heapsize;
 extern crate tokio_core;
 extern crate sync;
 extern crate chain;
@@ -22,6 +23,8 @@ extern crate miner;
 extern crate verification;
 extern crate script as global_script;
 extern crate keys;
+#[cfg(test)]
+extern crate parking_lot;
 
 pub mod v1;
 pub mod rpc_server;
@@ -1,13 +1,19 @@
 use v1::traits::BlockChain;
 use v1::types::{BlockRef, GetBlockResponse, VerboseBlock, RawBlock};
+use v1::types::{GetBlockHeaderResponse, VerboseBlockHeader, RawBlockHeader};
 use v1::types::{GetTxOutResponse, TransactionOutputScript};
 use v1::types::GetTxOutSetInfoResponse;
+use v1::types::{GetBlockChainInfoResponse, NetworkUpgradeInfo, NetworkUpgradeStatus};
+use v1::types::{ChainTip, ChainTipStatus};
+use v1::types::GetMemPoolInfoResponse;
 use v1::types::H256;
-use keys::{self, Address};
+use keys::Address;
 use v1::helpers::errors::{block_not_found, block_at_height_not_found, transaction_not_found,
 	transaction_output_not_found, transaction_of_side_branch, invalid_params};
 use jsonrpc_core::Error;
 use storage;
+use sync;
+use heapsize::HeapSizeOf;
 use global_script::Script;
 use chain::OutPoint;
 use verification;
@@ -24,21 +30,28 @@ pub trait BlockChainClientCoreApi: Send + Sync + 'static {
 	fn block_count(&self) -> u32;
 	fn block_hash(&self, height: u32) -> Option<GlobalH256>;
 	fn difficulty(&self) -> f64;
+	fn blockchain_info(&self) -> GetBlockChainInfoResponse;
+	fn chain_tips(&self) -> Vec<ChainTip>;
 	fn raw_block(&self, hash: GlobalH256) -> Option<RawBlock>;
 	fn verbose_block(&self, hash: GlobalH256) -> Option<VerboseBlock>;
+	fn raw_block_header(&self, hash: GlobalH256) -> Option<RawBlockHeader>;
+	fn verbose_block_header(&self, hash: GlobalH256) -> Option<VerboseBlockHeader>;
 	fn verbose_transaction_out(&self, prev_out: OutPoint) -> Result<GetTxOutResponse, Error>;
+	fn mempool_info(&self) -> GetMemPoolInfoResponse;
 }
 
 pub struct BlockChainClientCore {
 	consensus: ConsensusParams,
 	storage: storage::SharedStore,
+	memory_pool: sync::MemoryPoolRef,
 }
 
 impl BlockChainClientCore {
-	pub fn new(consensus: ConsensusParams, storage: storage::SharedStore) -> Self {
+	pub fn new(consensus: ConsensusParams, storage: storage::SharedStore, memory_pool: sync::MemoryPoolRef) -> Self {
 		BlockChainClientCore {
 			consensus: consensus,
 			storage: storage,
+			memory_pool: memory_pool,
 		}
 	}
 }
@@ -67,7 +80,75 @@ impl BlockChainClientCoreApi for BlockChainClientCore {
 			self.storage.as_block_header_provider(),
 			&self.consensus);
 
-		next_work_required.to_f64(self.consensus.network.max_bits().into())
+		verification::difficulty_from_bits(next_work_required, self.consensus.network.max_bits().into())
+	}
+
+	fn blockchain_info(&self) -> GetBlockChainInfoResponse {
+		let best_block = self.storage.best_block();
+		let median_time = verification::median_timestamp_inclusive(
+			best_block.hash.clone(),
+			best_block.number,
+			self.storage.as_block_header_provider(),
+		);
+		let upgrades = self.consensus.network_upgrades().iter()
+			// sprout isn't an upgrade
+			.filter(|&&(_, branch_id)| branch_id != 0)
+			.map(|&(activation_height, branch_id)| (format!("{:08x}", branch_id), NetworkUpgradeInfo {
+				activationheight: activation_height,
+				status: if best_block.number >= activation_height {
+					NetworkUpgradeStatus::Active
+				} else {
+					NetworkUpgradeStatus::Pending
+				},
+			}))
+			.collect();
+
+		GetBlockChainInfoResponse {
+			chain: match self.consensus.network {
+				Network::Mainnet | Network::Other(_) => "main",
+				Network::Testnet => "test",
+				Network::Regtest | Network::Unitest => "regtest",
+			}.to_owned(),
+			blocks: best_block.number,
+			bestblockhash: best_block.hash.into(),
+			difficulty: self.difficulty(),
+			mediantime: median_time,
+			upgrades: upgrades,
+		}
+	}
+
+	fn chain_tips(&self) -> Vec<ChainTip> {
+		let best_block = self.storage.best_block();
+		let mut tips = vec![ChainTip {
+			height: best_block.number,
+			hash: best_block.hash.into(),
+			branchlen: 0,
+			status: ChainTipStatus::Active,
+		}];
+
+		for side_chain_tip in self.storage.side_chain_tips() {
+			// go back until we reach the main chain
+			let mut branch_len = 0;
+			let mut hash = side_chain_tip.hash.clone();
+			while self.storage.block_number(&hash).is_none() {
+				match self.storage.block_header(hash.into()) {
+					Some(header) => hash = header.raw.previous_header_hash,
+					None => break,
+				}
+				branch_len += 1;
+			}
+
+			tips.push(ChainTip {
+				height: side_chain_tip.number,
+				hash: side_chain_tip.hash.into(),
+				branchlen: branch_len,
+				status: ChainTipStatus::ValidFork,
+			});
+		}
+
+		// highest tips first
+		tips.sort_by(|a, b| b.height.cmp(&a.height));
+		tips
 	}
 
 	fn raw_block(&self, hash: GlobalH256) -> Option<RawBlock> {
@@ -91,7 +172,10 @@ impl BlockChainClientCoreApi for BlockChainClientCore {
 					confirmations: confirmations,
 					size: block_size as u32,
 					height: height,
-					difficulty: block.header.raw.bits.to_f64(self.consensus.network.max_bits().into()),
+					difficulty: verification::difficulty_from_bits(block.header.raw.bits, self.consensus.network.max_bits().into()),
+					chainwork: self.storage.block_chain_work(block.hash())
+						.expect("chain work is known for every stored block; qed")
+						.into(),
 					previousblockhash: Some(block.header.raw.previous_header_hash.clone().into()),
 					nextblockhash: height.and_then(|h| self.storage.block_hash(h + 1).map(|h| h.into())),
 					bits: block.header.raw.bits.into(),
@@ -106,6 +190,51 @@ impl BlockChainClientCoreApi for BlockChainClientCore {
 			})
 	}
 
+	fn raw_block_header(&self, hash: GlobalH256) -> Option<RawBlockHeader> {
+		self.storage.block_header(hash.into())
+			.map(|header| {
+				serialize(&header.raw).into()
+			})
+	}
+
+	fn verbose_block_header(&self, hash: GlobalH256) -> Option<VerboseBlockHeader> {
+		self.storage.block_header(hash.into())
+			.map(|header| {
+				let height = self.storage.block_number(&header.hash);
+				let confirmations = match height {
+					Some(block_number) => (self.storage.best_block().number - block_number + 1) as i64,
+					None => -1,
+				};
+				let median_time = match height {
+					Some(block_number) => verification::median_timestamp_inclusive(
+						header.hash.clone(),
+						block_number,
+						self.storage.as_block_header_provider(),
+					),
+					None => verification::median_timestamp_inclusive_n(
+						header.hash.clone(),
+						self.storage.as_block_header_provider(),
+						verification::constants::MEDIAN_TIMESTAMP_WINDOW,
+					),
+				};
+
+				VerboseBlockHeader {
+					confirmations: confirmations,
+					height: height,
+					mediantime: median_time,
+					previousblockhash: Some(header.raw.previous_header_hash.clone().into()),
+					nextblockhash: height.and_then(|h| self.storage.block_hash(h + 1).map(|h| h.into())),
+					bits: header.raw.bits.into(),
+					hash: header.hash.clone().into(),
+					merkleroot: header.raw.merkle_root_hash.clone().into(),
+					nonce: header.raw.nonce.clone().into(),
+					solution: header.raw.solution.as_ref().to_vec().into(),
+					time: header.raw.time,
+					version: header.raw.version,
+				}
+			})
+	}
+
 	fn verbose_transaction_out(&self, prev_out: OutPoint) -> Result<GetTxOutResponse, Error> {
 		let transaction = match self.storage.transaction(&prev_out.hash) {
 			Some(transaction) => transaction,
@@ -123,8 +252,8 @@ impl BlockChainClientCoreApi for BlockChainClientCore {
 			None => return Err(transaction_of_side_branch(prev_out.hash)),
 		};
 
-		let block_header = match self.storage.block_header(meta.height().into()) {
-			Some(block_header) => block_header,
+		let block_hash = match self.storage.transaction_block_hash(&prev_out.hash) {
+			Some(block_hash) => block_hash,
 			// this is possible during reorgs
 			None => return Err(transaction_not_found(prev_out.hash)),
 		};
@@ -141,7 +270,7 @@ impl BlockChainClientCoreApi for BlockChainClientCore {
 		let script_addresses = script.extract_destinations().unwrap_or(vec![]);
 
 		Ok(GetTxOutResponse {
-			bestblock: block_header.hash.into(),
+			bestblock: block_hash.into(),
 			confirmations: best_block.number - meta.height() + 1,
 			value: 0.00000001f64 * (transaction.raw.outputs[prev_out.index as usize].value as f64),
 			script: TransactionOutputScript {
@@ -150,12 +279,7 @@ impl BlockChainClientCoreApi for BlockChainClientCore {
 				req_sigs: script.num_signatures_required() as u32,
 				script_type: script.script_type().into(),
 				addresses: script_addresses.into_iter().map(|a| Address {
-					network: match self.consensus.network {
-						Network::Mainnet => keys::Network::Mainnet,
-						// there's no correct choices for Regtests && Other networks
-						// => let's just make Testnet key
-						_ => keys::Network::Testnet,
-					},
+					network: self.consensus.network.address_network(),
 					hash: a.hash,
 					kind: a.kind,
 				}).collect(),
@@ -164,6 +288,16 @@ impl BlockChainClientCoreApi for BlockChainClientCore {
 			coinbase: transaction.raw.is_coinbase(),
 		})
 	}
+
+	fn mempool_info(&self) -> GetMemPoolInfoResponse {
+		let memory_pool = self.memory_pool.read();
+		let information = memory_pool.information();
+		GetMemPoolInfoResponse {
+			size: information.transactions_count,
+			bytes: information.transactions_size_in_bytes,
+			usage: memory_pool.heap_size_of_children(),
+		}
+	}
 }
 
 impl<T> BlockChainClient<T> where T: BlockChainClientCoreApi {
@@ -193,6 +327,21 @@ impl<T> BlockChain for BlockChainClient<T> where T: BlockChainClientCoreApi {
 		Ok(self.core.difficulty())
 	}
 
+	fn blockchain_info(&self) -> Result<GetBlockChainInfoResponse, Error> {
+		let mut info = self.core.blockchain_info();
+		info.bestblockhash = info.bestblockhash.reversed();
+		Ok(info)
+	}
+
+	fn chain_tips(&self) -> Result<Vec<ChainTip>, Error> {
+		Ok(self.core.chain_tips().into_iter()
+			.map(|mut tip| {
+				tip.hash = tip.hash.reversed();
+				tip
+			})
+			.collect())
+	}
+
 	fn block(&self, block: BlockRef, verbosity: Option<u8>) -> Result<GetBlockResponse, Error> {
 		let global_hash = match block {
 			BlockRef::Number(number) => self.core
@@ -211,20 +360,10 @@ impl<T> BlockChain for BlockChainClient<T> where T: BlockChainClientCoreApi {
 				.map(GetBlockResponse::Raw)
 				.ok_or(block_not_found(global_hash.reversed())),
 			// if verbosity is 1, returns an Object with information about the block.
-			None | Some(1) => {
-				let verbose_block = self.core.verbose_block(global_hash);
-				if let Some(mut verbose_block) = verbose_block {
-					verbose_block.previousblockhash = verbose_block.previousblockhash.map(|h| h.reversed());
-					verbose_block.nextblockhash = verbose_block.nextblockhash.map(|h| h.reversed());
-					verbose_block.hash = verbose_block.hash.reversed();
-					verbose_block.merkleroot = verbose_block.merkleroot.reversed();
-					verbose_block.finalsaplingroot = verbose_block.finalsaplingroot.reversed();
-					verbose_block.tx = verbose_block.tx.into_iter().map(|h| h.reversed()).collect();
-					Some(GetBlockResponse::Verbose(verbose_block))
-				} else {
-					None
-				}.ok_or(block_not_found(global_hash.reversed()))
-			},
+			None | Some(1) => self.core
+				.verbose_block(global_hash)
+				.map(GetBlockResponse::Verbose)
+				.ok_or(block_not_found(global_hash.reversed())),
 			// if verbosity is 2, returns an Object with information about the block and information about each transaction.
 			// we do not (yet?) support getrawtransaction call => nothing to return
 			Some(2) => rpc_unimplemented!(),
@@ -232,19 +371,44 @@ impl<T> BlockChain for BlockChainClient<T> where T: BlockChainClientCoreApi {
 		}
 	}
 
+	fn block_header(&self, hash: H256, verbose: Option<bool>) -> Result<GetBlockHeaderResponse, Error> {
+		let global_hash: GlobalH256 = hash.into();
+		let global_hash = global_hash.reversed();
+
+		// if verbose is false, returns a string that is serialized, hex-encoded data for the block header.
+		if verbose == Some(false) {
+			return self.core
+				.raw_block_header(global_hash)
+				.map(GetBlockHeaderResponse::Raw)
+				.ok_or(block_not_found(global_hash.reversed()));
+		}
+
+		// if verbose is true, returns an Object with information about the block header.
+		self.core
+			.verbose_block_header(global_hash)
+			.map(|mut verbose_header| {
+				verbose_header.previousblockhash = verbose_header.previousblockhash.map(|h| h.reversed());
+				verbose_header.nextblockhash = verbose_header.nextblockhash.map(|h| h.reversed());
+				verbose_header.hash = verbose_header.hash.reversed();
+				verbose_header.merkleroot = verbose_header.merkleroot.reversed();
+				GetBlockHeaderResponse::Verbose(verbose_header)
+			})
+			.ok_or(block_not_found(global_hash.reversed()))
+	}
+
 	fn transaction_out(&self, transaction_hash: H256, out_index: u32, _include_mempool: Option<bool>) -> Result<GetTxOutResponse, Error> {
 		// TODO: include_mempool
 		let transaction_hash: GlobalH256 = transaction_hash.into();
 		self.core.verbose_transaction_out(OutPoint { hash: transaction_hash.reversed(), index: out_index })
-			.map(|mut response| {
-				response.bestblock = response.bestblock.reversed();
-				response
-			})
 	}
 
 	fn transaction_out_set_info(&self) -> Result<GetTxOutSetInfoResponse, Error> {
 		rpc_unimplemented!()
 	}
+
+	fn mempool_info(&self) -> Result<GetMemPoolInfoResponse, Error> {
+		Ok(self.core.mempool_info())
+	}
 }
 
 #[cfg(test)]
@@ -257,15 +421,18 @@ pub mod tests {
 	use db::{BlockChainDatabase};
 	use primitives::bytes::Bytes as GlobalBytes;
 	use primitives::hash::H256 as GlobalH256;
-	use v1::types::{VerboseBlock, RawBlock};
+	use v1::types::{VerboseBlock, RawBlock, VerboseBlockHeader, RawBlockHeader};
 	use v1::traits::BlockChain;
 	use v1::types::{GetTxOutResponse, TransactionOutputScript};
 	use v1::helpers::errors::block_not_found;
 	use v1::types::Bytes;
-	use v1::types::H256;
+	use v1::types::RpcHash;
 	use v1::types::ScriptType;
-	use chain::OutPoint;
+	use chain::{IndexedBlock, OutPoint};
 	use network::Network;
+	use miner::{MemoryPool, NonZeroFeeCalculator};
+	use parking_lot::RwLock;
+	use serde_json;
 	use super::*;
 
 	#[derive(Default)]
@@ -290,6 +457,26 @@ pub mod tests {
 			1f64
 		}
 
+		fn blockchain_info(&self) -> GetBlockChainInfoResponse {
+			GetBlockChainInfoResponse {
+				chain: "main".to_owned(),
+				blocks: 1,
+				bestblockhash: test_data::genesis().hash().into(),
+				difficulty: 1f64,
+				mediantime: 1231469665,
+				upgrades: Default::default(),
+			}
+		}
+
+		fn chain_tips(&self) -> Vec<ChainTip> {
+			vec![ChainTip {
+				height: 1,
+				hash: test_data::genesis().hash().into(),
+				branchlen: 0,
+				status: ChainTipStatus::Active,
+			}]
+		}
+
 		fn raw_block(&self, _hash: GlobalH256) -> Option<RawBlock> {
 			let b2_bytes: GlobalBytes = "010000004860eb18bf1b1620e37e9490fc8a427514416fd75159ab86688e9a8300000000d5fdcc541e25de1c7a5addedf24858b8bb665c9f36ef744ee42c316022c90f9bb0bc6649ffff001d08d2bd610101000000010000000000000000000000000000000000000000000000000000000000000000ffffffff0704ffff001d010bffffffff0100f2052a010000004341047211a824f55b505228e4c3d5194c1fcfaa15a456abdf37f9b9d97a4040afc073dee6c89064984f03385237d92167c13e236446b417ab79a0fcae412ae3316b77ac00000000".into();
 			Some(RawBlock::from(b2_bytes))
@@ -311,15 +498,38 @@ pub mod tests {
 				nonce: 42.into(),
 				bits: 486604799,
 				difficulty: 1.0,
+				chainwork: 0x300030003u64.into(),
 				previousblockhash: Some("4860eb18bf1b1620e37e9490fc8a427514416fd75159ab86688e9a8300000000".into()),
 				nextblockhash: None,
 				finalsaplingroot: "a5556cd346010000000000000000000000000000000000000000000000000002".into(),
 			})
 		}
 
+		fn raw_block_header(&self, _hash: GlobalH256) -> Option<RawBlockHeader> {
+			let b2_header_bytes: GlobalBytes = "010000004860eb18bf1b1620e37e9490fc8a427514416fd75159ab86688e9a8300000000d5fdcc541e25de1c7a5addedf24858b8bb665c9f36ef744ee42c316022c90f9bb0bc6649ffff001d08d2bd61".into();
+			Some(RawBlockHeader::from(b2_header_bytes))
+		}
+
+		fn verbose_block_header(&self, _hash: GlobalH256) -> Option<VerboseBlockHeader> {
+			Some(VerboseBlockHeader {
+				hash: "bddd99ccfda39da1b108ce1a5d70038d0a967bacb68b6b63065f626a00000000".into(),
+				confirmations: 1, // h2
+				height: Some(2),
+				version: 1,
+				merkleroot: "d5fdcc541e25de1c7a5addedf24858b8bb665c9f36ef744ee42c316022c90f9b".into(),
+				time: 1231469744,
+				mediantime: 1231469665,
+				nonce: 42.into(),
+				solution: vec![1, 2, 3].into(),
+				bits: 486604799,
+				previousblockhash: Some("4860eb18bf1b1620e37e9490fc8a427514416fd75159ab86688e9a8300000000".into()),
+				nextblockhash: None,
+			})
+		}
+
 		fn verbose_transaction_out(&self, _prev_out: OutPoint) -> Result<GetTxOutResponse, Error> {
 			Ok(GetTxOutResponse {
-				bestblock: H256::from(0x56),
+				bestblock: RpcHash::from(0x56),
 				confirmations: 777,
 				value: 100000.56,
 				script: TransactionOutputScript {
@@ -333,6 +543,14 @@ pub mod tests {
 				coinbase: false,
 			})
 		}
+
+		fn mempool_info(&self) -> GetMemPoolInfoResponse {
+			GetMemPoolInfoResponse {
+				size: 2,
+				bytes: 200,
+				usage: 1000,
+			}
+		}
 	}
 
 	impl BlockChainClientCoreApi for ErrorBlockChainClientCore {
@@ -352,6 +570,21 @@ pub mod tests {
 			1f64
 		}
 
+		fn blockchain_info(&self) -> GetBlockChainInfoResponse {
+			GetBlockChainInfoResponse {
+				chain: "main".to_owned(),
+				blocks: 1,
+				bestblockhash: test_data::genesis().hash().into(),
+				difficulty: 1f64,
+				mediantime: 1231469665,
+				upgrades: Default::default(),
+			}
+		}
+
+		fn chain_tips(&self) -> Vec<ChainTip> {
+			Vec::new()
+		}
+
 		fn raw_block(&self, _hash: GlobalH256) -> Option<RawBlock> {
 			None
 		}
@@ -360,9 +593,25 @@ pub mod tests {
 			None
 		}
 
+		fn raw_block_header(&self, _hash: GlobalH256) -> Option<RawBlockHeader> {
+			None
+		}
+
+		fn verbose_block_header(&self, _hash: GlobalH256) -> Option<VerboseBlockHeader> {
+			None
+		}
+
 		fn verbose_transaction_out(&self, prev_out: OutPoint) -> Result<GetTxOutResponse, Error> {
 			Err(block_not_found(prev_out.hash))
 		}
+
+		fn mempool_info(&self) -> GetMemPoolInfoResponse {
+			GetMemPoolInfoResponse {
+				size: 0,
+				bytes: 0,
+				usage: 0,
+			}
+		}
 	}
 
 	#[test]
@@ -450,6 +699,83 @@ pub mod tests {
 		assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":1.0,"id":1}"#);
 	}
 
+	#[test]
+	fn blockchain_info_contents() {
+		let storage = Arc::new(BlockChainDatabase::init_test_chain(
+			vec![
+				test_data::genesis().into(),
+				test_data::block_h1().into(),
+				test_data::block_h2().into(),
+			]
+		));
+
+		let core = BlockChainClientCore::new(ConsensusParams::new(Network::Unitest), storage, Default::default());
+		let client = BlockChainClient::new(core);
+		let mut handler = IoHandler::new();
+		handler.extend_with(client.to_delegate());
+
+		let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getblockchaininfo",
+				"params": [],
+				"id": 1
+			}"#)).unwrap();
+
+		let response: serde_json::Value = serde_json::from_str(&sample).unwrap();
+		let info = &response["result"];
+		assert_eq!(info["chain"], "regtest");
+		assert_eq!(info["blocks"], 2);
+		assert_eq!(info["bestblockhash"], "0002a26c902619fc964443264feb16f1e3e2d71322fc53dcb81cc5d797e273ed");
+		// sapling is never activated on unitest
+		assert_eq!(info["upgrades"]["76b809bb"]["activationheight"], ::std::u32::MAX);
+		assert_eq!(info["upgrades"]["76b809bb"]["status"], "pending");
+	}
+
+	#[test]
+	fn chain_tips_contents() {
+		let storage = Arc::new(BlockChainDatabase::init_test_chain(
+			vec![
+				test_data::genesis().into(),
+				test_data::block_h1().into(),
+				test_data::block_h2().into(),
+			]
+		));
+
+		// side chain block at height 2
+		let side_block: IndexedBlock = test_data::block_builder()
+			.header().parent(test_data::block_h1().hash()).build()
+			.transaction().coinbase().build()
+			.build()
+			.into();
+		storage.insert(side_block.clone()).unwrap();
+
+		let core = BlockChainClientCore::new(ConsensusParams::new(Network::Unitest), storage, Default::default());
+		let client = BlockChainClient::new(core);
+		let mut handler = IoHandler::new();
+		handler.extend_with(client.to_delegate());
+
+		let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getchaintips",
+				"params": [],
+				"id": 1
+			}"#)).unwrap();
+
+		let response: serde_json::Value = serde_json::from_str(&sample).unwrap();
+		let tips = response["result"].as_array().unwrap();
+		assert_eq!(tips.len(), 2);
+		assert_eq!(tips[0]["height"], 2);
+		assert_eq!(tips[0]["hash"], "0002a26c902619fc964443264feb16f1e3e2d71322fc53dcb81cc5d797e273ed");
+		assert_eq!(tips[0]["branchlen"], 0);
+		assert_eq!(tips[0]["status"], "active");
+		assert_eq!(tips[1]["height"], 2);
+		assert_eq!(tips[1]["hash"], serde_json::Value::String(side_block.hash().to_reversed_str()));
+		assert_eq!(tips[1]["branchlen"], 1);
+		assert_eq!(tips[1]["status"], "valid-fork");
+	}
+
 	#[test]
 	fn verbose_block_contents() {
 		let storage = Arc::new(BlockChainDatabase::init_test_chain(
@@ -460,7 +786,7 @@ pub mod tests {
 			]
 		));
 
-		let core = BlockChainClientCore::new(ConsensusParams::new(Network::Mainnet), storage);
+		let core = BlockChainClientCore::new(ConsensusParams::new(Network::Mainnet), storage, Default::default());
 
 		// get info on block #1:
 		// https://zcash.blockexplorer.com/block/0007bc227e1c57a4a70e237cad00e7b7ce565155ab49166bc57397a26d339283
@@ -477,6 +803,7 @@ pub mod tests {
 			nonce: "7534e8cf161ff2e49d54bdb3bfbcde8cdbf2fc5963c9ec7d86aed4a67e975790".into(),
 			bits: 520617983,
 			difficulty: 1.0,
+			chainwork: 0x4000.into(),
 			previousblockhash: Some("08ce3d9731b000c08338455c8a4a6bd05da16e26b11daa1b917184ece80f0400".into()),
 			nextblockhash: Some("ed73e297d7c51cb8dc53fc2213d7e2e3f116eb4f26434496fc1926906ca20200".into()),
 			finalsaplingroot: "0000000000000000000000000000000000000000000000000000000000000000".into(),
@@ -497,6 +824,7 @@ pub mod tests {
 			nonce: "a5556cd346010000000000000000000000000000000000000000000000000002".into(),
 			bits: 520617983,
 			difficulty: 1.0,
+			chainwork: 0x6000.into(),
 			previousblockhash: Some("8392336da29773c56b1649ab555156ceb7e700ad7c230ea7a4571c7e22bc0700".into()),
 			nextblockhash: None,
 			finalsaplingroot: "0000000000000000000000000000000000000000000000000000000000000000".into(),
@@ -544,7 +872,7 @@ pub mod tests {
 		let mut handler = IoHandler::new();
 		handler.extend_with(client.to_delegate());
 
-		let expected = r#"{"jsonrpc":"2.0","result":{"bits":486604799,"confirmations":1,"difficulty":1.0,"finalsaplingroot":"02000000000000000000000000000000000000000000000000000146d36c55a5","hash":"000000006a625f06636b8bb6ac7b960a8d03705d1ace08b1a19da3fdcc99ddbd","height":2,"merkleroot":"9b0fc92260312ce44e74ef369f5c66bbb85848f2eddd5a7a1cde251e54ccfdd5","nextblockhash":null,"nonce":"2a00000000000000000000000000000000000000000000000000000000000000","previousblockhash":"00000000839a8e6886ab5951d76f411475428afc90947ee320161bbf18eb6048","size":215,"time":1231469744,"tx":["9b0fc92260312ce44e74ef369f5c66bbb85848f2eddd5a7a1cde251e54ccfdd5"],"version":1},"id":1}"#;
+		let expected = r#"{"jsonrpc":"2.0","result":{"bits":486604799,"chainwork":"300030003","confirmations":1,"difficulty":1.0,"finalsaplingroot":"02000000000000000000000000000000000000000000000000000146d36c55a5","hash":"000000006a625f06636b8bb6ac7b960a8d03705d1ace08b1a19da3fdcc99ddbd","height":2,"merkleroot":"9b0fc92260312ce44e74ef369f5c66bbb85848f2eddd5a7a1cde251e54ccfdd5","nextblockhash":null,"nonce":"2a00000000000000000000000000000000000000000000000000000000000000","previousblockhash":"00000000839a8e6886ab5951d76f411475428afc90947ee320161bbf18eb6048","size":215,"time":1231469744,"tx":["9b0fc92260312ce44e74ef369f5c66bbb85848f2eddd5a7a1cde251e54ccfdd5"],"version":1},"id":1}"#;
 
 		let sample = handler.handle_request_sync(&(r#"
 			{
@@ -584,10 +912,128 @@ pub mod tests {
 		assert_eq!(&sample, r#"{"jsonrpc":"2.0","error":{"code":-32099,"message":"Block with given hash is not found","data":"000000006a625f06636b8bb6ac7b960a8d03705d1ace08b1a19da3fdcc99ddbd"},"id":1}"#);
 	}
 
+	#[test]
+	fn verbose_block_header_contents() {
+		let storage = Arc::new(BlockChainDatabase::init_test_chain(
+			vec![
+				test_data::genesis().into(),
+				test_data::block_h1().into(),
+				test_data::block_h2().into(),
+			]
+		));
+
+		let core = BlockChainClientCore::new(ConsensusParams::new(Network::Mainnet), storage, Default::default());
+
+		// get info on block #1:
+		// https://zcash.blockexplorer.com/block/0007bc227e1c57a4a70e237cad00e7b7ce565155ab49166bc57397a26d339283
+		let verbose_header = core.verbose_block_header("8392336da29773c56b1649ab555156ceb7e700ad7c230ea7a4571c7e22bc0700".into()).unwrap();
+		assert_eq!(verbose_header.hash, "8392336da29773c56b1649ab555156ceb7e700ad7c230ea7a4571c7e22bc0700".into());
+		assert_eq!(verbose_header.confirmations, 2);
+		assert_eq!(verbose_header.height, Some(1));
+		assert_eq!(verbose_header.version, 4);
+		assert_eq!(verbose_header.merkleroot, "0946edb9c083c9942d92305444527765fad789c438c717783276a9f7fbf61b85".into());
+		assert_eq!(verbose_header.time, 1477671596);
+		// median of genesis && block #1 times
+		assert_eq!(verbose_header.mediantime, 1477671596);
+		assert_eq!(verbose_header.nonce, "7534e8cf161ff2e49d54bdb3bfbcde8cdbf2fc5963c9ec7d86aed4a67e975790".into());
+		assert_eq!(verbose_header.solution.0, test_data::block_h1().block_header.solution.as_ref().to_vec());
+		assert_eq!(verbose_header.bits, 520617983);
+		assert_eq!(verbose_header.previousblockhash, Some("08ce3d9731b000c08338455c8a4a6bd05da16e26b11daa1b917184ece80f0400".into()));
+		assert_eq!(verbose_header.nextblockhash, Some("ed73e297d7c51cb8dc53fc2213d7e2e3f116eb4f26434496fc1926906ca20200".into()));
+
+		// raw header of block #2
+		let raw_header = core.raw_block_header("ed73e297d7c51cb8dc53fc2213d7e2e3f116eb4f26434496fc1926906ca20200".into());
+		assert_eq!(raw_header, Some(serialize(&test_data::block_h2().block_header).into()));
+
+		// unknown block
+		assert_eq!(core.verbose_block_header(GlobalH256::from(1)), None);
+	}
+
+	#[test]
+	fn raw_block_header_success() {
+		let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
+		let mut handler = IoHandler::new();
+		handler.extend_with(client.to_delegate());
+
+		let expected = r#"{"jsonrpc":"2.0","result":"010000004860eb18bf1b1620e37e9490fc8a427514416fd75159ab86688e9a8300000000d5fdcc541e25de1c7a5addedf24858b8bb665c9f36ef744ee42c316022c90f9bb0bc6649ffff001d08d2bd61","id":1}"#;
+
+		let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getblockheader",
+				"params": ["000000006a625f06636b8bb6ac7b960a8d03705d1ace08b1a19da3fdcc99ddbd", false],
+				"id": 1
+			}"#)).unwrap();
+		assert_eq!(&sample, expected);
+	}
+
+	#[test]
+	fn raw_block_header_error() {
+		let client = BlockChainClient::new(ErrorBlockChainClientCore::default());
+		let mut handler = IoHandler::new();
+		handler.extend_with(client.to_delegate());
+
+		let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getblockheader",
+				"params": ["000000006a625f06636b8bb6ac7b960a8d03705d1ace08b1a19da3fdcc99ddbd", false],
+				"id": 1
+			}"#)).unwrap();
+
+		assert_eq!(&sample, r#"{"jsonrpc":"2.0","error":{"code":-32099,"message":"Block with given hash is not found","data":"000000006a625f06636b8bb6ac7b960a8d03705d1ace08b1a19da3fdcc99ddbd"},"id":1}"#);
+	}
+
+	#[test]
+	fn verbose_block_header_success() {
+		let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
+		let mut handler = IoHandler::new();
+		handler.extend_with(client.to_delegate());
+
+		let expected = r#"{"jsonrpc":"2.0","result":{"bits":486604799,"confirmations":1,"hash":"000000006a625f06636b8bb6ac7b960a8d03705d1ace08b1a19da3fdcc99ddbd","height":2,"mediantime":1231469665,"merkleroot":"9b0fc92260312ce44e74ef369f5c66bbb85848f2eddd5a7a1cde251e54ccfdd5","nextblockhash":null,"nonce":"2a00000000000000000000000000000000000000000000000000000000000000","previousblockhash":"00000000839a8e6886ab5951d76f411475428afc90947ee320161bbf18eb6048","solution":"010203","time":1231469744,"version":1},"id":1}"#;
+
+		let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getblockheader",
+				"params": ["000000006a625f06636b8bb6ac7b960a8d03705d1ace08b1a19da3fdcc99ddbd",true],
+				"id": 1
+			}"#)).unwrap();
+
+		assert_eq!(&sample, expected);
+
+		// try without optional parameter
+		let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getblockheader",
+				"params": ["000000006a625f06636b8bb6ac7b960a8d03705d1ace08b1a19da3fdcc99ddbd"],
+				"id": 1
+			}"#)).unwrap();
+		assert_eq!(&sample, expected);
+	}
+
+	#[test]
+	fn verbose_block_header_error() {
+		let client = BlockChainClient::new(ErrorBlockChainClientCore::default());
+		let mut handler = IoHandler::new();
+		handler.extend_with(client.to_delegate());
+
+		let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getblockheader",
+				"params": ["000000006a625f06636b8bb6ac7b960a8d03705d1ace08b1a19da3fdcc99ddbd", true],
+				"id": 1
+			}"#)).unwrap();
+
+		assert_eq!(&sample, r#"{"jsonrpc":"2.0","error":{"code":-32099,"message":"Block with given hash is not found","data":"000000006a625f06636b8bb6ac7b960a8d03705d1ace08b1a19da3fdcc99ddbd"},"id":1}"#);
+	}
+
 	#[test]
 	fn verbose_transaction_out_contents() {
 		let storage = Arc::new(BlockChainDatabase::init_test_chain(vec![test_data::genesis().into(), test_data::block_h1().into()]));
-		let core = BlockChainClientCore::new(ConsensusParams::new(Network::Mainnet), storage);
+		let core = BlockChainClientCore::new(ConsensusParams::new(Network::Mainnet), storage, Default::default());
 
 		// get info on tx from block#1:
 		// https://zcash.blockexplorer.com/tx/851bf6fbf7a976327817c738c489d7fa657752445430922d94c983c0b9ed4609
@@ -644,4 +1090,50 @@ pub mod tests {
 
 		assert_eq!(&sample, r#"{"jsonrpc":"2.0","error":{"code":-32099,"message":"Block with given hash is not found","data":"3ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a"},"id":1}"#);
 	}
+
+	#[test]
+	fn mempool_info_success() {
+		let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
+		let mut handler = IoHandler::new();
+		handler.extend_with(client.to_delegate());
+
+		let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getmempoolinfo",
+				"params": [],
+				"id": 1
+			}"#)).unwrap();
+
+		assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":{"size":2,"bytes":200,"usage":1000},"id":1}"#);
+	}
+
+	#[test]
+	fn mempool_info_contents() {
+		let storage = Arc::new(BlockChainDatabase::init_test_chain(vec![test_data::genesis().into()]));
+		let tx1 = test_data::TransactionBuilder::with_output(10).into();
+		let tx2 = test_data::TransactionBuilder::with_output(20).into();
+		let mut memory_pool = MemoryPool::new();
+		memory_pool.insert_verified(tx1, &NonZeroFeeCalculator);
+		memory_pool.insert_verified(tx2, &NonZeroFeeCalculator);
+
+		let core = BlockChainClientCore::new(ConsensusParams::new(Network::Unitest), storage, Arc::new(RwLock::new(memory_pool)));
+		let client = BlockChainClient::new(core);
+		let mut handler = IoHandler::new();
+		handler.extend_with(client.to_delegate());
+
+		let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getmempoolinfo",
+				"params": [],
+				"id": 1
+			}"#)).unwrap();
+
+		let response: serde_json::Value = serde_json::from_str(&sample).unwrap();
+		let info = &response["result"];
+		assert_eq!(info["size"], 2);
+		assert!(info["bytes"].as_u64().unwrap() > 0);
+		assert!(info["usage"].as_u64().unwrap() > 0);
+	}
 }
@@ -3,8 +3,12 @@ use jsonrpc_core::Error;
 
 use v1::types::{BlockRef, H256};
 use v1::types::GetBlockResponse;
+use v1::types::GetBlockHeaderResponse;
 use v1::types::GetTxOutResponse;
 use v1::types::GetTxOutSetInfoResponse;
+use v1::types::GetBlockChainInfoResponse;
+use v1::types::ChainTip;
+use v1::types::GetMemPoolInfoResponse;
 
 /// Parity-bitcoin blockchain data interface.
 #[rpc]
@@ -25,11 +29,24 @@ pub trait BlockChain {
 	/// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getdifficulty", "params": [], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
 	#[rpc(name = "getdifficulty")]
 	fn difficulty(&self) -> Result<f64, Error>;
+	/// Get state of the best chain and network upgrades.
+	/// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getblockchaininfo", "params": [], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+	#[rpc(name = "getblockchaininfo")]
+	fn blockchain_info(&self) -> Result<GetBlockChainInfoResponse, Error>;
+	/// Get tips of all known chains: the main chain and side chains.
+	/// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getchaintips", "params": [], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+	#[rpc(name = "getchaintips")]
+	fn chain_tips(&self) -> Result<Vec<ChainTip>, Error>;
 	/// Get information on given block.
 	/// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getblock", "params": ["0002a26c902619fc964443264feb16f1e3e2d71322fc53dcb81cc5d797e273ed", 0], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
 	/// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getblock", "params": ["0002a26c902619fc964443264feb16f1e3e2d71322fc53dcb81cc5d797e273ed"], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
 	#[rpc(name = "getblock")]
 	fn block(&self, BlockRef, Option<u8>) -> Result<GetBlockResponse, Error>;
+	/// Get information on given block header.
+	/// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getblockheader", "params": ["0002a26c902619fc964443264feb16f1e3e2d71322fc53dcb81cc5d797e273ed", false], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+	/// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getblockheader", "params": ["0002a26c902619fc964443264feb16f1e3e2d71322fc53dcb81cc5d797e273ed"], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+	#[rpc(name = "getblockheader")]
+	fn block_header(&self, H256, Option<bool>) -> Result<GetBlockHeaderResponse, Error>;
 	/// Get details about an unspent transaction output.
 	/// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "gettxout", "params": ["4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b", 0], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
 	#[rpc(name = "gettxout")]
@@ -38,4 +55,8 @@ pub trait BlockChain {
 	/// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "gettxoutsetinfo", "params": [], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
 	#[rpc(name = "gettxoutsetinfo")]
 	fn transaction_out_set_info(&self) -> Result<GetTxOutSetInfoResponse, Error>;
+	/// Get details about the memory pool.
+	/// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getmempoolinfo", "params": [], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+	#[rpc(name = "getmempoolinfo")]
+	fn mempool_info(&self) -> Result<GetMemPoolInfoResponse, Error>;
 }
@@ -0,0 +1,89 @@
+use serde::{Serialize, Serializer};
+use super::bytes::Bytes;
+use super::hash::H256;
+
+/// Hex-encoded block header
+pub type RawBlockHeader = Bytes;
+
+/// Response to getblockheader RPC request
+#[derive(Debug)]
+pub enum GetBlockHeaderResponse {
+	/// When asking for short response
+	Raw(RawBlockHeader),
+	/// When asking for verbose response
+	Verbose(VerboseBlockHeader),
+}
+
+/// Verbose block header information
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct VerboseBlockHeader {
+	/// Block hash
+	pub hash: H256,
+	/// Number of confirmations. -1 if block is on the side chain
+	pub confirmations: i64,
+	/// Block height
+	pub height: Option<u32>,
+	/// Block version
+	pub version: u32,
+	/// Merkle root of this block
+	pub merkleroot: H256,
+	/// Block time in seconds since epoch (Jan 1 1970 GMT)
+	pub time: u32,
+	/// Median block time in seconds since epoch (Jan 1 1970 GMT)
+	pub mediantime: u32,
+	/// Block nonce
+	pub nonce: H256,
+	/// Equihash solution
+	pub solution: Bytes,
+	/// Block nbits
+	pub bits: u32,
+	/// Hash of previous block
+	pub previousblockhash: Option<H256>,
+	/// Hash of next block
+	pub nextblockhash: Option<H256>,
+}
+
+impl Serialize for GetBlockHeaderResponse {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+		match *self {
+			GetBlockHeaderResponse::Raw(ref raw_header) => raw_header.serialize(serializer),
+			GetBlockHeaderResponse::Verbose(ref verbose_header) => verbose_header.serialize(serializer),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::bytes::Bytes;
+	use super::super::hash::H256;
+	use serde_json;
+	use super::*;
+
+	#[test]
+	fn verbose_block_header_serialize() {
+		let header = VerboseBlockHeader::default();
+		assert_eq!(serde_json::to_string(&header).unwrap(), r#"{"hash":"0000000000000000000000000000000000000000000000000000000000000000","confirmations":0,"height":null,"version":0,"merkleroot":"0000000000000000000000000000000000000000000000000000000000000000","time":0,"mediantime":0,"nonce":"0000000000000000000000000000000000000000000000000000000000000000","solution":"","bits":0,"previousblockhash":null,"nextblockhash":null}"#);
+
+		let header = VerboseBlockHeader {
+			hash: H256::from(1),
+			confirmations: -1,
+			height: Some(3513513),
+			version: 4,
+			merkleroot: H256::from(2),
+			time: 111,
+			mediantime: 100,
+			nonce: 124.into(),
+			solution: Bytes::new(vec![1, 2, 3]),
+			bits: 13513,
+			previousblockhash: Some(H256::from(4)),
+			nextblockhash: Some(H256::from(5)),
+		};
+		assert_eq!(serde_json::to_string(&header).unwrap(), r#"{"hash":"0100000000000000000000000000000000000000000000000000000000000000","confirmations":-1,"height":3513513,"version":4,"merkleroot":"0200000000000000000000000000000000000000000000000000000000000000","time":111,"mediantime":100,"nonce":"7c00000000000000000000000000000000000000000000000000000000000000","solution":"010203","bits":13513,"previousblockhash":"0400000000000000000000000000000000000000000000000000000000000000","nextblockhash":"0500000000000000000000000000000000000000000000000000000000000000"}"#);
+	}
+
+	#[test]
+	fn get_block_header_response_raw_serialize() {
+		let raw_response = GetBlockHeaderResponse::Raw(Bytes::new(vec![0]));
+		assert_eq!(serde_json::to_string(&raw_response).unwrap(), r#""00""#);
+	}
+}
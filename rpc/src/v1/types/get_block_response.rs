@@ -1,5 +1,6 @@
 use serde::{Serialize, Serializer};
-use super::hash::H256;
+use super::hash::{H256, RpcHash};
+use super::uint::U256;
 use super::block::RawBlock;
 
 /// Response to getblock RPC request
@@ -15,7 +16,7 @@ pub enum GetBlockResponse {
 #[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
 pub struct VerboseBlock {
 	/// Block hash
-	pub hash: H256,
+	pub hash: RpcHash,
 	/// Number of confirmations. -1 if block is on the side chain
 	pub confirmations: i64,
 	/// Block size
@@ -26,11 +27,11 @@ pub struct VerboseBlock {
 	/// Block version
 	pub version: u32,
 	/// Merkle root of this block
-	pub merkleroot: H256,
+	pub merkleroot: RpcHash,
 	/// The root of the Sapling commitment tree after applying this block.
-	pub finalsaplingroot: H256,
+	pub finalsaplingroot: RpcHash,
 	/// Transactions ids
-	pub tx: Vec<H256>,
+	pub tx: Vec<RpcHash>,
 	/// Block time in seconds since epoch (Jan 1 1970 GMT)
 	pub time: u32,
 	/// Block nonce
@@ -39,10 +40,12 @@ pub struct VerboseBlock {
 	pub bits: u32,
 	/// Block difficulty
 	pub difficulty: f64,
+	/// Expected number of hashes required to produce the chain up to this block
+	pub chainwork: U256,
 	/// Hash of previous block
-	pub previousblockhash: Option<H256>,
+	pub previousblockhash: Option<RpcHash>,
 	/// Hash of next block
-	pub nextblockhash: Option<H256>,
+	pub nextblockhash: Option<RpcHash>,
 }
 
 impl Serialize for GetBlockResponse {
@@ -57,59 +60,60 @@ impl Serialize for GetBlockResponse {
 #[cfg(test)]
 mod tests {
 	use super::super::bytes::Bytes;
-	use super::super::hash::H256;
 	use serde_json;
 	use super::*;
 
 	#[test]
 	fn verbose_block_serialize() {
 		let block = VerboseBlock::default();
-		assert_eq!(serde_json::to_string(&block).unwrap(), r#"{"hash":"0000000000000000000000000000000000000000000000000000000000000000","confirmations":0,"size":0,"height":null,"version":0,"merkleroot":"0000000000000000000000000000000000000000000000000000000000000000","finalsaplingroot":"0000000000000000000000000000000000000000000000000000000000000000","tx":[],"time":0,"nonce":"0000000000000000000000000000000000000000000000000000000000000000","bits":0,"difficulty":0.0,"previousblockhash":null,"nextblockhash":null}"#);
+		assert_eq!(serde_json::to_string(&block).unwrap(), r#"{"hash":"0000000000000000000000000000000000000000000000000000000000000000","confirmations":0,"size":0,"height":null,"version":0,"merkleroot":"0000000000000000000000000000000000000000000000000000000000000000","finalsaplingroot":"0000000000000000000000000000000000000000000000000000000000000000","tx":[],"time":0,"nonce":"0000000000000000000000000000000000000000000000000000000000000000","bits":0,"difficulty":0.0,"chainwork":"0","previousblockhash":null,"nextblockhash":null}"#);
 
 		let block = VerboseBlock {
-			hash: H256::from(1),
+			hash: RpcHash::from(1),
 			confirmations: -1,
 			size: 500000,
 			height: Some(3513513),
 			version: 1,
-			merkleroot: H256::from(2),
-			tx: vec![H256::from(3), H256::from(4)],
+			merkleroot: RpcHash::from(2),
+			tx: vec![RpcHash::from(3), RpcHash::from(4)],
 			time: 111,
 			nonce: 124.into(),
 			bits: 13513,
 			difficulty: 555.555,
-			previousblockhash: Some(H256::from(4)),
-			nextblockhash: Some(H256::from(5)),
-			finalsaplingroot: H256::from(3),
+			chainwork: 0x2000.into(),
+			previousblockhash: Some(RpcHash::from(4)),
+			nextblockhash: Some(RpcHash::from(5)),
+			finalsaplingroot: RpcHash::from(3),
 		};
-		assert_eq!(serde_json::to_string(&block).unwrap(), r#"{"hash":"0100000000000000000000000000000000000000000000000000000000000000","confirmations":-1,"size":500000,"height":3513513,"version":1,"merkleroot":"0200000000000000000000000000000000000000000000000000000000000000","finalsaplingroot":"0300000000000000000000000000000000000000000000000000000000000000","tx":["0300000000000000000000000000000000000000000000000000000000000000","0400000000000000000000000000000000000000000000000000000000000000"],"time":111,"nonce":"7c00000000000000000000000000000000000000000000000000000000000000","bits":13513,"difficulty":555.555,"previousblockhash":"0400000000000000000000000000000000000000000000000000000000000000","nextblockhash":"0500000000000000000000000000000000000000000000000000000000000000"}"#);
+		assert_eq!(serde_json::to_string(&block).unwrap(), r#"{"hash":"0000000000000000000000000000000000000000000000000000000000000001","confirmations":-1,"size":500000,"height":3513513,"version":1,"merkleroot":"0000000000000000000000000000000000000000000000000000000000000002","finalsaplingroot":"0000000000000000000000000000000000000000000000000000000000000003","tx":["0000000000000000000000000000000000000000000000000000000000000003","0000000000000000000000000000000000000000000000000000000000000004"],"time":111,"nonce":"7c00000000000000000000000000000000000000000000000000000000000000","bits":13513,"difficulty":555.555,"chainwork":"2000","previousblockhash":"0000000000000000000000000000000000000000000000000000000000000004","nextblockhash":"0000000000000000000000000000000000000000000000000000000000000005"}"#);
 	}
 
 	#[test]
 	fn verbose_block_deserialize() {
 		let block = VerboseBlock::default();
 		assert_eq!(
-			serde_json::from_str::<VerboseBlock>(r#"{"hash":"0000000000000000000000000000000000000000000000000000000000000000","confirmations":0,"size":0,"height":null,"version":0,"merkleroot":"0000000000000000000000000000000000000000000000000000000000000000","finalsaplingroot":"0000000000000000000000000000000000000000000000000000000000000000","tx":[],"time":0,"nonce":"0000000000000000000000000000000000000000000000000000000000000000","bits":0,"difficulty":0.0,"previousblockhash":null,"nextblockhash":null}"#).unwrap(),
+			serde_json::from_str::<VerboseBlock>(r#"{"hash":"0000000000000000000000000000000000000000000000000000000000000000","confirmations":0,"size":0,"height":null,"version":0,"merkleroot":"0000000000000000000000000000000000000000000000000000000000000000","finalsaplingroot":"0000000000000000000000000000000000000000000000000000000000000000","tx":[],"time":0,"nonce":"0000000000000000000000000000000000000000000000000000000000000000","bits":0,"difficulty":0.0,"chainwork":"0","previousblockhash":null,"nextblockhash":null}"#).unwrap(),
 			block);
 
 		let block = VerboseBlock {
-			hash: H256::from(1),
+			hash: RpcHash::from(1),
 			confirmations: -1,
 			size: 500000,
 			height: Some(3513513),
 			version: 1,
-			merkleroot: H256::from(2),
-			tx: vec![H256::from(3), H256::from(4)],
+			merkleroot: RpcHash::from(2),
+			tx: vec![RpcHash::from(3), RpcHash::from(4)],
 			time: 111,
 			nonce: 124.into(),
 			bits: 13513,
 			difficulty: 555.555,
-			previousblockhash: Some(H256::from(4)),
-			nextblockhash: Some(H256::from(5)),
-			finalsaplingroot: H256::from(3),
+			chainwork: 0x2000.into(),
+			previousblockhash: Some(RpcHash::from(4)),
+			nextblockhash: Some(RpcHash::from(5)),
+			finalsaplingroot: RpcHash::from(3),
 		};
 		assert_eq!(
-			serde_json::from_str::<VerboseBlock>(r#"{"hash":"0100000000000000000000000000000000000000000000000000000000000000","confirmations":-1,"size":500000,"height":3513513,"version":1,"merkleroot":"0200000000000000000000000000000000000000000000000000000000000000","finalsaplingroot":"0300000000000000000000000000000000000000000000000000000000000000","tx":["0300000000000000000000000000000000000000000000000000000000000000","0400000000000000000000000000000000000000000000000000000000000000"],"time":111,"nonce":"7c00000000000000000000000000000000000000000000000000000000000000","bits":13513,"difficulty":555.555,"previousblockhash":"0400000000000000000000000000000000000000000000000000000000000000","nextblockhash":"0500000000000000000000000000000000000000000000000000000000000000"}"#).unwrap(),
+			serde_json::from_str::<VerboseBlock>(r#"{"hash":"0000000000000000000000000000000000000000000000000000000000000001","confirmations":-1,"size":500000,"height":3513513,"version":1,"merkleroot":"0000000000000000000000000000000000000000000000000000000000000002","finalsaplingroot":"0000000000000000000000000000000000000000000000000000000000000003","tx":["0000000000000000000000000000000000000000000000000000000000000003","0000000000000000000000000000000000000000000000000000000000000004"],"time":111,"nonce":"7c00000000000000000000000000000000000000000000000000000000000000","bits":13513,"difficulty":555.555,"chainwork":"2000","previousblockhash":"0000000000000000000000000000000000000000000000000000000000000004","nextblockhash":"0000000000000000000000000000000000000000000000000000000000000005"}"#).unwrap(),
 			block);
 	}
 
@@ -123,6 +127,6 @@ mod tests {
 	fn get_block_response_verbose_serialize() {
 		let block = VerboseBlock::default();
 		let verbose_response = GetBlockResponse::Verbose(block);
-		assert_eq!(serde_json::to_string(&verbose_response).unwrap(), r#"{"hash":"0000000000000000000000000000000000000000000000000000000000000000","confirmations":0,"size":0,"height":null,"version":0,"merkleroot":"0000000000000000000000000000000000000000000000000000000000000000","finalsaplingroot":"0000000000000000000000000000000000000000000000000000000000000000","tx":[],"time":0,"nonce":"0000000000000000000000000000000000000000000000000000000000000000","bits":0,"difficulty":0.0,"previousblockhash":null,"nextblockhash":null}"#);
+		assert_eq!(serde_json::to_string(&verbose_response).unwrap(), r#"{"hash":"0000000000000000000000000000000000000000000000000000000000000000","confirmations":0,"size":0,"height":null,"version":0,"merkleroot":"0000000000000000000000000000000000000000000000000000000000000000","finalsaplingroot":"0000000000000000000000000000000000000000000000000000000000000000","tx":[],"time":0,"nonce":"0000000000000000000000000000000000000000000000000000000000000000","bits":0,"difficulty":0.0,"chainwork":"0","previousblockhash":null,"nextblockhash":null}"#);
 	}
 }
@@ -0,0 +1,69 @@
+use std::collections::BTreeMap;
+use super::hash::H256;
+
+/// Network upgrade activation status
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+pub enum NetworkUpgradeStatus {
+	/// Upgrade is activated at or below current best block
+	#[serde(rename="active")]
+	Active,
+	/// Upgrade is activated above current best block
+	#[serde(rename="pending")]
+	Pending,
+}
+
+/// Network upgrade information
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct NetworkUpgradeInfo {
+	/// Height of the first block where upgrade rules are active
+	pub activationheight: u32,
+	/// Activation status at current best block
+	pub status: NetworkUpgradeStatus,
+}
+
+/// getblockchaininfo response
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct GetBlockChainInfoResponse {
+	/// Network name: main, test or regtest
+	pub chain: String,
+	/// Height of the best block
+	pub blocks: u32,
+	/// Hash of the best block
+	pub bestblockhash: H256,
+	/// Proof-of-work difficulty for the next block as a multiple of the minimum difficulty
+	pub difficulty: f64,
+	/// Median time of the best block
+	pub mediantime: u32,
+	/// Network upgrades, keyed by hex-encoded consensus branch id
+	pub upgrades: BTreeMap<String, NetworkUpgradeInfo>,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::super::hash::H256;
+	use super::*;
+
+	#[test]
+	fn blockchain_info_serialize() {
+		let mut upgrades = BTreeMap::new();
+		upgrades.insert("5ba81b19".to_owned(), NetworkUpgradeInfo {
+			activationheight: 347500,
+			status: NetworkUpgradeStatus::Active,
+		});
+		upgrades.insert("76b809bb".to_owned(), NetworkUpgradeInfo {
+			activationheight: 419200,
+			status: NetworkUpgradeStatus::Pending,
+		});
+
+		let info = GetBlockChainInfoResponse {
+			chain: "main".to_owned(),
+			blocks: 400000,
+			bestblockhash: H256::from(0x56),
+			difficulty: 1.0,
+			mediantime: 1231469665,
+			upgrades: upgrades,
+		};
+		assert_eq!(serde_json::to_string(&info).unwrap(), r#"{"chain":"main","blocks":400000,"bestblockhash":"5600000000000000000000000000000000000000000000000000000000000000","difficulty":1.0,"mediantime":1231469665,"upgrades":{"5ba81b19":{"activationheight":347500,"status":"active"},"76b809bb":{"activationheight":419200,"status":"pending"}}}"#);
+	}
+}
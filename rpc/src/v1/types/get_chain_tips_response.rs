@@ -0,0 +1,43 @@
+use super::hash::H256;
+
+/// Chain tip status
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+pub enum ChainTipStatus {
+	/// Tip of the main chain
+	#[serde(rename="active")]
+	Active,
+	/// Tip of the side chain, which is not a part of the main chain
+	#[serde(rename="valid-fork")]
+	ValidFork,
+}
+
+/// Single entry of getchaintips response
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ChainTip {
+	/// Height of the tip block
+	pub height: u32,
+	/// Hash of the tip block
+	pub hash: H256,
+	/// Length of the branch, connecting the tip to the main chain (zero for the main chain)
+	pub branchlen: u32,
+	/// Tip status
+	pub status: ChainTipStatus,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::super::hash::H256;
+	use super::*;
+
+	#[test]
+	fn chain_tip_serialize() {
+		let tip = ChainTip {
+			height: 2,
+			hash: H256::from(0x56),
+			branchlen: 1,
+			status: ChainTipStatus::ValidFork,
+		};
+		assert_eq!(serde_json::to_string(&tip).unwrap(), r#"{"height":2,"hash":"5600000000000000000000000000000000000000000000000000000000000000","branchlen":1,"status":"valid-fork"}"#);
+	}
+}
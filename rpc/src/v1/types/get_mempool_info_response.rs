@@ -0,0 +1,10 @@
+/// getmempoolinfo response
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct GetMemPoolInfoResponse {
+	/// Number of transactions in the memory pool
+	pub size: usize,
+	/// Sum of serialized sizes of all transactions in the memory pool
+	pub bytes: usize,
+	/// Approximate memory usage of the memory pool
+	pub usage: usize,
+}
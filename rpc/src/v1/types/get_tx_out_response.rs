@@ -1,4 +1,4 @@
-use super::hash::H256;
+use super::hash::RpcHash;
 use super::transaction::TransactionOutputScript;
 
 /// gettxout response
@@ -6,7 +6,7 @@ use super::transaction::TransactionOutputScript;
 pub struct GetTxOutResponse {
 	/// Hash of the block this transaction output is included into.
 	/// Why it's called 'best'? Who knows
-	pub bestblock: H256,
+	pub bestblock: RpcHash,
 	/// Number of confirmations of this transaction
 	pub confirmations: u32,
 	/// Transaction value in BTC
@@ -24,7 +24,6 @@ pub struct GetTxOutResponse {
 mod tests {
 	use serde_json;
 	use super::super::bytes::Bytes;
-	use super::super::hash::H256;
 	use super::super::script::ScriptType;
 	use super::super::transaction::TransactionOutputScript;
 	use super::*;
@@ -32,7 +31,7 @@ mod tests {
 	#[test]
 	fn tx_out_response_serialize() {
 		let txout = GetTxOutResponse {
-			bestblock: H256::from(0x56),
+			bestblock: RpcHash::from(0x56),
 			confirmations: 777,
 			value: 100000.56,
 			script: TransactionOutputScript {
@@ -45,13 +44,13 @@ mod tests {
 			version: 33,
 			coinbase: false,
 		};
-		assert_eq!(serde_json::to_string(&txout).unwrap(), r#"{"bestblock":"5600000000000000000000000000000000000000000000000000000000000000","confirmations":777,"value":100000.56,"scriptPubKey":{"asm":"Hello, world!!!","hex":"01020304","reqSigs":777,"type":"multisig","addresses":["t2UNzUUx8mWBCRYPRezvA363EYXyEpHokyi","t2N9PH9Wk9xjqYg9iin1Ua3aekJqfAtE543"]},"version":33,"coinbase":false}"#);
+		assert_eq!(serde_json::to_string(&txout).unwrap(), r#"{"bestblock":"0000000000000000000000000000000000000000000000000000000000000056","confirmations":777,"value":100000.56,"scriptPubKey":{"asm":"Hello, world!!!","hex":"01020304","reqSigs":777,"type":"multisig","addresses":["t2UNzUUx8mWBCRYPRezvA363EYXyEpHokyi","t2N9PH9Wk9xjqYg9iin1Ua3aekJqfAtE543"]},"version":33,"coinbase":false}"#);
 	}
 
 	#[test]
 	fn tx_out_response_deserialize() {
 		let txout = GetTxOutResponse {
-			bestblock: H256::from(0x56),
+			bestblock: RpcHash::from(0x56),
 			confirmations: 777,
 			value: 100000.56,
 			script: TransactionOutputScript {
@@ -65,7 +64,7 @@ mod tests {
 			coinbase: false,
 		};
 		assert_eq!(
-			serde_json::from_str::<GetTxOutResponse>(r#"{"bestblock":"5600000000000000000000000000000000000000000000000000000000000000","confirmations":777,"value":100000.56,"scriptPubKey":{"asm":"Hello, world!!!","hex":"01020304","reqSigs":777,"type":"multisig","addresses":["t2UNzUUx8mWBCRYPRezvA363EYXyEpHokyi","t2N9PH9Wk9xjqYg9iin1Ua3aekJqfAtE543"]},"version":33,"coinbase":false}"#).unwrap(),
+			serde_json::from_str::<GetTxOutResponse>(r#"{"bestblock":"0000000000000000000000000000000000000000000000000000000000000056","confirmations":777,"value":100000.56,"scriptPubKey":{"asm":"Hello, world!!!","hex":"01020304","reqSigs":777,"type":"multisig","addresses":["t2UNzUUx8mWBCRYPRezvA363EYXyEpHokyi","t2N9PH9Wk9xjqYg9iin1Ua3aekJqfAtE543"]},"version":33,"coinbase":false}"#).unwrap(),
 			txout);
 	}
 }
@@ -144,9 +144,40 @@ impl H256 {
 	}
 }
 
+/// Hash that is stored in internal byte order, but is serialized in reversed
+/// (display) byte order, like block and transaction hashes are shown to users
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RpcHash(H256);
+
+impl<T> From<T> for RpcHash where H256: From<T> {
+	fn from(o: T) -> Self {
+		RpcHash(H256::from(o))
+	}
+}
+
+impl Into<GlobalH256> for RpcHash {
+	fn into(self) -> GlobalH256 {
+		self.0.into()
+	}
+}
+
+impl serde::Serialize for RpcHash {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where S: serde::Serializer {
+		self.0.reversed().serialize(serializer)
+	}
+}
+
+impl<'a> serde::Deserialize<'a> for RpcHash {
+	fn deserialize<D>(deserializer: D) -> Result<RpcHash, D::Error> where D: serde::Deserializer<'a> {
+		H256::deserialize(deserializer).map(|hash| RpcHash(hash.reversed()))
+	}
+}
+
 #[cfg(test)]
 mod tests {
-	use super::H256;
+	use serde_json;
+	use super::{H256, RpcHash};
 	use primitives::hash::H256 as GlobalH256;
 	use std::str::FromStr;
 
@@ -181,4 +212,14 @@ mod tests {
 		let global_converted: GlobalH256 = reversed_hash.into();
 		assert_eq!(global_converted, global_hash);
 	}
+
+	#[test]
+	fn rpc_hash_serialize_reversed() {
+		// block #1 hash in internal byte order
+		let hash = RpcHash::from("8392336da29773c56b1649ab555156ceb7e700ad7c230ea7a4571c7e22bc0700");
+		let serialized = serde_json::to_string(&hash).unwrap();
+		assert_eq!(serialized, r#""0007bc227e1c57a4a70e237cad00e7b7ce565155ab49166bc57397a26d339283""#);
+		assert_eq!(serialized, serde_json::to_string(&H256::from("8392336da29773c56b1649ab555156ceb7e700ad7c230ea7a4571c7e22bc0700").reversed()).unwrap());
+		assert_eq!(serde_json::from_str::<RpcHash>(&serialized).unwrap(), hash);
+	}
 }
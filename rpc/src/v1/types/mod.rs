@@ -3,7 +3,11 @@ mod block;
 mod block_template;
 mod block_template_request;
 mod bytes;
+mod get_block_header_response;
+mod get_chain_tips_response;
+mod get_blockchain_info_response;
 mod get_block_response;
+mod get_mempool_info_response;
 mod get_tx_out_response;
 mod get_tx_out_set_info_response;
 mod hash;
@@ -16,10 +20,14 @@ pub use self::block::{BlockRef, RawBlock};
 pub use self::block_template::{BlockTemplate, BlockTemplateTransaction};
 pub use self::block_template_request::{BlockTemplateRequest, BlockTemplateRequestMode};
 pub use self::bytes::Bytes;
+pub use self::get_block_header_response::{GetBlockHeaderResponse, RawBlockHeader, VerboseBlockHeader};
+pub use self::get_chain_tips_response::{ChainTip, ChainTipStatus};
+pub use self::get_blockchain_info_response::{GetBlockChainInfoResponse, NetworkUpgradeInfo, NetworkUpgradeStatus};
 pub use self::get_block_response::{GetBlockResponse, VerboseBlock};
+pub use self::get_mempool_info_response::GetMemPoolInfoResponse;
 pub use self::get_tx_out_response::GetTxOutResponse;
 pub use self::get_tx_out_set_info_response::GetTxOutSetInfoResponse;
-pub use self::hash::{H160, H256};
+pub use self::hash::{H160, H256, RpcHash};
 pub use self::script::ScriptType;
 pub use self::transaction::{RawTransaction, Transaction, TransactionInput, TransactionOutput,
 	TransactionOutputWithAddress, TransactionOutputWithScriptData, TransactionInputScript,
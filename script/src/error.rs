@@ -110,3 +110,47 @@ impl fmt::Display for Error {
 		}
 	}
 }
+
+/// Script that was being evaluated when verification has failed.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ScriptKind {
+	ScriptSig,
+	ScriptPubKey,
+	RedeemScript,
+}
+
+/// Position of the opcode at which script evaluation has failed.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct OpcodePosition {
+	/// Script that contains failed opcode.
+	pub script: ScriptKind,
+	/// Byte offset of failed opcode within the script.
+	pub offset: usize,
+}
+
+/// Script verification error, annotated with position of the failed opcode.
+///
+/// Position is only known when error has happened while executing some opcode.
+#[derive(Debug, PartialEq)]
+pub struct VerificationError {
+	pub error: Error,
+	pub position: Option<OpcodePosition>,
+}
+
+impl From<Error> for VerificationError {
+	fn from(error: Error) -> Self {
+		VerificationError {
+			error: error,
+			position: None,
+		}
+	}
+}
+
+impl fmt::Display for VerificationError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self.position {
+			Some(ref position) => write!(f, "{} (opcode at byte {} of {:?})", self.error, position.offset, position.script),
+			None => self.error.fmt(f),
+		}
+	}
+}
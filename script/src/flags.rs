@@ -1,7 +1,7 @@
 //! Script interpreter verification flags
 
 /// Script interpreter verification flags
-#[derive(Default, Debug, PartialEq)]
+#[derive(Default, Debug, Clone, PartialEq)]
 pub struct VerificationFlags {
 	pub none: bool,
 
@@ -102,4 +102,22 @@ impl VerificationFlags {
 		self
 	}
 
+	/// Returns flags that enable every check enabled in either `self` or `other`.
+	pub fn union(self, other: &VerificationFlags) -> Self {
+		VerificationFlags {
+			none: self.none || other.none,
+			verify_p2sh: self.verify_p2sh || other.verify_p2sh,
+			verify_strictenc: self.verify_strictenc || other.verify_strictenc,
+			verify_dersig: self.verify_dersig || other.verify_dersig,
+			verify_low_s: self.verify_low_s || other.verify_low_s,
+			verify_nulldummy: self.verify_nulldummy || other.verify_nulldummy,
+			verify_sigpushonly: self.verify_sigpushonly || other.verify_sigpushonly,
+			verify_minimaldata: self.verify_minimaldata || other.verify_minimaldata,
+			verify_discourage_upgradable_nops: self.verify_discourage_upgradable_nops || other.verify_discourage_upgradable_nops,
+			verify_cleanstack: self.verify_cleanstack || other.verify_cleanstack,
+			verify_locktime: self.verify_locktime || other.verify_locktime,
+			verify_checksequence: self.verify_checksequence || other.verify_checksequence,
+		}
+	}
+
 }
@@ -231,28 +231,38 @@ pub fn verify_script(
 	flags: &VerificationFlags,
 	checker: &mut SignatureChecker,
 ) -> Result<(), Error> {
+	verify_script_detailed(script_sig, script_pubkey, flags, checker).map_err(|e| e.error)
+}
+
+/// Verifies script signature and pubkey, reporting position of the failed opcode
+pub fn verify_script_detailed(
+	script_sig: &Script,
+	script_pubkey: &Script,
+	flags: &VerificationFlags,
+	checker: &mut SignatureChecker,
+) -> Result<(), VerificationError> {
 	if flags.verify_sigpushonly && !script_sig.is_push_only() {
-		return Err(Error::SignaturePushOnly);
+		return Err(Error::SignaturePushOnly.into());
 	}
 
 	let mut stack = Stack::new();
 	let mut stack_copy = Stack::new();
 
-	eval_script(&mut stack, script_sig, flags, checker)?;
+	eval_script_at(&mut stack, script_sig, ScriptKind::ScriptSig, flags, checker)?;
 
 	if flags.verify_p2sh {
 		stack_copy = stack.clone();
 	}
 
-	let res = eval_script(&mut stack, script_pubkey, flags, checker)?;
+	let res = eval_script_at(&mut stack, script_pubkey, ScriptKind::ScriptPubKey, flags, checker)?;
 	if !res {
-		return Err(Error::EvalFalse);
+		return Err(Error::EvalFalse.into());
 	}
 
 	// Additional validation for spend-to-script-hash transactions:
 	if flags.verify_p2sh && script_pubkey.is_pay_to_script_hash() {
 		if !script_sig.is_push_only() {
-			return Err(Error::SignaturePushOnly);
+			return Err(Error::SignaturePushOnly.into());
 		}
 
 		mem::swap(&mut stack, &mut stack_copy);
@@ -264,9 +274,9 @@ pub fn verify_script(
 
 		let pubkey2: Script = stack.pop()?.into();
 
-		let res = eval_script(&mut stack, &pubkey2, flags, checker)?;
+		let res = eval_script_at(&mut stack, &pubkey2, ScriptKind::RedeemScript, flags, checker)?;
 		if !res {
-			return Err(Error::EvalFalse);
+			return Err(Error::EvalFalse.into());
 		}
 	}
 
@@ -278,7 +288,7 @@ pub fn verify_script(
 		// would be possible, which is not a softfork (and P2SH should be one).
 		assert!(flags.verify_p2sh);
 		if stack.len() != 1 {
-			return Err(Error::Cleanstack);
+			return Err(Error::Cleanstack.into());
 		}
 	}
 
@@ -286,12 +296,41 @@ pub fn verify_script(
 }
 
 /// Evaluates the script
-#[cfg_attr(feature="cargo-clippy", allow(match_same_arms))]
 pub fn eval_script(
 	stack: &mut Stack<Bytes>,
 	script: &Script,
 	flags: &VerificationFlags,
 	checker: &mut SignatureChecker,
+) -> Result<bool, Error> {
+	eval_script_inner(stack, script, flags, checker, &mut None)
+}
+
+/// Evaluates the script, reporting position of the failed opcode
+fn eval_script_at(
+	stack: &mut Stack<Bytes>,
+	script: &Script,
+	kind: ScriptKind,
+	flags: &VerificationFlags,
+	checker: &mut SignatureChecker,
+) -> Result<bool, VerificationError> {
+	let mut opcode_offset = None;
+	eval_script_inner(stack, script, flags, checker, &mut opcode_offset)
+		.map_err(|error| VerificationError {
+			error: error,
+			position: opcode_offset.map(|offset| OpcodePosition {
+				script: kind,
+				offset: offset,
+			}),
+		})
+}
+
+#[cfg_attr(feature="cargo-clippy", allow(match_same_arms))]
+fn eval_script_inner(
+	stack: &mut Stack<Bytes>,
+	script: &Script,
+	flags: &VerificationFlags,
+	checker: &mut SignatureChecker,
+	opcode_offset: &mut Option<usize>,
 ) -> Result<bool, Error> {
 	if script.len() > script::MAX_SCRIPT_SIZE {
 		return Err(Error::ScriptSize);
@@ -303,6 +342,7 @@ pub fn eval_script(
 	let mut altstack = Stack::<Bytes>::new();
 
 	while pc < script.len() {
+		*opcode_offset = Some(pc);
 		let executing = exec_stack.iter().all(|x| *x);
 		let instruction = match script.get_instruction(pc) {
 			Ok(i) => i,
@@ -857,6 +897,7 @@ pub fn eval_script(
 		}
 	}
 
+	*opcode_offset = None;
 	if !exec_stack.is_empty() {
 		return Err(Error::UnbalancedConditional);
 	}
@@ -875,9 +916,9 @@ mod tests {
 	use chain::Transaction;
 	use {
 		Opcode, Script, VerificationFlags, Builder, Error, Num, TransactionInputSigner,
-		NoopSignatureChecker, TransactionSignatureChecker, Stack
+		NoopSignatureChecker, TransactionSignatureChecker, Stack, VerificationError, OpcodePosition, ScriptKind,
 	};
-	use super::{eval_script, verify_script, is_public_key};
+	use super::{eval_script, verify_script, verify_script_detailed, is_public_key};
 
 	#[test]
 	fn tests_is_public_key() {
@@ -1940,4 +1981,36 @@ mod tests {
 		let result = Ok(true);
 		basic_test(&script, result, vec![vec![1].into()].into());
 	}
+
+	#[test]
+	fn test_verify_script_reports_failed_opcode_position() {
+		let flags = VerificationFlags::default()
+			.verify_p2sh(true);
+		let input = Builder::default()
+			.push_data(&[0x4])
+			.into_script();
+		let output = Builder::default()
+			.push_opcode(Opcode::OP_DUP)
+			.push_data(&[0x3])
+			.push_opcode(Opcode::OP_EQUALVERIFY)
+			.into_script();
+
+		assert_eq!(verify_script_detailed(&input, &output, &flags, &mut NoopSignatureChecker), Err(VerificationError {
+			error: Error::EqualVerify,
+			position: Some(OpcodePosition {
+				script: ScriptKind::ScriptPubKey,
+				offset: 3,
+			}),
+		}));
+		assert_eq!(verify_script(&input, &output, &flags, &mut NoopSignatureChecker), Err(Error::EqualVerify));
+
+		// errors that are detected after execution have no position
+		let output = Builder::default()
+			.push_opcode(Opcode::OP_0)
+			.into_script();
+		assert_eq!(verify_script_detailed(&input, &output, &flags, &mut NoopSignatureChecker), Err(VerificationError {
+			error: Error::EvalFalse,
+			position: None,
+		}));
+	}
 }
@@ -10,6 +10,8 @@ extern crate serialization as ser;
 extern crate serde_json;
 #[cfg(test)]
 extern crate rustc_hex as hex;
+#[cfg(test)]
+extern crate network;
 
 mod builder;
 mod error;
@@ -25,13 +27,14 @@ mod verify;
 pub use primitives::{bytes, hash};
 
 pub use self::builder::Builder;
-pub use self::error::Error;
+pub use self::error::{Error, VerificationError, OpcodePosition, ScriptKind};
 pub use self::flags::VerificationFlags;
-pub use self::interpreter::{eval_script, verify_script};
+pub use self::interpreter::{eval_script, verify_script, verify_script_detailed};
 pub use self::opcode::Opcode;
 pub use self::num::Num;
 pub use self::script::{Script, ScriptType, ScriptAddress};
-pub use self::sign::{SighashBase, SighashCache, TransactionInputSigner, UnsignedTransactionInput};
+pub use self::sign::{SighashBase, SighashCache, TransactionInputSigner, TransactionInputSignerRef, InputSigner,
+	UnsignedTransactionInput};
 pub use self::stack::Stack;
 pub use self::verify::{SignatureChecker, NoopSignatureChecker, TransactionSignatureChecker};
 
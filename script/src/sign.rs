@@ -123,6 +123,35 @@ pub struct TransactionInputSigner {
 	pub sapling: Option<Sapling>,
 }
 
+/// Signer that borrows the transaction instead of taking ownership of its parts.
+#[derive(Debug, Clone, Copy)]
+pub struct TransactionInputSignerRef<'a> {
+	pub transaction: &'a Transaction,
+}
+
+/// Transaction data, required by the signature checker.
+pub trait InputSigner {
+	/// Transaction version.
+	fn version(&self) -> i32;
+
+	/// Transaction lock time.
+	fn lock_time(&self) -> u32;
+
+	/// Sequence of the input with given index.
+	fn input_sequence(&self, input_index: usize) -> u32;
+
+	/// Pass None as input_index to compute transparent input signature
+	fn signature_hash(
+		&self,
+		cache: &mut SighashCache,
+		input_index: Option<usize>,
+		input_amount: u64,
+		script_pubkey: &Script,
+		sighashtype: u32,
+		consensus_branch_id: u32,
+	) -> H256;
+}
+
 #[derive(Debug, PartialEq, Clone, Copy)]
 enum SignatureVersion {
 	Sprout,
@@ -147,6 +176,14 @@ impl From<Transaction> for TransactionInputSigner {
 	}
 }
 
+impl<'a> From<&'a Transaction> for TransactionInputSignerRef<'a> {
+	fn from(t: &'a Transaction) -> Self {
+		TransactionInputSignerRef {
+			transaction: t,
+		}
+	}
+}
+
 impl TransactionInputSigner {
 	/// Pass None as input_index to compute transparent input signature
 	pub fn signature_hash(
@@ -157,6 +194,167 @@ impl TransactionInputSigner {
 		script_pubkey: &Script,
 		sighashtype: u32,
 		consensus_branch_id: u32,
+	) -> H256 {
+		self.sighash_data().signature_hash(cache, input_index, input_amount, script_pubkey, sighashtype, consensus_branch_id)
+	}
+
+	/// Precomputes signature portions that are shared by all inputs signed with SIGHASH_ALL.
+	///
+	/// Cached portions are only used by sighash types they're valid for, so the same cache
+	/// could be used to verify inputs signed with ANYONECANPAY, SINGLE or NONE.
+	pub fn sighash_cache(&self) -> SighashCache {
+		self.sighash_data().sighash_cache()
+	}
+
+	fn sighash_data(&self) -> SighashData<UnsignedTransactionInput> {
+		SighashData {
+			overwintered: self.overwintered,
+			version: self.version,
+			version_group_id: self.version_group_id,
+			inputs: &self.inputs,
+			outputs: &self.outputs,
+			lock_time: self.lock_time,
+			expiry_height: self.expiry_height,
+			join_split: self.join_split.as_ref(),
+			sapling: self.sapling.as_ref(),
+		}
+	}
+}
+
+impl<'a> TransactionInputSignerRef<'a> {
+	/// Pass None as input_index to compute transparent input signature
+	pub fn signature_hash(
+		&self,
+		cache: &mut SighashCache,
+		input_index: Option<usize>,
+		input_amount: u64,
+		script_pubkey: &Script,
+		sighashtype: u32,
+		consensus_branch_id: u32,
+	) -> H256 {
+		self.sighash_data().signature_hash(cache, input_index, input_amount, script_pubkey, sighashtype, consensus_branch_id)
+	}
+
+	/// Precomputes signature portions that are shared by all inputs signed with SIGHASH_ALL.
+	pub fn sighash_cache(&self) -> SighashCache {
+		self.sighash_data().sighash_cache()
+	}
+
+	fn sighash_data(&self) -> SighashData<'a, TransactionInput> {
+		SighashData {
+			overwintered: self.transaction.overwintered,
+			version: self.transaction.version,
+			version_group_id: self.transaction.version_group_id,
+			inputs: &self.transaction.inputs,
+			outputs: &self.transaction.outputs,
+			lock_time: self.transaction.lock_time,
+			expiry_height: self.transaction.expiry_height,
+			join_split: self.transaction.join_split.as_ref(),
+			sapling: self.transaction.sapling.as_ref(),
+		}
+	}
+}
+
+impl InputSigner for TransactionInputSigner {
+	fn version(&self) -> i32 {
+		self.version
+	}
+
+	fn lock_time(&self) -> u32 {
+		self.lock_time
+	}
+
+	fn input_sequence(&self, input_index: usize) -> u32 {
+		self.inputs[input_index].sequence
+	}
+
+	fn signature_hash(
+		&self,
+		cache: &mut SighashCache,
+		input_index: Option<usize>,
+		input_amount: u64,
+		script_pubkey: &Script,
+		sighashtype: u32,
+		consensus_branch_id: u32,
+	) -> H256 {
+		TransactionInputSigner::signature_hash(self, cache, input_index, input_amount, script_pubkey, sighashtype, consensus_branch_id)
+	}
+}
+
+impl<'a> InputSigner for TransactionInputSignerRef<'a> {
+	fn version(&self) -> i32 {
+		self.transaction.version
+	}
+
+	fn lock_time(&self) -> u32 {
+		self.transaction.lock_time
+	}
+
+	fn input_sequence(&self, input_index: usize) -> u32 {
+		self.transaction.inputs[input_index].sequence
+	}
+
+	fn signature_hash(
+		&self,
+		cache: &mut SighashCache,
+		input_index: Option<usize>,
+		input_amount: u64,
+		script_pubkey: &Script,
+		sighashtype: u32,
+		consensus_branch_id: u32,
+	) -> H256 {
+		TransactionInputSignerRef::signature_hash(self, cache, input_index, input_amount, script_pubkey, sighashtype, consensus_branch_id)
+	}
+}
+
+/// Input fields that take part in signature hash computation.
+trait SighashInput {
+	fn previous_output(&self) -> &OutPoint;
+	fn sequence(&self) -> u32;
+}
+
+impl SighashInput for UnsignedTransactionInput {
+	fn previous_output(&self) -> &OutPoint {
+		&self.previous_output
+	}
+
+	fn sequence(&self) -> u32 {
+		self.sequence
+	}
+}
+
+impl SighashInput for TransactionInput {
+	fn previous_output(&self) -> &OutPoint {
+		&self.previous_output
+	}
+
+	fn sequence(&self) -> u32 {
+		self.sequence
+	}
+}
+
+/// Borrowed transaction parts, used to compute signature hash.
+struct SighashData<'a, I: 'a> {
+	overwintered: bool,
+	version: i32,
+	version_group_id: u32,
+	inputs: &'a [I],
+	outputs: &'a [TransactionOutput],
+	lock_time: u32,
+	expiry_height: u32,
+	join_split: Option<&'a JoinSplit>,
+	sapling: Option<&'a Sapling>,
+}
+
+impl<'a, I> SighashData<'a, I> where I: SighashInput {
+	fn signature_hash(
+		&self,
+		cache: &mut SighashCache,
+		input_index: Option<usize>,
+		input_amount: u64,
+		script_pubkey: &Script,
+		sighashtype: u32,
+		consensus_branch_id: u32,
 	) -> H256 {
 		let sighash = Sighash::from_u32(sighashtype);
 		let signature_version = self.signature_version();
@@ -184,15 +382,15 @@ impl TransactionInputSigner {
 		let inputs = if sighash.anyone_can_pay {
 			let input = &self.inputs[input_index];
 			vec![TransactionInput {
-				previous_output: input.previous_output.clone(),
+				previous_output: input.previous_output().clone(),
 				script_sig: script_pubkey.to_bytes(),
-				sequence: input.sequence,
+				sequence: input.sequence(),
 			}]
 		} else {
 			self.inputs.iter()
 				.enumerate()
 				.map(|(n, input)| TransactionInput {
-					previous_output: input.previous_output.clone(),
+					previous_output: input.previous_output().clone(),
 					script_sig: if n == input_index {
 						script_pubkey.to_bytes()
 					} else {
@@ -200,14 +398,14 @@ impl TransactionInputSigner {
 					},
 					sequence: match sighash.base {
 						SighashBase::Single | SighashBase::None if n != input_index => 0,
-						_ => input.sequence,
+						_ => input.sequence(),
 					},
 				})
 				.collect()
 		};
 
 		let outputs = match sighash.base {
-			SighashBase::All => self.outputs.clone(),
+			SighashBase::All => self.outputs.to_vec(),
 			SighashBase::Single => self.outputs.iter()
 				.take(input_index + 1)
 				.enumerate()
@@ -228,7 +426,7 @@ impl TransactionInputSigner {
 			outputs: outputs,
 			lock_time: self.lock_time,
 			expiry_height: self.expiry_height,
-			join_split: self.join_split.as_ref().map(|js| {
+			join_split: self.join_split.map(|js| {
 				JoinSplit {
 					descriptions: js.descriptions.clone(),
 					pubkey: js.pubkey.clone(),
@@ -261,12 +459,12 @@ impl TransactionInputSigner {
 		//
 		// compute_* decides if it wants to use cached value
 		// compute_* decides if it wants to cache computed value
-		let (hash_prevouts, cache_hash_prevouts) = compute_hash_prevouts(cache, sighash, &self.inputs);
-		let (hash_sequence, cache_hash_sequence) = compute_hash_sequence(cache, sighash, &self.inputs);
-		let (hash_outputs, cache_hash_outputs) = compute_hash_outputs(cache, sighash, input_index, &self.outputs);
-		let (hash_join_split, cache_hash_join_split) = compute_hash_join_split(cache, self.join_split.as_ref());
-		let (hash_sapling_spends, cache_hash_sapling_spends) = compute_hash_sapling_spends(cache, sapling, self.sapling.as_ref());
-		let (hash_sapling_outputs, cache_hash_sapling_outputs) = compute_hash_sapling_outputs(cache, sapling, self.sapling.as_ref());
+		let (hash_prevouts, cache_hash_prevouts) = compute_hash_prevouts(cache, sighash, self.inputs);
+		let (hash_sequence, cache_hash_sequence) = compute_hash_sequence(cache, sighash, self.inputs);
+		let (hash_outputs, cache_hash_outputs) = compute_hash_outputs(cache, sighash, input_index, self.outputs);
+		let (hash_join_split, cache_hash_join_split) = compute_hash_join_split(cache, self.join_split);
+		let (hash_sapling_spends, cache_hash_sapling_spends) = compute_hash_sapling_spends(cache, sapling, self.sapling);
+		let (hash_sapling_outputs, cache_hash_sapling_outputs) = compute_hash_sapling_outputs(cache, sapling, self.sapling);
 
 		// update cache
 		if cache_hash_prevouts {
@@ -311,7 +509,7 @@ impl TransactionInputSigner {
 		stream.append(&self.lock_time);
 		stream.append(&self.expiry_height);
 		if sapling {
-			if let Some(ref sapling) = self.sapling {
+			if let Some(sapling) = self.sapling {
 				stream.append(&sapling.balancing_value);
 			}
 		}
@@ -319,15 +517,39 @@ impl TransactionInputSigner {
 		stream.append(&sighashtype);
 
 		if let Some(input_index) = input_index {
-			stream.append(&self.inputs[input_index].previous_output);
+			stream.append(self.inputs[input_index].previous_output());
 			stream.append_list(&**script_pubkey);
 			stream.append(&input_amount);
-			stream.append(&self.inputs[input_index].sequence);
+			stream.append(&self.inputs[input_index].sequence());
 		}
 
 		blake2b_personal(&personalization, &stream.out())
 	}
 
+	fn sighash_cache(&self) -> SighashCache {
+		let mut cache = SighashCache::default();
+		let signature_version = self.signature_version();
+		if signature_version == SignatureVersion::Sprout {
+			return cache;
+		}
+
+		let sighash = Sighash::new(SighashBase::All, false, false);
+		let sapling = signature_version == SignatureVersion::Sapling;
+		cache.hash_prevouts = Some(compute_hash_prevouts(&cache, sighash, self.inputs).0);
+		cache.hash_sequence = Some(compute_hash_sequence(&cache, sighash, self.inputs).0);
+		cache.hash_outputs = Some(compute_hash_outputs(&cache, sighash, None, self.outputs).0);
+		if let (hash_join_split, true) = compute_hash_join_split(&cache, self.join_split) {
+			cache.hash_join_split = Some(hash_join_split);
+		}
+		if let (hash_sapling_spends, true) = compute_hash_sapling_spends(&cache, sapling, self.sapling) {
+			cache.hash_sapling_spends = Some(hash_sapling_spends);
+		}
+		if let (hash_sapling_outputs, true) = compute_hash_sapling_outputs(&cache, sapling, self.sapling) {
+			cache.hash_sapling_outputs = Some(hash_sapling_outputs);
+		}
+		cache
+	}
+
 	fn signature_version(&self) -> SignatureVersion {
 		if self.overwintered {
 			if self.version_group_id == SAPLING_TX_VERSION_GROUP_ID {
@@ -341,10 +563,10 @@ impl TransactionInputSigner {
 	}
 }
 
-fn compute_hash_prevouts(
+fn compute_hash_prevouts<I: SighashInput>(
 	cache: &SighashCache,
 	sighash: Sighash,
-	inputs: &[UnsignedTransactionInput],
+	inputs: &[I],
 ) -> (H256, bool) {
 	const PERSONALIZATION: &'static [u8; 16] = b"ZcashPrevoutHash";
 
@@ -352,7 +574,7 @@ fn compute_hash_prevouts(
 		false => (cache.hash_prevouts.unwrap_or_else(|| {
 			let mut stream = Stream::default();
 			for input in inputs {
-				stream.append(&input.previous_output);
+				stream.append(input.previous_output());
 			}
 			blake2b_personal(PERSONALIZATION, &stream.out())
 		}), true),
@@ -360,10 +582,10 @@ fn compute_hash_prevouts(
 	}
 }
 
-fn compute_hash_sequence(
+fn compute_hash_sequence<I: SighashInput>(
 	cache: &SighashCache,
 	sighash: Sighash,
-	inputs: &[UnsignedTransactionInput],
+	inputs: &[I],
 ) -> (H256, bool) {
 	const PERSONALIZATION: &'static [u8; 16] = b"ZcashSequencHash";
 
@@ -371,7 +593,7 @@ fn compute_hash_sequence(
 		SighashBase::All if !sighash.anyone_can_pay => (cache.hash_sequence.unwrap_or_else(|| {
 			let mut stream = Stream::default();
 			for input in inputs {
-				stream.append(&input.sequence);
+				stream.append(&input.sequence());
 			}
 			blake2b_personal(PERSONALIZATION, &stream.out())
 		}), true),
@@ -480,10 +702,11 @@ mod tests {
 	use bytes::Bytes;
 	use hash::H256;
 	use keys::{KeyPair, Private, Address};
-	use chain::{OutPoint, TransactionOutput, Transaction};
+	use chain::{OutPoint, TransactionOutput, Transaction, Sapling, SAPLING_TX_VERSION, SAPLING_TX_VERSION_GROUP_ID};
+	use network::SAPLING_BRANCH_ID;
 	use script::Script;
 	use ser::deserialize;
-	use super::{Sighash, UnsignedTransactionInput, TransactionInputSigner, SighashBase};
+	use super::{Sighash, UnsignedTransactionInput, TransactionInputSigner, TransactionInputSignerRef, SighashBase, SighashCache};
 	use {verify_script, VerificationFlags, TransactionSignatureChecker};
 
 	#[test]
@@ -562,7 +785,7 @@ mod tests {
 		let input_index = if input_index as u64 == ::std::u64::MAX { None } else { Some(input_index) };
 		let hash = signer.signature_hash(&mut cache, input_index, 0, &script, hash_type as u32, consensus_branch_id);
 		if expected != hash {
-			panic!("Test#{} of {:?} sighash failed: expected {}, got {}", idx, signer.signature_version(), expected, hash);
+			panic!("Test#{} of {:?} sighash failed: expected {}, got {}", idx, signer.sighash_data().signature_version(), expected, hash);
 		} else {
 			println!("Test#{} succeeded: expected {}, got {}", idx, expected, hash);
 		}
@@ -590,6 +813,68 @@ mod tests {
 		}
 	}
 
+	// ZIP243: hashOutputs is 32 zero bytes when SIGHASH_SINGLE input has no corresponding output.
+	// Expected hashes are computed by the ZIP243 reference algorithm, checked against sighash_tests.json.
+	#[test]
+	fn test_sapling_sighash_single_without_output() {
+		let input = |hash: u8, index: u32| UnsignedTransactionInput {
+			previous_output: OutPoint { hash: H256::from(hash), index: index },
+			sequence: 0xffff_fffe,
+		};
+		let signer = TransactionInputSigner {
+			overwintered: true,
+			version: SAPLING_TX_VERSION,
+			version_group_id: SAPLING_TX_VERSION_GROUP_ID,
+			lock_time: 0,
+			expiry_height: 0,
+			inputs: vec![input(1, 0), input(2, 1)],
+			outputs: vec![TransactionOutput {
+				value: 100,
+				script_pubkey: "76a914df3bd30160e6c6145baaf2c88a8844c13a00d1d588ac".into(),
+			}],
+			join_split: None,
+			sapling: Some(Sapling::default()),
+		};
+		let script_pubkey: Script = "76a914df3bd30160e6c6145baaf2c88a8844c13a00d1d588ac".into();
+		let consensus_branch_id = SAPLING_BRANCH_ID;
+		let single: u32 = SighashBase::Single.into();
+		let single_anyone_can_pay: u32 = Sighash::new(SighashBase::Single, true, false).into();
+
+		let test_cases: Vec<(usize, u32, H256)> = vec![
+			(0, single, "033d421a52eef75b39296a4e4b9e9332b7989d39f71cf7dda5a859aad2043473".into()),
+			(1, single, "75a1b24e70aa886c7c54d24ea0c999ac832ca8abcaf1cfd76ea6fef82d5be818".into()),
+			(0, single_anyone_can_pay, "6a8799ceb90a5ab54d55c85870a066aaed37411e60b685e21f7f5b32e37793e3".into()),
+			(1, single_anyone_can_pay, "522143aee09f6b283bb4252307271e80d8eb794025a7b5d4c11baab53df69a8d".into()),
+		];
+		for (input_index, sighash_type, expected) in test_cases {
+			let hash = signer.signature_hash(&mut Default::default(), Some(input_index), 1000, &script_pubkey,
+				sighash_type, consensus_branch_id);
+			assert_eq!(hash, expected);
+		}
+	}
+
+	#[test]
+	fn test_borrowed_signature_hash_matches_owned() {
+		let tests = include_bytes!("../data/sighash_tests.json");
+		let tests: Vec<Value> = from_slice(tests).unwrap();
+		for test in tests.into_iter().skip(1) {
+			let tx: Transaction = deserialize(&test[0].as_str().unwrap().from_hex::<Vec<u8>>().unwrap() as &[u8]).unwrap();
+			let script: Script = Script::new(test[1].as_str().unwrap().parse().unwrap());
+			let input_index = test[2].as_u64().unwrap();
+			let input_index = if input_index == ::std::u64::MAX { None } else { Some(input_index as usize) };
+			let hash_type = test[3].as_i64().unwrap() as u32;
+			let consensus_branch_id = test[4].as_u64().unwrap() as u32;
+
+			let borrowed = TransactionInputSignerRef::from(&tx);
+			let owned: TransactionInputSigner = tx.clone().into();
+			assert_eq!(borrowed.sighash_cache(), owned.sighash_cache());
+			assert_eq!(
+				borrowed.signature_hash(&mut Default::default(), input_index, 0, &script, hash_type, consensus_branch_id),
+				owned.signature_hash(&mut Default::default(), input_index, 0, &script, hash_type, consensus_branch_id)
+			);
+		}
+	}
+
 	#[test]
 	fn test_sighash_cache_works_correctly() {
 		let test_cases: Vec<(Transaction, Transaction, usize)> = vec![
@@ -620,7 +905,7 @@ mod tests {
 			let output_index = spend_tx.inputs[input_index].previous_output.index as usize;
 
 			// prepare tx signature checker
-			let consensus_branch_id = 0x76b809bb; // all test cases are for sapling era
+			let consensus_branch_id = SAPLING_BRANCH_ID; // all test cases are for sapling era
 			let signer: TransactionInputSigner = spend_tx.clone().into();
 			let mut checker = TransactionSignatureChecker {
 				signer,
@@ -650,4 +935,52 @@ mod tests {
 			assert_eq!(verify_script(&input, &output, &flags, &mut checker), Ok(()));
 		}
 	}
+
+	#[test]
+	fn test_precomputed_sighash_cache_matches_uncached() {
+		let input = |hash: u8, index: u32| UnsignedTransactionInput {
+			previous_output: OutPoint { hash: H256::from(hash), index: index },
+			sequence: 0xffff_fffe,
+		};
+		let output = |value: u64| TransactionOutput {
+			value: value,
+			script_pubkey: "76a914df3bd30160e6c6145baaf2c88a8844c13a00d1d588ac".into(),
+		};
+		let signer = TransactionInputSigner {
+			overwintered: true,
+			version: SAPLING_TX_VERSION,
+			version_group_id: SAPLING_TX_VERSION_GROUP_ID,
+			lock_time: 0,
+			expiry_height: 0,
+			inputs: vec![input(1, 0), input(2, 1), input(3, 2)],
+			outputs: vec![output(100), output(200)],
+			join_split: None,
+			sapling: None,
+		};
+		let script_pubkey: Script = "76a914df3bd30160e6c6145baaf2c88a8844c13a00d1d588ac".into();
+		let consensus_branch_id = SAPLING_BRANCH_ID;
+
+		let mut cache = signer.sighash_cache();
+		assert!(cache.hash_prevouts.is_some());
+		assert!(cache.hash_sequence.is_some());
+		assert!(cache.hash_outputs.is_some());
+
+		let sighash_types: [u32; 6] = [
+			SighashBase::All.into(),
+			SighashBase::None.into(),
+			SighashBase::Single.into(),
+			Sighash::new(SighashBase::All, true, false).into(),
+			Sighash::new(SighashBase::None, true, false).into(),
+			Sighash::new(SighashBase::Single, true, false).into(),
+		];
+		for input_index in 0..signer.inputs.len() {
+			for sighash_type in &sighash_types {
+				let cached = signer.signature_hash(&mut cache, Some(input_index), 1000, &script_pubkey,
+					*sighash_type, consensus_branch_id);
+				let uncached = signer.signature_hash(&mut SighashCache::default(), Some(input_index), 1000,
+					&script_pubkey, *sighash_type, consensus_branch_id);
+				assert_eq!(cached, uncached);
+			}
+		}
+	}
 }
@@ -3,7 +3,7 @@ use chain::constants::{
 	SEQUENCE_FINAL, SEQUENCE_LOCKTIME_DISABLE_FLAG,
 	SEQUENCE_LOCKTIME_MASK, SEQUENCE_LOCKTIME_TYPE_FLAG, LOCKTIME_THRESHOLD
 };
-use {Script, SighashCache, TransactionInputSigner, Num};
+use {Script, SighashCache, TransactionInputSigner, InputSigner, Num};
 
 /// Checks transaction signature
 pub trait SignatureChecker {
@@ -48,15 +48,15 @@ impl SignatureChecker for NoopSignatureChecker {
 }
 
 #[derive(Debug)]
-pub struct TransactionSignatureChecker {
-	pub signer: TransactionInputSigner,
+pub struct TransactionSignatureChecker<S = TransactionInputSigner> {
+	pub signer: S,
 	pub input_index: usize,
 	pub input_amount: u64,
 	pub consensus_branch_id: u32,
 	pub cache: SighashCache,
 }
 
-impl SignatureChecker for TransactionSignatureChecker {
+impl<S> SignatureChecker for TransactionSignatureChecker<S> where S: InputSigner {
 	fn verify_signature(
 		&self,
 		signature: &Signature,
@@ -94,15 +94,15 @@ impl SignatureChecker for TransactionSignatureChecker {
 		// the nLockTime in the transaction.
 		let lock_time_u32: u32 = lock_time.into();
 		if !(
-			(self.signer.lock_time < LOCKTIME_THRESHOLD && lock_time_u32 < LOCKTIME_THRESHOLD) ||
-			(self.signer.lock_time >= LOCKTIME_THRESHOLD && lock_time_u32 >= LOCKTIME_THRESHOLD)
+			(self.signer.lock_time() < LOCKTIME_THRESHOLD && lock_time_u32 < LOCKTIME_THRESHOLD) ||
+			(self.signer.lock_time() >= LOCKTIME_THRESHOLD && lock_time_u32 >= LOCKTIME_THRESHOLD)
 		) {
 			return false;
 		}
 
 		// Now that we know we're comparing apples-to-apples, the
 		// comparison is a simple numeric one.
-		if i64::from(lock_time) > self.signer.lock_time as i64 {
+		if i64::from(lock_time) > self.signer.lock_time() as i64 {
 			return false;
 		}
 
@@ -116,17 +116,17 @@ impl SignatureChecker for TransactionSignatureChecker {
 		// prevent this condition. Alternatively we could test all
 		// inputs, but testing just this input minimizes the data
 		// required to prove correct CHECKLOCKTIMEVERIFY execution.
-		SEQUENCE_FINAL != self.signer.inputs[self.input_index].sequence
+		SEQUENCE_FINAL != self.signer.input_sequence(self.input_index)
 	}
 
 	fn check_sequence(&self, sequence: Num) -> bool {
 		// Relative lock times are supported by comparing the passed
 		// in operand to the sequence number of the input.
-		let to_sequence: i64 = self.signer.inputs[self.input_index].sequence as i64;
+		let to_sequence: i64 = self.signer.input_sequence(self.input_index) as i64;
 
 		// Fail if the transaction's version number is not set high
 		// enough to trigger BIP 68 rules.
-		if (self.signer.version as u32) < 2 {
+		if (self.signer.version() as u32) < 2 {
 			return false;
 		}
 
@@ -31,6 +31,10 @@ macro_rules! impl_fixed_array {
 			fn serialize(&self, stream: &mut Stream) {
 				self.iter().for_each(|item| { stream.append(item); });
 			}
+
+			fn serialized_size(&self) -> usize {
+				self.iter().map(Serializable::serialized_size).sum()
+			}
 		}
 
 		impl<T: DefaultItem + Deserializable> Deserializable for [T; $size] {
@@ -2,6 +2,7 @@ use std::io;
 use byteorder::{ReadBytesExt, WriteBytesExt, LittleEndian};
 use bytes::Bytes;
 use compact::Compact;
+use primitives::bigint::U256;
 use hash::{H32, H48, H96, H160, H256, H264, H512, H520};
 use compact_integer::CompactInteger;
 use {Serializable, Stream, Deserializable, Reader, Error};
@@ -229,9 +230,7 @@ impl Serializable for Bytes {
 impl Deserializable for Bytes {
 	fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, Error> where T: io::Read {
 		let len = try!(reader.read::<CompactInteger>());
-		let mut bytes = Bytes::new_with_len(len.into());
-		try!(reader.read_slice(&mut bytes));
-		Ok(bytes)
+		reader.read_bytes_exact(len.into()).map(Into::into)
 	}
 }
 
@@ -239,6 +238,11 @@ impl Serializable for Compact {
 	fn serialize(&self, stream: &mut Stream) {
 		stream.append(&u32::from(*self));
 	}
+
+	#[inline]
+	fn serialized_size(&self) -> usize {
+		4
+	}
 }
 
 impl Deserializable for Compact {
@@ -247,6 +251,27 @@ impl Deserializable for Compact {
 	}
 }
 
+impl Serializable for U256 {
+	fn serialize(&self, stream: &mut Stream) {
+		let mut bytes = [0u8; 32];
+		self.to_little_endian(&mut bytes);
+		stream.append_slice(&bytes);
+	}
+
+	#[inline]
+	fn serialized_size(&self) -> usize {
+		32
+	}
+}
+
+impl Deserializable for U256 {
+	fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, Error> where T: io::Read {
+		let mut bytes = [0u8; 32];
+		try!(reader.read_slice(&mut bytes));
+		Ok(U256::from_little_endian(&bytes))
+	}
+}
+
 impl<T: Serializable + Sized> Serializable for Option<T> {
 	fn serialize(&self, stream: &mut Stream) {
 		match *self {
@@ -254,6 +279,13 @@ impl<T: Serializable + Sized> Serializable for Option<T> {
 			Some(ref t) => { stream.append(&true); stream.append(t); },
 		}
 	}
+
+	fn serialized_size(&self) -> usize {
+		match *self {
+			None => 1,
+			Some(ref t) => 1 + t.serialized_size(),
+		}
+	}
 }
 
 impl<T: Deserializable + Sized> Deserializable for Option<T> {
@@ -272,7 +304,9 @@ impl<T: Deserializable + Sized> Deserializable for Option<T> {
 #[cfg(test)]
 mod tests {
 	use bytes::Bytes;
-	use {serialize, deserialize, deserialize_iterator, Stream, Reader, Error};
+	use hash::H256;
+	use primitives::bigint::U256;
+	use {serialize, deserialize, deserialize_at, deserialize_iterator, Stream, Reader, Error};
 
 	#[test]
 	fn test_reader_read() {
@@ -293,6 +327,40 @@ mod tests {
 		assert_eq!(Error::UnexpectedEnd, reader.read::<u8>().unwrap_err());
 	}
 
+	#[test]
+	fn test_reader_position() {
+		let buffer = vec![
+			1,
+			2, 0,
+			3, 0, 0, 0,
+		];
+
+		let mut reader = Reader::new(&buffer);
+		assert_eq!(reader.position(), 0);
+		assert_eq!(1u8, reader.read().unwrap());
+		assert_eq!(reader.position(), 1);
+		assert!(!reader.is_finished());
+		assert_eq!(reader.position(), 1);
+		assert_eq!(2u16, reader.read().unwrap());
+		assert_eq!(reader.position(), 3);
+		assert_eq!(3u32, reader.read().unwrap());
+		assert_eq!(reader.position(), 7);
+		assert!(reader.is_finished());
+		assert_eq!(reader.position(), 7);
+	}
+
+	#[test]
+	fn test_deserialize_at() {
+		let buffer = vec![
+			2, 0,
+			3, 0, 0, 0,
+		];
+
+		assert_eq!((2u16, 2), deserialize_at(&buffer).unwrap());
+		assert_eq!((3u32, 4), deserialize_at(&buffer[2..]).unwrap());
+		assert_eq!(Error::UnexpectedEnd, deserialize_at::<u64>(&buffer).unwrap_err());
+	}
+
 	#[test]
 	fn test_reader_iterator() {
 		let buffer = vec![
@@ -333,6 +401,39 @@ mod tests {
 		assert_eq!(expected, deserialize(raw.as_ref()).unwrap());
 	}
 
+	#[test]
+	fn test_reader_list_len_limit() {
+		let buffer = vec![3, 1, 2, 3];
+		assert_eq!(Reader::new(&buffer).read_list::<u8>(), Ok(vec![1, 2, 3]));
+		assert_eq!(Reader::new(&buffer).with_max_list_len(3).read_list::<u8>(), Ok(vec![1, 2, 3]));
+		assert_eq!(Reader::new(&buffer).with_max_list_len(2).read_list::<u8>(), Err(Error::ListTooLong(3)));
+
+		// length prefix is checked before anything is allocated
+		let buffer = vec![0xfe, 0xff, 0xff, 0xff, 0x0f];
+		assert_eq!(Reader::new(&buffer).read_list::<u8>(), Err(Error::ListTooLong(0x0fffffff)));
+	}
+
+	#[test]
+	fn test_reader_list_huge_len_truncated_body() {
+		// 1_999_999 elements are announced, but only 2 are present
+		let buffer = vec![0xfe, 0x7f, 0x84, 0x1e, 0x00, 1, 2];
+		assert_eq!(Reader::new(&buffer).read_list::<u64>(), Err(Error::UnexpectedEnd));
+		assert_eq!(Reader::new(&buffer).read_list_max::<u64>(2_000_000), Err(Error::UnexpectedEnd));
+		assert_eq!(Reader::new(&buffer).read_list_exact::<u64>(1_999_999), Err(Error::UnexpectedEnd));
+	}
+
+	#[test]
+	fn test_reader_read_bytes_exact() {
+		let buffer = vec![1, 2, 3];
+		assert_eq!(Reader::new(&buffer).read_bytes_exact(2), Ok(vec![1, 2]));
+		assert_eq!(Reader::new(&buffer).read_bytes_exact(3), Ok(vec![1, 2, 3]));
+		assert_eq!(Reader::new(&buffer).read_bytes_exact(0xfffffff), Err(Error::UnexpectedEnd));
+
+		// huge length prefix with short data
+		let buffer = vec![0xfe, 0xff, 0xff, 0xff, 0x0f, 1, 2];
+		assert_eq!(deserialize::<_, Bytes>(&buffer as &[u8]), Err(Error::UnexpectedEnd));
+	}
+
 	#[test]
 	fn test_bytes_serialize() {
 		let expected: Bytes = "020145".into();
@@ -360,6 +461,36 @@ mod tests {
 		assert_eq!(expected, deserialize::<_, String>(raw.as_ref()).unwrap());
 	}
 
+	#[test]
+	fn test_option_roundtrip() {
+		let some_hash = Some(H256::from(0x42));
+		let none_hash: Option<H256> = None;
+
+		let mut stream = Stream::default();
+		stream.append_option(&some_hash).append_option(&none_hash);
+		let buffer = stream.out();
+		assert_eq!(buffer.len(), 1 + 32 + 1);
+		assert_eq!(buffer[0], 1);
+		assert_eq!(buffer[33], 0);
+
+		let mut reader = Reader::new(&buffer);
+		assert_eq!(reader.read_option::<H256>().unwrap(), some_hash);
+		assert_eq!(reader.read_option::<H256>().unwrap(), none_hash);
+		assert!(reader.is_finished());
+
+		// presence flag without value
+		assert_eq!(Reader::new(&[1]).read_option::<H256>().unwrap_err(), Error::UnexpectedEnd);
+	}
+
+	#[test]
+	fn test_u256_roundtrip() {
+		let value = U256::from(0x2000) << 128;
+		let raw = serialize(&value);
+		assert_eq!(raw.len(), 32);
+		assert_eq!(raw[17], 0x20);
+		assert_eq!(deserialize::<_, U256>(raw.as_ref()).unwrap(), value);
+	}
+
 	#[test]
 	fn test_steam_append_slice() {
 		let mut slice = [0u8; 4];
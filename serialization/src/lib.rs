@@ -13,8 +13,8 @@ pub use primitives::{hash, bytes, compact};
 pub use compact_integer::CompactInteger;
 pub use list::List;
 pub use reader::{
-	Reader, Deserializable, deserialize, deserialize_iterator, ReadIterator, Error,
+	Reader, Deserializable, deserialize, deserialize_at, deserialize_iterator, ReadIterator, Error,
 };
 pub use stream::{
-	Stream, Serializable, serialize, serialize_list, serialized_list_size,
+	Stream, Serializable, serialize, serialize_with_capacity, serialize_list, serialized_list_size,
 };
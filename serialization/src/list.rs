@@ -1,5 +1,6 @@
 use std::io;
-use {Serializable, Deserializable, Error, Reader, Stream};
+use std::borrow::Borrow;
+use {Serializable, Deserializable, Error, Reader, Stream, CompactInteger};
 
 #[derive(Debug, Clone)]
 pub struct List<T>(Vec<T>);
@@ -14,6 +15,71 @@ impl<T> List<T> where T: Serializable + Deserializable {
 	}
 }
 
+impl<T> List<T> where T: Serializable {
+	/// Writes `count`-prefixed list to the writer, serializing elements one by one,
+	/// without building the whole list in memory.
+	///
+	/// Fails if iterator yields other than `count` elements.
+	pub fn write_streaming<W, I, K>(writer: &mut W, count: usize, iter: I) -> io::Result<()>
+		where W: io::Write, I: Iterator<Item=K>, K: Borrow<T>
+	{
+		let mut writer = FirstErrorWriter { writer: writer, error: None };
+		let result = {
+			let mut stream = Stream::with_writer(&mut writer);
+			stream.append(&CompactInteger::from(count));
+			write_items(&mut stream, count, iter)
+		};
+
+		match writer.error {
+			Some(error) => Err(error),
+			None => result,
+		}
+	}
+}
+
+fn write_items<T, I, K>(stream: &mut Stream, count: usize, iter: I) -> io::Result<()>
+	where T: Serializable, I: Iterator<Item=K>, K: Borrow<T>
+{
+	let mut written = 0;
+	for item in iter {
+		if written == count {
+			return Err(io::Error::new(io::ErrorKind::InvalidInput, "list has more elements than declared"));
+		}
+
+		stream.append(item.borrow());
+		written += 1;
+	}
+
+	if written != count {
+		return Err(io::Error::new(io::ErrorKind::InvalidInput, "list has fewer elements than declared"));
+	}
+
+	Ok(())
+}
+
+/// Writer that never fails. The first error of the inner writer is remembered
+/// and all subsequent writes are ignored.
+struct FirstErrorWriter<'a, W: 'a> {
+	writer: &'a mut W,
+	error: Option<io::Error>,
+}
+
+impl<'a, W> io::Write for FirstErrorWriter<'a, W> where W: io::Write {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		if self.error.is_none() {
+			if let Err(error) = self.writer.write_all(buf) {
+				self.error = Some(error);
+			}
+		}
+
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		Ok(())
+	}
+}
+
 impl<S> Serializable for List<S> where S: Serializable {
 	fn serialize(&self, s: &mut Stream) {
 		s.append_list(&self.0);
@@ -25,3 +91,30 @@ impl<D> Deserializable for List<D> where D: Deserializable {
 		reader.read_list().map(List)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use std::io;
+	use {serialize_list, CompactInteger, Reader};
+	use super::List;
+
+	#[test]
+	fn test_list_write_streaming() {
+		let items: Vec<u32> = (0..100).collect();
+		let mut buffer = Vec::new();
+		List::<u32>::write_streaming(&mut buffer, items.len(), items.iter()).unwrap();
+		assert_eq!(buffer, serialize_list::<u32, u32>(&items).take());
+
+		let mut reader = Reader::new(&buffer);
+		let count: CompactInteger = reader.read().unwrap();
+		assert_eq!(usize::from(count), items.len());
+
+		assert!(List::<u32>::write_streaming(&mut Vec::new(), 99, items.iter()).is_err());
+		assert!(List::<u32>::write_streaming(&mut Vec::new(), 101, items.iter()).is_err());
+
+		// writer errors are propagated
+		let mut small_buffer = [0u8; 8];
+		let error = List::<u32>::write_streaming(&mut &mut small_buffer[..], items.len(), items.iter()).unwrap_err();
+		assert_eq!(error.kind(), io::ErrorKind::WriteZero);
+	}
+}
@@ -1,6 +1,17 @@
-use std::{io, marker};
+use std::{cmp, io, marker};
 use compact_integer::CompactInteger;
 
+/// Default limit of elements count in the CompactInteger-prefixed list.
+///
+/// Every list element occupies at least one byte, so no list in the valid block
+/// (which is at most 2MB) can have more elements.
+pub const DEFAULT_MAX_LIST_LEN: usize = 2_000_000;
+
+/// Maximal number of list elements preallocated before they're actually read.
+///
+/// List length prefix isn't trusted, so the list grows while elements are read.
+const MAX_LIST_PREALLOCATION: usize = 1024;
+
 pub fn deserialize<R, T>(buffer: R) -> Result<T, Error> where R: io::Read, T: Deserializable {
 	let mut reader = Reader::from_read(buffer);
 	let result = try!(reader.read());
@@ -12,6 +23,15 @@ pub fn deserialize<R, T>(buffer: R) -> Result<T, Error> where R: io::Read, T: De
 	}
 }
 
+/// Deserialize value from the beginning of the buffer.
+///
+/// Returns deserialized value and the number of bytes it has consumed.
+pub fn deserialize_at<T>(buffer: &[u8]) -> Result<(T, usize), Error> where T: Deserializable {
+	let mut reader = Reader::new(buffer);
+	let result = try!(reader.read());
+	Ok((result, reader.position()))
+}
+
 pub fn deserialize_iterator<R, T>(buffer: R) -> ReadIterator<R, T> where R: io::Read, T: Deserializable {
 	ReadIterator {
 		reader: Reader::from_read(buffer),
@@ -25,6 +45,10 @@ pub enum Error {
 	UnexpectedEnd,
 	UnreadData,
 	InvalidFormat(String),
+	/// CompactInteger-prefixed list has more elements than reader allows.
+	ListTooLong(usize),
+	/// Overwintered transaction has version that is newer than any known version.
+	UnsupportedTransactionVersion(i32),
 }
 
 impl From<io::Error> for Error {
@@ -42,15 +66,14 @@ pub trait Deserializable {
 pub struct Reader<T> {
 	buffer: T,
 	peeked: Option<u8>,
+	position: usize,
+	max_list_len: usize,
 }
 
 impl<'a> Reader<&'a [u8]> {
 	/// Convenient way of creating for slice of bytes
 	pub fn new(buffer: &'a [u8]) -> Self {
-		Reader {
-			buffer: buffer,
-			peeked: None,
-		}
+		Reader::from_read(buffer)
 	}
 }
 
@@ -59,17 +82,20 @@ impl<T> io::Read for Reader<T> where T: io::Read {
 		// most of the times, there will be nothing in peeked,
 		// so to make it as efficient as possible, check it
 		// only once
-		match self.peeked.take() {
-			None => io::Read::read(&mut self.buffer, buf),
+		let read = match self.peeked.take() {
+			None => io::Read::read(&mut self.buffer, buf)?,
 			Some(peeked) if buf.is_empty() => {
 				self.peeked = Some(peeked);
-				Ok(0)
+				0
 			},
 			Some(peeked) => {
 				buf[0] = peeked;
-				io::Read::read(&mut self.buffer, &mut buf[1..]).map(|x| x + 1)
+				io::Read::read(&mut self.buffer, &mut buf[1..]).map(|x| x + 1)?
 			},
-		}
+		};
+
+		self.position += read;
+		Ok(read)
 	}
 }
 
@@ -78,15 +104,29 @@ impl<R> Reader<R> where R: io::Read {
 		Reader {
 			buffer: read,
 			peeked: None,
+			position: 0,
+			max_list_len: DEFAULT_MAX_LIST_LEN,
 		}
 	}
 
+	/// Limits number of elements in lists, read by this reader.
+	pub fn with_max_list_len(mut self, max_list_len: usize) -> Self {
+		self.max_list_len = max_list_len;
+		self
+	}
+
 	pub fn read<T>(&mut self) -> Result<T, Error> where T: Deserializable {
 		T::deserialize(self)
 	}
 
+	/// Number of bytes consumed by this reader so far.
+	pub fn position(&self) -> usize {
+		self.position
+	}
+
 	pub fn read_with_proxy<T, F>(&mut self, proxy: F) -> Result<T, Error> where T: Deserializable, F: FnMut(&[u8]) {
-		let mut reader = Reader::from_read(Proxy::new(self, proxy));
+		let max_list_len = self.max_list_len;
+		let mut reader = Reader::from_read(Proxy::new(self, proxy)).with_max_list_len(max_list_len);
 		T::deserialize(&mut reader)
 	}
 
@@ -105,6 +145,8 @@ impl<R> Reader<R> where R: io::Read {
 				self.peeked = Some(next);
 				return Ok(());
 			}
+
+			self.position += 1;
 		}
 	}
 
@@ -112,9 +154,30 @@ impl<R> Reader<R> where R: io::Read {
 		io::Read::read_exact(self, bytes).map_err(|_| Error::UnexpectedEnd)
 	}
 
+	/// Reads exactly `len` bytes. Buffer grows while reading, so `len` isn't trusted
+	/// to preallocate memory.
+	pub fn read_bytes_exact(&mut self, len: usize) -> Result<Vec<u8>, Error> {
+		let mut result = Vec::new();
+		io::Read::read_to_end(&mut io::Read::take(&mut *self, len as u64), &mut result)?;
+		if result.len() != len {
+			return Err(Error::UnexpectedEnd);
+		}
+
+		Ok(result)
+	}
+
+	/// Reads optional value, prefixed with presence flag.
+	pub fn read_option<T>(&mut self) -> Result<Option<T>, Error> where T: Deserializable {
+		self.read()
+	}
+
 	pub fn read_list<T>(&mut self) -> Result<Vec<T>, Error> where T: Deserializable {
 		let len: usize = try!(self.read::<CompactInteger>()).into();
-		let mut result = Vec::with_capacity(len);
+		if len > self.max_list_len {
+			return Err(Error::ListTooLong(len));
+		}
+
+		let mut result = Vec::with_capacity(cmp::min(len, MAX_LIST_PREALLOCATION));
 
 		for _ in 0..len {
 			result.push(try!(self.read()));
@@ -129,7 +192,24 @@ impl<R> Reader<R> where R: io::Read {
 			return Err(Error::MalformedData);
 		}
 
-		let mut result = Vec::with_capacity(len);
+		let mut result = Vec::with_capacity(cmp::min(len, MAX_LIST_PREALLOCATION));
+
+		for _ in 0..len {
+			result.push(try!(self.read()));
+		}
+
+		Ok(result)
+	}
+
+	/// Reads list, which can't have more than `max` elements. Unlike `read_list_max`, which is used
+	/// for network messages, too long list is reported as `Error::InvalidFormat` with given list name.
+	pub fn read_list_max_named<T>(&mut self, max: usize, name: &str) -> Result<Vec<T>, Error> where T: Deserializable {
+		let len: usize = try!(self.read::<CompactInteger>()).into();
+		if len > max {
+			return Err(Error::InvalidFormat(format!("Too many {}: {}", name, len)));
+		}
+
+		let mut result = Vec::with_capacity(cmp::min(len, MAX_LIST_PREALLOCATION));
 
 		for _ in 0..len {
 			result.push(try!(self.read()));
@@ -144,7 +224,7 @@ impl<R> Reader<R> where R: io::Read {
 			return Err(Error::MalformedData);
 		}
 
-		let mut result = Vec::with_capacity(len);
+		let mut result = Vec::with_capacity(cmp::min(len, MAX_LIST_PREALLOCATION));
 
 		for _ in 0..len {
 			result.push(try!(self.read()));
@@ -162,6 +242,8 @@ impl<R> Reader<R> where R: io::Read {
 		let peek: &mut [u8] = &mut [0u8];
 		match self.read_slice(peek) {
 			Ok(_) => {
+				// peeked byte isn't consumed yet
+				self.position -= 1;
 				self.peeked = Some(peek[0]);
 				false
 			},
@@ -10,6 +10,13 @@ pub fn serialize<T>(t: &T) -> Bytes where T: Serializable {
 	stream.out()
 }
 
+/// Serializes the struct into the buffer, pre-allocated using `Serializable::serialized_size`.
+pub fn serialize_with_capacity<T>(t: &T) -> Bytes where T: Serializable {
+	let mut stream = Stream::with_capacity(t.serialized_size());
+	stream.append(t);
+	stream.out()
+}
+
 pub fn serialize_list<T, K>(t: &[K]) -> Bytes where T: Serializable, K: Borrow<T> {
 	let mut stream = Stream::new();
 	stream.append_list(t);
@@ -34,14 +41,27 @@ pub trait Serializable {
 
 /// Stream used for serialization of Bitcoin structures
 #[derive(Default)]
-pub struct Stream {
+pub struct Stream<'a> {
 	buffer: Vec<u8>,
+	writer: Option<&'a mut Write>,
 }
 
-impl Stream {
+impl<'a> Stream<'a> {
 	/// New stream
 	pub fn new() -> Self {
-		Stream { buffer: Vec::new() }
+		Stream { buffer: Vec::new(), writer: None }
+	}
+
+	/// New stream with pre-allocated buffer of given capacity
+	pub fn with_capacity(capacity: usize) -> Self {
+		Stream { buffer: Vec::with_capacity(capacity), writer: None }
+	}
+
+	/// New stream that passes serialized data directly to the writer (e.g. hasher), without buffering.
+	///
+	/// Writer must not fail. The `out` of such stream is always empty.
+	pub fn with_writer(writer: &'a mut Write) -> Self {
+		Stream { buffer: Vec::new(), writer: Some(writer) }
 	}
 
 	/// Serializes the struct and appends it to the end of stream.
@@ -52,8 +72,8 @@ impl Stream {
 
 	/// Appends raw bytes to the end of the stream.
 	pub fn append_slice(&mut self, bytes: &[u8]) -> &mut Self {
-		// discard error for now, since we write to simple vector
-		self.buffer.write(bytes).unwrap();
+		// discard error for now, since we write to simple vector (or to infallible writer)
+		self.write_all(bytes).unwrap();
 		self
 	}
 
@@ -66,20 +86,32 @@ impl Stream {
 		self
 	}
 
+	/// Appends optional value, prefixed with presence flag, to the end of the stream.
+	pub fn append_option<T>(&mut self, t: &Option<T>) -> &mut Self where T: Serializable {
+		t.serialize(self);
+		self
+	}
+
 	/// Full stream.
 	pub fn out(self) -> Bytes {
 		self.buffer.into()
 	}
 }
 
-impl Write for Stream {
+impl<'a> Write for Stream<'a> {
 	#[inline]
 	fn write(&mut self, buf: &[u8]) -> Result<usize, io::Error> {
-		self.buffer.write(buf)
+		match self.writer {
+			Some(ref mut writer) => writer.write(buf),
+			None => self.buffer.write(buf),
+		}
 	}
 
 	#[inline]
 	fn flush(&mut self) -> Result<(), io::Error> {
-		self.buffer.flush()
+		match self.writer {
+			Some(ref mut writer) => writer.flush(),
+			None => self.buffer.flush(),
+		}
 	}
 }
@@ -1,5 +1,6 @@
-use std::fmt;
+use std::{fmt, io};
 use hash::H256;
+use ser::{Serializable, Deserializable, Error as ReaderError, Stream, Reader};
 
 /// Best block information
 #[derive(Clone, PartialEq, Default)]
@@ -18,3 +19,41 @@ impl fmt::Debug for BestBlock {
 			.finish()
 	}
 }
+
+impl Serializable for BestBlock {
+	fn serialize(&self, stream: &mut Stream) {
+		stream
+			.append(&self.number)
+			.append(&self.hash);
+	}
+}
+
+impl Deserializable for BestBlock {
+	fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, ReaderError> where T: io::Read {
+		let result = BestBlock {
+			number: reader.read()?,
+			hash: reader.read()?,
+		};
+
+		Ok(result)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use ser::{serialize, deserialize};
+	use super::BestBlock;
+
+	#[test]
+	fn best_block_serialization_roundtrip() {
+		let best_block = BestBlock {
+			number: 419200,
+			hash: 42.into(),
+		};
+
+		let serialized = serialize(&best_block);
+		assert_eq!(serialized.len(), 4 + 32);
+		assert_eq!(&serialized[..4], &[0x80, 0x65, 0x06, 0x00]);
+		assert_eq!(deserialize::<_, BestBlock>(&*serialized).unwrap(), best_block);
+	}
+}
@@ -13,6 +13,12 @@ impl<'a> BlockAncestors<'a> {
 			headers: headers,
 		}
 	}
+
+	/// Number of headers this iterator yields before stopping at the genesis block
+	/// (or at the first unknown block).
+	pub fn ancestors_until_genesis_count(self) -> usize {
+		self.count()
+	}
 }
 
 impl<'a> Iterator for BlockAncestors<'a> {
@@ -21,6 +27,8 @@ impl<'a> Iterator for BlockAncestors<'a> {
 	fn next(&mut self) -> Option<Self::Item> {
 		let result = self.block.take().and_then(|block| self.headers.block_header(block));
 		self.block = match result {
+			// genesis block has no parent
+			Some(ref header) if header.raw.previous_header_hash.is_zero() => None,
 			Some(ref header) => Some(BlockRef::Hash(header.raw.previous_header_hash.clone())),
 			None => None,
 		};
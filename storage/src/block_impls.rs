@@ -1,14 +1,9 @@
 use std::cmp;
+use std::collections::HashMap;
+use hash::H256;
 use chain::{OutPoint, TransactionOutput, IndexedBlock, IndexedTransaction};
 use {TransactionOutputProvider};
 
-fn transaction_output(transactions: &[IndexedTransaction], prevout: &OutPoint) -> Option<TransactionOutput> {
-	transactions.iter()
-		.find(|tx| tx.hash == prevout.hash)
-		.and_then(|tx| tx.raw.outputs.get(prevout.index as usize))
-		.cloned()
-}
-
 fn is_spent(transactions: &[IndexedTransaction], prevout: &OutPoint) -> bool {
 	// TODO: the code below is valid, but has rather poor performance!
 
@@ -25,11 +20,46 @@ fn is_spent(transactions: &[IndexedTransaction], prevout: &OutPoint) -> bool {
 
 impl TransactionOutputProvider for IndexedBlock {
 	fn transaction_output(&self, outpoint: &OutPoint, transaction_index: usize) -> Option<TransactionOutput> {
+		// only outputs of transactions that precede the spending one are visible
 		let take = cmp::min(transaction_index, self.transactions.len());
-		transaction_output(&self.transactions[..take], outpoint)
+		self.transaction_position(&outpoint.hash)
+			.filter(|position| *position < take)
+			.and_then(|position| self.transactions[position].raw.outputs.get(outpoint.index as usize))
+			.cloned()
 	}
 
 	fn is_spent(&self, outpoint: &OutPoint) -> bool {
 		is_spent(&self.transactions, outpoint)
 	}
 }
+
+/// Provides outputs of block transactions, found using the block transactions hash index.
+///
+/// Use it instead of `IndexedBlock` when outputs of many transactions are looked up.
+pub struct IndexedBlockOutputProvider<'a> {
+	block: &'a IndexedBlock,
+	index: HashMap<&'a H256, usize>,
+}
+
+impl<'a> IndexedBlockOutputProvider<'a> {
+	pub fn new(block: &'a IndexedBlock) -> Self {
+		IndexedBlockOutputProvider {
+			block: block,
+			index: block.build_hash_index(),
+		}
+	}
+}
+
+impl<'a> TransactionOutputProvider for IndexedBlockOutputProvider<'a> {
+	fn transaction_output(&self, outpoint: &OutPoint, transaction_index: usize) -> Option<TransactionOutput> {
+		// only outputs of transactions that precede the spending one are visible
+		self.index.get(&outpoint.hash)
+			.filter(|position| **position < transaction_index)
+			.and_then(|position| self.block.transactions[*position].raw.outputs.get(outpoint.index as usize))
+			.cloned()
+	}
+
+	fn is_spent(&self, outpoint: &OutPoint) -> bool {
+		is_spent(&self.block.transactions, outpoint)
+	}
+}
@@ -1,4 +1,6 @@
+use std::ops::Range;
 use hash::H256;
+use primitives::bigint::U256;
 use bytes::Bytes;
 use chain::{IndexedBlock, IndexedBlockHeader, IndexedTransaction};
 use {BlockRef};
@@ -9,6 +11,22 @@ pub trait BlockHeaderProvider {
 
 	/// resolves header bytes by block reference (number/hash)
 	fn block_header(&self, block_ref: BlockRef) -> Option<IndexedBlockHeader>;
+
+	/// resolves headers of blocks with numbers from given range, in order.
+	/// Stops at the first unknown block.
+	///
+	/// Default implementation reads headers one-by-one. Backends that support range
+	/// scans should override this.
+	fn block_headers_range(&self, range: Range<u32>) -> Vec<IndexedBlockHeader> {
+		let mut headers = Vec::with_capacity(range.len());
+		for number in range {
+			match self.block_header(number.into()) {
+				Some(header) => headers.push(header),
+				None => break,
+			}
+		}
+		headers
+	}
 }
 
 pub trait BlockProvider: BlockHeaderProvider {
@@ -31,4 +49,9 @@ pub trait BlockProvider: BlockHeaderProvider {
 
 	/// returns all transactions in the block by block reference (number/hash)
 	fn block_transactions(&self, block_ref: BlockRef) -> Vec<IndexedTransaction>;
+
+	/// returns total work of the chain, ending with given block (inclusive)
+	///
+	/// Returns None if block is unknown.
+	fn block_chain_work(&self, hash: &H256) -> Option<U256>;
 }
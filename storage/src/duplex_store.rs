@@ -1,8 +1,9 @@
 //! Some transaction validation rules,
 //! require sophisticated (in more than one source) previous transaction lookups
 
-use chain::{OutPoint, TransactionOutput};
-use TransactionOutputProvider;
+use hash::H256;
+use chain::{OutPoint, Transaction, TransactionOutput};
+use {TransactionOutputProvider, TransactionMetaProvider, TransactionMeta};
 
 #[derive(Clone, Copy)]
 pub struct DuplexTransactionOutputProvider<'a> {
@@ -25,11 +26,118 @@ impl<'a> TransactionOutputProvider for DuplexTransactionOutputProvider<'a> {
 			.or_else(|| self.second.transaction_output(prevout, transaction_index))
 	}
 
+	fn outputs_for_transaction(&self, tx: &Transaction, transaction_index: usize) -> Vec<Option<TransactionOutput>> {
+		let outputs = self.first.outputs_for_transaction(tx, transaction_index);
+		if outputs.iter().all(Option::is_some) {
+			return outputs;
+		}
+
+		outputs.into_iter()
+			.zip(self.second.outputs_for_transaction(tx, transaction_index))
+			.map(|(first, second)| first.or(second))
+			.collect()
+	}
+
 	fn is_spent(&self, prevout: &OutPoint) -> bool {
 		self.first.is_spent(prevout) || self.second.is_spent(prevout)
 	}
 }
 
+/// Same as `DuplexTransactionOutputProvider`, but with three sources, which are checked in order.
+///
+/// Transaction meta is read from meta sources, which are also checked in order. By default, sources
+/// have no meta; use `with_meta` for sources that have it.
+#[derive(Clone, Copy)]
+pub struct TriplexTransactionOutputProvider<'a> {
+	first: &'a TransactionOutputProvider,
+	second: &'a TransactionOutputProvider,
+	third: &'a TransactionOutputProvider,
+	first_meta: &'a TransactionMetaProvider,
+	second_meta: &'a TransactionMetaProvider,
+	third_meta: &'a TransactionMetaProvider,
+}
+
+impl<'a> TriplexTransactionOutputProvider<'a> {
+	pub fn new(
+		first: &'a TransactionOutputProvider,
+		second: &'a TransactionOutputProvider,
+		third: &'a TransactionOutputProvider,
+	) -> Self {
+		TriplexTransactionOutputProvider {
+			first: first,
+			second: second,
+			third: third,
+			first_meta: &NoopStore,
+			second_meta: &NoopStore,
+			third_meta: &NoopStore,
+		}
+	}
+
+	/// Sets meta sources, one per output source.
+	pub fn with_meta(
+		mut self,
+		first: &'a TransactionMetaProvider,
+		second: &'a TransactionMetaProvider,
+		third: &'a TransactionMetaProvider,
+	) -> Self {
+		self.first_meta = first;
+		self.second_meta = second;
+		self.third_meta = third;
+		self
+	}
+}
+
+impl<'a> TransactionOutputProvider for TriplexTransactionOutputProvider<'a> {
+	fn transaction_output(&self, prevout: &OutPoint, transaction_index: usize) -> Option<TransactionOutput> {
+		self.first.transaction_output(prevout, transaction_index)
+			.or_else(|| self.second.transaction_output(prevout, transaction_index))
+			.or_else(|| self.third.transaction_output(prevout, transaction_index))
+	}
+
+	fn outputs_for_transaction(&self, tx: &Transaction, transaction_index: usize) -> Vec<Option<TransactionOutput>> {
+		let outputs = DuplexTransactionOutputProvider::new(self.first, self.second)
+			.outputs_for_transaction(tx, transaction_index);
+		if outputs.iter().all(Option::is_some) {
+			return outputs;
+		}
+
+		outputs.into_iter()
+			.zip(self.third.outputs_for_transaction(tx, transaction_index))
+			.map(|(first, third)| first.or(third))
+			.collect()
+	}
+
+	fn is_spent(&self, prevout: &OutPoint) -> bool {
+		self.first.is_spent(prevout) || self.second.is_spent(prevout) || self.third.is_spent(prevout)
+	}
+}
+
+impl<'a> TransactionMetaProvider for TriplexTransactionOutputProvider<'a> {
+	fn transaction_meta(&self, hash: &H256) -> Option<TransactionMeta> {
+		self.first_meta.transaction_meta(hash)
+			.or_else(|| self.second_meta.transaction_meta(hash))
+			.or_else(|| self.third_meta.transaction_meta(hash))
+	}
+
+	fn transaction_is_coinbase(&self, hash: &H256) -> Option<bool> {
+		self.first_meta.transaction_is_coinbase(hash)
+			.or_else(|| self.second_meta.transaction_is_coinbase(hash))
+			.or_else(|| self.third_meta.transaction_is_coinbase(hash))
+	}
+
+	fn transaction_height(&self, hash: &H256) -> Option<u32> {
+		self.first_meta.transaction_height(hash)
+			.or_else(|| self.second_meta.transaction_height(hash))
+			.or_else(|| self.third_meta.transaction_height(hash))
+	}
+
+	fn transaction_block_hash(&self, hash: &H256) -> Option<H256> {
+		self.first_meta.transaction_block_hash(hash)
+			.or_else(|| self.second_meta.transaction_block_hash(hash))
+			.or_else(|| self.third_meta.transaction_block_hash(hash))
+	}
+}
+
 pub struct NoopStore;
 
 impl TransactionOutputProvider for NoopStore {
@@ -41,3 +149,135 @@ impl TransactionOutputProvider for NoopStore {
 		false
 	}
 }
+
+impl TransactionMetaProvider for NoopStore {
+	fn transaction_meta(&self, _hash: &H256) -> Option<TransactionMeta> {
+		None
+	}
+
+	fn transaction_block_hash(&self, _hash: &H256) -> Option<H256> {
+		None
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use chain::{OutPoint, Transaction, TransactionInput, TransactionOutput};
+	use primitives::hash::H256;
+	use {TransactionOutputProvider, TransactionMetaProvider, TransactionMeta};
+	use super::{DuplexTransactionOutputProvider, TriplexTransactionOutputProvider, NoopStore};
+
+	struct SingleOutputStore {
+		prevout: OutPoint,
+		output: TransactionOutput,
+		is_spent: bool,
+	}
+
+	impl SingleOutputStore {
+		fn new(value: u64, is_spent: bool) -> Self {
+			SingleOutputStore {
+				prevout: OutPoint { hash: H256::from(1), index: 0 },
+				output: TransactionOutput { value: value, ..Default::default() },
+				is_spent: is_spent,
+			}
+		}
+	}
+
+	impl TransactionOutputProvider for SingleOutputStore {
+		fn transaction_output(&self, prevout: &OutPoint, _transaction_index: usize) -> Option<TransactionOutput> {
+			if *prevout == self.prevout {
+				Some(self.output.clone())
+			} else {
+				None
+			}
+		}
+
+		fn is_spent(&self, prevout: &OutPoint) -> bool {
+			self.is_spent && *prevout == self.prevout
+		}
+	}
+
+	impl TransactionMetaProvider for SingleOutputStore {
+		fn transaction_meta(&self, hash: &H256) -> Option<TransactionMeta> {
+			if *hash == self.prevout.hash {
+				Some(TransactionMeta::new(self.output.value as u32, 1))
+			} else {
+				None
+			}
+		}
+
+		fn transaction_block_hash(&self, hash: &H256) -> Option<H256> {
+			self.transaction_meta(hash).map(|meta| H256::from(meta.height() as u8))
+		}
+	}
+
+	#[test]
+	fn triplex_output_from_third_source() {
+		let third = SingleOutputStore::new(30, true);
+		let store = TriplexTransactionOutputProvider::new(&NoopStore, &NoopStore, &third);
+
+		let prevout = OutPoint { hash: H256::from(1), index: 0 };
+		assert_eq!(store.transaction_output(&prevout, 0).map(|output| output.value), Some(30));
+		assert!(store.is_spent(&prevout));
+
+		let unknown_prevout = OutPoint { hash: H256::from(2), index: 0 };
+		assert_eq!(store.transaction_output(&unknown_prevout, 0), None);
+		assert!(!store.is_spent(&unknown_prevout));
+	}
+
+	#[test]
+	fn triplex_output_shadowed_by_earlier_source() {
+		let second = SingleOutputStore::new(20, false);
+		let third = SingleOutputStore::new(30, true);
+		let store = TriplexTransactionOutputProvider::new(&NoopStore, &second, &third);
+
+		let prevout = OutPoint { hash: H256::from(1), index: 0 };
+		assert_eq!(store.transaction_output(&prevout, 0).map(|output| output.value), Some(20));
+		// output is spent if it is spent in any source
+		assert!(store.is_spent(&prevout));
+	}
+
+	#[test]
+	fn triplex_meta_from_first_source_that_has_it() {
+		let second = SingleOutputStore::new(20, false);
+		let third = SingleOutputStore::new(30, false);
+
+		// sources have no meta by default
+		let store = TriplexTransactionOutputProvider::new(&NoopStore, &second, &third);
+		assert_eq!(store.transaction_height(&H256::from(1)), None);
+
+		let store = store.with_meta(&NoopStore, &NoopStore, &third);
+		assert_eq!(store.transaction_height(&H256::from(1)), Some(30));
+		assert_eq!(store.transaction_block_hash(&H256::from(1)), Some(H256::from(30)));
+
+		let store = store.with_meta(&NoopStore, &second, &third);
+		assert_eq!(store.transaction_meta(&H256::from(1)).map(|meta| meta.height()), Some(20));
+		assert_eq!(store.transaction_is_coinbase(&H256::from(1)), Some(false));
+		assert_eq!(store.transaction_meta(&H256::from(2)), None);
+	}
+
+	#[test]
+	fn duplex_outputs_for_transaction_from_both_sources() {
+		let first = SingleOutputStore::new(10, false);
+		let second = SingleOutputStore {
+			prevout: OutPoint { hash: H256::from(2), index: 0 },
+			..SingleOutputStore::new(20, false)
+		};
+		let input = |hash: u8| TransactionInput {
+			previous_output: OutPoint { hash: H256::from(hash), index: 0 },
+			..Default::default()
+		};
+		let tx = Transaction {
+			inputs: vec![input(2), input(1), input(3)],
+			..Default::default()
+		};
+
+		let values = |outputs: Vec<Option<TransactionOutput>>| outputs.into_iter()
+			.map(|output| output.map(|output| output.value))
+			.collect::<Vec<_>>();
+		let store = DuplexTransactionOutputProvider::new(&first, &second);
+		assert_eq!(values(store.outputs_for_transaction(&tx, 0)), vec![Some(20), Some(10), None]);
+		let store = TriplexTransactionOutputProvider::new(&NoopStore, &first, &second);
+		assert_eq!(values(store.outputs_for_transaction(&tx, 0)), vec![Some(20), Some(10), None]);
+	}
+}
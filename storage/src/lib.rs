@@ -26,6 +26,8 @@ mod store;
 mod transaction_meta;
 mod transaction_provider;
 mod nullifier_tracker;
+mod shielded_pools;
+mod shielded_pool_provider;
 mod tree_state;
 mod tree_state_provider;
 
@@ -34,11 +36,12 @@ pub use primitives::{hash, bytes};
 pub use best_block::BestBlock;
 pub use block_ancestors::BlockAncestors;
 pub use block_chain::{BlockChain, ForkChain, Forkable};
+pub use block_impls::IndexedBlockOutputProvider;
 pub use block_iterator::BlockIterator;
 pub use block_origin::{BlockOrigin, SideChainOrigin};
 pub use block_provider::{BlockHeaderProvider, BlockProvider};
 pub use block_ref::BlockRef;
-pub use duplex_store::{DuplexTransactionOutputProvider, NoopStore};
+pub use duplex_store::{DuplexTransactionOutputProvider, TriplexTransactionOutputProvider, NoopStore};
 pub use error::Error;
 pub use store::{AsSubstore, Store, SharedStore, CanonStore};
 pub use transaction_meta::TransactionMeta;
@@ -46,8 +49,10 @@ pub use transaction_provider::{
 	TransactionProvider, TransactionOutputProvider, TransactionMetaProvider, CachedTransactionOutputProvider,
 };
 pub use nullifier_tracker::NullifierTracker;
-pub use tree_state::{TreeState, H32 as H32TreeDim, Dim as TreeDim, SproutTreeState, SaplingTreeState};
+pub use tree_state::{TreeState, TreeStateCheckpoint, AuthPath, H32 as H32TreeDim, Dim as TreeDim, SproutTreeState, SaplingTreeState};
 pub use tree_state_provider::TreeStateProvider;
+pub use shielded_pools::ShieldedPools;
+pub use shielded_pool_provider::ShieldedPoolProvider;
 
 use hash::H256;
 
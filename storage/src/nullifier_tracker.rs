@@ -3,4 +3,14 @@ use EpochRef;
 /// Trait to query existing nullifier.
 pub trait NullifierTracker : Sync {
 	fn contains_nullifier(&self, nullifier: EpochRef) -> bool;
+
+	/// Returns first of given nullifiers that is already known.
+	///
+	/// Default implementation queries nullifiers one-by-one. Backends that support batched
+	/// reads should override this.
+	fn contains_any(&self, nullifiers: &[EpochRef]) -> Option<EpochRef> {
+		nullifiers.iter()
+			.find(|nullifier| self.contains_nullifier(**nullifier))
+			.cloned()
+	}
 }
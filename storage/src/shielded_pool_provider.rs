@@ -0,0 +1,10 @@
+use hash::H256;
+use ShieldedPools;
+
+/// Trait to query balances of shielded pools.
+pub trait ShieldedPoolProvider : Send + Sync {
+	/// Returns balances of shielded pools after the block has been applied.
+	///
+	/// Returns None if balances are unknown (e.g. block has been inserted by the older version).
+	fn shielded_pools_at_block(&self, block_hash: &H256) -> Option<ShieldedPools>;
+}
@@ -0,0 +1,129 @@
+//! Shielded value pools balances
+
+use std::io;
+use chain::Transaction;
+use ser::{Serializable, Deserializable, Error as ReaderError, Stream, Reader};
+use EpochTag;
+
+/// Total values held in shielded pools at some block.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ShieldedPools {
+	/// Total value of the sprout pool.
+	pub sprout: u64,
+	/// Total value of the sapling pool.
+	pub sapling: u64,
+}
+
+impl Serializable for ShieldedPools {
+	fn serialize(&self, stream: &mut Stream) {
+		stream
+			.append(&self.sprout)
+			.append(&self.sapling);
+	}
+}
+
+impl Deserializable for ShieldedPools {
+	fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, ReaderError> where T: io::Read {
+		let result = ShieldedPools {
+			sprout: reader.read()?,
+			sapling: reader.read()?,
+		};
+
+		Ok(result)
+	}
+}
+
+impl ShieldedPools {
+	/// Applies value flows of all block transactions to the pools.
+	///
+	/// Pools are allowed to go negative in the middle of the block - ZIP-209 only requires
+	/// them to be non-negative after the whole block is applied.
+	/// Returns tag of the pool that is negative after the block, if any.
+	pub fn apply_block<'a, I>(&self, transactions: I) -> Result<ShieldedPools, EpochTag> where I: IntoIterator<Item=&'a Transaction> {
+		// signed sums can't overflow: every block transaction moves at most 2^64 per pool
+		let mut sprout = self.sprout as i128;
+		let mut sapling = self.sapling as i128;
+		for transaction in transactions {
+			// transaction which values overflow is invalid and can't be applied to the sprout pool
+			let balance = transaction.shielded_value_balance().map_err(|_| EpochTag::Sprout)?;
+			sprout += balance.sprout_in as i128;
+			sprout -= balance.sprout_out as i128;
+			sapling -= balance.sapling_net as i128;
+		}
+
+		Ok(ShieldedPools {
+			sprout: pool_value(sprout).ok_or(EpochTag::Sprout)?,
+			sapling: pool_value(sapling).ok_or(EpochTag::Sapling)?,
+		})
+	}
+}
+
+fn pool_value(value: i128) -> Option<u64> {
+	if value < 0 || value > u64::max_value() as i128 {
+		None
+	} else {
+		Some(value as u64)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use chain::{Transaction, JoinSplit, JoinSplitDescription, Sapling};
+	use ser::{serialize, deserialize};
+	use EpochTag;
+	use super::ShieldedPools;
+
+	fn join_split_transaction(value_pub_old: u64, value_pub_new: u64) -> Transaction {
+		Transaction {
+			join_split: Some(JoinSplit {
+				descriptions: vec![JoinSplitDescription {
+					value_pub_old: value_pub_old,
+					value_pub_new: value_pub_new,
+					..Default::default()
+				}],
+				..Default::default()
+			}),
+			..Default::default()
+		}
+	}
+
+	fn sapling_transaction(balancing_value: i64) -> Transaction {
+		Transaction {
+			sapling: Some(Sapling {
+				balancing_value: balancing_value,
+				..Default::default()
+			}),
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn test_shielded_pools_apply_block() {
+		let pools = ShieldedPools::default();
+		let pools = pools.apply_block(&[join_split_transaction(100, 0), join_split_transaction(0, 40)]).unwrap();
+		let pools = pools.apply_block(&[sapling_transaction(-50), sapling_transaction(20)]).unwrap();
+		assert_eq!(pools, ShieldedPools { sprout: 60, sapling: 30 });
+
+		assert_eq!(pools.apply_block(&[join_split_transaction(0, 61)]), Err(EpochTag::Sprout));
+		assert_eq!(pools.apply_block(&[sapling_transaction(31)]), Err(EpochTag::Sapling));
+	}
+
+	#[test]
+	fn test_shielded_pools_apply_block_checks_pools_at_block_end() {
+		// pool goes negative in the middle of the block, but is refilled by later transaction
+		let pools = ShieldedPools { sprout: 10, sapling: 10 };
+		assert_eq!(pools.apply_block(&[join_split_transaction(0, 20), join_split_transaction(15, 0)]),
+			Ok(ShieldedPools { sprout: 5, sapling: 10 }));
+		assert_eq!(pools.apply_block(&[sapling_transaction(20), sapling_transaction(-15)]),
+			Ok(ShieldedPools { sprout: 10, sapling: 5 }));
+
+		// pool is negative after the block
+		assert_eq!(pools.apply_block(&[join_split_transaction(0, 20), join_split_transaction(5, 0)]), Err(EpochTag::Sprout));
+	}
+
+	#[test]
+	fn test_shielded_pools_serialization() {
+		let pools = ShieldedPools { sprout: 1, sapling: 2 };
+		assert_eq!(deserialize::<_, ShieldedPools>(serialize(&pools).as_ref()).unwrap(), pools);
+	}
+}
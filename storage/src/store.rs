@@ -3,6 +3,7 @@ use chain::IndexedBlockHeader;
 use {
 	BestBlock, BlockProvider, BlockHeaderProvider, TransactionProvider, TransactionMetaProvider,
 	TransactionOutputProvider, BlockChain, Forkable, NullifierTracker, TreeStateProvider,
+	ShieldedPoolProvider,
 };
 
 pub trait CanonStore: Store + Forkable {
@@ -16,6 +17,9 @@ pub trait Store: AsSubstore {
 
 	/// get best header
 	fn best_header(&self) -> IndexedBlockHeader;
+
+	/// get tips of known side chains
+	fn side_chain_tips(&self) -> Vec<BestBlock>;
 }
 
 /// Allows casting Arc<Store> to reference to any substore type
@@ -40,6 +44,8 @@ pub trait AsSubstore:
 	fn as_nullifier_tracker(&self) -> &NullifierTracker;
 
 	fn as_tree_state_provider(&self) -> &TreeStateProvider;
+
+	fn as_shielded_pool_provider(&self) -> &ShieldedPoolProvider;
 }
 
 impl<T> AsSubstore for T
@@ -49,7 +55,8 @@ impl<T> AsSubstore for T
 		TransactionMetaProvider +
 		TransactionOutputProvider +
 		NullifierTracker +
-		TreeStateProvider
+		TreeStateProvider +
+		ShieldedPoolProvider
 {
 	fn as_block_provider(&self) -> &BlockProvider {
 		&*self
@@ -78,6 +85,10 @@ impl<T> AsSubstore for T
 	fn as_tree_state_provider(&self) -> &TreeStateProvider {
 		&*self
 	}
+
+	fn as_shielded_pool_provider(&self) -> &ShieldedPoolProvider {
+		&*self
+	}
 }
 
 pub type SharedStore = Arc<CanonStore + Send + Sync>;
@@ -3,7 +3,8 @@
 use std::io;
 use bit_vec::BitVec;
 use bytes::Bytes;
-use ser::{Serializable, Deserializable, Error as ReaderError, Stream, Reader};
+use chain::TransactionOutput;
+use ser::{Serializable, Deserializable, Error as ReaderError, Stream, Reader, CompactInteger};
 
 /// structure for indexing transaction info
 #[derive(Debug, Clone)]
@@ -49,6 +50,20 @@ impl TransactionMeta {
 		result
 	}
 
+	/// Reads block height and coinbase flag from the serialized meta, without decoding the rest of bits.
+	pub fn read_height_and_coinbase(bytes: &[u8]) -> Result<(u32, bool), ReaderError> {
+		let mut reader = Reader::new(bytes);
+		let block_height = reader.read()?;
+		let bits_len: usize = reader.read::<CompactInteger>()?.into();
+		if bits_len == 0 {
+			return Err(ReaderError::MalformedData);
+		}
+
+		// the first bit is the most significant bit of the first byte
+		let first_byte: u8 = reader.read()?;
+		Ok((block_height, first_byte & 0x80 != 0))
+	}
+
 	/// Returns true if it is a coinbase transaction
 	pub fn is_coinbase(&self) -> bool {
 		self.bits.get(0)
@@ -60,6 +75,15 @@ impl TransactionMeta {
 		self.bits.set(index + 1 , true);
 	}
 
+	/// Denote provably unspendable outputs as used, so that they're never tracked as unspent
+	pub fn denote_unspendable(&mut self, outputs: &[TransactionOutput]) {
+		for (index, output) in outputs.iter().enumerate() {
+			if output.is_unspendable() {
+				self.denote_used(index);
+			}
+		}
+	}
+
 	/// Denote particular output as not used
 	pub fn denote_unused(&mut self, index: usize) {
 		self.bits.set(index + 1, false);
@@ -81,8 +105,20 @@ impl TransactionMeta {
 
 #[cfg(test)]
 mod tests {
+	use chain::TransactionOutput;
+	use ser::serialize;
 	use super::TransactionMeta;
 
+	#[test]
+	fn test_read_height_and_coinbase() {
+		let meta = TransactionMeta::new(100, 10);
+		assert_eq!(TransactionMeta::read_height_and_coinbase(&serialize(&meta)), Ok((100, false)));
+
+		let mut meta = TransactionMeta::new_coinbase(200, 10);
+		meta.denote_used(0);
+		assert_eq!(TransactionMeta::read_height_and_coinbase(&serialize(&meta)), Ok((200, true)));
+	}
+
 	#[test]
 	fn test_is_fully_spent() {
 		let t = TransactionMeta::new(0, 0);
@@ -95,4 +131,20 @@ mod tests {
 		t.denote_unused(0);
 		assert!(!t.is_fully_spent());
 	}
+
+	#[test]
+	fn test_denote_unspendable() {
+		let outputs = vec![
+			TransactionOutput { value: 0, script_pubkey: "6a0b68656c6c6f20776f726c64".into() },
+			TransactionOutput { value: 10, script_pubkey: "76a914c8e90996c7c6080ee06284600c684ed904d14c5c88ac".into() },
+		];
+
+		let mut t = TransactionMeta::new(0, outputs.len());
+		t.denote_unspendable(&outputs);
+		assert_eq!(t.is_spent(0), Some(true));
+		assert_eq!(t.is_spent(1), Some(false));
+		assert!(!t.is_fully_spent());
+		t.denote_used(1);
+		assert!(t.is_fully_spent());
+	}
 }
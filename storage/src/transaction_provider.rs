@@ -2,7 +2,7 @@ use std::collections::HashMap;
 use parking_lot::RwLock;
 use hash::H256;
 use bytes::Bytes;
-use chain::{IndexedTransaction, OutPoint, TransactionOutput};
+use chain::{IndexedTransaction, OutPoint, Transaction, TransactionOutput};
 use {TransactionMeta};
 
 /// Should be used to obtain all transactions from canon chain and forks.
@@ -24,6 +24,13 @@ pub trait TransactionOutputProvider: Send + Sync {
 	/// Returns transaction output.
 	fn transaction_output(&self, outpoint: &OutPoint, transaction_index: usize) -> Option<TransactionOutput>;
 
+	/// Returns outputs spent by all inputs of given transaction, in input order.
+	fn outputs_for_transaction(&self, tx: &Transaction, transaction_index: usize) -> Vec<Option<TransactionOutput>> {
+		tx.inputs.iter()
+			.map(|input| self.transaction_output(&input.previous_output, transaction_index))
+			.collect()
+	}
+
 	/// Returns true if we know that output is double spent.
 	fn is_spent(&self, outpoint: &OutPoint) -> bool;
 }
@@ -33,6 +40,22 @@ pub trait TransactionMetaProvider: Send + Sync {
 	/// Returns None if transaction with given hash does not exist
 	/// Otherwise returns transaction meta object
 	fn transaction_meta(&self, hash: &H256) -> Option<TransactionMeta>;
+
+	/// Returns None if transaction with given hash does not exist
+	/// Otherwise returns true if it is a coinbase transaction
+	fn transaction_is_coinbase(&self, hash: &H256) -> Option<bool> {
+		self.transaction_meta(hash).map(|meta| meta.is_coinbase())
+	}
+
+	/// Returns None if transaction with given hash does not exist
+	/// Otherwise returns height of the block that contains this transaction
+	fn transaction_height(&self, hash: &H256) -> Option<u32> {
+		self.transaction_meta(hash).map(|meta| meta.height())
+	}
+
+	/// Returns None if transaction with given hash does not exist in the canon chain
+	/// Otherwise returns hash of the block that contains this transaction
+	fn transaction_block_hash(&self, hash: &H256) -> Option<H256>;
 }
 
 /// Transaction output provider that caches all read outputs.
@@ -67,6 +90,32 @@ impl<'a> TransactionOutputProvider for CachedTransactionOutputProvider<'a> {
 		}
 	}
 
+	fn outputs_for_transaction(&self, tx: &Transaction, transaction_index: usize) -> Vec<Option<TransactionOutput>> {
+		let cached_values = {
+			let cached_outputs = self.cached_outputs.read();
+			tx.inputs.iter()
+				.map(|input| cached_outputs.get(&input.previous_output).cloned())
+				.collect::<Vec<_>>()
+		};
+		if cached_values.iter().all(Option::is_some) {
+			return cached_values.into_iter().map(|value| value.expect("checked above; qed")).collect();
+		}
+
+		// outputs that aren't cached yet are read from the backend at once
+		let values_from_backend = self.backend.outputs_for_transaction(tx, transaction_index);
+		let mut cached_outputs = self.cached_outputs.write();
+		tx.inputs.iter()
+			.zip(cached_values.into_iter().zip(values_from_backend))
+			.map(|(input, (cached_value, value_from_backend))| match cached_value {
+				Some(cached_value) => cached_value,
+				None => {
+					cached_outputs.insert(input.previous_output.clone(), value_from_backend.clone());
+					value_from_backend
+				},
+			})
+			.collect()
+	}
+
 	fn is_spent(&self, outpoint: &OutPoint) -> bool {
 		self.backend.is_spent(outpoint)
 	}
@@ -1,5 +1,13 @@
 use hash::H256;
 use crypto::{sha256_compress, pedersen_hash};
+#[cfg(feature = "sapling-tree-cache")]
+use lru_cache::LruCache;
+#[cfg(feature = "sapling-tree-cache")]
+use parking_lot::Mutex;
+
+/// Max number of cached sapling tree nodes.
+#[cfg(feature = "sapling-tree-cache")]
+const SAPLING_COMBINE_CACHE_SIZE: usize = 4096;
 
 lazy_static! {
 	static ref SPROUT_EMPTY_ROOTS: Vec<H256> = [
@@ -138,6 +146,14 @@ lazy_static! {
 	].to_vec();
 }
 
+#[cfg(feature = "sapling-tree-cache")]
+lazy_static! {
+	/// Cache of (left, right, depth) => parent hashes of sapling tree nodes.
+	/// Depth is the part of the key, because pedersen hash is personalized with it.
+	static ref SAPLING_COMBINE_CACHE: Mutex<LruCache<(H256, H256, usize), H256>> =
+		Mutex::new(LruCache::new(SAPLING_COMBINE_CACHE_SIZE));
+}
+
 pub trait Dim {
 	const HEIGHT: usize;
 }
@@ -185,9 +201,42 @@ impl TreeHash for SaplingTreeHash {
 		&SAPLING_EMPTY_ROOTS
 	}
 
+	#[cfg(not(feature = "sapling-tree-cache"))]
 	fn combine(left: &H256, right: &H256, depth: usize) -> H256 {
 		pedersen_hash(&**left, &**right, depth)
 	}
+
+	#[cfg(feature = "sapling-tree-cache")]
+	fn combine(left: &H256, right: &H256, depth: usize) -> H256 {
+		// lock isn't held while hashing => concurrent callers may hash the same pair twice,
+		// but they never wait for each other
+		let key = (left.clone(), right.clone(), depth);
+		if let Some(hash) = SAPLING_COMBINE_CACHE.lock().get_mut(&key) {
+			return hash.clone();
+		}
+
+		let hash = pedersen_hash(&**left, &**right, depth);
+		SAPLING_COMBINE_CACHE.lock().insert(key, hash.clone());
+		hash
+	}
+}
+
+/// Authentication path of the leaf in the commitment tree.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AuthPath {
+	/// Index of the leaf in the tree.
+	pub position: u64,
+	/// Sibling hashes, starting from the leaf level up to the root.
+	pub siblings: Vec<H256>,
+}
+
+/// Saved state of the tree, used to revert appended leaves.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TreeStateCheckpoint {
+	left: Option<H256>,
+	right: Option<H256>,
+	parents: Vec<Option<H256>>,
+	is_empty: bool,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -266,6 +315,73 @@ impl<D: Dim, H: TreeHash> TreeState<D, H> {
 	pub fn empty_root() -> H256 {
 		H::empty()[D::HEIGHT]
 	}
+
+	/// Save current state of the tree.
+	pub fn checkpoint(&self) -> TreeStateCheckpoint {
+		TreeStateCheckpoint {
+			left: self.left.clone(),
+			right: self.right.clone(),
+			parents: self.parents.clone(),
+			is_empty: self.is_empty,
+		}
+	}
+
+	/// Revert all leaves, appended after the checkpoint has been made.
+	pub fn rollback(&mut self, checkpoint: TreeStateCheckpoint) {
+		debug_assert_eq!(checkpoint.parents.len(), D::HEIGHT - 1);
+
+		self.left = checkpoint.left;
+		self.right = checkpoint.right;
+		self.parents = checkpoint.parents;
+		self.is_empty = checkpoint.is_empty;
+	}
+
+	/// Authentication path of the most recently appended leaf.
+	///
+	/// Returns None if the tree is empty.
+	pub fn witness(&self) -> Option<AuthPath> {
+		if self.is_empty {
+			return None;
+		}
+
+		let mut position = 0u64;
+		let mut siblings = Vec::with_capacity(D::HEIGHT);
+		match self.right {
+			Some(_) => {
+				position |= 1;
+				siblings.push(self.left.clone().expect("right is only set after left; qed"));
+			},
+			None => siblings.push(H::empty()[0].clone()),
+		}
+
+		for i in 0..D::HEIGHT-1 {
+			match &self.parents[i] {
+				&Some(ref parent) => {
+					position |= 1 << (i + 1);
+					siblings.push(parent.clone());
+				},
+				&None => siblings.push(H::empty()[i + 1].clone()),
+			}
+		}
+
+		Some(AuthPath {
+			position: position,
+			siblings: siblings,
+		})
+	}
+
+	/// Compute tree root from the leaf and its authentication path.
+	pub fn root_from_path(leaf: &H256, path: &AuthPath) -> H256 {
+		let mut root = leaf.clone();
+		for (depth, sibling) in path.siblings.iter().enumerate() {
+			root = match (path.position >> depth) & 1 {
+				0 => H::combine(&root, sibling, depth),
+				_ => H::combine(sibling, &root, depth),
+			};
+		}
+
+		root
+	}
 }
 
 pub type SproutTreeState = TreeState<H29, SproutTreeHash>;
@@ -273,8 +389,8 @@ pub type SaplingTreeState = TreeState<H32, SaplingTreeHash>;
 
 impl<D: Dim, H: TreeHash> serialization::Serializable for TreeState<D, H> {
 	fn serialize(&self, stream: &mut serialization::Stream) {
-		stream.append(&self.left);
-		stream.append(&self.right);
+		stream.append_option(&self.left);
+		stream.append_option(&self.right);
 		stream.append_list(&self.parents);
 	}
 }
@@ -284,8 +400,8 @@ impl<D: Dim, H: TreeHash> serialization::Deserializable for TreeState<D, H> {
 		-> Result<Self, serialization::Error>
 	{
 		let mut tree_state = TreeState::new();
-		tree_state.left = reader.read()?;
-		tree_state.right = reader.read()?;
+		tree_state.left = reader.read_option()?;
+		tree_state.right = reader.read_option()?;
 		tree_state.parents = reader.read_list()?;
 
 		tree_state.is_empty = tree_state.left.is_none()
@@ -520,6 +636,78 @@ mod tests {
 		assert!(tree.append(H256::from("0bf622cb9f901b7532433ea2e7c1b7632f5935899b62dcf897a71551997dc8cc")).is_err());
 	}
 
+	#[test]
+	fn witness_empty() {
+		assert_eq!(TestSproutTreeState::new().witness(), None);
+		assert_eq!(TestSaplingTreeState::new().witness(), None);
+	}
+
+	#[test]
+	fn witness_commitments_full() {
+		let mut tree = TestSproutTreeState::new();
+
+		for i in 0..TEST_COMMITMENTS.len() {
+			tree.append(TEST_COMMITMENTS[i].clone()).expect(&format!("Failed to add commitment #{}", i));
+
+			let path = tree.witness().expect("tree is not empty");
+			assert_eq!(path.position, i as u64);
+			assert_eq!(path.siblings.len(), 4);
+			assert_eq!(TestSproutTreeState::root_from_path(&TEST_COMMITMENTS[i], &path), tree.root());
+		}
+	}
+
+	#[test]
+	fn witness_sapling() {
+		let mut tree = TestSaplingTreeState::new();
+
+		for i in 0..TEST_COMMITMENTS.len() {
+			tree.append(TEST_COMMITMENTS[i].clone()).expect(&format!("Failed to add commitment #{}", i));
+
+			let path = tree.witness().expect("tree is not empty");
+			assert_eq!(TestSaplingTreeState::root_from_path(&TEST_COMMITMENTS[i], &path), tree.root());
+		}
+	}
+
+	#[test]
+	fn rollback() {
+		let mut tree = TestSproutTreeState::new();
+		for i in 0..5 {
+			tree.append(TEST_COMMITMENTS[i].clone()).expect(&format!("Failed to add commitment #{}", i));
+		}
+
+		let checkpoint = tree.checkpoint();
+		let checkpoint_root = tree.root();
+		let checkpoint_bytes = serialization::serialize(&tree);
+
+		for i in 5..TEST_COMMITMENTS.len() {
+			tree.append(TEST_COMMITMENTS[i].clone()).expect(&format!("Failed to add commitment #{}", i));
+		}
+		assert!(tree.root() != checkpoint_root);
+
+		tree.rollback(checkpoint);
+		assert_eq!(tree.root(), checkpoint_root);
+		assert_eq!(serialization::serialize(&tree), checkpoint_bytes);
+
+		// appending after rollback gives the same roots again
+		tree.append(TEST_COMMITMENTS[5].clone()).unwrap();
+		assert_eq!(tree.root(), H256::from("2ff57f5468c6afdad30ec0fb6c2cb67289f12584e2c20c4e0065f66748697d77"));
+	}
+
+	#[test]
+	fn rollback_to_empty() {
+		let mut tree = TestSaplingTreeState::new();
+		let checkpoint = tree.checkpoint();
+
+		tree.append(TEST_COMMITMENTS[0].clone()).unwrap();
+		tree.append(TEST_COMMITMENTS[1].clone()).unwrap();
+		tree.append(TEST_COMMITMENTS[2].clone()).unwrap();
+
+		tree.rollback(checkpoint);
+		assert!(tree.is_empty);
+		assert_eq!(tree.root(), TestSaplingTreeState::empty_root());
+		assert_eq!(serialization::serialize(&tree), serialization::serialize(&TestSaplingTreeState::new()));
+	}
+
 	#[test]
 	fn serde() {
 		let mut tree = TestSproutTreeState::new();
@@ -618,4 +806,28 @@ mod tests {
 			assert_eq!(actual_root, *expected_root);
 		}
 	}
+
+	#[cfg(feature = "sapling-tree-cache")]
+	#[test]
+	fn sapling_tree_cache() {
+		let build_tree = || {
+			let mut tree = TestSaplingTreeState::new();
+			for commitment in TEST_COMMITMENTS.iter().take(6) {
+				tree.append(commitment.clone()).unwrap();
+			}
+			tree
+		};
+
+		// first tree fills the cache, second one reads from it
+		let tree1 = build_tree();
+		let tree2 = build_tree();
+		assert_eq!(tree1.root(), tree2.root());
+
+		// same hashes at different depths aren't mixed up
+		let (left, right) = (&TEST_COMMITMENTS[0], &TEST_COMMITMENTS[1]);
+		for depth in 0..4 {
+			assert_eq!(SaplingTreeHash::combine(left, right, depth), pedersen_hash(&**left, &**right, depth));
+		}
+		assert!(SaplingTreeHash::combine(left, right, 0) != SaplingTreeHash::combine(left, right, 1));
+	}
 }
@@ -1,5 +1,5 @@
 use hash::H256;
-use {SproutTreeState, SaplingTreeState};
+use {SproutTreeState, SaplingTreeState, EpochTag};
 
 pub trait TreeStateProvider : Send + Sync {
 	fn sprout_tree_at(&self, root: &H256) -> Option<SproutTreeState>;
@@ -10,6 +10,12 @@ pub trait TreeStateProvider : Send + Sync {
 
 	fn sapling_block_root(&self, block_hash: &H256) -> Option<H256>;
 
+	/// Root of the epoch tree after the canonical block at given height.
+	fn root_at_height(&self, epoch: EpochTag, height: u32) -> Option<H256>;
+
+	/// Height of the first canonical block after which the epoch tree has given root.
+	fn height_of_root(&self, epoch: EpochTag, root: &H256) -> Option<u32>;
+
 	fn sprout_tree_at_block(&self, block_hash: &H256) -> Option<SproutTreeState> {
 		self.sprout_block_root(block_hash).and_then(|h| self.sprout_tree_at(&h))
 	}
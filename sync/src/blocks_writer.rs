@@ -15,6 +15,7 @@ use synchronization_verifier::{
 use types::{PeerIndex, StorageRef};
 use utils::OrphanBlocksPool;
 use VerificationParameters;
+use verification::Error as VerificationError;
 
 /// Maximum number of orphaned in-memory blocks
 pub const MAX_ORPHANED_BLOCKS: usize = 1024;
@@ -127,8 +128,8 @@ impl BlockVerificationSink for BlocksWriterSink {
 		None
 	}
 
-	fn on_block_verification_error(&self, err: &str, _hash: &H256) {
-		self.data.lock().err = Some(Error::Verification(err.into()));
+	fn on_block_verification_error(&self, err: &VerificationError, _hash: &H256) {
+		self.data.lock().err = Some(Error::Verification(format!("{:?}", err)));
 	}
 }
 
@@ -40,6 +40,7 @@ mod utils;
 
 pub use types::LocalNodeRef;
 pub use types::PeersRef;
+pub use types::MemoryPoolRef;
 
 use std::sync::Arc;
 use parking_lot::RwLock;
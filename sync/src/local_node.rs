@@ -66,6 +66,11 @@ impl<U, V> LocalNode<U, V> where U: Server, V: Client {
 		self.state.clone()
 	}
 
+	/// Return shared reference to memory pool.
+	pub fn memory_pool(&self) -> MemoryPoolRef {
+		self.memory_pool.clone()
+	}
+
 	/// When new peer connects to the node
 	pub fn on_connect(&self, peer_index: PeerIndex, peer_name: String, version: types::Version) {
 		trace!(target: "sync", "Starting new sync session with peer#{}: {}", peer_index, peer_name);
@@ -20,6 +20,7 @@ use synchronization_verifier::{
 	TransactionVerificationSink, VerificationTask, PartiallyVerifiedBlock,
 };
 use types::{BlockHeight, ClientCoreRef, PeersRef, PeerIndex, SynchronizationStateRef, EmptyBoxFuture, SyncListenerRef};
+use verification::Error as VerificationError;
 use utils::{AverageSpeedMeter, OrphanBlocksPool, OrphanTransactionsPool, HashPosition};
 #[cfg(test)] use synchronization_peers_tasks::{Information as PeersTasksInformation};
 #[cfg(test)] use synchronization_chain::{Information as ChainInformation};
@@ -784,7 +785,7 @@ impl<T> BlockVerificationSink for CoreVerificationSink<T> where T: TaskExecutor
 	}
 
 	/// Process failed block verification
-	fn on_block_verification_error(&self, err: &str, hash: &H256) {
+	fn on_block_verification_error(&self, err: &VerificationError, hash: &H256) {
 		self.core.lock().on_block_verification_error(err, hash)
 	}
 }
@@ -1197,7 +1198,7 @@ impl<T> SynchronizationClientCore<T> where T: TaskExecutor {
 		}
 	}
 
-	fn on_block_verification_error(&mut self, err: &str, hash: &H256) {
+	fn on_block_verification_error(&mut self, err: &VerificationError, hash: &H256) {
 		warn!(target: "sync", "Block {:?} verification failed with error {:?}", hash.to_reversed_str(), err);
 
 		// remove flags
@@ -1217,7 +1218,11 @@ impl<T> SynchronizationClientCore<T> where T: TaskExecutor {
 		self.chain.forget_block_with_children(hash);
 
 		// mark failed block as dead end (this branch won't be synchronized)
-		self.chain.mark_dead_end_block(hash);
+		// mutated block has the same hash as the valid block (CVE-2012-2459) => valid block must
+		// still be accepted if some peer provides it
+		if *err != VerificationError::MerkleMutation {
+			self.chain.mark_dead_end_block(hash);
+		}
 
 		// awake threads, waiting for this block insertion
 		self.awake_waiting_threads(hash);
@@ -1344,7 +1349,7 @@ pub mod tests {
 	use miner::MemoryPool;
 	use network::{ConsensusParams, Network};
 	use primitives::hash::H256;
-	use verification::BackwardsCompatibleChainVerifier as ChainVerifier;
+	use verification::{BackwardsCompatibleChainVerifier as ChainVerifier, Error as VerificationError};
 	use inbound_connection::tests::DummyOutboundSyncConnection;
 	use synchronization_chain::{Chain, BlockState};
 	use synchronization_client::{SynchronizationClient, Client};
@@ -2097,7 +2102,7 @@ pub mod tests {
 
 		// simulate verification during b21 verification
 		let mut dummy_verifier = DummyVerifier::default();
-		dummy_verifier.error_when_verifying(b21.hash(), "simulated");
+		dummy_verifier.block_error_when_verifying(b21.hash(), VerificationError::Empty);
 
 		let (_, _, sync) = create_sync(None, Some(dummy_verifier));
 
@@ -2204,7 +2209,7 @@ pub mod tests {
 
 		// simulate verification error during b0 verification
 		let mut dummy_verifier = DummyVerifier::default();
-		dummy_verifier.error_when_verifying(b0.hash(), "simulated");
+		dummy_verifier.block_error_when_verifying(b0.hash(), VerificationError::Empty);
 
 		let (_, core, sync) = create_sync(None, Some(dummy_verifier));
 
@@ -2216,6 +2221,27 @@ pub mod tests {
 		assert!(!core.lock().peers.enumerate().contains(&0));
 	}
 
+	#[test]
+	fn mutated_block_is_not_marked_as_dead_end() {
+		let genesis = test_data::genesis();
+		let b0 = test_data::block_builder().header().parent(genesis.hash()).build().build();
+		let b1 = test_data::block_builder().header().parent(genesis.hash()).nonce(1.into()).build().build();
+
+		// simulate verification errors
+		let mut dummy_verifier = DummyVerifier::default();
+		dummy_verifier.block_error_when_verifying(b0.hash(), VerificationError::MerkleMutation);
+		dummy_verifier.block_error_when_verifying(b1.hash(), VerificationError::Empty);
+
+		let (_, core, sync) = create_sync(None, Some(dummy_verifier));
+
+		sync.on_block(0, b0.clone().into());
+		sync.on_block(1, b1.clone().into());
+
+		// block with the same hash, but with valid transactions could still be synchronized
+		assert_eq!(core.lock().chain().block_state(&b0.hash()), BlockState::Unknown);
+		assert_eq!(core.lock().chain().block_state(&b1.hash()), BlockState::DeadEnd);
+	}
+
 	#[test]
 	fn collection_closed_on_begin_dead_end_block_header() {
 		let genesis = test_data::genesis();
@@ -2430,7 +2456,7 @@ pub mod tests {
 		let storage = Arc::new(BlockChainDatabase::init_test_chain(vec![b0.into()]));
 
 		let (_, core, sync) = create_sync(Some(storage), Some(dummy_verifier));
-		sync.on_block(0, b1.clone().into());
+		sync.on_block(1, b1.clone().into());
 		sync.on_transaction(0, tx2.clone().into());
 		sync.on_transaction(0, tx3.clone().into());
 		assert_eq!(core.lock().information().chain.stored, 2); // b0 + b1
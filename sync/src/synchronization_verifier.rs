@@ -36,7 +36,7 @@ pub trait BlockVerificationSink : Send + Sync + 'static {
 	/// When block verification has completed successfully.
 	fn on_block_verification_success(&self, block: IndexedBlock) -> Option<Vec<VerificationTask>>;
 	/// When block verification has failed.
-	fn on_block_verification_error(&self, err: &str, hash: &H256);
+	fn on_block_verification_error(&self, err: &VerificationError, hash: &H256);
 }
 
 /// Transaction verification events sink
@@ -248,7 +248,7 @@ impl AsyncVerifier {
 							}
 						},
 						Err(e) => {
-							sink.on_block_verification_error(&format!("{:?}", e), block.hash())
+							sink.on_block_verification_error(&e, block.hash())
 						}
 					}
 				},
@@ -261,9 +261,13 @@ impl AsyncVerifier {
 						},
 						Ok(tx_output_provider) => {
 							let time: u32 = get_time().sec as u32;
-							match verifier.verifier.verify_mempool_transaction(storage.as_block_header_provider(), &tx_output_provider, height, time, &transaction) {
+							let (ancestors, conflicts) = {
+								let memory_pool = memory_pool.read();
+								(memory_pool.ancestors_summary(&transaction.raw), memory_pool.conflicts_summary(&transaction.raw))
+							};
+							match verifier.verifier.verify_mempool_transaction(storage.as_block_header_provider(), &tx_output_provider, height, time, &transaction, ancestors, conflicts) {
 								Ok(_) => sink.on_transaction_verification_success(transaction.into()),
-								Err(e) => sink.on_transaction_verification_error(&format!("{:?}", e), &transaction.hash),
+								Err(e) => sink.on_transaction_verification_error(&format!("{}", e), &transaction.hash),
 							}
 						},
 					};
@@ -344,7 +348,7 @@ impl<T> Verifier for SyncVerifier<T> where T: VerificationSink {
 				// => we could ignore decanonized transactions
 				self.sink.on_block_verification_success(block.into());
 			},
-			Err(e) => self.sink.on_block_verification_error(&format!("{:?}", e), block.hash()),
+			Err(e) => self.sink.on_block_verification_error(&e, block.hash()),
 		}
 	}
 
@@ -364,7 +368,7 @@ pub mod tests {
 	use db::BlockChainDatabase;
 	use network::{Network, ConsensusParams};
 	use verification::{VerificationLevel, BackwardsCompatibleChainVerifier as ChainVerifier, Error as VerificationError, TransactionError};
-	use script::Error as ScriptError;
+	use script::{Error as ScriptError, VerificationError as ScriptVerificationError, OpcodePosition, ScriptKind};
 	use synchronization_client_core::CoreVerificationSink;
 	use synchronization_executor::tests::DummyTaskExecutor;
 	use primitives::hash::H256;
@@ -378,6 +382,7 @@ pub mod tests {
 	pub struct DummyVerifier {
 		sink: Option<Arc<CoreVerificationSink<DummyTaskExecutor>>>,
 		errors: HashMap<H256, String>,
+		block_errors: HashMap<H256, VerificationError>,
 		actual_checks: HashSet<H256>,
 		storage: Option<StorageRef>,
 		memory_pool: Option<MemoryPoolRef>,
@@ -408,6 +413,10 @@ pub mod tests {
 			self.errors.insert(hash, err.into());
 		}
 
+		pub fn block_error_when_verifying(&mut self, hash: H256, err: VerificationError) {
+			self.block_errors.insert(hash, err);
+		}
+
 		pub fn actual_check_when_verifying(&mut self, hash: H256) {
 			self.actual_checks.insert(hash);
 		}
@@ -423,8 +432,8 @@ pub mod tests {
 
 		fn verify_block(&self, block: PartiallyVerifiedBlock) {
 			match self.sink {
-				Some(ref sink) => match self.errors.get(&block.hash()) {
-					Some(err) => sink.on_block_verification_error(&err, &block.hash()),
+				Some(ref sink) => match self.block_errors.get(&block.hash()) {
+					Some(err) => sink.on_block_verification_error(err, &block.hash()),
 					None => {
 						if self.actual_checks.contains(block.hash()) {
 							AsyncVerifier::execute_single_task(sink, self.storage.as_ref().unwrap(), self.memory_pool.as_ref().unwrap(), self.verifier.as_ref().unwrap(), VerificationTask::VerifyBlock(block));
@@ -533,7 +542,10 @@ pub mod tests {
 			verification_level: VerificationLevel::FULL,
 			verification_edge: 1.into(),
 		});
-		assert_eq!(wrapper.verify_block(&bad_transaction_block.into()), Err(VerificationError::Transaction(1, TransactionError::Signature(0, ScriptError::InvalidStackOperation))));
+		assert_eq!(wrapper.verify_block(&bad_transaction_block.into()), Err(VerificationError::Transaction(1, TransactionError::Signature(0, ScriptVerificationError {
+			error: ScriptError::InvalidStackOperation,
+			position: Some(OpcodePosition { script: ScriptKind::ScriptPubKey, offset: 0 }),
+		}))));
 	}
 
 	#[test]
@@ -75,6 +75,27 @@ impl TransactionOutputProvider for MemoryPoolTransactionOutputProvider {
 		self.storage_provider.transaction_output(prevout, transaction_index)
 	}
 
+	fn outputs_for_transaction(&self, tx: &Transaction, transaction_index: usize) -> Vec<Option<TransactionOutput>> {
+		// outputs of all inputs are read from the storage at once
+		let storage_outputs = self.storage_provider.outputs_for_transaction(tx, transaction_index);
+		tx.inputs.iter()
+			.zip(storage_outputs)
+			.map(|(input, storage_output)| {
+				let hashed_prevout: HashedOutPoint = input.previous_output.clone().into();
+				if let Some(ref nonfinal_spends) = self.nonfinal_spends {
+					if nonfinal_spends.dependent_spends.contains(&hashed_prevout) {
+						return None;
+					}
+				}
+
+				match self.mempool_inputs.get(&hashed_prevout) {
+					Some(&Some(ref output)) => Some(output.clone()),
+					_ => storage_output,
+				}
+			})
+			.collect()
+	}
+
 	fn is_spent(&self, prevout: &OutPoint) -> bool {
 		// check if this output is spent by some non-final mempool transaction
 		if let Some(ref nonfinal_spends) = self.nonfinal_spends {
@@ -2,7 +2,9 @@ use primitives::hash::H256;
 use ser::Serializable;
 use primitives::bytes::Bytes;
 use chain::{Transaction, IndexedTransaction, TransactionInput, TransactionOutput, OutPoint,
-	JoinSplit, Sapling};
+	JoinSplit, Sapling, SAPLING_TX_VERSION, SAPLING_TX_VERSION_GROUP_ID};
+use script::{TransactionInputSigner, SighashBase};
+use sapling::{self, SaplingSecrets};
 
 #[derive(Debug, Default, Clone)]
 pub struct ChainBuilder {
@@ -12,6 +14,8 @@ pub struct ChainBuilder {
 #[derive(Debug, Default, Clone)]
 pub struct TransactionBuilder {
 	pub transaction: Transaction,
+	/// Secrets of valid sapling spends/outputs, required by `sign_sapling`.
+	pub sapling_secrets: SaplingSecrets,
 }
 
 impl ChainBuilder {
@@ -42,10 +46,7 @@ impl Into<Transaction> for TransactionBuilder {
 
 impl Into<IndexedTransaction> for TransactionBuilder {
 	fn into(self) -> IndexedTransaction {
-		IndexedTransaction {
-			hash: self.transaction.hash(),
-			raw: self.transaction,
-		}
+		IndexedTransaction::new(self.transaction.hash(), self.transaction)
 	}
 }
 
@@ -56,6 +57,12 @@ impl TransactionBuilder {
 		builder
 	}
 
+	pub fn sapling() -> TransactionBuilder {
+		TransactionBuilder::overwintered()
+			.set_version(SAPLING_TX_VERSION)
+			.set_version_group_id(SAPLING_TX_VERSION_GROUP_ID)
+	}
+
 	pub fn coinbase() -> TransactionBuilder {
 		let mut builder = TransactionBuilder::default();
 		builder.transaction.inputs.push(TransactionInput::coinbase(Default::default()));
@@ -82,6 +89,16 @@ impl TransactionBuilder {
 		builder.set_sapling(sapling)
 	}
 
+	/// See `add_valid_sapling_spend`.
+	pub fn with_valid_sapling_spend(value: u64) -> TransactionBuilder {
+		TransactionBuilder::sapling().add_valid_sapling_spend(value)
+	}
+
+	/// See `add_valid_sapling_output`.
+	pub fn with_valid_sapling_output(value: u64) -> TransactionBuilder {
+		TransactionBuilder::sapling().add_valid_sapling_output(value)
+	}
+
 	pub fn with_join_split(join_split: JoinSplit) -> TransactionBuilder {
 		let builder = TransactionBuilder::default();
 		builder.set_join_split(join_split)
@@ -198,4 +215,37 @@ impl TransactionBuilder {
 		self.transaction.join_split = Some(Default::default());
 		self
 	}
+
+	/// Adds sapling spend with real value commitment && proof, updating balancing value.
+	/// The proof is only valid against `sapling::spend_verifying_key()`.
+	/// `sign_sapling` must be called after all spends/outputs are added.
+	pub fn add_valid_sapling_spend(mut self, value: u64) -> Self {
+		let spend = sapling::spend_description(value, &mut self.sapling_secrets);
+		let bundle = self.transaction.sapling.get_or_insert_with(Default::default);
+		bundle.balancing_value += value as i64;
+		bundle.spends.push(spend);
+		self
+	}
+
+	/// Adds sapling output with real value commitment && proof, updating balancing value.
+	/// The proof is only valid against `sapling::output_verifying_key()`.
+	/// `sign_sapling` must be called after all spends/outputs are added.
+	pub fn add_valid_sapling_output(mut self, value: u64) -> Self {
+		let output = sapling::output_description(value, &mut self.sapling_secrets);
+		let bundle = self.transaction.sapling.get_or_insert_with(Default::default);
+		bundle.balancing_value -= value as i64;
+		bundle.outputs.push(output);
+		self
+	}
+
+	/// Computes spend_auth_sig of every valid sapling spend and recomputes binding_sig.
+	/// Transaction must not be modified after this call.
+	pub fn sign_sapling(mut self, consensus_branch_id: u32) -> Self {
+		let signer: TransactionInputSigner = self.transaction.clone().into();
+		let sighash = signer.signature_hash(&mut Default::default(), None, 0, &From::from(vec![]), SighashBase::All.into(), consensus_branch_id);
+		if let Some(ref mut bundle) = self.transaction.sapling {
+			sapling::sign(&sighash.into(), bundle, &self.sapling_secrets);
+		}
+		self
+	}
 }
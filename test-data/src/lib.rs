@@ -1,7 +1,9 @@
 //! Various chain-specific test dummies
 
+extern crate rand;
 extern crate time;
 
+extern crate bitcrypto as crypto;
 extern crate chain;
 extern crate network;
 extern crate primitives;
@@ -13,6 +15,7 @@ use chain::{Block, Transaction};
 pub mod chain_builder;
 pub mod block;
 pub mod invoke;
+pub mod sapling;
 
 pub use chain_builder::{ChainBuilder, TransactionBuilder};
 pub use block::{block_builder, block_hash_builder, build_n_empty_blocks, build_n_empty_blocks_from_genesis, build_n_empty_blocks_from};
@@ -0,0 +1,213 @@
+//! Sapling test helpers.
+//!
+//! Real Sapling proving parameters are too large to be shipped with the repository, so proofs
+//! created here are generated for a test-only circuit, which has the same public inputs as
+//! Sapling Spend/Output circuits, but puts no constraints on them. Proving keys for this circuit
+//! are generated deterministically from `SPEND_PARAMETERS_SEED` and `OUTPUT_PARAMETERS_SEED`.
+//!
+//! So proofs are only valid against `spend_verifying_key()` and `output_verifying_key()` and
+//! will NOT pass verification with the mainnet/testnet verifying keys. Value commitments,
+//! spend authorization signatures and binding signature are computed the same way as
+//! it is done by real wallets.
+
+use std::fmt;
+use rand::{Rng, SeedableRng, XorShiftRng, thread_rng};
+use chain::{Sapling, SaplingSpendDescription, SaplingOutputDescription};
+use crypto::{Groth16VerifyingKey, JUBJUB};
+use crypto::pairing::{Field, PrimeField, PrimeFieldRepr, bls12_381::{Bls12, Fr}};
+use crypto::bellman::{Circuit, ConstraintSystem, SynthesisError};
+use crypto::bellman::groth16::{Parameters, generate_random_parameters, create_random_proof};
+use crypto::sapling_crypto::circuit::multipack;
+use crypto::sapling_crypto::jubjub::{edwards, fs::Fs, FixedGenerators, JubjubParams, Unknown};
+use crypto::sapling_crypto::primitives::ValueCommitment;
+use crypto::sapling_crypto::redjubjub::{PrivateKey, PublicKey};
+
+/// Seed that is used to generate test spend proving key.
+pub const SPEND_PARAMETERS_SEED: [u32; 4] = [0x5a91_e9e1, 0x0000_0001, 0x0000_0000, 0x0000_0000];
+/// Seed that is used to generate test output proving key.
+pub const OUTPUT_PARAMETERS_SEED: [u32; 4] = [0x5a91_e9e1, 0x0000_0002, 0x0000_0000, 0x0000_0000];
+
+/// Number of public inputs of Sapling Spend circuit.
+const SPEND_PUBLIC_INPUTS: usize = 7;
+/// Number of public inputs of Sapling Output circuit.
+const OUTPUT_PUBLIC_INPUTS: usize = 5;
+
+type Point = edwards::Point<Bls12, Unknown>;
+
+/// Secrets of sapling descriptions, added to the transaction. Required to sign it.
+#[derive(Clone, Default)]
+pub struct SaplingSecrets {
+	/// Randomized spend authorization private keys (one per spend).
+	spend_auth_keys: Vec<Fs>,
+	/// Value commitments randomness. Randomness of outputs is negated.
+	value_commitment_randomness: Vec<Fs>,
+}
+
+impl fmt::Debug for SaplingSecrets {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("SaplingSecrets")
+			.field("spends", &self.spend_auth_keys.len())
+			.field("commitments", &self.value_commitment_randomness.len())
+			.finish()
+	}
+}
+
+/// Verifying key for test spend proofs.
+pub fn spend_verifying_key() -> Groth16VerifyingKey {
+	parameters(SPEND_PARAMETERS_SEED, SPEND_PUBLIC_INPUTS).vk.into()
+}
+
+/// Verifying key for test output proofs.
+pub fn output_verifying_key() -> Groth16VerifyingKey {
+	parameters(OUTPUT_PARAMETERS_SEED, OUTPUT_PUBLIC_INPUTS).vk.into()
+}
+
+/// Creates spend description of given value. The spend_auth_sig is left empty until `sign`.
+pub fn spend_description(value: u64, secrets: &mut SaplingSecrets) -> SaplingSpendDescription {
+	let mut rng = thread_rng();
+	let mut spend = SaplingSpendDescription::default();
+
+	let (value_commitment, randomness) = value_commitment(value, &mut rng);
+	write_point(&value_commitment, &mut spend.value_commitment);
+	secrets.value_commitment_randomness.push(randomness);
+
+	let spend_auth_key = PrivateKey::<Bls12>(rng.gen());
+	let randomized_key = PublicKey::from_private(&spend_auth_key, FixedGenerators::SpendingKeyGenerator, &JUBJUB);
+	write_point(&randomized_key.0, &mut spend.randomized_key);
+	secrets.spend_auth_keys.push(spend_auth_key.0);
+
+	// anchor is zero, nullifier is random
+	rng.fill_bytes(&mut spend.nullifier);
+	let nullifier = multipack::bytes_to_bits_le(&spend.nullifier);
+	let nullifier = multipack::compute_multipacking::<Bls12>(&nullifier);
+
+	let (randomized_key_x, randomized_key_y) = randomized_key.0.into_xy();
+	let (value_commitment_x, value_commitment_y) = value_commitment.into_xy();
+	let public_input = vec![
+		randomized_key_x,
+		randomized_key_y,
+		value_commitment_x,
+		value_commitment_y,
+		Fr::zero(),
+		nullifier[0],
+		nullifier[1],
+	];
+	create_proof(SPEND_PARAMETERS_SEED, public_input, &mut spend.zkproof);
+
+	spend
+}
+
+/// Creates output description of given value. Ciphertexts are left empty.
+pub fn output_description(value: u64, secrets: &mut SaplingSecrets) -> SaplingOutputDescription {
+	let mut rng = thread_rng();
+	let mut output = SaplingOutputDescription::default();
+
+	let (value_commitment, mut randomness) = value_commitment(value, &mut rng);
+	write_point(&value_commitment, &mut output.value_commitment);
+	randomness.negate();
+	secrets.value_commitment_randomness.push(randomness);
+
+	let note_commitment: Fr = rng.gen();
+	note_commitment.into_repr().write_le(&mut output.note_commitment[..])
+		.expect("note commitment is 32 bytes; qed");
+
+	let ephemeral_secret: Fs = rng.gen();
+	let ephemeral_key: Point = JUBJUB.generator(FixedGenerators::SpendingKeyGenerator)
+		.mul(ephemeral_secret.into_repr(), &JUBJUB)
+		.into();
+	write_point(&ephemeral_key, &mut output.ephemeral_key);
+
+	let (ephemeral_key_x, ephemeral_key_y) = ephemeral_key.into_xy();
+	let (value_commitment_x, value_commitment_y) = value_commitment.into_xy();
+	let public_input = vec![
+		value_commitment_x,
+		value_commitment_y,
+		ephemeral_key_x,
+		ephemeral_key_y,
+		note_commitment,
+	];
+	create_proof(OUTPUT_PARAMETERS_SEED, public_input, &mut output.zkproof);
+
+	output
+}
+
+/// Computes spend_auth_sig of every spend and binding_sig of the sapling bundle.
+///
+/// The `balancing_value` must be already set, because it affects the sighash.
+pub fn sign(sighash: &[u8; 32], sapling: &mut Sapling, secrets: &SaplingSecrets) {
+	let mut rng = thread_rng();
+
+	for (spend, spend_auth_key) in sapling.spends.iter_mut().zip(secrets.spend_auth_keys.iter()) {
+		let mut data_to_be_signed = [0u8; 64];
+		data_to_be_signed[..32].copy_from_slice(&spend.randomized_key);
+		data_to_be_signed[32..].copy_from_slice(sighash);
+
+		PrivateKey::<Bls12>(*spend_auth_key)
+			.sign(&data_to_be_signed, &mut rng, FixedGenerators::SpendingKeyGenerator, &JUBJUB)
+			.write(&mut spend.spend_auth_sig[..])
+			.expect("signature is 64 bytes; qed");
+	}
+
+	let mut binding_key = Fs::zero();
+	for randomness in &secrets.value_commitment_randomness {
+		binding_key.add_assign(randomness);
+	}
+	let binding_key = PrivateKey::<Bls12>(binding_key);
+	let binding_verification_key = PublicKey::from_private(&binding_key, FixedGenerators::ValueCommitmentRandomness, &JUBJUB);
+
+	let mut data_to_be_signed = [0u8; 64];
+	write_point(&binding_verification_key.0, &mut data_to_be_signed[..32]);
+	data_to_be_signed[32..].copy_from_slice(sighash);
+
+	binding_key
+		.sign(&data_to_be_signed, &mut rng, FixedGenerators::ValueCommitmentRandomness, &JUBJUB)
+		.write(&mut sapling.binding_sig[..])
+		.expect("signature is 64 bytes; qed");
+}
+
+/// Test circuit that only exposes public inputs.
+struct PublicInputsCircuit {
+	inputs: Vec<Option<Fr>>,
+}
+
+impl Circuit<Bls12> for PublicInputsCircuit {
+	fn synthesize<CS: ConstraintSystem<Bls12>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+		for (idx, input) in self.inputs.into_iter().enumerate() {
+			cs.alloc_input(|| format!("input {}", idx), || input.ok_or(SynthesisError::AssignmentMissing))?;
+		}
+
+		// single auxiliary variable, so that the circuit isn't empty
+		let one = cs.alloc(|| "one", || Ok(Fr::one()))?;
+		cs.enforce(|| "one * one = one", |lc| lc + one, |lc| lc + one, |lc| lc + one);
+
+		Ok(())
+	}
+}
+
+fn parameters(seed: [u32; 4], inputs: usize) -> Parameters<Bls12> {
+	let circuit = PublicInputsCircuit { inputs: vec![None; inputs] };
+	generate_random_parameters(circuit, &mut XorShiftRng::from_seed(seed))
+		.expect("test circuit is always synthesized; qed")
+}
+
+fn create_proof(seed: [u32; 4], public_input: Vec<Fr>, zkproof: &mut [u8; 192]) {
+	let params = parameters(seed, public_input.len());
+	let circuit = PublicInputsCircuit { inputs: public_input.into_iter().map(Some).collect() };
+	create_random_proof(circuit, &params, &mut thread_rng())
+		.expect("test circuit is always satisfied; qed")
+		.write(&mut zkproof[..])
+		.expect("proof is 192 bytes; qed");
+}
+
+fn value_commitment<R: Rng>(value: u64, rng: &mut R) -> (Point, Fs) {
+	let randomness: Fs = rng.gen();
+	let value_commitment = ValueCommitment::<Bls12> {
+		value: value,
+		randomness: randomness,
+	}.cm(&JUBJUB).into();
+	(value_commitment, randomness)
+}
+
+fn write_point(point: &Point, to: &mut [u8]) {
+	point.write(to).expect("point is 32 bytes; qed");
+}
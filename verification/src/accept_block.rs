@@ -1,30 +1,33 @@
-use keys::Address;
 use network::{ConsensusParams};
 use storage::{DuplexTransactionOutputProvider, TransactionOutputProvider, BlockHeaderProvider,
-	TreeStateProvider, SaplingTreeState};
-use script::{self, Builder};
+	TreeStateProvider, SaplingTreeState, ShieldedPoolProvider, ShieldedPools};
+use script;
 use sigops::transaction_sigops;
 use deployments::BlockDeployments;
 use canon::CanonBlock;
-use error::Error;
+use error::{Error, TransactionError};
 use timestamp::median_timestamp;
 use fee::checked_transaction_fee;
+use primitives::bytes::Bytes;
 
 /// Flexible verification of ordered block
 pub struct BlockAcceptor<'a> {
 	pub finality: BlockFinality<'a>,
 	pub serialized_size: BlockSerializedSize<'a>,
 	pub sigops: BlockSigops<'a>,
+	pub coinbase_max_money: BlockCoinbaseMaxMoney<'a>,
 	pub miner_reward: BlockCoinbaseMinerReward<'a>,
-	pub founder_reward: BlockFounderReward<'a>,
-	pub coinbase_script: BlockCoinbaseScript<'a>,
+	pub founders_reward: BlockCoinbaseFoundersReward<'a>,
+	pub coinbase_height: BlockCoinbaseHeight<'a>,
 	pub sapling_root: BlockSaplingRoot<'a>,
+	pub shielded_pools: BlockShieldedPools<'a>,
 }
 
 impl<'a> BlockAcceptor<'a> {
 	pub fn new(
 		tx_out_store: &'a TransactionOutputProvider,
 		tree_state_store: &'a TreeStateProvider,
+		shielded_pool_store: &'a ShieldedPoolProvider,
 		consensus: &'a ConsensusParams,
 		block: CanonBlock<'a>,
 		height: u32,
@@ -34,11 +37,13 @@ impl<'a> BlockAcceptor<'a> {
 		BlockAcceptor {
 			finality: BlockFinality::new(block, height, deployments, headers),
 			serialized_size: BlockSerializedSize::new(block, consensus),
-			coinbase_script: BlockCoinbaseScript::new(block, consensus, height),
+			coinbase_height: BlockCoinbaseHeight::new(block, consensus, height),
+			coinbase_max_money: BlockCoinbaseMaxMoney::new(block, consensus),
 			miner_reward: BlockCoinbaseMinerReward::new(block, tx_out_store, consensus, height),
-			founder_reward: BlockFounderReward::new(block, consensus, height),
+			founders_reward: BlockCoinbaseFoundersReward::new(block, consensus, height),
 			sigops: BlockSigops::new(block, tx_out_store, consensus),
 			sapling_root: BlockSaplingRoot::new(block, tree_state_store, consensus, height),
+			shielded_pools: BlockShieldedPools::new(block, shielded_pool_store),
 		}
 	}
 
@@ -46,10 +51,12 @@ impl<'a> BlockAcceptor<'a> {
 		self.finality.check()?;
 		self.sigops.check()?;
 		self.serialized_size.check()?;
+		self.coinbase_max_money.check()?;
 		self.miner_reward.check()?;
-		self.founder_reward.check()?;
-		self.coinbase_script.check()?;
+		self.founders_reward.check()?;
+		self.coinbase_height.check()?;
 		self.sapling_root.check()?;
+		self.shielded_pools.check()?;
 		Ok(())
 	}
 }
@@ -80,10 +87,9 @@ impl<'a> BlockFinality<'a> {
 			self.block.header.raw.time
 		};
 
-		if self.block.transactions.iter().all(|tx| tx.raw.is_final_in_block(self.height, time_cutoff)) {
-			Ok(())
-		} else {
-			Err(Error::NonFinalBlock)
+		match self.block.transactions.iter().position(|tx| !tx.raw.is_final_in_block(self.height, time_cutoff)) {
+			Some(tx_idx) => Err(Error::NonFinalTransaction(tx_idx)),
+			None => Ok(()),
 		}
 	}
 }
@@ -138,7 +144,7 @@ impl<'a> BlockSigops<'a> {
 	fn check(&self) -> Result<(), Error> {
 		let store = DuplexTransactionOutputProvider::new(self.store, &*self.block);
 		let sigops = self.block.transactions.iter()
-			.map(|tx| transaction_sigops(&tx.raw, &store, self.bip16_active))
+			.map(|tx| transaction_sigops(&tx.raw, &store, self.bip16_active, false))
 			.fold(0, |acc, tx_sigops| (acc + tx_sigops));
 
 		if sigops > self.max_block_sigops {
@@ -149,6 +155,29 @@ impl<'a> BlockSigops<'a> {
 	}
 }
 
+pub struct BlockCoinbaseMaxMoney<'a> {
+	block: CanonBlock<'a>,
+	max_money: u64,
+}
+
+impl<'a> BlockCoinbaseMaxMoney<'a> {
+	fn new(block: CanonBlock<'a>, consensus: &ConsensusParams) -> Self {
+		BlockCoinbaseMaxMoney {
+			block: block,
+			max_money: consensus.max_money(),
+		}
+	}
+
+	fn check(&self) -> Result<(), Error> {
+		let claim = self.block.transactions[0].raw.total_spends();
+		if claim > self.max_money {
+			Err(Error::CoinbaseOverspend { expected_max: self.max_money, actual: claim })
+		} else {
+			Ok(())
+		}
+	}
+}
+
 pub struct BlockCoinbaseMinerReward<'a> {
 	block: CanonBlock<'a>,
 	store: &'a TransactionOutputProvider,
@@ -176,7 +205,7 @@ impl<'a> BlockCoinbaseMinerReward<'a> {
 
 		for (tx_idx, tx) in self.block.transactions.iter().enumerate().skip(1) {
 			let tx_fee = checked_transaction_fee(&store, tx_idx, &tx.raw)
-				.map_err(|tx_err| Error::Transaction(tx_idx, tx_err))?;
+				.map_err(|fee_err| Error::Transaction(tx_idx, fee_err.into()))?;
 
 			// Adding to total fees (with possible overflow)
 			let (sum, overflow) = fees.overflowing_add(tx_fee);
@@ -202,15 +231,15 @@ impl<'a> BlockCoinbaseMinerReward<'a> {
 	}
 }
 
-pub struct BlockCoinbaseScript<'a> {
+pub struct BlockCoinbaseHeight<'a> {
 	block: CanonBlock<'a>,
 	bip34_active: bool,
 	height: u32,
 }
 
-impl<'a> BlockCoinbaseScript<'a> {
+impl<'a> BlockCoinbaseHeight<'a> {
 	fn new(block: CanonBlock<'a>, consensus_params: &ConsensusParams, height: u32) -> Self {
-		BlockCoinbaseScript {
+		BlockCoinbaseHeight {
 			block: block,
 			bip34_active: height >= consensus_params.bip34_height,
 			height: height,
@@ -226,49 +255,68 @@ impl<'a> BlockCoinbaseScript<'a> {
 			.push_i64(self.height.into())
 			.into_script();
 
-		let matches = self.block.transactions.first()
+		let script_sig = self.block.transactions.first()
 			.and_then(|tx| tx.raw.inputs.first())
-			.map(|input| input.script_sig.starts_with(&prefix))
-			.unwrap_or(false);
-
-		if matches {
-			Ok(())
-		} else {
-			Err(Error::CoinbaseScript)
+			.map(|input| &input.script_sig);
+
+		match script_sig {
+			// height must be minimally encoded, so raw prefix is compared here
+			Some(script_sig) if script_sig.starts_with(&prefix) => Ok(()),
+			script_sig => Err(Error::CoinbaseHeightMismatch {
+				expected: self.height,
+				actual: script_sig.and_then(|script_sig| coinbase_height(&script_sig.clone().into())),
+			}),
 		}
 	}
 }
 
-pub struct BlockFounderReward<'a> {
+/// Decodes the height pushed by the first instruction of coinbase script_sig.
+fn coinbase_height(script_sig: &script::Script) -> Option<i64> {
+	let instruction = match script_sig.get_instruction(0) {
+		Ok(instruction) => instruction,
+		Err(_) => return None,
+	};
+
+	match instruction.data {
+		Some(data) => script::Num::from_slice(data, false, 5).ok().map(Into::into),
+		None if instruction.opcode.is_within_op_n() => Some(instruction.opcode.decode_op_n().into()),
+		None => None,
+	}
+}
+
+pub struct BlockCoinbaseFoundersReward<'a> {
 	block: CanonBlock<'a>,
-	founder_address: Option<Address>,
+	founder_script: Option<Bytes>,
 	founder_reward: u64,
 }
 
-impl<'a> BlockFounderReward<'a> {
+impl<'a> BlockCoinbaseFoundersReward<'a> {
 	fn new(block: CanonBlock<'a>, consensus_params: &ConsensusParams, height: u32) -> Self {
-		BlockFounderReward {
+		BlockCoinbaseFoundersReward {
 			block: block,
-			founder_address: consensus_params.founder_address(height),
+			founder_script: consensus_params.founder_address_script(height),
 			founder_reward: consensus_params.founder_reward(height),
 		}
 	}
 
 	fn check(&self) -> Result<(), Error> {
-		if let Some(ref founder_address) = self.founder_address {
-			let script = Builder::build_p2sh(&founder_address.hash);
-			let has_founder_reward = self.block.transactions.first()
-				.map(|tx| tx.raw.outputs.iter().any(|output|
-					**output.script_pubkey == *script &&
-					output.value == self.founder_reward))
-				.unwrap_or(false);
-
-			if !has_founder_reward {
-				return Err(Error::MissingFoundersReward);
-			}
-		}
+		// founders reward is only paid inside the founders reward window
+		let founder_script = match self.founder_script {
+			Some(ref founder_script) => founder_script,
+			None => return Ok(()),
+		};
 
-		Ok(())
+		let has_founders_reward = self.block.transactions.first()
+			.map(|tx| tx.raw.outputs.iter().any(|output|
+				output.script_pubkey == *founder_script &&
+				output.value == self.founder_reward))
+			.unwrap_or(false);
+
+		if has_founders_reward {
+			Ok(())
+		} else {
+			Err(Error::Transaction(0, TransactionError::CoinbaseMissingFoundersReward))
+		}
 	}
 }
 
@@ -325,20 +373,76 @@ impl<'a> BlockSaplingRoot<'a> {
 	}
 }
 
+pub struct BlockShieldedPools<'a> {
+	block: CanonBlock<'a>,
+	shielded_pool_store: &'a ShieldedPoolProvider,
+}
+
+impl<'a> BlockShieldedPools<'a> {
+	fn new(block: CanonBlock<'a>, shielded_pool_store: &'a ShieldedPoolProvider) -> Self {
+		BlockShieldedPools {
+			block: block,
+			shielded_pool_store: shielded_pool_store,
+		}
+	}
+
+	fn check(&self) -> Result<(), Error> {
+		let parent_hash = &self.block.header.raw.previous_header_hash;
+		let pools = if parent_hash.is_zero() {
+			ShieldedPools::default()
+		} else {
+			self.shielded_pool_store.shielded_pools_at_block(parent_hash)
+				.ok_or(Error::MissingShieldedPools)?
+		};
+
+		pools.apply_block(self.block.transactions.iter().map(|tx| &tx.raw))
+			.map(|_| ())
+			.map_err(Error::NegativeShieldedPool)
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	extern crate test_data;
 
 	use std::collections::HashMap;
-	use chain::{OutPoint, TransactionOutput};
+	use hash::H256;
+	use chain::{OutPoint, Transaction, TransactionOutput, IndexedBlock, JoinSplit, JoinSplitDescription};
 	use db::BlockChainDatabase;
 	use network::{ConsensusParams, Network};
-	use storage::{SaplingTreeState, TransactionOutputProvider};
-	use {Error, CanonBlock};
-	use super::{BlockCoinbaseScript, BlockSaplingRoot, BlockCoinbaseMinerReward};
+	use storage::{SaplingTreeState, TransactionOutputProvider, EpochTag};
+	use {Error, TransactionError, CanonBlock};
+	use super::{BlockFinality, BlockCoinbaseHeight, BlockSaplingRoot, BlockCoinbaseMinerReward, BlockCoinbaseFoundersReward,
+		BlockSerializedSize, BlockShieldedPools, BlockCoinbaseMaxMoney};
+
+	fn block_of_size(size: usize) -> IndexedBlock {
+		fn build_block(transactions: Vec<Transaction>) -> IndexedBlock {
+			test_data::block_builder()
+				.with_transactions(transactions)
+				.header().build()
+				.build()
+				.into()
+		}
+
+		// 20 transactions, padded with 100_000 bytes of output script each
+		let mut transactions: Vec<Transaction> = (0..20).map(|index| {
+			let mut tx: Transaction = test_data::TransactionBuilder::with_output(index + 1).into();
+			tx.outputs[0].script_pubkey = vec![0u8; 100_000].into();
+			tx
+		}).collect();
+
+		// adjust padding of the last transaction to get block of requested size
+		let actual_size = build_block(transactions.clone()).size();
+		let last_script_len = (100_000 + size as isize - actual_size as isize) as usize;
+		transactions.last_mut().unwrap().outputs[0].script_pubkey = vec![0u8; last_script_len].into();
+
+		let block = build_block(transactions);
+		assert_eq!(block.size(), size);
+		block
+	}
 
 	#[test]
-	fn test_block_coinbase_script() {
+	fn test_block_coinbase_height() {
 		// transaction from block 461373
 		// https://blockchain.info/rawtx/7cf05175ce9c8dbfff9aafa8263edc613fc08f876e476553009afcf7e3868a0c?format=hex
 		let tx = "01000000010000000000000000000000000000000000000000000000000000000000000000ffffffff3f033d0a070004b663ec58049cba630608733867a0787a02000a425720537570706f727420384d200a666973686572206a696e78696e092f425720506f6f6c2fffffffff01903d9d4e000000001976a914721afdf638d570285d02d3076d8be6a03ee0794d88ac00000000".into();
@@ -349,21 +453,54 @@ mod tests {
 			.build()
 			.into();
 
-		let coinbase_script_validator = BlockCoinbaseScript {
+		let coinbase_height_validator = BlockCoinbaseHeight {
 			block: CanonBlock::new(&block),
 			bip34_active: true,
 			height: block_number,
 		};
 
-		assert_eq!(coinbase_script_validator.check(), Ok(()));
+		assert_eq!(coinbase_height_validator.check(), Ok(()));
 
-		let coinbase_script_validator2 = BlockCoinbaseScript {
+		let coinbase_height_validator2 = BlockCoinbaseHeight {
 			block: CanonBlock::new(&block),
 			bip34_active: true,
 			height: block_number - 1,
 		};
 
-		assert_eq!(coinbase_script_validator2.check(), Err(Error::CoinbaseScript));
+		assert_eq!(coinbase_height_validator2.check(), Err(Error::CoinbaseHeightMismatch {
+			expected: block_number - 1,
+			actual: Some(block_number as i64),
+		}));
+	}
+
+	#[test]
+	fn test_block_coinbase_height_small_and_missing() {
+		fn check(script_sig: &'static str, height: u32) -> Result<(), Error> {
+			let mut coinbase: Transaction = test_data::TransactionBuilder::coinbase().into();
+			coinbase.inputs[0].script_sig = script_sig.into();
+			let block = test_data::block_builder()
+				.with_transaction(coinbase)
+				.header().build()
+				.build()
+				.into();
+
+			BlockCoinbaseHeight {
+				block: CanonBlock::new(&block),
+				bip34_active: true,
+				height: height,
+			}.check()
+		}
+
+		// OP_5
+		assert_eq!(check("5500", 5), Ok(()));
+		assert_eq!(check("5500", 6), Err(Error::CoinbaseHeightMismatch { expected: 6, actual: Some(5) }));
+		// PUSHBYTES_1 0x05 isn't the minimal encoding of 5
+		assert_eq!(check("010500", 5), Err(Error::CoinbaseHeightMismatch { expected: 5, actual: Some(5) }));
+		// PUSHBYTES_2 0x2c01
+		assert_eq!(check("022c0100", 300), Ok(()));
+		// OP_NOP
+		assert_eq!(check("61", 300), Err(Error::CoinbaseHeightMismatch { expected: 300, actual: None }));
+		assert_eq!(check("", 300), Err(Error::CoinbaseHeightMismatch { expected: 300, actual: None }));
 	}
 
 	#[test]
@@ -426,4 +563,162 @@ mod tests {
 		let consensus = ConsensusParams::new(Network::Mainnet);
 		assert_eq!(BlockCoinbaseMinerReward::new(CanonBlock::new(&block.into()), &store, &consensus, 419221).check(), Ok(()));
 	}
+
+	#[test]
+	fn test_block_coinbase_max_money() {
+		let consensus = ConsensusParams::new(Network::Mainnet);
+		let max_money = consensus.max_money();
+		let block = |value: u64| -> IndexedBlock {
+			test_data::block_builder()
+				.transaction().coinbase().output().value(value).build().build()
+				.header().build()
+				.build()
+				.into()
+		};
+
+		let block1 = block(max_money);
+		assert_eq!(BlockCoinbaseMaxMoney::new(CanonBlock::new(&block1), &consensus).check(), Ok(()));
+
+		let block2 = block(max_money + 1);
+		assert_eq!(BlockCoinbaseMaxMoney::new(CanonBlock::new(&block2), &consensus).check(),
+			Err(Error::CoinbaseOverspend { expected_max: max_money, actual: max_money + 1 }));
+	}
+
+	#[test]
+	fn test_block_coinbase_founders_reward() {
+		let consensus = ConsensusParams::new(Network::Mainnet);
+
+		// coinbase pays founders reward
+		let block = test_data::block_builder()
+			.transaction().coinbase().founder_reward(&consensus, 1).build()
+			.header().build()
+			.build()
+			.into();
+		assert_eq!(BlockCoinbaseFoundersReward::new(CanonBlock::new(&block), &consensus, 1).check(), Ok(()));
+
+		// founders reward is paid to the address of another height range
+		assert_eq!(BlockCoinbaseFoundersReward::new(CanonBlock::new(&block), &consensus, 17_709).check(),
+			Err(Error::Transaction(0, TransactionError::CoinbaseMissingFoundersReward)));
+
+		// coinbase is missing founders reward
+		let block = test_data::block_builder()
+			.transaction().coinbase().output().value(consensus.founder_reward(1)).build().build()
+			.header().build()
+			.build()
+			.into();
+		assert_eq!(BlockCoinbaseFoundersReward::new(CanonBlock::new(&block), &consensus, 1).check(),
+			Err(Error::Transaction(0, TransactionError::CoinbaseMissingFoundersReward)));
+
+		// founders reward isn't required outside of founders reward window
+		assert_eq!(BlockCoinbaseFoundersReward::new(CanonBlock::new(&block), &consensus, 0).check(), Ok(()));
+		assert_eq!(BlockCoinbaseFoundersReward::new(CanonBlock::new(&block), &consensus, 850_000).check(), Ok(()));
+	}
+
+	#[test]
+	fn test_block_finality() {
+		let storage = BlockChainDatabase::init_test_chain(vec![test_data::genesis().into()]);
+		let genesis_time = test_data::genesis().block_header.time;
+		let check = |block: &IndexedBlock, height: u32, csv_active: bool| BlockFinality {
+			block: CanonBlock::new(block),
+			height: height,
+			csv_active: csv_active,
+			headers: &storage,
+		}.check();
+
+		// transaction is locked until block 500_000
+		let block: IndexedBlock = test_data::block_builder()
+			.with_transaction(test_data::TransactionBuilder::coinbase().into())
+			.with_transaction(test_data::TransactionBuilder::with_default_input(0).lock().into())
+			.header().parent(test_data::genesis().hash()).build()
+			.build()
+			.into();
+		assert_eq!(check(&block, 500_000, false), Err(Error::NonFinalTransaction(1)));
+		assert_eq!(check(&block, 500_001, false), Ok(()));
+
+		// transaction is locked until time, that is between median time past and block time
+		let mut locked_tx: Transaction = test_data::TransactionBuilder::with_default_input(0).lock().into();
+		locked_tx.lock_time = genesis_time + 500;
+		let block: IndexedBlock = test_data::block_builder()
+			.with_transaction(test_data::TransactionBuilder::coinbase().into())
+			.with_transaction(locked_tx)
+			.header().parent(test_data::genesis().hash()).time(genesis_time + 1000).build()
+			.build()
+			.into();
+		assert_eq!(check(&block, 1, false), Ok(()));
+		// BIP113: median time past is used when CSV is active
+		assert_eq!(check(&block, 1, true), Err(Error::NonFinalTransaction(1)));
+	}
+
+	#[test]
+	fn test_block_serialized_size() {
+		let consensus = ConsensusParams::new(Network::Mainnet);
+
+		let block = block_of_size(consensus.max_block_size());
+		assert_eq!(BlockSerializedSize::new(CanonBlock::new(&block), &consensus).check(), Ok(()));
+
+		let block = block_of_size(consensus.max_block_size() + 1);
+		assert_eq!(BlockSerializedSize::new(CanonBlock::new(&block), &consensus).check(),
+			Err(Error::Size(consensus.max_block_size() + 1)));
+	}
+
+	#[test]
+	fn test_block_shielded_pools() {
+		let storage = BlockChainDatabase::init_test_chain(vec![test_data::genesis().into()]);
+
+		let join_split_transaction = |value_pub_old, value_pub_new| -> Transaction {
+			let join_split = JoinSplit {
+				descriptions: vec![JoinSplitDescription {
+					value_pub_old: value_pub_old,
+					value_pub_new: value_pub_new,
+					..Default::default()
+				}],
+				..Default::default()
+			};
+			test_data::TransactionBuilder::with_join_split(join_split).into()
+		};
+
+		let join_split_block = |parent, value_pub_old, value_pub_new| -> IndexedBlock {
+			test_data::block_builder()
+				.header().parent(parent).build()
+				.transaction().coinbase().build()
+				.with_transaction(join_split_transaction(value_pub_old, value_pub_new))
+				.build()
+				.into()
+		};
+
+		// when block withdraws from empty sprout pool
+		let block = join_split_block(test_data::genesis().hash(), 0, 1);
+		assert_eq!(BlockShieldedPools::new(CanonBlock::new(&block), &storage).check(),
+			Err(Error::NegativeShieldedPool(EpochTag::Sprout)));
+
+		// when block deposits to sprout pool
+		let block = join_split_block(test_data::genesis().hash(), 100, 0);
+		assert_eq!(BlockShieldedPools::new(CanonBlock::new(&block), &storage).check(), Ok(()));
+		storage.insert(block.clone()).unwrap();
+		storage.canonize(block.hash()).unwrap();
+
+		// when block withdraws everything from sprout pool
+		let next_block = join_split_block(*block.hash(), 0, 100);
+		assert_eq!(BlockShieldedPools::new(CanonBlock::new(&next_block), &storage).check(), Ok(()));
+
+		// when block withdraws more than sprout pool holds
+		let next_block = join_split_block(*block.hash(), 0, 101);
+		assert_eq!(BlockShieldedPools::new(CanonBlock::new(&next_block), &storage).check(),
+			Err(Error::NegativeShieldedPool(EpochTag::Sprout)));
+
+		// when block withdraws more than sprout pool holds, but refills it later
+		let next_block: IndexedBlock = test_data::block_builder()
+			.header().parent(*block.hash()).build()
+			.transaction().coinbase().build()
+			.with_transaction(join_split_transaction(0, 150))
+			.with_transaction(join_split_transaction(60, 0))
+			.build()
+			.into();
+		assert_eq!(BlockShieldedPools::new(CanonBlock::new(&next_block), &storage).check(), Ok(()));
+
+		// when parent pools balances are unknown
+		let orphan_block = join_split_block(H256::from(1), 100, 0);
+		assert_eq!(BlockShieldedPools::new(CanonBlock::new(&orphan_block), &storage).check(),
+			Err(Error::MissingShieldedPools));
+	}
 }
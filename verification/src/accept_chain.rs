@@ -1,44 +1,56 @@
 use rayon::prelude::{IntoParallelRefIterator, IndexedParallelIterator, ParallelIterator};
 use storage::{
 	DuplexTransactionOutputProvider, TransactionOutputProvider, TransactionMetaProvider,
-	BlockHeaderProvider, TreeStateProvider, NullifierTracker,
+	BlockHeaderProvider, TreeStateProvider, NullifierTracker, ShieldedPoolProvider,
 };
 use network::ConsensusParams;
 use error::Error;
 use canon::CanonBlock;
 use accept_block::BlockAcceptor;
 use accept_header::HeaderAcceptor;
-use accept_transaction::TransactionAcceptor;
+use accept_transaction::{TransactionAcceptor, AcceptedContext};
 use deployments::BlockDeployments;
+use validated_tx_cache::ValidatedTxCache;
+use constants::PARALLEL_VERIFICATION_MIN_TRANSACTIONS;
 use VerificationLevel;
 
 pub struct ChainAcceptor<'a> {
 	pub block: BlockAcceptor<'a>,
 	pub header: HeaderAcceptor<'a>,
 	pub transactions: Vec<TransactionAcceptor<'a>>,
+	pub context: AcceptedContext,
 }
 
 impl<'a> ChainAcceptor<'a> {
 	pub fn new(
 		tx_out_provider: &'a TransactionOutputProvider,
+		// outputs of the block transactions
+		block_out_provider: &'a TransactionOutputProvider,
 		tx_meta_provider: &'a TransactionMetaProvider,
 		header_provider: &'a BlockHeaderProvider,
 		tree_state_provider: &'a TreeStateProvider,
 		nullifier_tracker: &'a NullifierTracker,
+		shielded_pool_provider: &'a ShieldedPoolProvider,
 		consensus: &'a ConsensusParams,
 		verification_level: VerificationLevel,
 		block: CanonBlock<'a>,
 		height: u32,
 		time: u32,
 		deployments: &'a BlockDeployments,
+		validated_txs: Option<&'a ValidatedTxCache>,
 	) -> Self {
 		trace!(target: "verification", "Block verification {}", block.hash().to_reversed_str());
-		let output_store = DuplexTransactionOutputProvider::new(tx_out_provider, block.raw());
+		let output_store = DuplexTransactionOutputProvider::new(tx_out_provider, block_out_provider);
+		let block_coinbase = block.raw().transactions.first()
+			.filter(|tx| tx.raw.is_coinbase())
+			.map(|tx| &tx.hash);
+		let context = AcceptedContext::new(consensus, height, block.header.raw.time, deployments);
 
 		ChainAcceptor {
 			block: BlockAcceptor::new(
 				tx_out_provider,
 				tree_state_provider,
+				shielded_pool_provider,
 				consensus,
 				block,
 				height,
@@ -51,32 +63,138 @@ impl<'a> ChainAcceptor<'a> {
 				.enumerate()
 				.map(|(tx_index, tx)| TransactionAcceptor::new(
 						tx_meta_provider,
+						block_coinbase,
 						output_store,
 						nullifier_tracker,
 						consensus,
 						tx,
 						verification_level,
 						height,
-						block.header.raw.time,
 						tx_index,
-						deployments,
+						context,
 						tree_state_provider,
+						validated_txs,
 				))
 				.collect(),
+			context: context,
 		}
 	}
 
 	pub fn check(&self) -> Result<(), Error> {
-		try!(self.block.check());
-		try!(self.header.check());
+		try!(self.check_block_and_header());
 		try!(self.check_transactions());
 		Ok(())
 	}
 
+	/// Runs block-level and header checks only. On success returns the context
+	/// which transactions of this block are verified with.
+	pub fn check_block_and_header(&self) -> Result<AcceptedContext, Error> {
+		try!(self.block.check());
+		try!(self.header.check());
+		Ok(self.context)
+	}
+
 	fn check_transactions(&self) -> Result<(), Error> {
+		// coinbase transaction is not counted
+		if self.transactions.len() > PARALLEL_VERIFICATION_MIN_TRANSACTIONS + 1 {
+			self.check_transactions_parallel()
+		} else {
+			self.check_transactions_sequential()
+		}
+	}
+
+	fn check_transactions_sequential(&self) -> Result<(), Error> {
+		for (index, tx) in self.transactions.iter().enumerate() {
+			tx.check().map_err(|err| Error::Transaction(index, err))?;
+		}
+
+		Ok(())
+	}
+
+	/// Checks transactions using rayon thread pool. Error of the transaction
+	/// with the lowest index is returned.
+	fn check_transactions_parallel(&self) -> Result<(), Error> {
 		self.transactions.par_iter()
 			.enumerate()
-			.fold(|| Ok(()), |result, (index, tx)| result.and_then(|_| tx.check().map_err(|err| Error::Transaction(index, err))))
-			.reduce(|| Ok(()), |acc, check| acc.and(check))
+			.map(|(index, tx)| tx.check().map_err(|err| Error::Transaction(index, err)))
+			.find_first(Result::is_err)
+			.unwrap_or(Ok(()))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	extern crate test_data;
+
+	use chain::IndexedBlock;
+	use db::BlockChainDatabase;
+	use storage::IndexedBlockOutputProvider;
+	use network::{Network, ConsensusParams};
+	use canon::CanonBlock;
+	use deployments::{Deployments, BlockDeployments};
+	use error::{Error, TransactionError};
+	use script::{Error as ScriptError, VerificationError as ScriptVerificationError, OpcodePosition, ScriptKind};
+	use VerificationLevel;
+	use super::ChainAcceptor;
+
+	#[test]
+	fn parallel_and_sequential_transactions_check_agree() {
+		let consensus = ConsensusParams::new(Network::Unitest);
+
+		let mut genesis_builder = test_data::block_builder()
+			.transaction().coinbase().build()
+			.transaction();
+		for _ in 0..30 {
+			genesis_builder = genesis_builder.output().value(10).build();
+		}
+		let genesis = genesis_builder.build()
+			.merkled_header().build()
+			.build();
+
+		let storage = BlockChainDatabase::init_test_chain(vec![genesis.clone().into()]);
+		let reference_tx = genesis.transactions()[1].hash();
+
+		let mut block_builder = test_data::block_builder()
+			.transaction().coinbase().build();
+		for index in 0..30 {
+			// OP_RETURN in signature script makes script verification fail
+			let signature = if index == 11 || index == 23 { "6a" } else { "" };
+			block_builder = block_builder.transaction()
+				.input().hash(reference_tx.clone()).index(index).signature(signature).build()
+				.output().value(5).build()
+				.build();
+		}
+		let block: IndexedBlock = block_builder
+			.merkled_header().parent(genesis.hash()).build()
+			.build()
+			.into();
+
+		let deployments = Deployments::new();
+		let block_deployments = BlockDeployments::new(&deployments, 1, &storage, &consensus);
+		let block_out_provider = IndexedBlockOutputProvider::new(&block);
+		let acceptor = ChainAcceptor::new(
+			&storage,
+			&block_out_provider,
+			&storage,
+			&storage,
+			&storage,
+			&storage,
+			&storage,
+			&consensus,
+			VerificationLevel::FULL,
+			CanonBlock::new(&block),
+			1,
+			block.header.raw.time,
+			&block_deployments,
+			None,
+		);
+
+		let expected = Err(Error::Transaction(12, TransactionError::Signature(0, ScriptVerificationError {
+			error: ScriptError::ReturnOpcode,
+			position: Some(OpcodePosition { script: ScriptKind::ScriptSig, offset: 0 }),
+		})));
+		assert_eq!(acceptor.check_transactions_sequential(), expected);
+		assert_eq!(acceptor.check_transactions_parallel(), expected);
+		assert_eq!(acceptor.check_transactions(), expected);
 	}
 }
@@ -3,17 +3,17 @@ use crypto::Groth16VerifyingKey;
 use storage::{EpochRef, NullifierTracker, EpochTag, TransactionMetaProvider, TransactionOutputProvider,
 	DuplexTransactionOutputProvider, TreeStateProvider};
 use network::{ConsensusParams};
-use script::{Script, verify_script, VerificationFlags, TransactionSignatureChecker, TransactionInputSigner, SighashBase};
+use script::{Script, verify_script_detailed, VerificationFlags, TransactionSignatureChecker, TransactionInputSignerRef, SighashBase};
 use deployments::BlockDeployments;
 use sapling::accept_sapling;
 use sigops::transaction_sigops;
 use canon::CanonTransaction;
-use chain::{OVERWINTER_TX_VERSION, SAPLING_TX_VERSION, OVERWINTER_TX_VERSION_GROUP_ID, SAPLING_TX_VERSION_GROUP_ID};
-use constants::COINBASE_MATURITY;
+use chain::{OVERWINTER_TX_VERSION, SAPLING_TX_VERSION};
 use error::TransactionError;
-use primitives::hash::H256;
+use primitives::hash::{H256, DisplayReversed};
 use {checked_transaction_fee, VerificationLevel};
 use tree_cache::TreeCache;
+use validated_tx_cache::ValidatedTxCache;
 
 pub struct TransactionAcceptor<'a> {
 	pub version: TransactionVersion<'a>,
@@ -26,12 +26,17 @@ pub struct TransactionAcceptor<'a> {
 	pub eval: TransactionEval<'a>,
 	pub join_split: JoinSplitVerification<'a>,
 	pub sapling: SaplingVerification<'a>,
+	pub transaction: CanonTransaction<'a>,
+	pub context: AcceptedContext,
+	pub validated_txs: Option<&'a ValidatedTxCache>,
 }
 
 impl<'a> TransactionAcceptor<'a> {
 	pub fn new(
 		// in case of block validation, it's only current block,
 		meta_store: &'a TransactionMetaProvider,
+		// in case of block validation, hash of the currently processed block coinbase
+		block_coinbase: Option<&'a H256>,
 		// previous transaction outputs
 		// in case of block validation, that's database and currently processed block
 		output_store: DuplexTransactionOutputProvider<'a>,
@@ -40,28 +45,33 @@ impl<'a> TransactionAcceptor<'a> {
 		transaction: CanonTransaction<'a>,
 		verification_level: VerificationLevel,
 		height: u32,
-		time: u32,
 		transaction_index: usize,
-		deployments: &'a BlockDeployments<'a>,
+		context: AcceptedContext,
 		tree_state_provider: &'a TreeStateProvider,
+		// transactions with already verified scripts and proofs
+		validated_txs: Option<&'a ValidatedTxCache>,
 	) -> Self {
-		trace!(target: "verification", "Tx verification {}", transaction.hash.to_reversed_str());
+		trace!(target: "verification", "Tx verification {}", DisplayReversed(&transaction.hash));
 		TransactionAcceptor {
 			version: TransactionVersion::new(transaction, consensus, height),
 			size: TransactionSize::new(transaction, consensus, height),
 			expiry: TransactionExpiry::new(transaction, consensus, height),
 			bip30: TransactionBip30::new_for_sync(transaction, meta_store),
 			missing_inputs: TransactionMissingInputs::new(transaction, output_store, transaction_index),
-			maturity: TransactionMaturity::new(transaction, meta_store, height),
+			maturity: TransactionMaturity::new(transaction, consensus, meta_store, block_coinbase, height),
 			double_spent: TransactionDoubleSpend::new(transaction, output_store),
-			eval: TransactionEval::new(transaction, output_store, consensus, verification_level, height, time, deployments),
+			eval: TransactionEval::new(transaction, output_store, context, verification_level, None),
 			join_split: JoinSplitVerification::new(consensus, transaction, nullifier_tracker, tree_state_provider),
 			sapling: SaplingVerification::new(
 				nullifier_tracker,
+				tree_state_provider,
 				consensus.sapling_spend_verifying_key,
 				consensus.sapling_output_verifying_key,
 				transaction,
 			),
+			transaction: transaction,
+			context: context,
+			validated_txs: validated_txs,
 		}
 	}
 
@@ -74,16 +84,49 @@ impl<'a> TransactionAcceptor<'a> {
 		self.maturity.check()?;
 		self.double_spent.check()?;
 
+		// scripts and proofs of this transaction have been verified already => only run contextual checks
+		let is_validated = self.validated_txs
+			.map(|cache| cache.contains(&self.transaction.hash, &self.context))
+			.unwrap_or(false);
+		if is_validated {
+			self.join_split.check_contextual()?;
+			self.sapling.check_contextual()?;
+			return Ok(());
+		}
+
 		// to make sure we're using the sighash-cache, let's make all sighash-related
 		// calls from single checker && pass sighash to other checkers
 		let sighash = self.eval.check()?;
 		self.join_split.check(sighash)?;
 		self.sapling.check(sighash)?;
 
+		// scripts aren't verified on lower verification levels => do not remember these transactions
+		let scripts_verified = !self.eval.verification_level.intersects(VerificationLevel::HEADER | VerificationLevel::NO_VERIFICATION);
+		match self.validated_txs {
+			Some(cache) if scripts_verified => cache.insert(self.transaction.hash, self.context),
+			_ => (),
+		}
+
 		Ok(())
 	}
 }
 
+/// Stage of memory pool transaction verification.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum MempoolCheckStage {
+	Version,
+	Size,
+	Expiry,
+	MissingInputs,
+	Maturity,
+	Overspent,
+	Sigops,
+	DoubleSpent,
+	Eval,
+	JoinSplit,
+	Sapling,
+}
+
 pub struct MemoryPoolTransactionAcceptor<'a> {
 	pub version: TransactionVersion<'a>,
 	pub size: TransactionSize<'a>,
@@ -111,8 +154,10 @@ impl<'a> MemoryPoolTransactionAcceptor<'a> {
 		time: u32,
 		deployments: &'a BlockDeployments<'a>,
 		tree_state_provider: &'a TreeStateProvider,
+		// script verification flags of the memory pool policy, enabled in addition to consensus flags
+		script_flags: Option<VerificationFlags>,
 	) -> Self {
-		trace!(target: "verification", "Mempool-Tx verification {}", transaction.hash.to_reversed_str());
+		trace!(target: "verification", "Mempool-Tx verification {}", DisplayReversed(&transaction.hash));
 		let transaction_index = 0;
 		let max_block_sigops = consensus.max_block_sigops();
 		MemoryPoolTransactionAcceptor {
@@ -120,14 +165,21 @@ impl<'a> MemoryPoolTransactionAcceptor<'a> {
 			size: TransactionSize::new(transaction, consensus, height),
 			expiry: TransactionExpiry::new(transaction, consensus, height),
 			missing_inputs: TransactionMissingInputs::new(transaction, output_store, transaction_index),
-			maturity: TransactionMaturity::new(transaction, meta_store, height),
+			maturity: TransactionMaturity::new(transaction, consensus, meta_store, None, height),
 			overspent: TransactionOverspent::new(transaction, output_store),
 			sigops: TransactionSigops::new(transaction, output_store, consensus, max_block_sigops, time),
 			double_spent: TransactionDoubleSpend::new(transaction, output_store),
-			eval: TransactionEval::new(transaction, output_store, consensus, VerificationLevel::FULL, height, time, deployments),
+			eval: TransactionEval::new(
+				transaction,
+				output_store,
+				AcceptedContext::new(consensus, height, time, deployments),
+				VerificationLevel::FULL,
+				script_flags,
+			),
 			join_split: JoinSplitVerification::new(consensus, transaction, nullifier_tracker, tree_state_provider),
 			sapling: SaplingVerification::new(
 				nullifier_tracker,
+				tree_state_provider,
 				consensus.sapling_spend_verifying_key,
 				consensus.sapling_output_verifying_key,
 				transaction,
@@ -136,22 +188,27 @@ impl<'a> MemoryPoolTransactionAcceptor<'a> {
 	}
 
 	pub fn check(&self) -> Result<(), TransactionError> {
+		self.check_detailed().map_err(|(_, error)| error)
+	}
+
+	/// Same as `check`, but also returns the stage at which verification has failed.
+	pub fn check_detailed(&self) -> Result<(), (MempoolCheckStage, TransactionError)> {
 		// Bip30 is not checked because we don't need to allow tx pool acceptance of an unspent duplicate.
 		// Tx pool validation is not strictly a matter of consensus.
-		self.version.check()?;
-		self.size.check()?;
-		self.expiry.check()?;
-		self.missing_inputs.check()?;
-		self.maturity.check()?;
-		self.overspent.check()?;
-		self.sigops.check()?;
-		self.double_spent.check()?;
+		self.version.check().map_err(|e| (MempoolCheckStage::Version, e))?;
+		self.size.check().map_err(|e| (MempoolCheckStage::Size, e))?;
+		self.expiry.check().map_err(|e| (MempoolCheckStage::Expiry, e))?;
+		self.missing_inputs.check().map_err(|e| (MempoolCheckStage::MissingInputs, e))?;
+		self.maturity.check().map_err(|e| (MempoolCheckStage::Maturity, e))?;
+		self.overspent.check().map_err(|e| (MempoolCheckStage::Overspent, e))?;
+		self.sigops.check().map_err(|e| (MempoolCheckStage::Sigops, e))?;
+		self.double_spent.check().map_err(|e| (MempoolCheckStage::DoubleSpent, e))?;
 
 		// to make sure we're using the sighash-cache, let's make all sighash-related
 		// calls from single checker && pass sighash to other checkers
-		let sighash = self.eval.check()?;
-		self.join_split.check(sighash)?;
-		self.sapling.check(sighash)?;
+		let sighash = self.eval.check().map_err(|e| (MempoolCheckStage::Eval, e))?;
+		self.join_split.check(sighash).map_err(|e| (MempoolCheckStage::JoinSplit, e))?;
+		self.sapling.check(sighash).map_err(|e| (MempoolCheckStage::Sapling, e))?;
 
 		Ok(())
 	}
@@ -208,12 +265,14 @@ impl<'a> TransactionMissingInputs<'a> {
 	}
 
 	fn check(&self) -> Result<(), TransactionError> {
-		let missing_index = self.transaction.raw.inputs.iter()
-			.position(|input| {
-				let is_not_null = !input.previous_output.is_null();
-				let is_missing = self.store.transaction_output(&input.previous_output, self.transaction_index).is_none();
-				is_not_null && is_missing
-			});
+		// coinbase transaction doesn't spend any outputs
+		if self.transaction.is_coinbase() {
+			return Ok(());
+		}
+
+		// null prevouts are only allowed in coinbase, so indexes of spent outpoints match input indexes
+		let missing_index = self.transaction.raw.spent_outpoints()
+			.position(|outpoint| self.store.transaction_output(outpoint, self.transaction_index).is_none());
 
 		match missing_index {
 			Some(index) => Err(TransactionError::Input(index)),
@@ -225,24 +284,40 @@ impl<'a> TransactionMissingInputs<'a> {
 pub struct TransactionMaturity<'a> {
 	transaction: CanonTransaction<'a>,
 	store: &'a TransactionMetaProvider,
+	block_coinbase: Option<&'a H256>,
 	height: u32,
+	coinbase_maturity: u32,
 }
 
 impl<'a> TransactionMaturity<'a> {
-	fn new(transaction: CanonTransaction<'a>, store: &'a TransactionMetaProvider, height: u32) -> Self {
+	fn new(
+		transaction: CanonTransaction<'a>,
+		consensus: &'a ConsensusParams,
+		store: &'a TransactionMetaProvider,
+		block_coinbase: Option<&'a H256>,
+		height: u32,
+	) -> Self {
 		TransactionMaturity {
 			transaction: transaction,
 			store: store,
+			block_coinbase: block_coinbase,
 			height: height,
+			coinbase_maturity: consensus.coinbase_maturity(),
 		}
 	}
 
 	fn check(&self) -> Result<(), TransactionError> {
-		// TODO: this is should also fail when we are trying to spend current block coinbase
 		let immature_spend = self.transaction.raw.inputs.iter()
-			.any(|input| match self.store.transaction_meta(&input.previous_output.hash) {
-				Some(ref meta) if meta.is_coinbase() && self.height < meta.height() + COINBASE_MATURITY => true,
-				_ => false,
+			.any(|input| {
+				// coinbase of the currently processed block is never mature
+				if self.block_coinbase == Some(&input.previous_output.hash) {
+					return true;
+				}
+
+				// height is only read for coinbase transactions
+				let hash = &input.previous_output.hash;
+				self.store.transaction_is_coinbase(hash).unwrap_or(false) &&
+					self.store.transaction_height(hash).map_or(false, |height| self.height < height + self.coinbase_maturity)
 			});
 
 		if immature_spend {
@@ -273,6 +348,7 @@ impl<'a> TransactionOverspent<'a> {
 
 		checked_transaction_fee(&self.store, ::std::usize::MAX, &self.transaction.raw)
 			.map(|_| ())
+			.map_err(Into::into)
 	}
 }
 
@@ -297,7 +373,7 @@ impl<'a> TransactionSigops<'a> {
 
 	fn check(&self) -> Result<(), TransactionError> {
 		let bip16_active = self.time >= self.consensus_params.bip16_time;
-		let sigops = transaction_sigops(&self.transaction.raw, &self.store, bip16_active);
+		let sigops = transaction_sigops(&self.transaction.raw, &self.store, bip16_active, false);
 		if sigops > self.max_sigops {
 			Err(TransactionError::MaxSigops)
 		} else {
@@ -306,74 +382,96 @@ impl<'a> TransactionSigops<'a> {
 	}
 }
 
+/// Script verification rules and consensus branch of the block.
+///
+/// These are derived once per block and shared by all transactions of the block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AcceptedContext {
+	pub verify_p2sh: bool,
+	pub verify_strictenc: bool,
+	pub verify_locktime: bool,
+	pub verify_checksequence: bool,
+	pub verify_dersig: bool,
+	pub verify_nulldummy: bool,
+	pub verify_sigpushonly: bool,
+	pub verify_cleanstack: bool,
+	pub consensus_branch_id: u32,
+}
+
+impl AcceptedContext {
+	pub fn new(params: &ConsensusParams, height: u32, time: u32, deployments: &BlockDeployments) -> Self {
+		AcceptedContext {
+			verify_p2sh: time >= params.bip16_time,
+			verify_strictenc: false,
+			verify_locktime: height >= params.bip65_height,
+			verify_checksequence: deployments.csv(),
+			verify_dersig: height >= params.bip66_height,
+			verify_nulldummy: false,
+			verify_sigpushonly: false,
+			verify_cleanstack: false,
+			consensus_branch_id: params.consensus_branch_id(height),
+		}
+	}
+}
+
 pub struct TransactionEval<'a> {
 	transaction: CanonTransaction<'a>,
 	store: DuplexTransactionOutputProvider<'a>,
 	verification_level: VerificationLevel,
-	verify_p2sh: bool,
-	verify_strictenc: bool,
-	verify_locktime: bool,
-	verify_checksequence: bool,
-	verify_dersig: bool,
-	verify_nulldummy: bool,
-	verify_sigpushonly: bool,
-	verify_cleanstack: bool,
-	consensus_branch_id: u32,
+	context: AcceptedContext,
+	flags: VerificationFlags,
 }
 
 impl<'a> TransactionEval<'a> {
+	/// Script verification flags are derived from the context. Policy `flags` may only
+	/// enable additional checks on top of them.
 	fn new(
 		transaction: CanonTransaction<'a>,
 		store: DuplexTransactionOutputProvider<'a>,
-		params: &ConsensusParams,
+		context: AcceptedContext,
 		verification_level: VerificationLevel,
-		height: u32,
-		time: u32,
-		deployments: &'a BlockDeployments,
+		flags: Option<VerificationFlags>,
 	) -> Self {
-		let verify_p2sh = time >= params.bip16_time;
-		let verify_strictenc = false;
-		let verify_locktime = height >= params.bip65_height;
-		let verify_dersig = height >= params.bip66_height;
-
-		let verify_checksequence = deployments.csv();
-		let verify_sigpushonly = false;
-		let verify_cleanstack = false;
-
-		let consensus_branch_id = params.consensus_branch_id(height);
+		let consensus_flags = VerificationFlags::default()
+			.verify_p2sh(context.verify_p2sh)
+			.verify_strictenc(context.verify_strictenc)
+			.verify_locktime(context.verify_locktime)
+			.verify_checksequence(context.verify_checksequence)
+			.verify_dersig(context.verify_dersig)
+			.verify_nulldummy(context.verify_nulldummy)
+			.verify_sigpushonly(context.verify_sigpushonly)
+			.verify_cleanstack(context.verify_cleanstack);
+		let flags = match flags {
+			Some(ref policy_flags) => consensus_flags.union(policy_flags),
+			None => consensus_flags,
+		};
 
 		TransactionEval {
 			transaction: transaction,
 			store: store,
 			verification_level: verification_level,
-			verify_p2sh: verify_p2sh,
-			verify_strictenc: verify_strictenc,
-			verify_locktime: verify_locktime,
-			verify_checksequence: verify_checksequence,
-			verify_dersig: verify_dersig,
-			verify_nulldummy: false,
-			verify_sigpushonly: verify_sigpushonly,
-			verify_cleanstack: verify_cleanstack,
-			consensus_branch_id: consensus_branch_id,
+			context: context,
+			flags: flags,
 		}
 	}
 
 	/// Returns no-input sighash for transactions that have non-empty JoinSplit
 	/// or non-empty Sapling.
 	fn check(&self) -> Result<H256, TransactionError> {
-		let signer: TransactionInputSigner = self.transaction.raw.clone().into();
+		let signer = TransactionInputSignerRef::from(&self.transaction.raw);
+		// signature portions that are shared by all inputs are computed once
+		let cache = signer.sighash_cache();
 
 		let mut checker = TransactionSignatureChecker {
 			signer: signer,
 			input_index: 0,
 			input_amount: 0,
-			consensus_branch_id: self.consensus_branch_id,
-			cache: Default::default(),
+			consensus_branch_id: self.context.consensus_branch_id,
+			cache: cache,
 		};
 
 		// generate sighash that is not associated with a transparent input
-		let require_no_input_sighash = self.transaction.raw.join_split.is_some()
-			|| self.transaction.raw.sapling.is_some();
+		let require_no_input_sighash = self.transaction.raw.is_shielded();
 		let no_input_sighash = match require_no_input_sighash {
 			true => checker.signer.signature_hash(
 				&mut checker.cache,
@@ -394,8 +492,10 @@ impl<'a> TransactionEval<'a> {
 			return Ok(no_input_sighash);
 		}
 
-		for (index, input) in self.transaction.raw.inputs.iter().enumerate() {
-			let output = self.store.transaction_output(&input.previous_output, usize::max_value())
+		// previous outputs of all inputs are read at once
+		let outputs = self.store.outputs_for_transaction(&self.transaction.raw, usize::max_value());
+		for (index, (input, output)) in self.transaction.raw.inputs.iter().zip(outputs).enumerate() {
+			let output = output
 				.ok_or_else(|| TransactionError::UnknownReference(input.previous_output.hash.clone()))?;
 
 			checker.input_index = index;
@@ -404,17 +504,10 @@ impl<'a> TransactionEval<'a> {
 			let input: Script = input.script_sig.clone().into();
 			let output: Script = output.script_pubkey.into();
 
-			let flags = VerificationFlags::default()
-				.verify_p2sh(self.verify_p2sh)
-				.verify_strictenc(self.verify_strictenc)
-				.verify_locktime(self.verify_locktime)
-				.verify_checksequence(self.verify_checksequence)
-				.verify_dersig(self.verify_dersig)
-				.verify_nulldummy(self.verify_nulldummy)
-				.verify_sigpushonly(self.verify_sigpushonly)
-				.verify_cleanstack(self.verify_cleanstack);
-
-			verify_script(&input, &output, &flags, &mut checker)
+			#[cfg(test)]
+			::PROOF_VERIFICATIONS.with(|counter| counter.set(counter.get() + 1));
+
+			verify_script_detailed(&input, &output, &self.flags, &mut checker)
 				.map_err(|e| TransactionError::Signature(index, e))?;
 		}
 
@@ -436,16 +529,14 @@ impl<'a> TransactionDoubleSpend<'a> {
 	}
 
 	fn check(&self) -> Result<(), TransactionError> {
-		if self.transaction.raw.is_coinbase() {
+		// coinbase transaction doesn't spend any outputs
+		if self.transaction.is_coinbase() {
 			return Ok(());
 		}
 
-		for input in &self.transaction.raw.inputs {
-			if self.store.is_spent(&input.previous_output) {
-				return Err(TransactionError::UsingSpentOutput(
-					input.previous_output.hash.clone(),
-					input.previous_output.index
-				))
+		for outpoint in self.transaction.raw.spent_outpoints() {
+			if self.store.is_spent(outpoint) {
+				return Err(TransactionError::UsingSpentOutput(outpoint.hash.clone(), outpoint.index))
 			}
 		}
 		Ok(())
@@ -467,7 +558,7 @@ impl<'a> TransactionSize<'a> {
 	}
 
 	fn check(&self) -> Result<(), TransactionError> {
-		let size = self.transaction.raw.serialized_size();
+		let size = self.transaction.serialized_size();
 		if size > self.max_size {
 			Err(TransactionError::MaxSize)
 		} else {
@@ -532,12 +623,12 @@ impl<'a> TransactionVersion<'a> {
 		if self.is_overwinter_active {
 			// when sapling is active, version group id must be set to sapling
 			// when sapling is inactive, version group id must be set to overwinter
-			let required_version_group_id = if self.is_sapling_active {
-				SAPLING_TX_VERSION_GROUP_ID
+			let has_required_version_group = if self.is_sapling_active {
+				self.transaction.raw.has_sapling_version_group()
 			} else {
-				OVERWINTER_TX_VERSION_GROUP_ID
+				self.transaction.raw.has_overwinter_version_group()
 			};
-			if self.transaction.raw.version_group_id != required_version_group_id {
+			if !has_required_version_group {
 				return Err(TransactionError::InvalidVersionGroup);
 			}
 
@@ -572,19 +663,25 @@ impl<'a> JoinSplitProof<'a> {
 		}
 	}
 
-	fn check(&self) -> Result<(), TransactionError> {
+	/// Checks anchors of all descriptions and (if `verify_proofs` is true) their proofs.
+	fn check(&self, verify_proofs: bool) -> Result<(), TransactionError> {
 		use sprout;
 
 		if let Some(ref join_split) = self.transaction.raw.join_split {
 			let mut index = 0;
 			let mut tree_cache = TreeCache::new(self.tree_state_provider);
 			for desc in join_split.descriptions.iter() {
-				sprout::verify(
-					&desc,
-					&join_split,
-					&self.consensus_params.joinsplit_verification_key,
-					&self.consensus_params.joinsplit_groth16_verification_key,
-				).map_err(|_e| TransactionError::InvalidJoinSplit(index))?;
+				if verify_proofs {
+					#[cfg(test)]
+					::PROOF_VERIFICATIONS.with(|counter| counter.set(counter.get() + 1));
+
+					sprout::verify(
+						&desc,
+						&join_split,
+						&self.consensus_params.joinsplit_verification_key,
+						&self.consensus_params.joinsplit_groth16_verification_key,
+					).map_err(|_e| TransactionError::InvalidJoinSplit(index))?;
+				}
 
 				tree_cache.continue_root(&desc.anchor.into(), &desc.commitments)?;
 
@@ -609,14 +706,13 @@ impl<'a> JoinSplitNullifiers<'a> {
 
 	fn check(&self) -> Result<(), TransactionError> {
 		if let Some(ref join_split) = self.transaction.raw.join_split {
-			for description in join_split.descriptions.iter() {
-				for nullifier in &description.nullifiers[..] {
-					let check = EpochRef::new(EpochTag::Sprout, H256::from(&nullifier[..]));
+			let nullifiers: Vec<_> = join_split.descriptions.iter()
+				.flat_map(|description| description.nullifiers.iter())
+				.map(|nullifier| EpochRef::new(EpochTag::Sprout, H256::from(&nullifier[..])))
+				.collect();
 
-					if self.tracker.contains_nullifier(check) {
-						return Err(TransactionError::JoinSplitDeclared(*check.hash()))
-					}
-				}
+			if let Some(declared) = self.tracker.contains_any(&nullifiers) {
+				return Err(TransactionError::JoinSplitDeclared(*declared.hash()))
 			}
 		}
 
@@ -652,7 +748,13 @@ impl<'a> JoinSplitVerification<'a> {
 				.map_err(|e| TransactionError::JoinSplitSignature(e))?;
 		}
 
-		self.proof.check()?;
+		self.proof.check(true)?;
+		self.nullifiers.check()
+	}
+
+	/// Same as `check`, but signature and proofs are not verified.
+	pub fn check_contextual(&self) -> Result<(), TransactionError> {
+		self.proof.check(false)?;
 		self.nullifiers.check()
 	}
 }
@@ -670,12 +772,12 @@ impl<'a> SaplingNullifiers<'a> {
 
 	fn check(&self) -> Result<(), TransactionError> {
 		if let Some(ref sapling) = self.transaction.raw.sapling {
-			for spend in &sapling.spends {
-				let check = EpochRef::new(EpochTag::Sapling, H256::from(&spend.nullifier[..]));
+			let nullifiers: Vec<_> = sapling.spends.iter()
+				.map(|spend| EpochRef::new(EpochTag::Sapling, H256::from(&spend.nullifier[..])))
+				.collect();
 
-				if self.tracker.contains_nullifier(check) {
-					return Err(TransactionError::SaplingDeclared(*check.hash()))
-				}
+			if let Some(declared) = self.tracker.contains_any(&nullifiers) {
+				return Err(TransactionError::SaplingDeclared(*declared.hash()))
 			}
 		}
 
@@ -683,6 +785,30 @@ impl<'a> SaplingNullifiers<'a> {
 	}
 }
 
+/// Checks that sapling spends anchors are known sapling tree roots.
+pub struct SaplingAnchors<'a> {
+	tree_state_provider: &'a TreeStateProvider,
+	transaction: CanonTransaction<'a>,
+}
+
+impl<'a> SaplingAnchors<'a> {
+	fn new(tree_state_provider: &'a TreeStateProvider, transaction: CanonTransaction<'a>) -> Self {
+		SaplingAnchors { tree_state_provider: tree_state_provider, transaction: transaction }
+	}
+
+	fn check(&self) -> Result<(), TransactionError> {
+		if let Some(ref sapling) = self.transaction.raw.sapling {
+			for spend in &sapling.spends {
+				let anchor = H256::from(spend.anchor);
+				if self.tree_state_provider.sapling_tree_at(&anchor).is_none() {
+					return Err(TransactionError::InvalidSaplingAnchor(anchor));
+				}
+			}
+		}
+
+		Ok(())
+	}
+}
 
 /// Checks that sapling signatures/proofs are valid.
 pub struct SaplingProof<'a> {
@@ -706,6 +832,9 @@ impl<'a> SaplingProof<'a> {
 
 	fn check(&self, sighash: H256) -> Result<(), TransactionError> {
 		if let Some(sapling) = self.transaction.raw.sapling.as_ref() {
+			#[cfg(test)]
+			::PROOF_VERIFICATIONS.with(|counter| counter.set(counter.get() + 1));
+
 			accept_sapling(self.spend_vk, self.output_vk, &sighash, sapling)
 				.map_err(|_| TransactionError::InvalidSapling)?;
 		}
@@ -718,11 +847,13 @@ impl<'a> SaplingProof<'a> {
 pub struct SaplingVerification<'a> {
 	proof: SaplingProof<'a>,
 	nullifiers: SaplingNullifiers<'a>,
+	anchors: SaplingAnchors<'a>,
 }
 
 impl<'a> SaplingVerification<'a> {
 	pub fn new(
 		tracker: &'a NullifierTracker,
+		tree_state_provider: &'a TreeStateProvider,
 		spend_vk: &'a Groth16VerifyingKey,
 		output_vk: &'a Groth16VerifyingKey,
 		transaction: CanonTransaction<'a>
@@ -731,12 +862,21 @@ impl<'a> SaplingVerification<'a> {
 		SaplingVerification {
 			proof: SaplingProof::new(spend_vk, output_vk, transaction),
 			nullifiers: SaplingNullifiers::new(tracker, transaction),
+			anchors: SaplingAnchors::new(tree_state_provider, transaction),
 		}
 	}
 
 	pub fn check(&self, sighash: H256) -> Result<(), TransactionError> {
-		self.proof.check(sighash)?;
-		self.nullifiers.check()
+		// store lookups are much cheaper than proofs verification
+		self.nullifiers.check()?;
+		self.anchors.check()?;
+		self.proof.check(sighash)
+	}
+
+	/// Same as `check`, but proofs are not verified.
+	pub fn check_contextual(&self) -> Result<(), TransactionError> {
+		self.nullifiers.check()?;
+		self.anchors.check()
 	}
 }
 
@@ -745,10 +885,15 @@ mod tests {
 	extern crate test_data;
 
 
-	use chain::{BTC_TX_VERSION, Transaction, Sapling};
+	use chain::{BTC_TX_VERSION, OVERWINTER_TX_VERSION_GROUP_ID, SAPLING_TX_VERSION_GROUP_ID, Transaction,
+		IndexedTransaction, IndexedBlock, Sapling, SaplingSpendDescription, OutPoint, TransactionInput, TransactionOutput};
 	use db::BlockChainDatabase;
-	use network::{Network, ConsensusParams};
-	use script::{Script, VerificationFlags, TransactionSignatureChecker, TransactionInputSigner, verify_script};
+	use deployments::Deployments;
+	use storage::NoopStore;
+	use network::{Network, ConsensusParams, SPROUT_BRANCH_ID, OVERWINTER_BRANCH_ID, SAPLING_BRANCH_ID};
+	use crypto::dhash160;
+	use script::{Script, Builder, VerificationFlags, TransactionSignatureChecker, TransactionInputSigner, verify_script,
+		Error as ScriptError, VerificationError as ScriptVerificationError, OpcodePosition, ScriptKind};
 	use super::*;
 
 	#[test]
@@ -811,6 +956,83 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn sapling_anchors_works() {
+		let storage = BlockChainDatabase::init_test_chain(vec![test_data::genesis().into()]);
+		let known_anchor = test_data::genesis().block_header.final_sapling_root;
+
+		// when anchor is the root of the known sapling tree
+		let tx: IndexedTransaction = test_data::TransactionBuilder::with_sapling(Sapling {
+			spends: vec![SaplingSpendDescription { anchor: known_anchor.into(), ..Default::default() }],
+			..Default::default()
+		}).into();
+		assert_eq!(SaplingAnchors::new(&storage, CanonTransaction::new(&tx)).check(), Ok(()));
+
+		// when anchor is unknown
+		let unknown_anchor = H256::from(1);
+		let tx: IndexedTransaction = test_data::TransactionBuilder::with_sapling(Sapling {
+			spends: vec![
+				SaplingSpendDescription { anchor: known_anchor.into(), ..Default::default() },
+				SaplingSpendDescription { anchor: unknown_anchor.into(), ..Default::default() },
+			],
+			..Default::default()
+		}).into();
+		assert_eq!(
+			SaplingAnchors::new(&storage, CanonTransaction::new(&tx)).check(),
+			Err(TransactionError::InvalidSaplingAnchor(unknown_anchor))
+		);
+	}
+
+	#[test]
+	fn sapling_verification_works() {
+		let consensus = ConsensusParams::new(Network::Mainnet);
+		let consensus_branch_id = consensus.consensus_branch_id(consensus.sapling_height);
+		let storage = BlockChainDatabase::init_test_chain(vec![test_data::genesis().into()]);
+		let spend_vk = test_data::sapling::spend_verifying_key();
+		let output_vk = test_data::sapling::output_verifying_key();
+
+		let tx: IndexedTransaction = test_data::TransactionBuilder::with_valid_sapling_spend(100)
+			.add_valid_sapling_spend(50)
+			.add_valid_sapling_output(120)
+			.sign_sapling(consensus_branch_id)
+			.into();
+		let signer: TransactionInputSigner = tx.raw.clone().into();
+		let sighash = signer.signature_hash(&mut Default::default(), None, 0, &From::from(vec![]), SighashBase::All.into(), consensus_branch_id);
+
+		// when everything is valid
+		assert_eq!(
+			SaplingVerification::new(&storage, &storage, &spend_vk, &output_vk, CanonTransaction::new(&tx)).check(sighash),
+			Ok(())
+		);
+
+		// when transaction is signed for other branch
+		let other_sighash = signer.signature_hash(&mut Default::default(), None, 0, &From::from(vec![]), SighashBase::All.into(), 0);
+		assert_eq!(
+			SaplingVerification::new(&storage, &storage, &spend_vk, &output_vk, CanonTransaction::new(&tx)).check(other_sighash),
+			Err(TransactionError::InvalidSapling)
+		);
+	}
+
+	#[test]
+	fn sapling_verification_checks_anchors_before_proofs() {
+		let storage = BlockChainDatabase::init_test_chain(vec![test_data::genesis().into()]);
+		let spend_vk = test_data::sapling::spend_verifying_key();
+		let output_vk = test_data::sapling::output_verifying_key();
+
+		let unknown_anchor = H256::from(1);
+		let tx: IndexedTransaction = test_data::TransactionBuilder::with_sapling(Sapling {
+			spends: vec![SaplingSpendDescription { anchor: unknown_anchor.into(), ..Default::default() }],
+			..Default::default()
+		}).into();
+
+		let proof_verifications = ::PROOF_VERIFICATIONS.with(|counter| counter.get());
+		assert_eq!(
+			SaplingVerification::new(&storage, &storage, &spend_vk, &output_vk, CanonTransaction::new(&tx)).check(Default::default()),
+			Err(TransactionError::InvalidSaplingAnchor(unknown_anchor))
+		);
+		assert_eq!(::PROOF_VERIFICATIONS.with(|counter| counter.get()), proof_verifications);
+	}
+
 	#[test]
 	fn transaction_expiry_works() {
 		let consensus = ConsensusParams::new(Network::Mainnet);
@@ -914,4 +1136,313 @@ mod tests {
 			CanonTransaction::new(&tx), &consensus, consensus.sapling_height + 1
 		).check(), Ok(()));
 	}
+
+	struct SingleOutputStore {
+		output: TransactionOutput,
+		is_spent: bool,
+	}
+
+	impl TransactionOutputProvider for SingleOutputStore {
+		fn transaction_output(&self, _outpoint: &OutPoint, _transaction_index: usize) -> Option<TransactionOutput> {
+			Some(self.output.clone())
+		}
+
+		fn is_spent(&self, _outpoint: &OutPoint) -> bool {
+			self.is_spent
+		}
+	}
+
+	fn check_mempool_transaction(tx: &IndexedTransaction, prevout_store: &SingleOutputStore) -> Result<(), (MempoolCheckStage, TransactionError)> {
+		check_mempool_transaction_with_flags(tx, prevout_store, None)
+	}
+
+	fn check_mempool_transaction_with_flags(
+		tx: &IndexedTransaction,
+		prevout_store: &SingleOutputStore,
+		script_flags: Option<VerificationFlags>,
+	) -> Result<(), (MempoolCheckStage, TransactionError)> {
+		let consensus = ConsensusParams::new(Network::Unitest);
+		let storage = BlockChainDatabase::init_test_chain(vec![test_data::genesis().into()]);
+		let deployments = Deployments::new();
+		let block_deployments = BlockDeployments::new(&deployments, 1, &storage, &consensus);
+		MemoryPoolTransactionAcceptor::new(
+			&storage,
+			DuplexTransactionOutputProvider::new(prevout_store, &NoopStore),
+			&storage,
+			&consensus,
+			CanonTransaction::new(tx),
+			1,
+			0,
+			&block_deployments,
+			&storage,
+			script_flags,
+		).check_detailed()
+	}
+
+	#[test]
+	fn memory_pool_script_flags_override() {
+		let store = SingleOutputStore {
+			// OP_1
+			output: TransactionOutput { value: 100, script_pubkey: vec![0x51].into() },
+			is_spent: false,
+		};
+		// OP_1: leaves two elements on the stack
+		let mut tx: Transaction = test_data::TransactionBuilder::with_version(BTC_TX_VERSION)
+			.add_default_input(0)
+			.add_output(10)
+			.into();
+		tx.inputs[0].script_sig = vec![0x51].into();
+		let tx: IndexedTransaction = tx.into();
+
+		assert_eq!(check_mempool_transaction(&tx, &store), Ok(()));
+
+		let policy_flags = VerificationFlags::default()
+			.verify_p2sh(true)
+			.verify_cleanstack(true);
+		assert_eq!(
+			check_mempool_transaction_with_flags(&tx, &store, Some(policy_flags)),
+			Err((MempoolCheckStage::Eval, TransactionError::Signature(0, ScriptError::Cleanstack.into()))),
+		);
+	}
+
+	#[test]
+	fn memory_pool_script_flags_do_not_weaken_consensus() {
+		// P2SH output with redeem script OP_0
+		let redeem_script = vec![0x00];
+		let store = SingleOutputStore {
+			output: TransactionOutput { value: 100, script_pubkey: Builder::build_p2sh(&dhash160(&redeem_script)).into() },
+			is_spent: false,
+		};
+		let mut tx: Transaction = test_data::TransactionBuilder::with_version(BTC_TX_VERSION)
+			.add_default_input(0)
+			.add_output(10)
+			.into();
+		tx.inputs[0].script_sig = Builder::default().push_data(&redeem_script).into_script().into();
+		let tx: IndexedTransaction = tx.into();
+
+		let expected_error = Err((MempoolCheckStage::Eval, TransactionError::Signature(0, ScriptError::EvalFalse.into())));
+		assert_eq!(check_mempool_transaction(&tx, &store), expected_error);
+		assert_eq!(check_mempool_transaction_with_flags(&tx, &store, Some(VerificationFlags::default())), expected_error);
+	}
+
+	#[test]
+	fn memory_pool_signature_error_reports_opcode_position() {
+		let store = SingleOutputStore {
+			// OP_1 OP_EQUALVERIFY
+			output: TransactionOutput { value: 100, script_pubkey: vec![0x51, 0x88].into() },
+			is_spent: false,
+		};
+		// OP_0
+		let mut tx: Transaction = test_data::TransactionBuilder::with_version(BTC_TX_VERSION)
+			.add_default_input(0)
+			.add_output(10)
+			.into();
+		tx.inputs[0].script_sig = vec![0x00].into();
+		let tx: IndexedTransaction = tx.into();
+
+		assert_eq!(check_mempool_transaction(&tx, &store), Err((MempoolCheckStage::Eval, TransactionError::Signature(0, ScriptVerificationError {
+			error: ScriptError::EqualVerify,
+			position: Some(OpcodePosition { script: ScriptKind::ScriptPubKey, offset: 1 }),
+		}))));
+	}
+
+	#[test]
+	fn transaction_double_spend_skips_coinbase() {
+		let spent_store = SingleOutputStore {
+			output: TransactionOutput { value: 100, script_pubkey: Default::default() },
+			is_spent: true,
+		};
+
+		let coinbase: IndexedTransaction = Transaction {
+			inputs: vec![TransactionInput::coinbase(vec![0u8; 2].into())],
+			outputs: vec![TransactionOutput { value: 10, script_pubkey: Default::default() }],
+			..Default::default()
+		}.into();
+		assert!(coinbase.is_coinbase());
+		assert_eq!(TransactionDoubleSpend::new(
+			CanonTransaction::new(&coinbase), DuplexTransactionOutputProvider::new(&spent_store, &NoopStore)
+		).check(), Ok(()));
+
+		let tx: IndexedTransaction = test_data::TransactionBuilder::with_version(BTC_TX_VERSION)
+			.add_default_input(0)
+			.add_output(10)
+			.into();
+		assert!(!tx.is_coinbase());
+		assert!(TransactionDoubleSpend::new(
+			CanonTransaction::new(&tx), DuplexTransactionOutputProvider::new(&spent_store, &NoopStore)
+		).check().is_err());
+	}
+
+	#[test]
+	fn mempool_check_detailed_reports_size_stage() {
+		let prevout_store = SingleOutputStore {
+			output: TransactionOutput { value: 100, script_pubkey: Default::default() },
+			is_spent: false,
+		};
+		let mut tx: Transaction = test_data::TransactionBuilder::with_version(BTC_TX_VERSION)
+			.add_default_input(0)
+			.add_output(10)
+			.into();
+		tx.outputs[0].script_pubkey = vec![0u8; 200_000].into();
+		let tx: IndexedTransaction = tx.into();
+
+		assert_eq!(
+			check_mempool_transaction(&tx, &prevout_store),
+			Err((MempoolCheckStage::Size, TransactionError::MaxSize))
+		);
+	}
+
+	#[test]
+	fn mempool_check_detailed_reports_double_spent_stage() {
+		let prevout_store = SingleOutputStore {
+			output: TransactionOutput { value: 100, script_pubkey: Default::default() },
+			is_spent: true,
+		};
+		let tx: IndexedTransaction = test_data::TransactionBuilder::with_version(BTC_TX_VERSION)
+			.add_default_input(0)
+			.add_output(10)
+			.into();
+		let prevout = tx.raw.inputs[0].previous_output.clone();
+
+		assert_eq!(
+			check_mempool_transaction(&tx, &prevout_store),
+			Err((MempoolCheckStage::DoubleSpent, TransactionError::UsingSpentOutput(prevout.hash, prevout.index)))
+		);
+	}
+
+	#[test]
+	fn transaction_maturity_rejects_own_block_coinbase_spend() {
+		let storage = BlockChainDatabase::init_test_chain(vec![test_data::genesis().into()]);
+		let coinbase: Transaction = test_data::TransactionBuilder::coinbase().add_output(10).into();
+		let spend: Transaction = test_data::TransactionBuilder::with_output(5).add_input(&coinbase, 0).into();
+		let block: IndexedBlock = test_data::block_builder()
+			.header().parent(test_data::genesis().hash()).build()
+			.with_transaction(coinbase)
+			.with_transaction(spend)
+			.build()
+			.into();
+		let coinbase = &block.transactions[0];
+		let tx = &block.transactions[1];
+
+		let consensus = ConsensusParams::new(Network::Mainnet);
+
+		// when coinbase of the current block isn't known, spend isn't detected
+		assert_eq!(TransactionMaturity::new(CanonTransaction::new(tx), &consensus, &storage, None, 1).check(), Ok(()));
+
+		// when spending coinbase of the current block, maturity doesn't matter
+		assert_eq!(
+			TransactionMaturity::new(CanonTransaction::new(tx), &consensus, &storage, Some(&coinbase.hash), 1_000).check(),
+			Err(TransactionError::Maturity)
+		);
+	}
+
+	#[test]
+	fn transaction_maturity_depends_on_network() {
+		let storage = BlockChainDatabase::init_test_chain(vec![test_data::genesis().into()]);
+		let genesis_coinbase = test_data::genesis().transactions[0].clone();
+		let tx: IndexedTransaction = test_data::TransactionBuilder::with_output(5).add_input(&genesis_coinbase, 0).into();
+
+		// genesis coinbase is spent 50 blocks later
+		let mainnet = ConsensusParams::new(Network::Mainnet);
+		assert_eq!(
+			TransactionMaturity::new(CanonTransaction::new(&tx), &mainnet, &storage, None, 50).check(),
+			Err(TransactionError::Maturity)
+		);
+
+		let regtest = ConsensusParams::new(Network::Regtest);
+		assert_eq!(TransactionMaturity::new(CanonTransaction::new(&tx), &regtest, &storage, None, 50).check(), Ok(()));
+	}
+
+	#[test]
+	fn accepted_context_matches_per_transaction_flags() {
+		let mut consensus = ConsensusParams::new(Network::Unitest);
+		consensus.bip16_time = 100;
+		consensus.bip65_height = 10;
+		consensus.bip66_height = 20;
+		consensus.overwinter_height = 30;
+		consensus.sapling_height = 40;
+		let storage = BlockChainDatabase::init_test_chain(vec![test_data::genesis().into()]);
+		let deployments = Deployments::new();
+
+		let context = |height: u32, time: u32| {
+			let block_deployments = BlockDeployments::new(&deployments, height, &storage, &consensus);
+			AcceptedContext::new(&consensus, height, time, &block_deployments)
+		};
+		let expected = |verify_p2sh: bool, verify_locktime: bool, verify_dersig: bool, consensus_branch_id: u32| AcceptedContext {
+			verify_p2sh: verify_p2sh,
+			verify_strictenc: false,
+			verify_locktime: verify_locktime,
+			verify_checksequence: false,
+			verify_dersig: verify_dersig,
+			verify_nulldummy: false,
+			verify_sigpushonly: false,
+			verify_cleanstack: false,
+			consensus_branch_id: consensus_branch_id,
+		};
+
+		assert_eq!(context(9, 99), expected(false, false, false, SPROUT_BRANCH_ID));
+		assert_eq!(context(9, 100), expected(true, false, false, SPROUT_BRANCH_ID));
+		assert_eq!(context(10, 100), expected(true, true, false, SPROUT_BRANCH_ID));
+		assert_eq!(context(19, 100), expected(true, true, false, SPROUT_BRANCH_ID));
+		assert_eq!(context(20, 100), expected(true, true, true, SPROUT_BRANCH_ID));
+		assert_eq!(context(29, 100), expected(true, true, true, SPROUT_BRANCH_ID));
+		assert_eq!(context(30, 100), expected(true, true, true, OVERWINTER_BRANCH_ID));
+		assert_eq!(context(39, 100), expected(true, true, true, OVERWINTER_BRANCH_ID));
+		assert_eq!(context(40, 100), expected(true, true, true, SAPLING_BRANCH_ID));
+	}
+
+	#[test]
+	fn transaction_acceptor_skips_proofs_of_validated_transaction() {
+		let consensus = ConsensusParams::new(Network::Unitest);
+		let storage = BlockChainDatabase::init_test_chain(vec![test_data::genesis().into()]);
+		let deployments = Deployments::new();
+		let block_deployments = BlockDeployments::new(&deployments, 1, &storage, &consensus);
+		let context = AcceptedContext::new(&consensus, 1, 0, &block_deployments);
+		let cache = ValidatedTxCache::default();
+
+		let tx: IndexedTransaction = test_data::TransactionBuilder::with_version(BTC_TX_VERSION)
+			.add_default_input(0)
+			.add_output(10)
+			.into();
+		let prevout = tx.raw.inputs[0].previous_output.clone();
+		// OP_1
+		let unspent_store = SingleOutputStore {
+			output: TransactionOutput { value: 100, script_pubkey: vec![0x51].into() },
+			is_spent: false,
+		};
+		let spent_store = SingleOutputStore {
+			output: TransactionOutput { value: 100, script_pubkey: vec![0x51].into() },
+			is_spent: true,
+		};
+
+		let check = |prevout_store: &SingleOutputStore| TransactionAcceptor::new(
+			&storage,
+			None,
+			DuplexTransactionOutputProvider::new(prevout_store, &NoopStore),
+			&storage,
+			&consensus,
+			CanonTransaction::new(&tx),
+			VerificationLevel::FULL,
+			1,
+			1,
+			context,
+			&storage,
+			Some(&cache),
+		).check();
+		let proof_verifications = || ::PROOF_VERIFICATIONS.with(|counter| counter.get());
+
+		// when transaction is verified for the first time
+		let verifications_before = proof_verifications();
+		assert_eq!(check(&unspent_store), Ok(()));
+		assert_eq!(proof_verifications(), verifications_before + 1);
+		assert!(cache.contains(&tx.hash, &context));
+
+		// when transaction is verified again, scripts aren't verified
+		assert_eq!(check(&unspent_store), Ok(()));
+		assert_eq!(proof_verifications(), verifications_before + 1);
+
+		// but contextual checks are still performed
+		assert_eq!(check(&spent_store), Err(TransactionError::UsingSpentOutput(prevout.hash, prevout.index)));
+		assert_eq!(proof_verifications(), verifications_before + 1);
+	}
 }
@@ -0,0 +1,238 @@
+use std::cmp;
+use std::collections::{HashMap, HashSet};
+use chain::{IndexedTransaction, OutPoint, Transaction, TransactionOutput};
+use network::ConsensusParams;
+use primitives::hash::H256;
+use ser::Serializable;
+use storage::TransactionOutputProvider;
+use fee::checked_transaction_fee;
+use sigops::transaction_sigops;
+
+/// Outputs of block template candidates, backed by the chain outputs.
+struct CandidatesOutputProvider<'a> {
+	candidates: HashMap<H256, &'a IndexedTransaction>,
+	/// Outpoints, spent by already selected candidates.
+	spent: HashSet<OutPoint>,
+	store: &'a TransactionOutputProvider,
+}
+
+impl<'a> TransactionOutputProvider for CandidatesOutputProvider<'a> {
+	fn transaction_output(&self, outpoint: &OutPoint, transaction_index: usize) -> Option<TransactionOutput> {
+		match self.candidates.get(&outpoint.hash) {
+			Some(tx) => tx.raw.outputs.get(outpoint.index as usize).cloned(),
+			None => self.store.transaction_output(outpoint, transaction_index),
+		}
+	}
+
+	fn outputs_for_transaction(&self, tx: &Transaction, transaction_index: usize) -> Vec<Option<TransactionOutput>> {
+		tx.inputs.iter()
+			.zip(self.store.outputs_for_transaction(tx, transaction_index))
+			.map(|(input, output)| match self.candidates.get(&input.previous_output.hash) {
+				Some(tx) => tx.raw.outputs.get(input.previous_output.index as usize).cloned(),
+				None => output,
+			})
+			.collect()
+	}
+
+	fn is_spent(&self, outpoint: &OutPoint) -> bool {
+		self.spent.contains(outpoint) || self.store.is_spent(outpoint)
+	}
+}
+
+/// Candidate transaction with precomputed properties.
+struct Candidate<'a> {
+	transaction: &'a IndexedTransaction,
+	fee: u64,
+	size: usize,
+	sigops: usize,
+	parents: Vec<usize>,
+}
+
+/// Selects transactions for the new block.
+///
+/// Transactions are greedily packed by fee rate. Transaction is only included after all its
+/// parents (from `candidates`) are included, so the result is dependency ordered. Total size and
+/// sigops of selected transactions do not exceed given limits (and limits of the network).
+/// Transactions with unknown prevouts and transactions that spend outputs, already spent by
+/// selected transactions, are skipped.
+pub fn assemble_block_transactions(
+	candidates: &[IndexedTransaction],
+	store: &TransactionOutputProvider,
+	consensus: &ConsensusParams,
+	max_size: usize,
+	max_sigops: usize,
+) -> Vec<IndexedTransaction> {
+	let max_size = cmp::min(max_size, consensus.max_block_size());
+	let max_sigops = cmp::min(max_sigops, consensus.max_block_sigops());
+
+	let indices: HashMap<H256, usize> = candidates.iter()
+		.enumerate()
+		.map(|(index, tx)| (tx.hash.clone(), index))
+		.collect();
+	let mut provider = CandidatesOutputProvider {
+		candidates: candidates.iter().map(|tx| (tx.hash.clone(), tx)).collect(),
+		spent: HashSet::new(),
+		store: store,
+	};
+
+	let candidates: Vec<Option<Candidate>> = candidates.iter()
+		.map(|tx| checked_transaction_fee(&provider, usize::max_value(), &tx.raw).ok().map(|fee| Candidate {
+			transaction: tx,
+			fee: fee,
+			size: tx.raw.serialized_size(),
+			sigops: transaction_sigops(&tx.raw, &provider, true, false),
+			parents: tx.raw.inputs.iter()
+				.filter_map(|input| indices.get(&input.previous_output.hash).cloned())
+				.collect::<HashSet<_>>()
+				.into_iter()
+				.collect(),
+		}))
+		.collect();
+
+	// order by fee rate (fee / size), descending
+	let mut by_fee_rate: Vec<usize> = (0..candidates.len())
+		.filter(|index| candidates[*index].is_some())
+		.collect();
+	by_fee_rate.sort_by(|a, b| {
+		let a = candidates[*a].as_ref().expect("filtered above; qed");
+		let b = candidates[*b].as_ref().expect("filtered above; qed");
+		(b.fee as u128 * a.size as u128).cmp(&(a.fee as u128 * b.size as u128))
+	});
+
+	let mut included = HashSet::new();
+	let mut rejected = HashSet::new();
+	let mut result = Vec::new();
+	let mut size = 0;
+	let mut sigops = 0;
+	loop {
+		let mut progress = false;
+		for &index in &by_fee_rate {
+			if included.contains(&index) || rejected.contains(&index) {
+				continue;
+			}
+
+			let candidate = candidates[index].as_ref().expect("filtered above; qed");
+			// parent that has been rejected (or has unknown prevouts) can never be included
+			if candidate.parents.iter().any(|parent| rejected.contains(parent) || candidates[*parent].is_none()) {
+				rejected.insert(index);
+				continue;
+			}
+
+			if !candidate.parents.iter().all(|parent| included.contains(parent)) {
+				continue;
+			}
+
+			// spent outputs only grow => conflicting transaction can never be included
+			if candidate.transaction.raw.inputs.iter().any(|input| provider.is_spent(&input.previous_output)) {
+				rejected.insert(index);
+				continue;
+			}
+
+			// size && sigops only grow => if transaction doesn't fit now, it won't fit later
+			if size + candidate.size > max_size || sigops + candidate.sigops > max_sigops {
+				rejected.insert(index);
+				continue;
+			}
+
+			size += candidate.size;
+			sigops += candidate.sigops;
+			provider.spent.extend(candidate.transaction.raw.inputs.iter().map(|input| input.previous_output.clone()));
+			included.insert(index);
+			result.push(candidate.transaction.clone());
+			progress = true;
+		}
+
+		if !progress {
+			break;
+		}
+	}
+
+	result
+}
+
+#[cfg(test)]
+mod tests {
+	extern crate test_data;
+
+	use chain::{IndexedBlock, IndexedTransaction, Transaction};
+	use network::{Network, ConsensusParams};
+	use ser::Serializable;
+	use super::assemble_block_transactions;
+
+	fn hashes(transactions: &[IndexedTransaction]) -> Vec<::primitives::hash::H256> {
+		transactions.iter().map(|tx| tx.hash.clone()).collect()
+	}
+
+	#[test]
+	fn assemble_block_transactions_keeps_parents_before_children() {
+		let consensus = ConsensusParams::new(Network::Unitest);
+		let funding: Transaction = test_data::TransactionBuilder::with_output(1_000).add_output(1_000).into();
+		let store: IndexedBlock = test_data::block_builder()
+			.header().build()
+			.with_transaction(funding.clone())
+			.build()
+			.into();
+
+		// parent pays low fee, child pays high fee
+		let parent: Transaction = test_data::TransactionBuilder::with_output(990).add_input(&funding, 0).into();
+		let child: Transaction = test_data::TransactionBuilder::with_output(500).add_input(&parent, 0).into();
+		// independent transaction with medium fee
+		let other: Transaction = test_data::TransactionBuilder::with_output(900).add_input(&funding, 1).into();
+		// transaction spending unknown output
+		let orphan: Transaction = test_data::TransactionBuilder::with_output(10).add_default_input(0).into();
+		let candidates: Vec<IndexedTransaction> = vec![child.into(), orphan.into(), other.into(), parent.into()];
+
+		let result = assemble_block_transactions(&candidates, &store, &consensus, usize::max_value(), usize::max_value());
+		assert_eq!(hashes(&result), vec![candidates[2].hash.clone(), candidates[3].hash.clone(), candidates[0].hash.clone()]);
+	}
+
+	#[test]
+	fn assemble_block_transactions_respects_limits() {
+		let consensus = ConsensusParams::new(Network::Unitest);
+		let funding: Transaction = test_data::TransactionBuilder::with_output(1_000).add_output(1_000).into();
+		let store: IndexedBlock = test_data::block_builder()
+			.header().build()
+			.with_transaction(funding.clone())
+			.build()
+			.into();
+
+		let parent: Transaction = test_data::TransactionBuilder::with_output(900).add_input(&funding, 0).into();
+		let child: Transaction = test_data::TransactionBuilder::with_output(800).add_input(&parent, 0).into();
+		let other: Transaction = test_data::TransactionBuilder::with_output(990).add_input(&funding, 1).into();
+		let candidates: Vec<IndexedTransaction> = vec![parent.into(), child.into(), other.into()];
+		let tx_size = candidates[0].raw.serialized_size();
+
+		// only two transactions fit
+		let result = assemble_block_transactions(&candidates, &store, &consensus, 2 * tx_size, usize::max_value());
+		assert_eq!(hashes(&result), vec![candidates[0].hash.clone(), candidates[1].hash.clone()]);
+
+		// only one transaction fits
+		let result = assemble_block_transactions(&candidates, &store, &consensus, tx_size, usize::max_value());
+		assert_eq!(hashes(&result), vec![candidates[0].hash.clone()]);
+
+		// when parent doesn't fit, child is not included either
+		let result = assemble_block_transactions(&candidates, &store, &consensus, tx_size - 1, usize::max_value());
+		assert!(result.is_empty());
+	}
+
+	#[test]
+	fn assemble_block_transactions_skips_conflicting_transactions() {
+		let consensus = ConsensusParams::new(Network::Unitest);
+		let funding: Transaction = test_data::TransactionBuilder::with_output(1_000).into();
+		let store: IndexedBlock = test_data::block_builder()
+			.header().build()
+			.with_transaction(funding.clone())
+			.build()
+			.into();
+
+		// both transactions spend the same output, only the one with higher fee is included
+		let low_fee: Transaction = test_data::TransactionBuilder::with_output(900).add_input(&funding, 0).into();
+		let high_fee: Transaction = test_data::TransactionBuilder::with_output(800).add_input(&funding, 0).into();
+		// child of the excluded transaction can't be included either
+		let low_fee_child: Transaction = test_data::TransactionBuilder::with_output(100).add_input(&low_fee, 0).into();
+		let candidates: Vec<IndexedTransaction> = vec![low_fee.into(), high_fee.into(), low_fee_child.into()];
+
+		let result = assemble_block_transactions(&candidates, &store, &consensus, usize::max_value(), usize::max_value());
+		assert_eq!(hashes(&result), vec![candidates[1].hash.clone()]);
+	}
+}
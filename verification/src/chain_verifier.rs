@@ -1,23 +1,34 @@
 //! Bitcoin chain verifier
 
 use chain::{IndexedBlock, IndexedBlockHeader, IndexedTransaction};
-use storage::{SharedStore, TransactionOutputProvider, BlockHeaderProvider, BlockOrigin,
-	DuplexTransactionOutputProvider, NoopStore, CachedTransactionOutputProvider};
+use primitives::hash::H256;
+use std::cmp;
+use storage::{SharedStore, Store, TransactionOutputProvider, BlockHeaderProvider, BlockOrigin, BlockRef,
+	SideChainOrigin, DuplexTransactionOutputProvider, NoopStore, CachedTransactionOutputProvider,
+	IndexedBlockOutputProvider, Error as DBError};
 use network::ConsensusParams;
+use script::VerificationFlags;
 use error::{Error, TransactionError};
 use canon::{CanonBlock, CanonTransaction};
 use verify_chain::ChainVerifier;
 use verify_header::HeaderVerifier;
-use verify_transaction::MemoryPoolTransactionVerifier;
+use verify_transaction::{MemoryPoolTransactionVerifier, AncestorsSummary, ConflictsSummary};
 use accept_chain::ChainAcceptor;
 use accept_transaction::MemoryPoolTransactionAcceptor;
 use deployments::{Deployments, BlockDeployments};
-use {Verify, VerificationLevel};
+use validated_tx_cache::ValidatedTxCache;
+use {Verify, VerificationLevel, checked_transaction_fee};
+
+/// Callback, invoked with height and hash of every successfully verified block.
+pub type ProgressCallback = Box<Fn(u32, &H256) + Send + Sync>;
 
 pub struct BackwardsCompatibleChainVerifier {
 	store: SharedStore,
 	consensus: ConsensusParams,
 	deployments: Deployments,
+	validated_txs: ValidatedTxCache,
+	progress_callback: Option<ProgressCallback>,
+	mempool_script_flags: Option<VerificationFlags>,
 }
 
 impl BackwardsCompatibleChainVerifier {
@@ -26,6 +37,27 @@ impl BackwardsCompatibleChainVerifier {
 			store: store,
 			consensus: consensus,
 			deployments: Deployments::new(),
+			validated_txs: ValidatedTxCache::default(),
+			progress_callback: None,
+			mempool_script_flags: None,
+		}
+	}
+
+	/// Sets callback that is invoked after every successfully verified block.
+	/// Useful to report progress of long bulk imports.
+	pub fn set_progress_callback(&mut self, callback: ProgressCallback) {
+		self.progress_callback = Some(callback);
+	}
+
+	/// Sets script verification flags that memory pool transactions are checked with
+	/// in addition to consensus flags.
+	pub fn set_mempool_script_flags(&mut self, flags: VerificationFlags) {
+		self.mempool_script_flags = Some(flags);
+	}
+
+	fn report_progress(&self, height: u32, hash: &H256) {
+		if let Some(ref callback) = self.progress_callback {
+			callback(height, hash);
 		}
 	}
 
@@ -49,85 +81,114 @@ impl BackwardsCompatibleChainVerifier {
 			block_origin,
 		);
 
-		let canon_block = CanonBlock::new(block);
-		match block_origin {
+		let block_number = match block_origin {
 			BlockOrigin::KnownBlock => {
 				// there should be no known blocks at this point
 				unreachable!("Trying to re-verify known block: {}", block.hash().reversed());
 			},
 			BlockOrigin::CanonChain { block_number } => {
-				let tx_out_provider = CachedTransactionOutputProvider::new(self.store.as_store().as_transaction_output_provider());
-				let tx_meta_provider = self.store.as_store().as_transaction_meta_provider();
-				let header_provider = self.store.as_store().as_block_header_provider();
-				let tree_state_provider = self.store.as_store().as_tree_state_provider();
-				let nullifier_tracker = self.store.as_store().as_nullifier_tracker();
-				let deployments = BlockDeployments::new(&self.deployments, block_number, header_provider, &self.consensus);
-				let chain_acceptor = ChainAcceptor::new(
-					&tx_out_provider,
-					tx_meta_provider,
-					header_provider,
-					tree_state_provider,
-					nullifier_tracker,
-					&self.consensus,
-					verification_level,
-					canon_block,
-					block_number,
-					block.header.raw.time,
-					&deployments,
-				);
-				chain_acceptor.check()?;
+				self.accept_block(self.store.as_store(), verification_level, block, block_number, Some(&self.validated_txs))?;
+				block_number
 			},
 			BlockOrigin::SideChain(origin) => {
 				let block_number = origin.block_number;
 				let fork = self.store.fork(origin)?;
-				let tx_out_provider = CachedTransactionOutputProvider::new(fork.store().as_transaction_output_provider());
-				let tx_meta_provider = fork.store().as_transaction_meta_provider();
-				let header_provider = fork.store().as_block_header_provider();
-				let tree_state_provider = fork.store().as_tree_state_provider();
-				let nullifier_tracker = fork.store().as_nullifier_tracker();
-				let deployments = BlockDeployments::new(&self.deployments, block_number, header_provider, &self.consensus);
-				let chain_acceptor = ChainAcceptor::new(
-					&tx_out_provider,
-					tx_meta_provider,
-					header_provider,
-					tree_state_provider,
-					nullifier_tracker,
-					&self.consensus,
-					verification_level,
-					canon_block,
-					block_number,
-					block.header.raw.time,
-					&deployments,
-				);
-				chain_acceptor.check()?;
+				self.accept_block(fork.store(), verification_level, block, block_number, Some(&self.validated_txs))?;
+				block_number
 			},
 			BlockOrigin::SideChainBecomingCanonChain(origin) => {
 				let block_number = origin.block_number;
 				let fork = self.store.fork(origin)?;
-				let tx_out_provider = CachedTransactionOutputProvider::new(fork.store().as_transaction_output_provider());
-				let tx_meta_provider = fork.store().as_transaction_meta_provider();
-				let header_provider = fork.store().as_block_header_provider();
-				let tree_state_provider = fork.store().as_tree_state_provider();
-				let nullifier_tracker = fork.store().as_nullifier_tracker();
-				let deployments = BlockDeployments::new(&self.deployments, block_number, header_provider, &self.consensus);
-				let chain_acceptor = ChainAcceptor::new(
-					&tx_out_provider,
-					tx_meta_provider,
-					header_provider,
-					tree_state_provider,
-					nullifier_tracker,
-					&self.consensus,
-					verification_level,
-					canon_block,
-					block_number,
-					block.header.raw.time,
-					&deployments,
-				);
-				chain_acceptor.check()?;
+				self.accept_block(fork.store(), verification_level, block, block_number, Some(&self.validated_txs))?;
+				block_number
 			},
 		};
 
 		assert_eq!(Some(self.store.best_block().hash), self.store.block_hash(self.store.best_block().number));
+		self.report_progress(block_number, block.hash());
+		Ok(())
+	}
+
+	/// Runs acceptance checks of the block against given store.
+	fn accept_block(
+		&self,
+		store: &Store,
+		verification_level: VerificationLevel,
+		block: &IndexedBlock,
+		block_number: u32,
+		validated_txs: Option<&ValidatedTxCache>,
+	) -> Result<(), Error> {
+		let tx_out_provider = CachedTransactionOutputProvider::new(store.as_transaction_output_provider());
+		let block_out_provider = IndexedBlockOutputProvider::new(block);
+		let tx_meta_provider = store.as_transaction_meta_provider();
+		let header_provider = store.as_block_header_provider();
+		let tree_state_provider = store.as_tree_state_provider();
+		let nullifier_tracker = store.as_nullifier_tracker();
+		let shielded_pool_provider = store.as_shielded_pool_provider();
+		let deployments = BlockDeployments::new(&self.deployments, block_number, header_provider, &self.consensus);
+		let chain_acceptor = ChainAcceptor::new(
+			&tx_out_provider,
+			&block_out_provider,
+			tx_meta_provider,
+			header_provider,
+			tree_state_provider,
+			nullifier_tracker,
+			shielded_pool_provider,
+			&self.consensus,
+			verification_level,
+			CanonBlock::new(block),
+			block_number,
+			block.header.raw.time,
+			&deployments,
+			validated_txs,
+		);
+		chain_acceptor.check()
+	}
+
+	/// Re-verifies already stored canon blocks in range [from_height; to_height].
+	///
+	/// Every block is verified against the state of the chain as it was before the block has been inserted.
+	/// Returns height of the first invalid block along with the verification error.
+	/// Genesis block is never verified. Blocks above the best block are ignored.
+	pub fn verify_range(&self, from_height: u32, to_height: u32, verification_level: VerificationLevel) -> Result<(), (u32, Error)> {
+		if verification_level.intersects(VerificationLevel::NO_VERIFICATION) {
+			return Ok(());
+		}
+
+		let best_block_number = self.store.best_block().number;
+		let from_height = cmp::max(from_height, 1);
+		let to_height = cmp::min(to_height, best_block_number);
+		for height in from_height..to_height + 1 {
+			self.verify_stored_block(height, best_block_number, verification_level)
+				.map_err(|error| (height, error))?;
+		}
+
+		Ok(())
+	}
+
+	fn verify_stored_block(&self, height: u32, best_block_number: u32, verification_level: VerificationLevel) -> Result<(), Error> {
+		let block = self.store.block(BlockRef::Number(height))
+			.ok_or_else(|| DBError::DatabaseError(format!("Missing canon block at height {}", height)))?;
+
+		let current_time = ::time::get_time().sec as u32;
+		let chain_verifier = ChainVerifier::new(&block, &self.consensus, current_time, verification_level);
+		chain_verifier.check()?;
+
+		// temporary decanonize this block and all its descendants
+		let decanonized_route = (height..best_block_number + 1)
+			.map(|number| self.store.block_hash(number)
+				.ok_or_else(|| DBError::DatabaseError(format!("Missing canon block at height {}", number))))
+			.collect::<Result<Vec<_>, _>>()?;
+		let fork = self.store.fork(SideChainOrigin {
+			ancestor: height - 1,
+			canonized_route: Vec::new(),
+			decanonized_route: decanonized_route,
+			block_number: height,
+		})?;
+
+		// this is explicit re-verification => do not use validated transactions cache
+		self.accept_block(fork.store(), verification_level, &block, height, None)?;
+		self.report_progress(height, block.hash());
 		Ok(())
 	}
 
@@ -147,10 +208,18 @@ impl BackwardsCompatibleChainVerifier {
 		height: u32,
 		time: u32,
 		transaction: &IndexedTransaction,
+		ancestors: AncestorsSummary,
+		conflicts: ConflictsSummary,
 	) -> Result<(), TransactionError> where T: TransactionOutputProvider {
+		// fee is only required to check whether transaction could replace conflicting in-pool transactions
+		let fee = match conflicts.count {
+			0 => 0,
+			_ => checked_transaction_fee(prevout_provider, ::std::usize::MAX, &transaction.raw)?,
+		};
+
 		// let's do preverification first
 		let deployments = BlockDeployments::new(&self.deployments, height, block_header_provider, &self.consensus);
-		let tx_verifier = MemoryPoolTransactionVerifier::new(&transaction, &self.consensus);
+		let tx_verifier = MemoryPoolTransactionVerifier::new(&transaction, &self.consensus, ancestors, conflicts, fee);
 		try!(tx_verifier.check());
 
 		let canon_tx = CanonTransaction::new(&transaction);
@@ -167,6 +236,7 @@ impl BackwardsCompatibleChainVerifier {
 			time,
 			&deployments,
 			self.store.as_tree_state_provider(),
+			self.mempool_script_flags.clone(),
 		);
 		tx_acceptor.check()
 	}
@@ -190,7 +260,9 @@ mod tests {
 	extern crate test_data;
 
 	use std::sync::Arc;
+	use parking_lot::Mutex;
 	use chain::{IndexedBlock};
+	use primitives::hash::H256;
 	use storage::Error as DBError;
 	use db::BlockChainDatabase;
 	use network::{Network, ConsensusParams};
@@ -198,6 +270,34 @@ mod tests {
 	use super::BackwardsCompatibleChainVerifier as ChainVerifier;
 	use {Verify, Error, TransactionError, VerificationLevel};
 
+	fn chain_of_three_blocks(consensus: &ConsensusParams, overspending_block: Option<u32>) -> Vec<IndexedBlock> {
+		let genesis: IndexedBlock = test_data::block_builder()
+			.transaction().coinbase().output().value(1).build().build()
+			.merkled_header().build()
+			.build()
+			.into();
+
+		let mut blocks = vec![genesis];
+		for height in 1..4 {
+			let miner_reward = match overspending_block == Some(height) {
+				true => consensus.block_reward(height) * 2,
+				false => height as u64,
+			};
+			let parent_hash = blocks.last().expect("genesis is always added; qed").hash().clone();
+			let block = test_data::block_builder()
+				.transaction()
+					.coinbase()
+					.founder_reward(consensus, height)
+					.output().value(miner_reward).build()
+					.build()
+				.merkled_header().parent(parent_hash).build()
+				.build();
+			blocks.push(block.into());
+		}
+
+		blocks
+	}
+
 	#[test]
 	fn verify_orphan() {
 		let storage = Arc::new(BlockChainDatabase::init_test_chain(vec![test_data::genesis().into()]));
@@ -492,4 +592,54 @@ mod tests {
 
 		assert_eq!(expected, verifier.verify(VerificationLevel::FULL, &block.into()));
 	}
+
+	#[test]
+	fn verify_range_works() {
+		let consensus = ConsensusParams::new(Network::Unitest);
+		let storage = Arc::new(BlockChainDatabase::init_test_chain(chain_of_three_blocks(&consensus, None)));
+		let verifier = ChainVerifier::new(storage.clone(), consensus);
+
+		assert_eq!(verifier.verify_range(0, 3, VerificationLevel::FULL), Ok(()));
+		// re-verification doesn't change the chain
+		assert_eq!(storage.best_block().number, 3);
+	}
+
+	#[test]
+	fn verify_range_detects_invalid_stored_block() {
+		let consensus = ConsensusParams::new(Network::Unitest);
+		let storage = Arc::new(BlockChainDatabase::init_test_chain(chain_of_three_blocks(&consensus, Some(2))));
+		let verifier = ChainVerifier::new(storage.clone(), consensus);
+
+		assert_matches!(
+			verifier.verify_range(0, 3, VerificationLevel::FULL),
+			Err((2, Error::CoinbaseOverspend { .. }))
+		);
+		// invalid block is out of range
+		assert_eq!(verifier.verify_range(3, 10, VerificationLevel::FULL), Ok(()));
+		assert_eq!(storage.best_block().number, 3);
+	}
+
+	#[test]
+	fn progress_callback_is_invoked_for_every_verified_block() {
+		let consensus = ConsensusParams::new(Network::Unitest);
+		let blocks = chain_of_three_blocks(&consensus, None);
+		let storage = Arc::new(BlockChainDatabase::init_test_chain(vec![blocks[0].clone()]));
+		let mut verifier = ChainVerifier::new(storage.clone(), consensus);
+
+		let progress = Arc::new(Mutex::new(Vec::new()));
+		let callback_progress = progress.clone();
+		verifier.set_progress_callback(Box::new(move |height, hash: &H256| callback_progress.lock().push((height, hash.clone()))));
+
+		for block in blocks.iter().skip(1) {
+			assert_eq!(verifier.verify(VerificationLevel::FULL, block), Ok(()));
+			storage.insert(block.clone()).unwrap();
+			storage.canonize(block.hash()).unwrap();
+		}
+
+		assert_eq!(*progress.lock(), vec![
+			(1, blocks[1].hash().clone()),
+			(2, blocks[2].hash().clone()),
+			(3, blocks[3].hash().clone()),
+		]);
+	}
 }
@@ -1,10 +1,14 @@
 //! Consenus constants
 
-pub const BLOCK_MAX_FUTURE: i64 = 2 * 60 * 60; // 2 hours
-pub const COINBASE_MATURITY: u32 = 100; // 2 hours
 pub const MIN_COINBASE_SIZE: usize = 2;
 pub const MAX_COINBASE_SIZE: usize = 100;
 
+// Number of blocks, used to compute median time past
+pub const MEDIAN_TIMESTAMP_WINDOW: usize = 11;
+
+// Minimal number of non-coinbase transactions in block to verify them in parallel
+pub const PARALLEL_VERIFICATION_MIN_TRANSACTIONS: usize = 16;
+
 pub const RETARGETING_FACTOR: u32 = 4;
 pub const TARGET_SPACING_SECONDS: u32 = 10 * 60;
 pub const DOUBLE_SPACING_SECONDS: u32 = 2 * TARGET_SPACING_SECONDS;
@@ -71,13 +71,16 @@ impl Deployments {
 	/// Returns true if csv deployment is active
 	pub fn csv(&self, number: u32, headers: &BlockHeaderProvider, consensus: &ConsensusParams) -> bool {
 		match consensus.csv_deployment {
-			Some(csv) => {
-				let mut cache = self.cache.lock();
-				threshold_state(&mut cache, csv, number, headers, consensus.miner_confirmation_window, consensus.rule_change_activation_threshold).is_active()
-			},
+			Some(csv) => self.is_active(csv, number, headers, consensus),
 			None => false
 		}
 	}
+
+	/// Returns true if given deployment is active
+	pub fn is_active(&self, deployment: Deployment, number: u32, headers: &BlockHeaderProvider, consensus: &ConsensusParams) -> bool {
+		let mut cache = self.cache.lock();
+		threshold_state(&mut cache, deployment, number, headers, consensus.miner_confirmation_window, consensus.rule_change_activation_threshold).is_active()
+	}
 }
 
 impl<'a> BlockDeployments<'a> {
@@ -93,6 +96,10 @@ impl<'a> BlockDeployments<'a> {
 	pub fn csv(&self) -> bool {
 		self.deployments.csv(self.number, self.headers, self.consensus)
 	}
+
+	pub fn is_active(&self, deployment: Deployment) -> bool {
+		self.deployments.is_active(deployment, self.number, self.headers, self.consensus)
+	}
 }
 
 impl AsRef<Deployments> for Deployments {
@@ -253,10 +260,10 @@ mod tests {
 	use std::collections::HashMap;
 	use chain::{BlockHeader, IndexedBlockHeader};
 	use storage::{BlockHeaderProvider, BlockRef};
-	use network::Deployment;
+	use network::{Deployment, ConsensusParams, Network};
 	use hash::H256;
 	use primitives::bytes::Bytes;
-	use super::{DeploymentStateCache, ThresholdState, first_of_the_period, threshold_state};
+	use super::{Deployments, BlockDeployments, DeploymentStateCache, ThresholdState, first_of_the_period, threshold_state};
 
 	const MINER_CONFIRMATION_WINDOW: u32 = 1000;
 	const RULE_CHANGE_ACTIVATION_THRESHOLD: u32 = 900;
@@ -470,4 +477,39 @@ mod tests {
 			assert_eq!(threshold_state(&mut DeploymentStateCache::default(), deployment, height, &headers, MINER_CONFIRMATION_WINDOW, RULE_CHANGE_ACTIVATION_THRESHOLD), state);
 		}
 	}
+
+	#[test]
+	fn test_deployments_is_active() {
+		let (_, mut headers, deployment) = prepare_deployments();
+		let mut consensus = ConsensusParams::new(Network::Unitest);
+		consensus.miner_confirmation_window = MINER_CONFIRMATION_WINDOW;
+		consensus.rule_change_activation_threshold = RULE_CHANGE_ACTIVATION_THRESHOLD;
+
+		let deployments = Deployments::new();
+		let test_cases = vec![
+			(1,		make_test_time(1),			0x20000000,	false),	// defined
+			(1000,	make_test_time(10000) - 1,	0x20000001,	false),	// defined
+			(2000,	make_test_time(10000),		0x20000001,	false),	// started
+			(2050,	make_test_time(10010),		0x20000000,	false),	// started
+			(2950,	make_test_time(10020),		0x20000001,	false),	// started
+			(2999,	make_test_time(19999),		0x20000000,	false),	// started
+			(3000,	make_test_time(29999),		0x20000000,	false),	// locked in
+			(3999,	make_test_time(30001),		0x20000000,	false),	// locked in
+			(4000,	make_test_time(30002),		0x20000000,	true),	// active
+			(14333,	make_test_time(30003),		0x20000000,	true),	// active
+		];
+
+		for (height, time, version, is_active) in test_cases {
+			headers.mine(height, time, version);
+
+			assert_eq!(deployments.is_active(deployment, height, &headers, &consensus), is_active);
+			assert_eq!(BlockDeployments::new(&deployments, height, &headers, &consensus).is_active(deployment), is_active);
+			assert_eq!(Deployments::new().is_active(deployment, height, &headers, &consensus), is_active);
+		}
+
+		// deployment with activation height doesn't depend on headers
+		let activated = Deployment { name: "activated", activation: Some(100), ..deployment };
+		assert!(!deployments.is_active(activated, 99, &headers, &consensus));
+		assert!(deployments.is_active(activated, 100, &headers, &consensus));
+	}
 }
@@ -15,6 +15,12 @@ pub fn verify_block_equihash_solution(params: (u32, u32), header: &BlockHeader)
 	)
 }
 
+/// Returns expected size (in bytes) of Equihash solution for given (N, K) parameters.
+pub fn equihash_solution_size(params: (u32, u32)) -> usize {
+	let (n, k) = params;
+	(1usize << k) * ((n / (k + 1)) as usize + 1) / 8
+}
+
 /// Equihash algorithm instance.
 ///
 /// A brief, yet incomplete overview of the algorithm:
@@ -415,4 +421,11 @@ mod tests {
 		let block = test_data::block_h170();
 		assert!(verify_block_equihash_solution((200, 9), &block.block_header));
 	}
+
+	#[test]
+	fn equihash_solution_size_works() {
+		assert_eq!(equihash_solution_size((OnChainEquihash::N, OnChainEquihash::K)), OnChainEquihash::SOLUTION_COMPRESSED_SIZE);
+		assert_eq!(equihash_solution_size((OnChainEquihash::N, OnChainEquihash::K)), 1344);
+		assert_eq!(equihash_solution_size((TestEquihash::N, TestEquihash::K)), TestEquihash::SOLUTION_COMPRESSED_SIZE);
+	}
 }
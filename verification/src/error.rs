@@ -1,7 +1,9 @@
-use hash::H256;
+use std::fmt;
+use hash::{H256, DisplayReversed};
 use compact::Compact;
-use storage::Error as DBError;
-use script::Error as SignatureError;
+use storage::{Error as DBError, EpochTag};
+use script::VerificationError as SignatureError;
+use chain::ShieldedBalanceError;
 
 #[derive(Debug, PartialEq)]
 /// All possible verification errors
@@ -10,10 +12,14 @@ pub enum Error {
 	Duplicate,
 	/// Contains duplicated transactions
 	DuplicatedTransactions,
+	/// Transactions with given indexes reveal the same (Sprout or Sapling) nullifier
+	DuplicateNullifierInBlock(usize, usize),
 	/// No transactions in block
 	Empty,
 	/// Invalid proof-of-work (Block hash does not satisfy nBits)
 	Pow,
+	/// nBits represent target that is above the network maximum
+	DifficultyTooLow,
 	/// Futuristic timestamp
 	FuturisticTimestamp,
 	/// Invalid timestamp
@@ -26,10 +32,13 @@ pub enum Error {
 	Difficulty { expected: Compact, actual: Compact },
 	/// Invalid merkle root
 	MerkleRoot,
+	/// Block transactions merkle tree is mutated (contains duplicated subtree)
+	MerkleMutation,
 	/// Coinbase spends too much
 	CoinbaseOverspend { expected_max: u64, actual: u64 },
-	/// Coinbase has invalid script sig prefix (BIP90 -> BIP34)
-	CoinbaseScript,
+	/// Coinbase script sig doesn't start with the block height (BIP34).
+	/// `actual` is the height decoded from the leading push, if any.
+	CoinbaseHeightMismatch { expected: u32, actual: Option<i64> },
 	/// Maximum sigops operations exceeded - will not provide how much it was in total
 	/// since it stops counting once `MAX_BLOCK_SIGOPS` is reached
 	MaximumSigops,
@@ -41,8 +50,8 @@ pub enum Error {
 	Size(usize),
 	/// Block weight is invalid
 	Weight,
-	/// Block transactions are not final.
-	NonFinalBlock,
+	/// Block transaction with given index is not final.
+	NonFinalTransaction(usize),
 	/// Old version block.
 	OldVersionBlock,
 	/// Sum of the transaction fees in block + coinbase reward exceeds u64::max
@@ -55,16 +64,20 @@ pub enum Error {
 	Database(DBError),
 	/// Invalid equihash solution
 	InvalidEquihashSolution,
+	/// Equihash solution has invalid length
+	InvalidEquihashSolutionLength { expected: usize, actual: usize },
 	/// Invalid block version
 	InvalidVersion,
-	/// Block' coinbase is missing founders reward output.
-	MissingFoundersReward,
 	/// Failed to read sapling commitment tree state from parent block.
 	MissingSaplingCommitmentTree,
 	/// Failed to append commitment note to sapling commitment tree.
 	FailedToAppendSaplingCommitmentNote(String),
 	/// Invalid value of sapling final root hash in the block header.
 	InvalidFinalSaplingRootHash { expected: H256, actual: H256 },
+	/// Failed to read shielded pools balances of the parent block.
+	MissingShieldedPools,
+	/// Block moves more value out of the shielded pool than the pool holds.
+	NegativeShieldedPool(EpochTag),
 }
 
 impl From<DBError> for Error {
@@ -90,6 +103,14 @@ pub enum TransactionError {
 	MaxSigops,
 	/// Transaction is a part of memory pool, but is a coinbase
 	MemoryPoolCoinbase,
+	/// Transaction is a part of memory pool, but input scriptSig is too large
+	InputScriptTooLarge(usize),
+	/// Transaction is a part of memory pool, but has too many in-pool ancestors
+	TooManyAncestors,
+	/// Transaction is a part of memory pool, but its in-pool ancestors are too large
+	AncestorSizeTooLarge,
+	/// Transaction is a part of memory pool, but doesn't pay enough to replace conflicting in-pool transactions
+	ReplacementUnderpaid,
 	/// Not found corresponding output for transaction input
 	Input(usize),
 	/// Referenced coinbase output for the transaction input is not mature enough
@@ -108,6 +129,8 @@ pub enum TransactionError {
 	SigopsP2SH(usize),
 	/// Coinbase transaction is found at position that is not 0
 	MisplacedCoinbase,
+	/// Coinbase transaction is missing output that pays founders reward.
+	CoinbaseMissingFoundersReward,
 	/// Not fully spent transaction with the same hash already exists, bip30.
 	UnspentTransactionWithTheSameHash,
 	/// Using output that is surely spent
@@ -146,6 +169,8 @@ pub enum TransactionError {
 	InvalidSapling,
 	/// Sapling nullifier already revealed earlier in the chain.
 	SaplingDeclared(H256),
+	/// Sapling spend anchor is not a known sapling tree root.
+	InvalidSaplingAnchor(H256),
 	/// Transaction is expired.
 	Expired,
 	/// Transaction overwintered flag is invalid.
@@ -155,3 +180,121 @@ pub enum TransactionError {
 	/// Unknown anchor used in join split
 	UnknownAnchor(H256),
 }
+
+impl fmt::Display for TransactionError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			TransactionError::Empty => write!(f, "Transaction has no inputs or no outputs"),
+			TransactionError::NullNonCoinbase => write!(f, "Non-coinbase transaction has null inputs"),
+			TransactionError::CoinbaseSignatureLength(ref len) => write!(f, "Coinbase signature length {} is not in the range 2-100", len),
+			TransactionError::MaxSize => write!(f, "Transaction size exceeds block size limit"),
+			TransactionError::MinSize => write!(f, "Transaction size is below min size limit"),
+			TransactionError::MaxSigops => write!(f, "Transaction has too many sigops"),
+			TransactionError::MemoryPoolCoinbase => write!(f, "Coinbase transaction is not accepted to memory pool"),
+			TransactionError::InputScriptTooLarge(ref index) => write!(f, "Input {} scriptSig is too large", index),
+			TransactionError::TooManyAncestors => write!(f, "Transaction has too many in-pool ancestors"),
+			TransactionError::AncestorSizeTooLarge => write!(f, "Transaction in-pool ancestors are too large"),
+			TransactionError::ReplacementUnderpaid => write!(f, "Replacement transaction pays too low fee"),
+			TransactionError::Input(ref index) => write!(f, "Input {} references missing output", index),
+			TransactionError::Maturity => write!(f, "Input references immature coinbase output"),
+			TransactionError::Signature(ref index, ref err) => write!(f, "Input {} has invalid signature: {}", index, err),
+			TransactionError::UnknownReference(ref hash) => write!(f, "Unknown previous transaction {}", DisplayReversed(hash)),
+			TransactionError::Overspend => write!(f, "Transaction spends more than it claims"),
+			TransactionError::SignatureMallformed(ref err) => write!(f, "Signature script is malformed: {}", err),
+			TransactionError::Sigops(ref sigops) => write!(f, "Too many signature operations: {}", sigops),
+			TransactionError::SigopsP2SH(ref sigops) => write!(f, "Too many signature operations once p2sh operations included: {}", sigops),
+			TransactionError::MisplacedCoinbase => write!(f, "Coinbase transaction is not at position 0"),
+			TransactionError::CoinbaseMissingFoundersReward => write!(f, "Coinbase transaction does not pay founders reward"),
+			TransactionError::UnspentTransactionWithTheSameHash => write!(f, "Not fully spent transaction with the same hash already exists"),
+			TransactionError::UsingSpentOutput(ref hash, ref index) => write!(f, "Output {}:{} is already spent", DisplayReversed(hash), index),
+			TransactionError::NonTransparentCoinbase => write!(f, "Coinbase transaction has non-transparent descriptions"),
+			TransactionError::InvalidVersion => write!(f, "Invalid transaction version"),
+			TransactionError::InvalidVersionGroup => write!(f, "Invalid transaction version group"),
+			TransactionError::OutputValueOverflow => write!(f, "Transaction output value is too large"),
+			TransactionError::InputValueOverflow => write!(f, "Transaction input value is too large"),
+			TransactionError::ExpiryHeightTooHigh => write!(f, "Transaction expiry height is too high"),
+			TransactionError::EmptySaplingHasBalance => write!(f, "Sapling without spends and outputs has non-zero balance"),
+			TransactionError::JoinSplitBothPubsNonZero => write!(f, "Join split has both vpub_old and vpub_new non-zero"),
+			TransactionError::DuplicateInput(ref first, ref second) => write!(f, "Inputs {} and {} spend the same output", first, second),
+			TransactionError::DuplicateJoinSplitNullifier(ref first, ref second) => write!(f, "Join splits {} and {} have duplicate nullifiers", first, second),
+			TransactionError::DuplicateSaplingSpendNullifier(ref first, ref second) => write!(f, "Sapling spends {} and {} have duplicate nullifiers", first, second),
+			TransactionError::JoinSplitDeclared(ref nullifier) => write!(f, "Join split nullifier {} is already revealed", DisplayReversed(nullifier)),
+			TransactionError::JoinSplitVersionInvalid => write!(f, "Join split is not supported in this transaction version"),
+			TransactionError::JoinSplitSignature(ref err) => write!(f, "Invalid join split signature: {:?}", err),
+			TransactionError::InvalidSapling => write!(f, "Sapling verification has failed"),
+			TransactionError::SaplingDeclared(ref nullifier) => write!(f, "Sapling nullifier {} is already revealed", DisplayReversed(nullifier)),
+			TransactionError::InvalidSaplingAnchor(ref anchor) => write!(f, "Unknown sapling anchor {}", DisplayReversed(anchor)),
+			TransactionError::Expired => write!(f, "Transaction is expired"),
+			TransactionError::InvalidOverwintered => write!(f, "Invalid transaction overwintered flag"),
+			TransactionError::InvalidJoinSplit(ref index) => write!(f, "Join split {} is invalid", index),
+			TransactionError::UnknownAnchor(ref anchor) => write!(f, "Unknown join split anchor {}", DisplayReversed(anchor)),
+		}
+	}
+}
+
+#[derive(Debug, PartialEq)]
+/// Possible transaction fee computation errors
+pub enum FeeError {
+	/// Not found corresponding output for transaction input with given index
+	MissingPrevout { input_index: usize },
+	/// Sum of inputs values overflows.
+	///
+	/// Contains index of the transparent input (None when value comes from JoinSplit
+	/// or Sapling), sum of values of previous inputs and the offending value.
+	InputValueOverflow { input_index: Option<usize>, incoming: u64, value: u64 },
+	/// Sum of outputs values overflows.
+	///
+	/// Contains sum of values of previous outputs and the offending value.
+	OutputValueOverflow { spends: u64, value: u64 },
+	/// Spends more than claims
+	Overspend { incoming: u64, spends: u64 },
+}
+
+impl From<FeeError> for TransactionError {
+	fn from(err: FeeError) -> Self {
+		match err {
+			FeeError::MissingPrevout { input_index } => TransactionError::Input(input_index),
+			FeeError::InputValueOverflow { .. } => TransactionError::InputValueOverflow,
+			FeeError::OutputValueOverflow { .. } => TransactionError::OutputValueOverflow,
+			FeeError::Overspend { .. } => TransactionError::Overspend,
+		}
+	}
+}
+
+impl From<ShieldedBalanceError> for TransactionError {
+	fn from(err: ShieldedBalanceError) -> Self {
+		match err {
+			// value_pub_old takes value from the transparent pool
+			ShieldedBalanceError::SproutInOverflow => TransactionError::OutputValueOverflow,
+			// value_pub_new adds value to the transparent pool
+			ShieldedBalanceError::SproutOutOverflow => TransactionError::InputValueOverflow,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use script::{Error as ScriptError, VerificationError as SignatureError, OpcodePosition, ScriptKind};
+	use hash::H256;
+	use super::TransactionError;
+
+	#[test]
+	fn transaction_error_display_works() {
+		assert_eq!(TransactionError::Input(3).to_string(), "Input 3 references missing output");
+		assert_eq!(TransactionError::Signature(1, ScriptError::EvalFalse.into()).to_string(),
+			"Input 1 has invalid signature: Script evaluated to false");
+		assert_eq!(TransactionError::Signature(1, SignatureError {
+			error: ScriptError::EqualVerify,
+			position: Some(OpcodePosition { script: ScriptKind::ScriptPubKey, offset: 3 }),
+		}).to_string(), "Input 1 has invalid signature: Failed equal verify operation (opcode at byte 3 of ScriptPubKey)");
+		assert_eq!(TransactionError::DuplicateInput(0, 2).to_string(), "Inputs 0 and 2 spend the same output");
+		assert_eq!(TransactionError::Overspend.to_string(), "Transaction spends more than it claims");
+
+		// hashes are displayed in the same (reversed) order as in block explorers && RPC
+		let hash = H256::from_reversed_str("c997a5e56e104102fa209c6a852dd90660a20b2d9c352423edce25857fcd3704");
+		assert_eq!(TransactionError::UnknownReference(hash.clone()).to_string(),
+			"Unknown previous transaction c997a5e56e104102fa209c6a852dd90660a20b2d9c352423edce25857fcd3704");
+		assert_eq!(TransactionError::UsingSpentOutput(hash, 1).to_string(),
+			"Output c997a5e56e104102fa209c6a852dd90660a20b2d9c352423edce25857fcd3704:1 is already spent");
+	}
+}
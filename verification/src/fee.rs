@@ -1,22 +1,32 @@
 use chain::Transaction;
 use storage::TransactionOutputProvider;
-use TransactionError;
+use FeeError;
+
+/// Resolve values of all transparent inputs of given transaction.
+///
+/// Returns error if prevout of any input is missing.
+pub fn resolve_input_values(store: &TransactionOutputProvider, tx_idx: usize, tx: &Transaction) -> Result<Vec<u64>, FeeError> {
+	store.outputs_for_transaction(tx, tx_idx).into_iter().enumerate()
+		.map(|(input_idx, prevout)| prevout
+			.map(|prevout| prevout.value)
+			.ok_or(FeeError::MissingPrevout { input_index: input_idx }))
+		.collect()
+}
 
 /// Compute miner fee for given transaction.
 ///
-/// Returns None if overflow/underflow happens during computation. Missed prevout
-/// is treated as 0-value.
-pub fn checked_transaction_fee(store: &TransactionOutputProvider, tx_idx: usize, tx: &Transaction) -> Result<u64, TransactionError> {
+/// Returns error if prevout is missing or overflow/underflow happens during computation.
+pub fn checked_transaction_fee(store: &TransactionOutputProvider, tx_idx: usize, tx: &Transaction) -> Result<u64, FeeError> {
 	// (1) Total sum of all transparent + shielded inputs
 	let mut incoming: u64 = 0;
-	for (input_idx, input) in tx.inputs.iter().enumerate() {
-		let prevout = match store.transaction_output(&input.previous_output, tx_idx) {
-			Some(prevout) => prevout,
-			None => return Err(TransactionError::Input(input_idx)),
-		};
-		incoming = match incoming.checked_add(prevout.value) {
+	for (input_idx, value) in resolve_input_values(store, tx_idx, tx)?.into_iter().enumerate() {
+		incoming = match incoming.checked_add(value) {
 			Some(incoming) => incoming,
-			None => return Err(TransactionError::InputValueOverflow),
+			None => return Err(FeeError::InputValueOverflow {
+				input_index: Some(input_idx),
+				incoming: incoming,
+				value: value,
+			}),
 		};
 	}
 
@@ -24,7 +34,11 @@ pub fn checked_transaction_fee(store: &TransactionOutputProvider, tx_idx: usize,
 		for js_desc in &join_split.descriptions {
 			incoming = match incoming.checked_add(js_desc.value_pub_new) {
 				Some(incoming) => incoming,
-				None => return Err(TransactionError::InputValueOverflow),
+				None => return Err(FeeError::InputValueOverflow {
+					input_index: None,
+					incoming: incoming,
+					value: js_desc.value_pub_new,
+				}),
 			};
 		}
 	}
@@ -35,7 +49,11 @@ pub fn checked_transaction_fee(store: &TransactionOutputProvider, tx_idx: usize,
 
 			incoming = match incoming.checked_add(balancing_value) {
 				Some(incoming) => incoming,
-				None => return Err(TransactionError::InputValueOverflow),
+				None => return Err(FeeError::InputValueOverflow {
+					input_index: None,
+					incoming: incoming,
+					value: balancing_value,
+				}),
 			};
 		}
 	}
@@ -47,7 +65,10 @@ pub fn checked_transaction_fee(store: &TransactionOutputProvider, tx_idx: usize,
 		for js_desc in &join_split.descriptions {
 			spends = match spends.checked_add(js_desc.value_pub_old) {
 				Some(spends) => spends,
-				None => return Err(TransactionError::OutputValueOverflow),
+				None => return Err(FeeError::OutputValueOverflow {
+					spends: spends,
+					value: js_desc.value_pub_old,
+				}),
 			};
 		}
 	}
@@ -56,12 +77,18 @@ pub fn checked_transaction_fee(store: &TransactionOutputProvider, tx_idx: usize,
 		if sapling.balancing_value < 0 {
 			let balancing_value = match sapling.balancing_value.checked_neg() {
 				Some(balancing_value) => balancing_value as u64,
-				None => return Err(TransactionError::OutputValueOverflow),
+				None => return Err(FeeError::OutputValueOverflow {
+					spends: spends,
+					value: sapling.balancing_value.wrapping_neg() as u64,
+				}),
 			};
-			
+
 			spends = match spends.checked_add(balancing_value) {
 				Some(spends) => spends,
-				None => return Err(TransactionError::OutputValueOverflow),
+				None => return Err(FeeError::OutputValueOverflow {
+					spends: spends,
+					value: balancing_value,
+				}),
 			};
 		}
 	}
@@ -69,7 +96,7 @@ pub fn checked_transaction_fee(store: &TransactionOutputProvider, tx_idx: usize,
 	// (3) Fee is the difference between (1) and (2)
 	match incoming.checked_sub(spends) {
 		Some(fee) => Ok(fee),
-		None => Err(TransactionError::Overspend),
+		None => Err(FeeError::Overspend { incoming: incoming, spends: spends }),
 	}
 }
 
@@ -80,6 +107,7 @@ mod tests {
 	use std::sync::Arc;
 	use storage::AsSubstore;
 	use db::BlockChainDatabase;
+	use chain::{JoinSplit, JoinSplitDescription, Sapling};
 	use super::*;
 
 	#[test]
@@ -104,7 +132,97 @@ mod tests {
 		let db = Arc::new(BlockChainDatabase::init_test_chain(vec![b0.into(), b1.into()]));
 		let store = db.as_transaction_output_provider();
 
-		assert_eq!(checked_transaction_fee(store, ::std::usize::MAX, &tx0), Err(TransactionError::Overspend));
+		assert_eq!(checked_transaction_fee(store, ::std::usize::MAX, &tx0), Err(FeeError::Overspend {
+			incoming: 0,
+			spends: 3_000_000,
+		}));
 		assert_eq!(checked_transaction_fee(store, ::std::usize::MAX, &tx2), Ok(500_000));
 	}
+
+	#[test]
+	fn test_resolve_input_values() {
+		let b0 = test_data::block_builder().header().nonce(1.into()).build()
+			.transaction()
+				.output().value(1_000_000).build()
+				.output().value(2_000_000).build()
+				.build()
+			.build();
+		let tx0_hash = b0.transactions[0].hash();
+		let tx1 = test_data::block_builder().header().parent(b0.hash().clone()).nonce(2.into()).build()
+			.transaction()
+				.input().hash(tx0_hash.clone()).index(1).build()
+				.input().hash(tx0_hash).index(0).build()
+				.output().value(2_900_000).build()
+				.build()
+			.build()
+			.transactions[0].clone();
+
+		let db = Arc::new(BlockChainDatabase::init_test_chain(vec![b0.into()]));
+		let store = db.as_transaction_output_provider();
+
+		assert_eq!(resolve_input_values(store, ::std::usize::MAX, &tx1), Ok(vec![2_000_000, 1_000_000]));
+		assert_eq!(checked_transaction_fee(store, ::std::usize::MAX, &tx1), Ok(100_000));
+	}
+
+	#[test]
+	fn test_transaction_fee_missing_prevout() {
+		let b0 = test_data::block_builder().header().nonce(1.into()).build()
+			.transaction()
+				.output().value(1_000_000).build()
+				.build()
+			.build();
+		let tx0_hash = b0.transactions[0].hash();
+		let tx1 = test_data::block_builder().header().parent(b0.hash().clone()).nonce(2.into()).build()
+			.transaction()
+				.input().hash(tx0_hash.clone()).index(0).build()
+				.input().hash(tx0_hash).index(1).build()
+				.output().value(500_000).build()
+				.build()
+			.build()
+			.transactions[0].clone();
+
+		let db = Arc::new(BlockChainDatabase::init_test_chain(vec![b0.into()]));
+		let store = db.as_transaction_output_provider();
+
+		assert_eq!(checked_transaction_fee(store, ::std::usize::MAX, &tx1), Err(FeeError::MissingPrevout {
+			input_index: 1,
+		}));
+	}
+
+	#[test]
+	fn test_transaction_fee_input_overflow() {
+		let b0 = test_data::block_builder().header().nonce(1.into()).build()
+			.transaction()
+				.output().value(1_000_000).build()
+				.build()
+			.build();
+		let tx0_hash = b0.transactions[0].hash();
+		let mut tx1 = test_data::block_builder().header().parent(b0.hash().clone()).nonce(2.into()).build()
+			.transaction()
+				.input().hash(tx0_hash).index(0).build()
+				.output().value(500_000).build()
+				.build()
+			.build()
+			.transactions[0].clone();
+		tx1.sapling = Some(Sapling {
+			balancing_value: ::std::i64::MAX,
+			..Default::default()
+		});
+		tx1.join_split = Some(JoinSplit {
+			descriptions: vec![JoinSplitDescription {
+				value_pub_new: ::std::u64::MAX - 1_000_000,
+				..Default::default()
+			}],
+			..Default::default()
+		});
+
+		let db = Arc::new(BlockChainDatabase::init_test_chain(vec![b0.into()]));
+		let store = db.as_transaction_output_provider();
+
+		assert_eq!(checked_transaction_fee(store, ::std::usize::MAX, &tx1), Err(FeeError::InputValueOverflow {
+			input_index: None,
+			incoming: ::std::u64::MAX,
+			value: ::std::i64::MAX as u64,
+		}));
+	}
 }
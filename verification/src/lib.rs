@@ -55,6 +55,7 @@ extern crate time;
 #[macro_use]
 extern crate log;
 extern crate parking_lot;
+extern crate lru_cache;
 extern crate rayon;
 extern crate byteorder;
 #[cfg(test)]
@@ -81,6 +82,7 @@ extern crate db;
 extern crate assert_matches;
 
 pub mod constants;
+mod block_template;
 mod canon;
 mod deployments;
 mod equihash;
@@ -108,6 +110,7 @@ mod accept_transaction;
 mod chain_verifier;
 
 mod tree_cache;
+mod validated_tx_cache;
 
 pub use primitives::{bigint, hash, compact};
 
@@ -115,21 +118,25 @@ pub use canon::{CanonBlock, CanonHeader, CanonTransaction};
 pub use accept_block::BlockAcceptor;
 pub use accept_chain::ChainAcceptor;
 pub use accept_header::HeaderAcceptor;
-pub use accept_transaction::{TransactionAcceptor, MemoryPoolTransactionAcceptor};
+pub use accept_transaction::{TransactionAcceptor, MemoryPoolTransactionAcceptor, MempoolCheckStage, AcceptedContext};
 
 pub use verify_block::BlockVerifier;
 pub use verify_chain::ChainVerifier;
-pub use verify_header::HeaderVerifier;
-pub use verify_transaction::{TransactionVerifier, MemoryPoolTransactionVerifier};
-
-pub use chain_verifier::BackwardsCompatibleChainVerifier;
-pub use error::{Error, TransactionError};
-pub use fee::checked_transaction_fee;
-pub use sigops::transaction_sigops;
-pub use timestamp::{median_timestamp, median_timestamp_inclusive};
-pub use work::{work_required, is_valid_proof_of_work, is_valid_proof_of_work_hash};
+pub use verify_header::{HeaderVerifier, verify_headers_batch};
+pub use verify_transaction::{TransactionVerifier, MemoryPoolTransactionVerifier, AncestorsSummary, ConflictsSummary};
+
+pub use chain_verifier::{BackwardsCompatibleChainVerifier, ProgressCallback};
+pub use error::{Error, TransactionError, FeeError};
+pub use fee::{checked_transaction_fee, resolve_input_values};
+pub use block_template::assemble_block_transactions;
+pub use sigops::{transaction_sigops, transaction_sigops_detailed, SigopCounts};
+pub use timestamp::{median_timestamp, median_timestamp_n, median_timestamp_inclusive, median_timestamp_inclusive_n};
+pub use work::{work_required, is_valid_proof_of_work, check_proof_of_work, is_valid_proof_of_work_hash, difficulty_from_bits,
+	block_work};
 pub use deployments::Deployments;
-pub use tree_cache::TreeCache;
+pub use tree_cache::{TreeCache, TreeCacheCheckpoint};
+pub use validated_tx_cache::{ValidatedTxCache, DEFAULT_VALIDATED_TX_CACHE_SIZE};
+pub use sapling::{sapling_binding_vk, verify_binding_sig, Error as SaplingError};
 
 bitflags! {
 	/// Blocks verification level.
@@ -146,6 +153,12 @@ bitflags! {
 	}
 }
 
+#[cfg(test)]
+thread_local! {
+	/// Number of script, JoinSplit and Sapling proof verifications performed by this thread.
+	static PROOF_VERIFICATIONS: ::std::cell::Cell<usize> = ::std::cell::Cell::new(0);
+}
+
 /// Interface for block verification
 pub trait Verify : Send + Sync {
 	fn verify(&self, level: VerificationLevel, block: &chain::IndexedBlock) -> Result<(), Error>;
@@ -1,7 +1,7 @@
 use std::io::Error as IoError;
 use chain::{Sapling, SaplingSpendDescription, SaplingOutputDescription};
 use crypto::{
-	Groth16VerifyingKey, JUBJUB,
+	Groth16VerifyingKey, Groth16BatchVerifier, JUBJUB,
 	pairing::{bls12_381::{Bls12, Fr, FrRepr}, PrimeField, PrimeFieldRepr, PrimeFieldDecodingError},
 	bellman::{SynthesisError, groth16::{verify_proof, Proof}},
 	sapling_crypto::{circuit::multipack, redjubjub::{self, Signature}},
@@ -72,6 +72,9 @@ pub enum ProofError {
 }
 
 /// Verify sapling proofs/signatures validity.
+///
+/// Spend and output proofs are verified in two batches (one per verifying key). If batch
+/// verification fails, proofs of the failed batch are verified one-by-one to find the invalid one.
 pub fn accept_sapling(
 	spend_vk: &Groth16VerifyingKey,
 	output_vk: &Groth16VerifyingKey,
@@ -81,29 +84,55 @@ pub fn accept_sapling(
 	// binding verification key is not encoded explicitly in transaction and must be recalculated
 	let mut total = edwards::Point::zero();
 
-	// verify each spend description
+	// check each spend description and accumulate spend proofs
+	let mut spend_batch = Groth16BatchVerifier::new(&spend_vk.1);
 	for (idx, spend) in sapling.spends.iter().enumerate() {
-		accept_spend(spend_vk, sighash, &mut total, spend)
+		let (zkproof, public_input) = check_spend(sighash, &mut total, spend)
 			.map_err(|err| Error::Spend(idx, err))?;
+		spend_batch.push(zkproof, public_input);
 	}
 
-	// verify each output description
+	// verify all spend proofs
+	verify_proofs_batch(spend_vk, &spend_batch)
+		.map_err(|(idx, err)| Error::Spend(idx, SpendError::Proof(err)))?;
+
+	// check each output description and accumulate output proofs
+	let mut output_batch = Groth16BatchVerifier::new(&output_vk.1);
 	for (idx, output) in sapling.outputs.iter().enumerate() {
-		accept_output(output_vk, &mut total, output)
+		let (zkproof, public_input) = check_output(&mut total, output)
 			.map_err(|err| Error::Output(idx, err))?;
+		output_batch.push(zkproof, public_input);
 	}
 
+	// verify all output proofs
+	verify_proofs_batch(output_vk, &output_batch)
+		.map_err(|(idx, err)| Error::Output(idx, OutputError::Proof(err)))?;
+
 	// check binding signature
 	accept_sapling_final(sighash, total, sapling)
 }
 
 /// Verify sapling spend description.
+#[cfg(test)]
 fn accept_spend(
 	spend_vk: &Groth16VerifyingKey,
 	sighash: &[u8; 32],
 	total: &mut Point,
 	spend: &SaplingSpendDescription,
 ) -> Result<(), SpendError> {
+	let (zkproof, public_input) = check_spend(sighash, total, spend)?;
+	verify_groth16_proof(spend_vk, &zkproof, &public_input)
+		.map_err(SpendError::Proof)
+}
+
+/// Check sapling spend description, except for the zk-proof.
+///
+/// Returns deserialized proof and its public input.
+fn check_spend(
+	sighash: &[u8; 32],
+	total: &mut Point,
+	spend: &SaplingSpendDescription,
+) -> Result<(Proof<Bls12>, Vec<Fr>), SpendError> {
 	// deserialize and check value commitment
 	let value_commitment = require_non_small_order_point(&spend.value_commitment)
 		.map_err(SpendError::ValueCommitment)?;
@@ -144,7 +173,7 @@ fn accept_spend(
 	// construct public input for circuit
 	let (randomized_key_x, randomized_key_y) = randomized_key.0.into_xy();
 	let (value_commitment_x, value_commitment_y) = value_commitment.into_xy();
-	let public_input = [
+	let public_input = vec![
 		randomized_key_x,
 		randomized_key_y,
 		value_commitment_x,
@@ -158,21 +187,28 @@ fn accept_spend(
 	let zkproof = Proof::<Bls12>::read(&spend.zkproof[..])
 		.map_err(|err| SpendError::Proof(ProofError::Invalid(err)))?;
 
-	// check the proof
-	let is_verification_ok = verify_proof(&spend_vk.0, &zkproof, &public_input[..])
-		.map_err(|err| SpendError::Proof(ProofError::Synthesis(err)))?;
-	if !is_verification_ok {
-		return Err(SpendError::Proof(ProofError::Failed));
-	}
-
-	Ok(())
+	Ok((zkproof, public_input))
 }
 
+/// Verify sapling output description.
+#[cfg(test)]
 fn accept_output(
 	output_vk: &Groth16VerifyingKey,
 	total: &mut Point,
 	output: &SaplingOutputDescription,
 ) -> Result<(), OutputError> {
+	let (zkproof, public_input) = check_output(total, output)?;
+	verify_groth16_proof(output_vk, &zkproof, &public_input)
+		.map_err(OutputError::Proof)
+}
+
+/// Check sapling output description, except for the zk-proof.
+///
+/// Returns deserialized proof and its public input.
+fn check_output(
+	total: &mut Point,
+	output: &SaplingOutputDescription,
+) -> Result<(Proof<Bls12>, Vec<Fr>), OutputError> {
 	// deserialize and check value commitment
 	let value_commitment = require_non_small_order_point(&output.value_commitment)
 		.map_err(OutputError::ValueCommitment)?;
@@ -191,7 +227,7 @@ fn accept_output(
 	// construct public input for circuit
 	let (ephemeral_key_x, ephemeral_key_y) = ephemeral_key.into_xy();
 	let (value_commitment_x, value_commitment_y) = value_commitment.into_xy();
-	let public_input = [
+	let public_input = vec![
 		value_commitment_x,
 		value_commitment_y,
 		ephemeral_key_x,
@@ -203,17 +239,76 @@ fn accept_output(
 	let zkproof = Proof::<Bls12>::read(&output.zkproof[..])
 		.map_err(|err| OutputError::Proof(ProofError::Invalid(err)))?;
 
-	// check the proof
-	let is_verification_ok = verify_proof(&output_vk.0, &zkproof, &public_input[..])
-		.map_err(|err| OutputError::Proof(ProofError::Synthesis(err)))?;
+	Ok((zkproof, public_input))
+}
+
+/// Verify single groth16 proof.
+fn verify_groth16_proof(vk: &Groth16VerifyingKey, zkproof: &Proof<Bls12>, public_input: &[Fr]) -> Result<(), ProofError> {
+	let is_verification_ok = verify_proof(&vk.0, zkproof, public_input)
+		.map_err(ProofError::Synthesis)?;
 	if !is_verification_ok {
-		return Err(OutputError::Proof(ProofError::Failed));
+		return Err(ProofError::Failed);
 	}
 
 	Ok(())
 }
 
-fn accept_sapling_final(sighash: &[u8; 32], total: Point, sapling: &Sapling) -> Result<(), Error> {
+/// Verify batch of groth16 proofs. If the batch is invalid, proofs are verified
+/// one-by-one and the index of the first invalid proof is returned.
+fn verify_proofs_batch(vk: &Groth16VerifyingKey, batch: &Groth16BatchVerifier) -> Result<(), (usize, ProofError)> {
+	match batch.verify() {
+		Ok(true) => return Ok(()),
+		Ok(false) | Err(_) => (),
+	}
+
+	for (idx, &(ref zkproof, ref public_input)) in batch.items().iter().enumerate() {
+		verify_groth16_proof(vk, zkproof, public_input)
+			.map_err(|err| (idx, err))?;
+	}
+
+	// every proof is valid on its own => batch has failed spuriously (randomization)
+	Ok(())
+}
+
+/// Computes binding verification key from value commitments and value balance of sapling descriptions.
+///
+/// Neither proofs nor signatures are verified.
+pub fn sapling_binding_vk(sapling: &Sapling) -> Result<[u8; 32], Error> {
+	let binding_verification_key = compute_binding_verification_key(sapling)?;
+
+	let mut result = [0u8; 32];
+	binding_verification_key.0.write(&mut result[..]).expect("bvk is 32 bytes");
+	Ok(result)
+}
+
+/// Verifies binding signature of sapling descriptions.
+///
+/// Binding verification key is computed from value commitments and value balance, neither
+/// proofs nor spend authorization signatures are verified.
+pub fn verify_binding_sig(sighash: &[u8; 32], sapling: &Sapling) -> Result<(), Error> {
+	let binding_verification_key = compute_binding_verification_key(sapling)?;
+	verify_binding_sig_with_key(sighash, binding_verification_key, sapling)
+}
+
+fn compute_binding_verification_key(sapling: &Sapling) -> Result<redjubjub::PublicKey<Bls12>, Error> {
+	let mut total = edwards::Point::zero();
+
+	for (idx, spend) in sapling.spends.iter().enumerate() {
+		let value_commitment = require_non_small_order_point(&spend.value_commitment)
+			.map_err(|err| Error::Spend(idx, SpendError::ValueCommitment(err)))?;
+		total = total.add(&value_commitment, &JUBJUB);
+	}
+
+	for (idx, output) in sapling.outputs.iter().enumerate() {
+		let value_commitment = require_non_small_order_point(&output.value_commitment)
+			.map_err(|err| Error::Output(idx, OutputError::ValueCommitment(err)))?;
+		total = total.add(&value_commitment.negate(), &JUBJUB);
+	}
+
+	binding_verification_key(total, sapling)
+}
+
+fn binding_verification_key(total: Point, sapling: &Sapling) -> Result<redjubjub::PublicKey<Bls12>, Error> {
 	// obtain current bvk from the context
 	let mut binding_verification_key = redjubjub::PublicKey(total);
 
@@ -224,6 +319,19 @@ fn accept_sapling_final(sighash: &[u8; 32], total: Point, sapling: &Sapling) ->
 	value_balance = value_balance.negate();
 	binding_verification_key.0 = binding_verification_key.0.add(&value_balance, &JUBJUB);
 
+	Ok(binding_verification_key)
+}
+
+fn accept_sapling_final(sighash: &[u8; 32], total: Point, sapling: &Sapling) -> Result<(), Error> {
+	let binding_verification_key = binding_verification_key(total, sapling)?;
+	verify_binding_sig_with_key(sighash, binding_verification_key, sapling)
+}
+
+fn verify_binding_sig_with_key(
+	sighash: &[u8; 32],
+	binding_verification_key: redjubjub::PublicKey<Bls12>,
+	sapling: &Sapling,
+) -> Result<(), Error> {
 	// compute the signature's message for binding_verification_key/binding_sig
 	let mut data_to_be_signed = [0u8; 64];
 	binding_verification_key.0.write(&mut data_to_be_signed[..32]).expect("bvk is 32 bytes");
@@ -296,6 +404,7 @@ mod tests {
 	extern crate test_data;
 
 	use chain::Transaction;
+	use network::SAPLING_BRANCH_ID;
 	use script::{TransactionInputSigner, SighashBase};
 	use super::*;
 
@@ -306,7 +415,7 @@ mod tests {
 
 	fn compute_sighash(tx: Transaction) -> [u8; 32] {
 		let signer: TransactionInputSigner = tx.into();
-		signer.signature_hash(&mut Default::default(), None, 0, &From::from(vec![]), SighashBase::All.into(), 0x76b809bb).into()
+		signer.signature_hash(&mut Default::default(), None, 0, &From::from(vec![]), SighashBase::All.into(), SAPLING_BRANCH_ID).into()
 	}
 
 	fn run_accept_sapling(tx: Transaction) -> Result<(), Error> {
@@ -344,9 +453,9 @@ mod tests {
 
 	fn bad_verifying_key() -> Groth16VerifyingKey {
 		use crypto::pairing::{CurveAffine, bls12_381::{G1Affine, G2Affine}};
-		use crypto::bellman::groth16::{VerifyingKey, prepare_verifying_key};
+		use crypto::bellman::groth16::VerifyingKey;
 
-		Groth16VerifyingKey(prepare_verifying_key(&VerifyingKey {
+		VerifyingKey {
 			alpha_g1: G1Affine::zero(),
 			beta_g1: G1Affine::zero(),
 			beta_g2: G2Affine::zero(),
@@ -354,7 +463,28 @@ mod tests {
 			delta_g1: G1Affine::zero(),
 			delta_g2: G2Affine::zero(),
 			ic: vec![],
-		}))
+		}.into()
+	}
+
+	fn accept_sapling_sequential(
+		spend_vk: &Groth16VerifyingKey,
+		output_vk: &Groth16VerifyingKey,
+		sighash: &[u8; 32],
+		sapling: &Sapling,
+	) -> Result<(), Error> {
+		let mut total = edwards::Point::zero();
+
+		for (idx, spend) in sapling.spends.iter().enumerate() {
+			accept_spend(spend_vk, sighash, &mut total, spend)
+				.map_err(|err| Error::Spend(idx, err))?;
+		}
+
+		for (idx, output) in sapling.outputs.iter().enumerate() {
+			accept_output(output_vk, &mut total, output)
+				.map_err(|err| Error::Output(idx, err))?;
+		}
+
+		accept_sapling_final(sighash, total, sapling)
 	}
 
 	#[test]
@@ -362,6 +492,54 @@ mod tests {
 		run_accept_sapling(test_tx()).unwrap();
 	}
 
+	#[test]
+	fn batch_and_sequential_verification_agree() {
+		let spend_vk = crypto::load_sapling_spend_verifying_key().unwrap();
+		let output_vk = crypto::load_sapling_output_verifying_key().unwrap();
+		let sighash = compute_sighash(test_tx());
+		let sapling = test_tx().sapling.unwrap();
+
+		// when everything is valid
+		assert_matches!(accept_sapling(&spend_vk, &output_vk, &sighash, &sapling), Ok(()));
+		assert_matches!(accept_sapling_sequential(&spend_vk, &output_vk, &sighash, &sapling), Ok(()));
+
+		// when all proofs are valid (binding signature isn't, because of duplicated spends)
+		let mut multi_spend = sapling.clone();
+		multi_spend.spends = vec![sapling.spends[0].clone(); 4];
+		assert_matches!(
+			accept_sapling(&spend_vk, &output_vk, &sighash, &multi_spend),
+			Err(Error::BadBindingSignature)
+		);
+		assert_matches!(
+			accept_sapling_sequential(&spend_vk, &output_vk, &sighash, &multi_spend),
+			Err(Error::BadBindingSignature)
+		);
+
+		// when one of spend proofs is invalid
+		multi_spend.spends[2].nullifier = [0; 32];
+		assert_matches!(
+			accept_sapling(&spend_vk, &output_vk, &sighash, &multi_spend),
+			Err(Error::Spend(2, SpendError::Proof(ProofError::Failed)))
+		);
+		assert_matches!(
+			accept_sapling_sequential(&spend_vk, &output_vk, &sighash, &multi_spend),
+			Err(Error::Spend(2, SpendError::Proof(ProofError::Failed)))
+		);
+
+		// when one of output proofs is invalid
+		let mut multi_output = sapling.clone();
+		multi_output.outputs = vec![sapling.outputs[0].clone(); 3];
+		multi_output.outputs[1].note_commitment = multi_output.outputs[1].value_commitment.clone();
+		assert_matches!(
+			accept_sapling(&spend_vk, &output_vk, &sighash, &multi_output),
+			Err(Error::Output(1, OutputError::Proof(ProofError::Failed)))
+		);
+		assert_matches!(
+			accept_sapling_sequential(&spend_vk, &output_vk, &sighash, &multi_output),
+			Err(Error::Output(1, OutputError::Proof(ProofError::Failed)))
+		);
+	}
+
 	#[test]
 	fn accept_spend_fails() {
 		let spend_vk = crypto::load_sapling_spend_verifying_key().unwrap();
@@ -528,4 +706,41 @@ mod tests {
 			Err(Error::BadBindingSignature)
 		);
 	}
+
+	#[test]
+	fn verify_binding_sig_works() {
+		let sighash = compute_sighash(test_tx().clone());
+		let sapling = test_tx().sapling.unwrap();
+
+		assert_matches!(verify_binding_sig(&sighash, &sapling), Ok(()));
+
+		// binding key is the same as the one computed during full verification
+		let mut total = edwards::Point::zero();
+		for spend in &sapling.spends {
+			check_spend(&sighash, &mut total, spend).unwrap();
+		}
+		for output in &sapling.outputs {
+			check_output(&mut total, output).unwrap();
+		}
+		let mut expected_vk = [0u8; 32];
+		binding_verification_key(total, &sapling).unwrap().0.write(&mut expected_vk[..]).unwrap();
+		assert_eq!(sapling_binding_vk(&sapling).unwrap(), expected_vk);
+
+		// when balancing value is changed
+		let mut bad_sapling = sapling.clone();
+		bad_sapling.balancing_value += 1;
+		assert_matches!(verify_binding_sig(&sighash, &bad_sapling), Err(Error::BadBindingSignature));
+		assert!(sapling_binding_vk(&bad_sapling).unwrap() != expected_vk);
+
+		// when sighash is changed
+		assert_matches!(verify_binding_sig(&[0u8; 32], &sapling), Err(Error::BadBindingSignature));
+
+		// when value commitment is small order point
+		let mut bad_sapling = sapling.clone();
+		bad_sapling.outputs[0].value_commitment = small_order_point();
+		assert_matches!(
+			sapling_binding_vk(&bad_sapling),
+			Err(Error::Output(0, OutputError::ValueCommitment(PointError::SmallOrder)))
+		);
+	}
 }
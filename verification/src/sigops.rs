@@ -2,23 +2,56 @@ use chain::Transaction;
 use storage::TransactionOutputProvider;
 use script::Script;
 
+/// Signature operations of transaction, split by origin
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct SigopCounts {
+	/// Signature operations in transaction inputs and outputs scripts
+	pub legacy: usize,
+	/// Signature operations in redeem scripts of spent P2SH outputs
+	pub p2sh: usize,
+}
+
+impl SigopCounts {
+	/// Total number of signature operations
+	pub fn total(&self) -> usize {
+		self.legacy + self.p2sh
+	}
+}
+
 /// Counts signature operations in given transaction
 /// bip16_active flag indicates if we should also count signature operations
 /// in previous transactions. If one of the previous transaction outputs is
 /// missing, we simply ignore that fact and just carry on counting
+/// use_accurate flag indicates if CHECKMULTISIG, preceded by OP_1..OP_16, should be
+/// counted as that many signature operations (instead of MAX_PUBKEYS_PER_MULTISIG)
+/// in transaction inputs and outputs scripts. Consensus rules require inaccurate counting.
 pub fn transaction_sigops(
 	transaction: &Transaction,
 	store: &TransactionOutputProvider,
 	bip16_active: bool,
+	use_accurate: bool,
 ) -> usize {
+	transaction_sigops_detailed(transaction, store, bip16_active, use_accurate).total()
+}
+
+/// Same as `transaction_sigops`, but counts legacy and P2SH signature operations separately
+pub fn transaction_sigops_detailed(
+	transaction: &Transaction,
+	store: &TransactionOutputProvider,
+	bip16_active: bool,
+	use_accurate: bool,
+) -> SigopCounts {
 	let output_sigops: usize = transaction.outputs.iter().map(|output| {
 		let output_script: Script = output.script_pubkey.clone().into();
-		output_script.sigops_count(false)
+		output_script.sigops_count(use_accurate)
 	}).sum();
 
 	// TODO: bitcoin/bitcoin also includes input_sigops here
 	if transaction.is_coinbase() {
-		return output_sigops;
+		return SigopCounts {
+			legacy: output_sigops,
+			p2sh: 0,
+		};
 	}
 
 	let mut input_sigops = 0usize;
@@ -26,7 +59,7 @@ pub fn transaction_sigops(
 
 	for input in &transaction.inputs {
 		let input_script: Script = input.script_sig.clone().into();
-		input_sigops += input_script.sigops_count(false);
+		input_sigops += input_script.sigops_count(use_accurate);
 		if bip16_active {
 			let previous_output = match store.transaction_output(&input.previous_output, usize::max_value()) {
 				Some(output) => output,
@@ -37,5 +70,124 @@ pub fn transaction_sigops(
 		}
 	}
 
-	input_sigops + output_sigops + bip16_sigops
+	SigopCounts {
+		legacy: input_sigops + output_sigops,
+		p2sh: bip16_sigops,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use chain::{Transaction, TransactionInput, TransactionOutput, OutPoint};
+	use crypto::dhash160;
+	use primitives::hash::{H160, H256};
+	use script::{Builder, Opcode, Script};
+	use storage::{TransactionOutputProvider, NoopStore};
+	use super::{transaction_sigops, transaction_sigops_detailed, SigopCounts};
+
+	struct PrevoutStore(TransactionOutput);
+
+	impl TransactionOutputProvider for PrevoutStore {
+		fn transaction_output(&self, _outpoint: &OutPoint, _transaction_index: usize) -> Option<TransactionOutput> {
+			Some(self.0.clone())
+		}
+
+		fn is_spent(&self, _outpoint: &OutPoint) -> bool {
+			false
+		}
+	}
+
+	fn spending_transaction(script_sig: Script) -> Transaction {
+		Transaction {
+			inputs: vec![TransactionInput {
+				previous_output: OutPoint { hash: H256::from(1), index: 0 },
+				script_sig: script_sig.to_bytes(),
+				sequence: 0xffffffff,
+			}],
+			outputs: vec![TransactionOutput {
+				value: 10,
+				script_pubkey: Builder::build_p2pkh(&H160::from(2)).to_bytes(),
+			}],
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn test_p2pkh_input_sigops() {
+		let store = PrevoutStore(TransactionOutput {
+			value: 10,
+			script_pubkey: Builder::build_p2pkh(&H160::from(3)).to_bytes(),
+		});
+		let script_sig = Builder::default()
+			.push_bytes(&[1; 71])
+			.push_bytes(&[2; 33])
+			.into_script();
+		let transaction = spending_transaction(script_sig);
+
+		let expected = SigopCounts { legacy: 1, p2sh: 0 };
+		assert_eq!(transaction_sigops_detailed(&transaction, &store, true, false), expected);
+		assert_eq!(transaction_sigops(&transaction, &store, true, false), 1);
+	}
+
+	#[test]
+	fn test_p2sh_multisig_input_sigops() {
+		let redeem_script = Builder::default()
+			.push_opcode(Opcode::OP_2)
+			.push_bytes(&[2; 33])
+			.push_bytes(&[3; 33])
+			.push_bytes(&[4; 33])
+			.push_opcode(Opcode::OP_3)
+			.push_opcode(Opcode::OP_CHECKMULTISIG)
+			.into_script();
+		let store = PrevoutStore(TransactionOutput {
+			value: 10,
+			script_pubkey: Builder::build_p2sh(&dhash160(&redeem_script)).to_bytes(),
+		});
+		let script_sig = Builder::default()
+			.push_opcode(Opcode::OP_0)
+			.push_bytes(&[1; 71])
+			.push_bytes(&[1; 71])
+			.push_bytes(&redeem_script)
+			.into_script();
+		let transaction = spending_transaction(script_sig);
+
+		let expected = SigopCounts { legacy: 1, p2sh: 3 };
+		assert_eq!(transaction_sigops_detailed(&transaction, &store, true, false), expected);
+		assert_eq!(transaction_sigops(&transaction, &store, true, false), 4);
+
+		// redeem script sigops are not counted before BIP16 activation
+		let expected = SigopCounts { legacy: 1, p2sh: 0 };
+		assert_eq!(transaction_sigops_detailed(&transaction, &store, false, false), expected);
+	}
+
+	#[test]
+	fn test_bare_multisig_output_accurate_sigops() {
+		let transaction = |script_pubkey: Script| Transaction {
+			inputs: vec![TransactionInput::coinbase(Default::default())],
+			outputs: vec![TransactionOutput {
+				value: 10,
+				script_pubkey: script_pubkey.to_bytes(),
+			}],
+			..Default::default()
+		};
+
+		// 2-of-3 multisig
+		let multisig = transaction(Builder::default()
+			.push_opcode(Opcode::OP_2)
+			.push_bytes(&[2; 33])
+			.push_bytes(&[3; 33])
+			.push_bytes(&[4; 33])
+			.push_opcode(Opcode::OP_3)
+			.push_opcode(Opcode::OP_CHECKMULTISIG)
+			.into_script());
+		assert_eq!(transaction_sigops(&multisig, &NoopStore, false, true), 3);
+		assert_eq!(transaction_sigops(&multisig, &NoopStore, false, false), 20);
+
+		// keys count is unknown
+		let bare_multisig = transaction(Builder::default()
+			.push_opcode(Opcode::OP_CHECKMULTISIGVERIFY)
+			.into_script());
+		assert_eq!(transaction_sigops(&bare_multisig, &NoopStore, false, true), 20);
+		assert_eq!(transaction_sigops(&bare_multisig, &NoopStore, false, false), 20);
+	}
 }
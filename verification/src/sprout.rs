@@ -182,9 +182,9 @@ mod tests {
 
 	fn dummy_groth16_key() -> crypto::Groth16VerifyingKey {
 		use crypto::pairing::{CurveAffine, bls12_381::{G1Affine, G2Affine}};
-		use crypto::bellman::groth16::{VerifyingKey, prepare_verifying_key};
+		use crypto::bellman::groth16::VerifyingKey;
 
-		crypto::Groth16VerifyingKey(prepare_verifying_key(&VerifyingKey {
+		VerifyingKey {
 			alpha_g1: G1Affine::zero(),
 			beta_g1: G1Affine::zero(),
 			beta_g2: G2Affine::zero(),
@@ -192,7 +192,7 @@ mod tests {
 			delta_g1: G1Affine::zero(),
 			delta_g2: G2Affine::zero(),
 			ic: vec![],
-		}))
+		}.into()
 	}
 
 	#[test]
@@ -1,23 +1,53 @@
 use chain::BlockHeader;
 use storage::{BlockHeaderProvider, BlockAncestors};
 use primitives::hash::H256;
+use constants::MEDIAN_TIMESTAMP_WINDOW;
 
 /// Returns median timestamp, of given header ancestors.
 /// The header should be later expected to have higher timestamp
 /// than this median timestamp
 pub fn median_timestamp(header: &BlockHeader, store: &BlockHeaderProvider) -> u32 {
-	median_timestamp_inclusive(header.previous_header_hash.clone(), store)
+	median_timestamp_n(header, store, MEDIAN_TIMESTAMP_WINDOW)
 }
 
-/// Returns median timestamp, of given header + its ancestors.
+/// Returns median timestamp, of up to `n` given header ancestors.
+/// If there are less than `n` ancestors (near genesis), median
+/// of all available ancestors is returned.
+pub fn median_timestamp_n(header: &BlockHeader, store: &BlockHeaderProvider, n: usize) -> u32 {
+	median_timestamp_inclusive_n(header.previous_header_hash.clone(), store, n)
+}
+
+/// Returns median timestamp, of given header (at given height) + its ancestors.
 /// The header should be later expected to have higher timestamp
 /// than this median timestamp
-pub fn median_timestamp_inclusive(previous_header_hash: H256, store: &BlockHeaderProvider) -> u32 {
-	let mut timestamps: Vec<_> = BlockAncestors::new(previous_header_hash.clone().into(), store)
-		.take(11)
+///
+/// Headers are read with single range request if the header belongs to the canon chain of the store.
+pub fn median_timestamp_inclusive(header_hash: H256, height: u32, store: &BlockHeaderProvider) -> u32 {
+	let first_height = (height + 1).saturating_sub(MEDIAN_TIMESTAMP_WINDOW as u32);
+	let headers = store.block_headers_range(first_height..height + 1);
+
+	// range request returns canon headers => check that they're ancestors of given header
+	let is_header_ancestors = headers.len() == (height + 1 - first_height) as usize
+		&& headers.last().map(|header| header.hash == header_hash).unwrap_or(false)
+		&& headers.windows(2).all(|pair| pair[1].raw.previous_header_hash == pair[0].hash);
+	if !is_header_ancestors {
+		return median_timestamp_inclusive_n(header_hash, store, MEDIAN_TIMESTAMP_WINDOW);
+	}
+
+	median(headers.into_iter().map(|header| header.raw.time).collect())
+}
+
+/// Returns median timestamp, of given header + up to `n - 1` its ancestors.
+pub fn median_timestamp_inclusive_n(previous_header_hash: H256, store: &BlockHeaderProvider, n: usize) -> u32 {
+	let timestamps: Vec<_> = BlockAncestors::new(previous_header_hash.into(), store)
+		.take(n)
 		.map(|header| header.raw.time)
 		.collect();
 
+	median(timestamps)
+}
+
+fn median(mut timestamps: Vec<u32>) -> u32 {
 	if timestamps.is_empty() {
 		return 0;
 	}
@@ -26,3 +56,103 @@ pub fn median_timestamp_inclusive(previous_header_hash: H256, store: &BlockHeade
 
 	timestamps[timestamps.len() / 2]
 }
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashMap;
+	use primitives::bytes::Bytes;
+	use primitives::hash::H256;
+	use chain::{BlockHeader, IndexedBlockHeader};
+	use storage::{BlockHeaderProvider, BlockRef};
+	use super::{median_timestamp, median_timestamp_n, median_timestamp_inclusive, median_timestamp_inclusive_n};
+
+	#[derive(Default)]
+	struct MemoryBlockHeaderProvider {
+		by_height: Vec<BlockHeader>,
+		by_hash: HashMap<H256, usize>,
+	}
+
+	impl MemoryBlockHeaderProvider {
+		fn with_times(times: &[u32]) -> Self {
+			let mut provider = MemoryBlockHeaderProvider::default();
+			for time in times {
+				let header = BlockHeader {
+					version: 4,
+					previous_header_hash: provider.by_height.last().map(BlockHeader::hash).unwrap_or_default(),
+					merkle_root_hash: Default::default(),
+					final_sapling_root: Default::default(),
+					time: *time,
+					bits: 0.into(),
+					nonce: Default::default(),
+					solution: Default::default(),
+				};
+				provider.by_hash.insert(header.hash(), provider.by_height.len());
+				provider.by_height.push(header);
+			}
+			provider
+		}
+	}
+
+	impl BlockHeaderProvider for MemoryBlockHeaderProvider {
+		fn block_header_bytes(&self, _block_ref: BlockRef) -> Option<Bytes> {
+			unimplemented!()
+		}
+
+		fn block_header(&self, block_ref: BlockRef) -> Option<IndexedBlockHeader> {
+			match block_ref {
+				BlockRef::Hash(ref hash) => self.by_hash.get(hash).map(|h| &self.by_height[*h]).cloned(),
+				BlockRef::Number(height) => self.by_height.get(height as usize).cloned(),
+			}.map(Into::into)
+		}
+	}
+
+	#[test]
+	fn median_timestamp_near_genesis() {
+		let provider = MemoryBlockHeaderProvider::with_times(&[100, 90, 120]);
+		let header = &provider.by_height[2];
+
+		// only 2 ancestors are available: [90, 100]
+		assert_eq!(median_timestamp(header, &provider), 100);
+		assert_eq!(median_timestamp_n(header, &provider, 11), 100);
+		assert_eq!(median_timestamp_n(header, &provider, 1), 90);
+		assert_eq!(median_timestamp_n(header, &provider, 0), 0);
+	}
+
+	#[test]
+	fn median_timestamp_window() {
+		// genesis + 20 blocks, timestamps are not ordered
+		let times: Vec<u32> = (0..21).map(|i| 1000 + ((i * 7) % 21) * 10).collect();
+		let provider = MemoryBlockHeaderProvider::with_times(&times);
+		let header = &provider.by_height[20];
+
+		let median_of_last = |n: usize| {
+			let mut window = times[20 - n..20].to_vec();
+			window.sort();
+			window[n / 2]
+		};
+
+		assert_eq!(median_timestamp(header, &provider), median_of_last(11));
+		assert_eq!(median_timestamp_n(header, &provider, 11), median_of_last(11));
+		assert_eq!(median_timestamp_n(header, &provider, 5), median_of_last(5));
+		assert_eq!(median_timestamp_n(header, &provider, 20), median_of_last(20));
+		// more than available
+		assert_eq!(median_timestamp_n(header, &provider, 100), median_of_last(20));
+	}
+
+	#[test]
+	fn median_timestamp_inclusive_uses_canon_headers() {
+		let times: Vec<u32> = (0..21).map(|i| 1000 + ((i * 7) % 21) * 10).collect();
+		let provider = MemoryBlockHeaderProvider::with_times(&times);
+
+		for height in 0..21 {
+			let hash = provider.by_height[height].hash();
+			assert_eq!(median_timestamp_inclusive(hash.clone(), height as u32, &provider),
+				median_timestamp_inclusive_n(hash, &provider, 11));
+		}
+
+		// when height doesn't match the header, ancestors are used
+		let hash = provider.by_height[15].hash();
+		assert_eq!(median_timestamp_inclusive(hash.clone(), 20, &provider),
+			median_timestamp_inclusive_n(hash, &provider, 11));
+	}
+}
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use chain::hash::H256;
-use storage::{TreeStateProvider, SproutTreeState, SaplingTreeState};
+use storage::{TreeStateProvider, SproutTreeState, SaplingTreeState, EpochTag};
 use error::TransactionError;
 
 
@@ -9,8 +9,14 @@ use error::TransactionError;
 pub struct TreeCache<'a> {
 	persistent: &'a TreeStateProvider ,
 	interstitial: HashMap<H256, SproutTreeState>,
+	/// Inserted roots (along with replaced trees), in insertion order.
+	journal: Vec<(H256, Option<SproutTreeState>)>,
 }
 
+/// Tree cache checkpoint.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TreeCacheCheckpoint(usize);
+
 struct NoPersistentStorage;
 
 const NO_PERSISTENT: &'static NoPersistentStorage = &NoPersistentStorage;
@@ -23,6 +29,10 @@ impl TreeStateProvider for NoPersistentStorage {
 	fn sprout_block_root(&self, _block_hash: &H256) -> Option<H256> { None }
 
 	fn sapling_block_root(&self, _block_hash: &H256) -> Option<H256> { None }
+
+	fn root_at_height(&self, _epoch: EpochTag, _height: u32) -> Option<H256> { None }
+
+	fn height_of_root(&self, _epoch: EpochTag, _root: &H256) -> Option<u32> { None }
 }
 
 impl<'a> TreeCache<'a> {
@@ -30,6 +40,7 @@ impl<'a> TreeCache<'a> {
 		TreeCache {
 			persistent: persistent,
 			interstitial: Default::default(),
+			journal: Default::default(),
 		}
 	}
 
@@ -37,6 +48,7 @@ impl<'a> TreeCache<'a> {
 		TreeCache {
 			persistent: NO_PERSISTENT,
 			interstitial: Default::default(),
+			journal: Default::default(),
 		}
 	}
 
@@ -51,9 +63,49 @@ impl<'a> TreeCache<'a> {
 		tree.append(commitments[0].into()).expect("Unrecoverable error: merkle tree full");
 		tree.append(commitments[1].into()).expect("Unrecoverable error: merkle tree full");
 
-		self.interstitial.insert(tree.root(), tree);
+		let root = tree.root();
+		let replaced = self.interstitial.insert(root, tree);
+		self.journal.push((root, replaced));
 
 		Ok(())
 	}
 
+	/// Remember current state of the cache.
+	pub fn checkpoint(&self) -> TreeCacheCheckpoint {
+		TreeCacheCheckpoint(self.journal.len())
+	}
+
+	/// Revert all trees, appended after the checkpoint has been made.
+	pub fn rollback(&mut self, checkpoint: TreeCacheCheckpoint) {
+		while self.journal.len() > checkpoint.0 {
+			let (root, replaced) = self.journal.pop().expect("journal.len() > checkpoint >= 0; qed");
+			match replaced {
+				Some(tree) => { self.interstitial.insert(root, tree); },
+				None => { self.interstitial.remove(&root); },
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use storage::SproutTreeState;
+	use super::TreeCache;
+
+	#[test]
+	fn rollback_removes_appended_trees() {
+		let mut cache = TreeCache::new_empty();
+		let empty_root = SproutTreeState::new().root();
+		cache.interstitial.insert(empty_root, SproutTreeState::new());
+
+		cache.continue_root(&empty_root, &[[1u8; 32], [2u8; 32]]).unwrap();
+		let checkpoint = cache.checkpoint();
+		let first_len = cache.interstitial.len();
+
+		cache.continue_root(&empty_root, &[[3u8; 32], [4u8; 32]]).unwrap();
+		assert_eq!(cache.interstitial.len(), first_len + 1);
+
+		cache.rollback(checkpoint);
+		assert_eq!(cache.interstitial.len(), first_len);
+	}
 }
\ No newline at end of file
@@ -0,0 +1,95 @@
+use lru_cache::LruCache;
+use parking_lot::Mutex;
+use hash::H256;
+use accept_transaction::AcceptedContext;
+
+/// Default number of transactions, remembered by the `ValidatedTxCache`.
+pub const DEFAULT_VALIDATED_TX_CACHE_SIZE: usize = 65_536;
+
+/// Transactions with already verified scripts and proofs.
+///
+/// Validity of scripts, JoinSplit and Sapling proofs only depends on the transaction itself
+/// (previous outputs are committed to by the txid), on the consensus branch id and on the script
+/// verification flags, which change with height (i.e. BIP65/BIP66 activation). All of them are
+/// parts of the `AcceptedContext`, so the context is a part of the key. When the same transaction
+/// is accepted again under the same context (i.e. during reorganization), these checks could be skipped.
+/// Contextual checks (double spends, maturity, nullifiers, anchors) are never cached.
+pub struct ValidatedTxCache {
+	cache: Mutex<LruCache<(H256, AcceptedContext), ()>>,
+}
+
+impl Default for ValidatedTxCache {
+	fn default() -> Self {
+		ValidatedTxCache::new(DEFAULT_VALIDATED_TX_CACHE_SIZE)
+	}
+}
+
+impl ValidatedTxCache {
+	pub fn new(capacity: usize) -> Self {
+		ValidatedTxCache {
+			cache: Mutex::new(LruCache::new(capacity)),
+		}
+	}
+
+	/// Returns true if scripts and proofs of the transaction have been verified under given context.
+	pub fn contains(&self, hash: &H256, context: &AcceptedContext) -> bool {
+		self.cache.lock().contains_key(&(*hash, *context))
+	}
+
+	/// Remembers that scripts and proofs of the transaction are valid under given context.
+	pub fn insert(&self, hash: H256, context: AcceptedContext) {
+		self.cache.lock().insert((hash, context), ());
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use hash::H256;
+	use accept_transaction::AcceptedContext;
+	use super::ValidatedTxCache;
+
+	fn context(consensus_branch_id: u32) -> AcceptedContext {
+		AcceptedContext {
+			verify_p2sh: true,
+			verify_strictenc: false,
+			verify_locktime: true,
+			verify_checksequence: true,
+			verify_dersig: true,
+			verify_nulldummy: false,
+			verify_sigpushonly: false,
+			verify_cleanstack: false,
+			consensus_branch_id: consensus_branch_id,
+		}
+	}
+
+	#[test]
+	fn validated_tx_cache_works() {
+		let cache = ValidatedTxCache::new(2);
+		cache.insert(H256::from(1), context(0));
+		assert!(cache.contains(&H256::from(1), &context(0)));
+		// other consensus branch
+		assert!(!cache.contains(&H256::from(1), &context(1)));
+
+		// least recently used entry is evicted
+		cache.insert(H256::from(2), context(0));
+		assert!(cache.contains(&H256::from(1), &context(0)));
+		cache.insert(H256::from(3), context(0));
+		assert!(!cache.contains(&H256::from(2), &context(0)));
+		assert!(cache.contains(&H256::from(1), &context(0)));
+		assert!(cache.contains(&H256::from(3), &context(0)));
+	}
+
+	#[test]
+	fn validated_tx_cache_respects_script_flags() {
+		let cache = ValidatedTxCache::new(2);
+		let weak_context = AcceptedContext {
+			verify_locktime: false,
+			verify_dersig: false,
+			..context(0)
+		};
+		cache.insert(H256::from(1), weak_context);
+		assert!(cache.contains(&H256::from(1), &weak_context));
+		// same transaction under stricter script verification flags
+		assert!(!cache.contains(&H256::from(1), &context(0)));
+	}
+}
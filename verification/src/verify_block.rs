@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use chain::IndexedBlock;
 use network::ConsensusParams;
 use sigops::transaction_sigops;
@@ -10,9 +10,10 @@ pub struct BlockVerifier<'a> {
 	pub coinbase: BlockCoinbase<'a>,
 	pub serialized_size: BlockSerializedSize<'a>,
 	pub extra_coinbases: BlockExtraCoinbases<'a>,
+	pub merkle_root: BlockMerkleRoot<'a>,
 	pub transactions_uniqueness: BlockTransactionsUniqueness<'a>,
+	pub nullifiers_uniqueness: BlockNullifiersUniqueness<'a>,
 	pub sigops: BlockSigops<'a>,
-	pub merkle_root: BlockMerkleRoot<'a>,
 }
 
 impl<'a> BlockVerifier<'a> {
@@ -22,9 +23,10 @@ impl<'a> BlockVerifier<'a> {
 			coinbase: BlockCoinbase::new(block),
 			serialized_size: BlockSerializedSize::new(block, consensus),
 			extra_coinbases: BlockExtraCoinbases::new(block),
+			merkle_root: BlockMerkleRoot::new(block),
 			transactions_uniqueness: BlockTransactionsUniqueness::new(block),
+			nullifiers_uniqueness: BlockNullifiersUniqueness::new(block),
 			sigops: BlockSigops::new(block, consensus),
-			merkle_root: BlockMerkleRoot::new(block),
 		}
 	}
 
@@ -33,9 +35,10 @@ impl<'a> BlockVerifier<'a> {
 		self.coinbase.check()?;
 		self.serialized_size.check()?;
 		self.extra_coinbases.check()?;
+		self.merkle_root.check()?;
 		self.transactions_uniqueness.check()?;
+		self.nullifiers_uniqueness.check()?;
 		self.sigops.check()?;
-		self.merkle_root.check()?;
 		Ok(())
 	}
 }
@@ -147,6 +150,50 @@ impl<'a> BlockTransactionsUniqueness<'a> {
 	}
 }
 
+/// Check that no nullifier is revealed by several transactions of the block.
+///
+/// Duplicate nullifiers within single transaction are checked by transaction verifier.
+pub struct BlockNullifiersUniqueness<'a> {
+	block: &'a IndexedBlock,
+}
+
+impl<'a> BlockNullifiersUniqueness<'a> {
+	fn new(block: &'a IndexedBlock) -> Self {
+		BlockNullifiersUniqueness {
+			block: block,
+		}
+	}
+
+	fn check(&self) -> Result<(), Error> {
+		fn insert<'b>(nullifiers: &mut HashMap<&'b [u8; 32], usize>, nullifier: &'b [u8; 32], tx_idx: usize) -> Result<(), Error> {
+			match nullifiers.insert(nullifier, tx_idx) {
+				Some(old_tx_idx) if old_tx_idx != tx_idx => Err(Error::DuplicateNullifierInBlock(old_tx_idx, tx_idx)),
+				_ => Ok(()),
+			}
+		}
+
+		let mut sprout_nullifiers = HashMap::new();
+		let mut sapling_nullifiers = HashMap::new();
+		for (tx_idx, tx) in self.block.transactions.iter().enumerate() {
+			if let Some(ref join_split) = tx.raw.join_split {
+				for description in &join_split.descriptions {
+					for nullifier in &description.nullifiers {
+						insert(&mut sprout_nullifiers, nullifier, tx_idx)?;
+					}
+				}
+			}
+
+			if let Some(ref sapling) = tx.raw.sapling {
+				for spend in &sapling.spends {
+					insert(&mut sapling_nullifiers, &spend.nullifier, tx_idx)?;
+				}
+			}
+		}
+
+		Ok(())
+	}
+}
+
 pub struct BlockSigops<'a> {
 	block: &'a IndexedBlock,
 	max_sigops: usize,
@@ -163,7 +210,7 @@ impl<'a> BlockSigops<'a> {
 	fn check(&self) -> Result<(), Error> {
 		// We cannot know if bip16 is enabled at this point so we disable it.
 		let sigops = self.block.transactions.iter()
-			.map(|tx| transaction_sigops(&tx.raw, &NoopStore, false))
+			.map(|tx| transaction_sigops(&tx.raw, &NoopStore, false, false))
 			.sum::<usize>();
 
 		if sigops > self.max_sigops {
@@ -186,10 +233,101 @@ impl<'a> BlockMerkleRoot<'a> {
 	}
 
 	fn check(&self) -> Result<(), Error> {
-		if self.block.merkle_root() == self.block.header.raw.merkle_root_hash {
-			Ok(())
-		} else {
+		let (matches, mutated) = self.block.merkle_root_matches();
+		if mutated {
+			// mutated block may have the same hash as the valid block => it must be checked before
+			// transactions uniqueness, which would fail with less specific error
+			Err(Error::MerkleMutation)
+		} else if !matches {
 			Err(Error::MerkleRoot)
+		} else {
+			Ok(())
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	extern crate test_data;
+
+	use chain::{IndexedBlock, Transaction, JoinSplit, JoinSplitDescription, Sapling, SaplingSpendDescription};
+	use network::{Network, ConsensusParams};
+	use error::Error;
+	use super::{BlockVerifier, BlockMerkleRoot, BlockNullifiersUniqueness};
+
+	#[test]
+	fn block_merkle_root_works() {
+		let consensus = ConsensusParams::new(Network::Unitest);
+		let block: IndexedBlock = test_data::block_builder()
+			.transaction().coinbase().output().value(10).build().build()
+			.transaction().output().value(20).build().build()
+			.transaction().output().value(30).build().build()
+			.merkled_header().build()
+			.build()
+			.into();
+		assert_eq!(BlockMerkleRoot::new(&block).check(), Ok(()));
+		assert_eq!(BlockVerifier::new(&block, &consensus).check(), Ok(()));
+
+		// duplicating last transaction leaves merkle root unchanged
+		let mut mutated = block.clone();
+		let last = mutated.transactions[2].clone();
+		mutated.transactions.push(last);
+		assert_eq!(mutated.merkle_root(), block.header.raw.merkle_root_hash);
+		assert_eq!(BlockMerkleRoot::new(&mutated).check(), Err(Error::MerkleMutation));
+		assert_eq!(BlockVerifier::new(&mutated, &consensus).check(), Err(Error::MerkleMutation));
+	}
+
+	#[test]
+	fn block_nullifiers_uniqueness_works() {
+		let sapling = |nullifier| Sapling {
+			spends: vec![SaplingSpendDescription { nullifier: nullifier, ..Default::default() }],
+			..Default::default()
+		};
+		let join_split = |nullifiers| JoinSplit {
+			descriptions: vec![JoinSplitDescription { nullifiers: nullifiers, ..Default::default() }],
+			..Default::default()
+		};
+		let block = |transactions: Vec<Transaction>| -> IndexedBlock {
+			test_data::block_builder()
+				.with_transactions(transactions)
+				.header().build()
+				.build()
+				.into()
+		};
+
+		// different nullifiers
+		let block1 = block(vec![
+			test_data::TransactionBuilder::with_sapling(sapling([1; 32])).into(),
+			test_data::TransactionBuilder::with_sapling(sapling([2; 32])).into(),
+		]);
+		assert_eq!(BlockNullifiersUniqueness::new(&block1).check(), Ok(()));
+
+		// two transactions reveal the same sapling nullifier
+		let block2 = block(vec![
+			test_data::TransactionBuilder::with_output(1).into(),
+			test_data::TransactionBuilder::with_sapling(sapling([1; 32])).into(),
+			test_data::TransactionBuilder::with_sapling(sapling([1; 32])).add_output(2).into(),
+		]);
+		assert_eq!(BlockNullifiersUniqueness::new(&block2).check(), Err(Error::DuplicateNullifierInBlock(1, 2)));
+
+		// two transactions reveal the same sprout nullifier
+		let block3 = block(vec![
+			test_data::TransactionBuilder::with_join_split(join_split([[1; 32], [2; 32]])).into(),
+			test_data::TransactionBuilder::with_join_split(join_split([[3; 32], [2; 32]])).into(),
+		]);
+		assert_eq!(BlockNullifiersUniqueness::new(&block3).check(), Err(Error::DuplicateNullifierInBlock(0, 1)));
+
+		// sprout and sapling nullifiers are from different sets
+		let block4 = block(vec![
+			test_data::TransactionBuilder::with_join_split(join_split([[1; 32], [2; 32]])).into(),
+			test_data::TransactionBuilder::with_sapling(sapling([1; 32])).into(),
+		]);
+		assert_eq!(BlockNullifiersUniqueness::new(&block4).check(), Ok(()));
+
+		// duplicates within single transaction are reported by the transaction verifier
+		let block5 = block(vec![
+			test_data::TransactionBuilder::with_join_split(join_split([[1; 32], [1; 32]])).into(),
+		]);
+		assert_eq!(BlockNullifiersUniqueness::new(&block5).check(), Ok(()));
+	}
+}
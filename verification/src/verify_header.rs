@@ -1,10 +1,10 @@
+use rayon::prelude::{IntoParallelRefIterator, IndexedParallelIterator, ParallelIterator};
 use primitives::compact::Compact;
 use chain::IndexedBlockHeader;
-use equihash::verify_block_equihash_solution;
+use equihash::{equihash_solution_size, verify_block_equihash_solution};
 use network::ConsensusParams;
-use work::is_valid_proof_of_work;
+use work::check_proof_of_work;
 use error::Error;
-use constants::BLOCK_MAX_FUTURE;
 
 pub struct HeaderVerifier<'a> {
 	pub version: HeaderVersion<'a>,
@@ -19,7 +19,7 @@ impl<'a> HeaderVerifier<'a> {
 			version: HeaderVersion::new(header, consensus),
 			proof_of_work: HeaderProofOfWork::new(header, consensus),
 			equihash: HeaderEquihashSolution::new(header, consensus),
-			timestamp: HeaderTimestamp::new(header, current_time, BLOCK_MAX_FUTURE as u32),
+			timestamp: HeaderTimestamp::new(header, consensus, current_time),
 		}
 	}
 
@@ -32,6 +32,23 @@ impl<'a> HeaderVerifier<'a> {
 	}
 }
 
+/// Checks context-free parts (version, equihash solution and proof of work) of the headers
+/// using rayon thread pool. Index of the first invalid header is returned along with the error.
+///
+/// Contextual checks (timestamp, required work, ...) are not performed.
+pub fn verify_headers_batch(headers: &[IndexedBlockHeader], consensus: &ConsensusParams) -> Result<(), (usize, Error)> {
+	headers.par_iter()
+		.enumerate()
+		.map(|(index, header)| {
+			HeaderVersion::new(header, consensus).check()
+				.and_then(|_| HeaderEquihashSolution::new(header, consensus).check())
+				.and_then(|_| HeaderProofOfWork::new(header, consensus).check())
+				.map_err(|err| (index, err))
+		})
+		.find_first(Result::is_err)
+		.unwrap_or(Ok(()))
+}
+
 pub struct HeaderProofOfWork<'a> {
 	header: &'a IndexedBlockHeader,
 	max_work_bits: Compact,
@@ -46,11 +63,7 @@ impl<'a> HeaderProofOfWork<'a> {
 	}
 
 	fn check(&self) -> Result<(), Error> {
-		if is_valid_proof_of_work(self.max_work_bits, self.header.raw.bits, &self.header.hash) {
-			Ok(())
-		} else {
-			Err(Error::Pow)
-		}
+		check_proof_of_work(self.max_work_bits, self.header.raw.bits, &self.header.hash)
 	}
 }
 
@@ -61,16 +74,16 @@ pub struct HeaderTimestamp<'a> {
 }
 
 impl<'a> HeaderTimestamp<'a> {
-	fn new(header: &'a IndexedBlockHeader, current_time: u32, max_future: u32) -> Self {
+	fn new(header: &'a IndexedBlockHeader, consensus: &ConsensusParams, current_time: u32) -> Self {
 		HeaderTimestamp {
 			header: header,
 			current_time: current_time,
-			max_future: max_future,
+			max_future: consensus.max_future_block_time(),
 		}
 	}
 
 	fn check(&self) -> Result<(), Error> {
-		if self.header.raw.time > self.current_time + self.max_future {
+		if self.header.raw.time > self.current_time.saturating_add(self.max_future) {
 			Err(Error::FuturisticTimestamp)
 		} else {
 			Ok(())
@@ -115,6 +128,16 @@ impl<'a> HeaderEquihashSolution<'a> {
 
 	fn check(&self) -> Result<(), Error> {
 		if let Some(equihash_params) = self.equihash_params {
+			// check solution length before doing expensive verification
+			let expected_solution_len = equihash_solution_size(equihash_params);
+			let actual_solution_len = self.header.raw.solution.as_ref().len();
+			if actual_solution_len != expected_solution_len {
+				return Err(Error::InvalidEquihashSolutionLength {
+					expected: expected_solution_len,
+					actual: actual_solution_len,
+				});
+			}
+
 			if !verify_block_equihash_solution(equihash_params, &self.header.raw) {
 				return Err(Error::InvalidEquihashSolution);
 			}
@@ -128,9 +151,10 @@ impl<'a> HeaderEquihashSolution<'a> {
 mod tests {
 	extern crate test_data;
 
+	use chain::IndexedBlockHeader;
 	use network::{Network, ConsensusParams};
 	use error::Error;
-	use super::HeaderVersion;
+	use super::{HeaderVersion, HeaderEquihashSolution, HeaderProofOfWork, HeaderTimestamp, verify_headers_batch};
 
 	#[test]
 	fn header_version_works() {
@@ -143,4 +167,94 @@ mod tests {
 		assert_eq!(HeaderVersion::new(&test_data::block_builder().header().version(consensus.min_block_version() + 1)
 			.build().build().block_header.into(), &consensus).check(), Ok(()));
 	}
+
+	#[test]
+	fn header_timestamp_works() {
+		let consensus = ConsensusParams::new(Network::Mainnet);
+		let now = 1_500_000_000;
+		let header_at = |time: u32| -> IndexedBlockHeader {
+			test_data::block_builder().header().time(time).build().build().block_header.into()
+		};
+
+		assert_eq!(HeaderTimestamp::new(&header_at(now + 60 * 60), &consensus, now).check(), Ok(()));
+		assert_eq!(HeaderTimestamp::new(&header_at(now + 2 * 60 * 60), &consensus, now).check(), Ok(()));
+		assert_eq!(HeaderTimestamp::new(&header_at(now + 3 * 60 * 60), &consensus, now).check(),
+			Err(Error::FuturisticTimestamp));
+	}
+
+	#[test]
+	fn header_equihash_solution_length_works() {
+		let consensus = ConsensusParams::new(Network::Mainnet);
+
+		// when solution is valid
+		let header = test_data::block_h1().block_header.into();
+		assert_eq!(HeaderEquihashSolution::new(&header, &consensus).check(), Ok(()));
+
+		// when solution has valid length, but is invalid
+		let header = test_data::block_builder().header().build().build().block_header.into();
+		assert_eq!(HeaderEquihashSolution::new(&header, &consensus).check(), Err(Error::InvalidEquihashSolution));
+
+		// when solution is truncated
+		let mut header = test_data::block_h1().block_header;
+		let truncated_solution = header.solution.as_ref()[..1343].to_vec();
+		header.solution = truncated_solution.into();
+		assert_eq!(HeaderEquihashSolution::new(&header.into(), &consensus).check(), Err(Error::InvalidEquihashSolutionLength {
+			expected: 1344,
+			actual: 1343,
+		}));
+	}
+
+	#[test]
+	fn header_proof_of_work_works() {
+		let consensus = ConsensusParams::new(Network::Mainnet);
+		let header: IndexedBlockHeader = test_data::block_h1().block_header.into();
+
+		// when hash is below target && target is below network maximum
+		assert_eq!(HeaderProofOfWork::new(&header, &consensus).check(), Ok(()));
+
+		// when target is above network maximum
+		let mut raw = header.raw.clone();
+		raw.bits = 0x2007ffff.into();
+		assert_eq!(HeaderProofOfWork::new(&IndexedBlockHeader::new(header.hash.clone(), raw), &consensus).check(),
+			Err(Error::DifficultyTooLow));
+
+		// when target overflows
+		let mut raw = header.raw.clone();
+		raw.bits = 0x23000001.into();
+		assert_eq!(HeaderProofOfWork::new(&IndexedBlockHeader::new(header.hash.clone(), raw), &consensus).check(),
+			Err(Error::DifficultyTooLow));
+
+		// when hash is above target
+		let mut raw = header.raw.clone();
+		raw.bits = 0x1d00ffff.into();
+		assert_eq!(HeaderProofOfWork::new(&IndexedBlockHeader::new(header.hash.clone(), raw), &consensus).check(),
+			Err(Error::Pow));
+	}
+
+	#[test]
+	fn verify_headers_batch_works() {
+		let consensus = ConsensusParams::new(Network::Mainnet);
+		let mut headers: Vec<IndexedBlockHeader> = vec![
+			test_data::block_h0().block_header.into(),
+			test_data::block_h1().block_header.into(),
+			test_data::block_h2().block_header.into(),
+			test_data::block_h3().block_header.into(),
+			test_data::block_h9().block_header.into(),
+		];
+		assert_eq!(verify_headers_batch(&headers, &consensus), Ok(()));
+
+		// 5th header has invalid solution
+		let mut raw = headers[4].raw.clone();
+		let mut solution = raw.solution.as_ref().to_vec();
+		solution[0] ^= 1;
+		raw.solution = solution.into();
+		headers[4] = IndexedBlockHeader::new(headers[4].hash.clone(), raw);
+
+		// 6th header has invalid version
+		let mut raw = headers[1].raw.clone();
+		raw.version = consensus.min_block_version() - 1;
+		headers.push(IndexedBlockHeader::new(headers[1].hash.clone(), raw));
+
+		assert_eq!(verify_headers_batch(&headers, &consensus), Err((4, Error::InvalidEquihashSolution)));
+	}
 }
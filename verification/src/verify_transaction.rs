@@ -1,11 +1,11 @@
 use std::{collections::HashMap, ops};
 use ser::Serializable;
-use chain::{IndexedTransaction, BTC_TX_VERSION, OVERWINTER_TX_VERSION,
-	OVERWINTER_TX_VERSION_GROUP_ID, SAPLING_TX_VERSION_GROUP_ID};
+use chain::{IndexedTransaction, BTC_TX_VERSION, OVERWINTER_TX_VERSION};
 use network::{ConsensusParams};
 use storage::NoopStore;
 use sigops::transaction_sigops;
 use error::TransactionError;
+use primitives::hash::DisplayReversed;
 use constants::{MIN_COINBASE_SIZE, MAX_COINBASE_SIZE};
 
 pub struct TransactionVerifier<'a> {
@@ -27,7 +27,7 @@ pub struct TransactionVerifier<'a> {
 
 impl<'a> TransactionVerifier<'a> {
 	pub fn new(transaction: &'a IndexedTransaction, consensus: &'a ConsensusParams) -> Self {
-		trace!(target: "verification", "Tx pre-verification {}", transaction.hash.to_reversed_str());
+		trace!(target: "verification", "Tx pre-verification {}", DisplayReversed(&transaction.hash));
 		TransactionVerifier {
 			version: TransactionVersion::new(transaction),
 			expiry: TransactionExpiry::new(transaction, consensus),
@@ -80,11 +80,20 @@ pub struct MemoryPoolTransactionVerifier<'a> {
 	pub duplicate_inputs: TransactionDuplicateInputs<'a>,
 	pub duplicate_join_split_nullifiers: TransactionDuplicateJoinSplitNullifiers<'a>,
 	pub duplicate_sapling_nullifiers: TransactionDuplicateSaplingNullifiers<'a>,
+	pub input_script_size: TransactionInputScriptSize<'a>,
+	pub ancestors: TransactionAncestors,
+	pub replacement: TransactionReplacement<'a>,
 }
 
 impl<'a> MemoryPoolTransactionVerifier<'a> {
-	pub fn new(transaction: &'a IndexedTransaction, consensus: &'a ConsensusParams) -> Self {
-		trace!(target: "verification", "Mempool-Tx pre-verification {}", transaction.hash.to_reversed_str());
+	pub fn new(
+		transaction: &'a IndexedTransaction,
+		consensus: &'a ConsensusParams,
+		ancestors: AncestorsSummary,
+		conflicts: ConflictsSummary,
+		fee: u64,
+	) -> Self {
+		trace!(target: "verification", "Mempool-Tx pre-verification {}", DisplayReversed(&transaction.hash));
 		MemoryPoolTransactionVerifier {
 			version: TransactionVersion::new(transaction),
 			expiry: TransactionExpiry::new(transaction, consensus),
@@ -100,6 +109,9 @@ impl<'a> MemoryPoolTransactionVerifier<'a> {
 			duplicate_inputs: TransactionDuplicateInputs::new(transaction),
 			duplicate_join_split_nullifiers: TransactionDuplicateJoinSplitNullifiers::new(transaction),
 			duplicate_sapling_nullifiers: TransactionDuplicateSaplingNullifiers::new(transaction),
+			input_script_size: TransactionInputScriptSize::new(transaction, consensus.max_script_sig_size),
+			ancestors: TransactionAncestors::new(ancestors, consensus.max_ancestors_count, consensus.max_ancestors_size),
+			replacement: TransactionReplacement::new(transaction, conflicts, fee),
 		}
 	}
 
@@ -109,6 +121,7 @@ impl<'a> MemoryPoolTransactionVerifier<'a> {
 		self.empty.check()?;
 		self.null_non_coinbase.check()?;
 		self.is_coinbase.check()?;
+		self.input_script_size.check()?;
 		self.size.check()?;
 		self.sigops.check()?;
 		self.sapling.check()?;
@@ -118,6 +131,8 @@ impl<'a> MemoryPoolTransactionVerifier<'a> {
 		self.duplicate_inputs.check()?;
 		self.duplicate_join_split_nullifiers.check()?;
 		self.duplicate_sapling_nullifiers.check()?;
+		self.ancestors.check()?;
+		self.replacement.check()?;
 		Ok(())
 	}
 }
@@ -223,6 +238,115 @@ impl<'a> TransactionMemoryPoolCoinbase<'a> {
 	}
 }
 
+/// Memory pool policy: scriptSig of every non-coinbase input must not exceed max size.
+pub struct TransactionInputScriptSize<'a> {
+	transaction: &'a IndexedTransaction,
+	max_script_sig_size: usize,
+}
+
+impl<'a> TransactionInputScriptSize<'a> {
+	fn new(transaction: &'a IndexedTransaction, max_script_sig_size: usize) -> Self {
+		TransactionInputScriptSize {
+			transaction: transaction,
+			max_script_sig_size: max_script_sig_size,
+		}
+	}
+
+	fn check(&self) -> Result<(), TransactionError> {
+		let oversized_input = self.transaction.raw.inputs.iter()
+			.position(|input| !input.is_coinbase_like() && input.script_sig.len() > self.max_script_sig_size);
+		match oversized_input {
+			Some(index) => Err(TransactionError::InputScriptTooLarge(index)),
+			None => Ok(()),
+		}
+	}
+}
+
+/// Summary of in-pool (unconfirmed) ancestors of the memory pool transaction.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct AncestorsSummary {
+	/// Number of in-pool ancestors.
+	pub count: usize,
+	/// Total serialized size of in-pool ancestors.
+	pub size: usize,
+}
+
+/// Memory pool policy: number and total size of in-pool ancestors must not exceed limits.
+pub struct TransactionAncestors {
+	ancestors: AncestorsSummary,
+	max_count: usize,
+	max_size: usize,
+}
+
+impl TransactionAncestors {
+	fn new(ancestors: AncestorsSummary, max_count: usize, max_size: usize) -> Self {
+		TransactionAncestors {
+			ancestors: ancestors,
+			max_count: max_count,
+			max_size: max_size,
+		}
+	}
+
+	fn check(&self) -> Result<(), TransactionError> {
+		if self.ancestors.count > self.max_count {
+			return Err(TransactionError::TooManyAncestors);
+		}
+
+		if self.ancestors.size > self.max_size {
+			return Err(TransactionError::AncestorSizeTooLarge);
+		}
+
+		Ok(())
+	}
+}
+
+/// Summary of in-pool transactions, which are spending the same outputs as the memory pool transaction.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ConflictsSummary {
+	/// Number of conflicting in-pool transactions.
+	pub count: usize,
+	/// Total fee of conflicting in-pool transactions.
+	pub fee: u64,
+	/// Total serialized size of conflicting in-pool transactions.
+	pub size: usize,
+}
+
+/// Memory pool policy: transaction, replacing in-pool transactions, must pay strictly
+/// higher absolute fee and fee rate than all of the replaced transactions.
+pub struct TransactionReplacement<'a> {
+	transaction: &'a IndexedTransaction,
+	conflicts: ConflictsSummary,
+	fee: u64,
+}
+
+impl<'a> TransactionReplacement<'a> {
+	fn new(transaction: &'a IndexedTransaction, conflicts: ConflictsSummary, fee: u64) -> Self {
+		TransactionReplacement {
+			transaction: transaction,
+			conflicts: conflicts,
+			fee: fee,
+		}
+	}
+
+	fn check(&self) -> Result<(), TransactionError> {
+		if self.conflicts.count == 0 {
+			return Ok(());
+		}
+
+		if self.fee <= self.conflicts.fee {
+			return Err(TransactionError::ReplacementUnderpaid);
+		}
+
+		// fee / size > conflicts.fee / conflicts.size
+		let size = self.transaction.serialized_size();
+		if self.fee as u128 * self.conflicts.size as u128 <= self.conflicts.fee as u128 * size as u128 {
+			return Err(TransactionError::ReplacementUnderpaid);
+		}
+
+		Ok(())
+	}
+}
+
 /// The encoded size of the transaction MUST be less than or equal to EVER possible max limit.
 pub struct TransactionAbsoluteSize<'a> {
 	transaction: &'a IndexedTransaction,
@@ -238,7 +362,7 @@ impl<'a> TransactionAbsoluteSize<'a> {
 	}
 
 	fn check(&self) -> Result<(), TransactionError> {
-		let size = self.transaction.raw.serialized_size();
+		let size = self.transaction.serialized_size();
 		if size > self.absoute_max_size {
 			Err(TransactionError::MaxSize)
 		} else {
@@ -261,7 +385,7 @@ impl<'a> TransactionSigops<'a> {
 	}
 
 	fn check(&self) -> Result<(), TransactionError> {
-		let sigops = transaction_sigops(&self.transaction.raw, &NoopStore, false);
+		let sigops = transaction_sigops(&self.transaction.raw, &NoopStore, false, false);
 		if sigops > self.max_sigops {
 			Err(TransactionError::MaxSigops)
 		} else {
@@ -294,8 +418,8 @@ impl<'a> TransactionVersion<'a> {
 			return Err(TransactionError::InvalidVersion);
 		}
 
-		let is_overwinter_group = self.transaction.raw.version_group_id == OVERWINTER_TX_VERSION_GROUP_ID;
-		let is_sapling_group = self.transaction.raw.version_group_id == SAPLING_TX_VERSION_GROUP_ID;
+		let is_overwinter_group = self.transaction.raw.has_overwinter_version_group();
+		let is_sapling_group = self.transaction.raw.has_sapling_version_group();
 		if !is_overwinter_group && !is_sapling_group {
 			return Err(TransactionError::InvalidVersionGroup);
 		}
@@ -410,35 +534,16 @@ impl<'a> TransactionOutputValueOverflow<'a> {
 	}
 
 	fn check(&self) -> Result<(), TransactionError> {
-		let mut total_output = 0i64;
-
 		// each output should be less than max_value
-		// the sum of all outputs should be less than max value
-		for output in &self.transaction.raw.outputs {
-			if output.value > self.max_value as u64 {
-				return Err(TransactionError::OutputValueOverflow)
-			}
-
-			total_output = match total_output.checked_add(output.value as i64) {
-				Some(total_output) if total_output <= self.max_value => total_output,
-				_ => return Err(TransactionError::OutputValueOverflow),
-			};
+		if self.transaction.raw.outputs.iter().any(|output| output.value > self.max_value as u64) {
+			return Err(TransactionError::OutputValueOverflow)
 		}
 
-		if let Some(ref sapling) = self.transaction.raw.sapling {
-			// check that sapling amount is within limits
-			if sapling.balancing_value < -self.max_value || sapling.balancing_value > self.max_value {
-				return Err(TransactionError::OutputValueOverflow);
-			}
-
-			// negative sapling amount takes value from transparent pool
-			if sapling.balancing_value < 0 {
-				total_output = match total_output.checked_add(-sapling.balancing_value) {
-					Some(total_output) if total_output <= self.max_value => total_output,
-					_ => return Err(TransactionError::OutputValueOverflow),
-				};
-			}
-		}
+		// the sum of all outputs should be less than max value
+		let mut total_output = match self.transaction.raw.checked_total_spends() {
+			Some(total_output) if total_output <= self.max_value as u64 => total_output as i64,
+			_ => return Err(TransactionError::OutputValueOverflow),
+		};
 
 		if let Some(ref join_split) = self.transaction.raw.join_split {
 			for desc in &join_split.descriptions {
@@ -449,14 +554,34 @@ impl<'a> TransactionOutputValueOverflow<'a> {
 				if desc.value_pub_new > self.max_value as u64 {
 					return Err(TransactionError::OutputValueOverflow);
 				}
-
-				total_output = match total_output.checked_add(desc.value_pub_old as i64) {
-					Some(total_output) if total_output <= self.max_value => total_output,
-					_ => return Err(TransactionError::OutputValueOverflow),
-				};
 			}
 		}
 
+		let balance = self.transaction.raw.shielded_value_balance()?;
+
+		// check that sapling amount is within limits
+		if balance.sapling_net < -self.max_value || balance.sapling_net > self.max_value {
+			return Err(TransactionError::OutputValueOverflow);
+		}
+
+		// negative sapling amount takes value from transparent pool
+		if balance.sapling_net < 0 {
+			total_output = match total_output.checked_add(-balance.sapling_net) {
+				Some(total_output) if total_output <= self.max_value => total_output,
+				_ => return Err(TransactionError::OutputValueOverflow),
+			};
+		}
+
+		// value_pub_old of all descriptions takes value from transparent pool
+		if balance.sprout_in > self.max_value as u64 {
+			return Err(TransactionError::OutputValueOverflow);
+		}
+
+		match total_output.checked_add(balance.sprout_in as i64) {
+			Some(total_output) if total_output <= self.max_value => (),
+			_ => return Err(TransactionError::OutputValueOverflow),
+		}
+
 		Ok(())
 	}
 }
@@ -476,33 +601,28 @@ impl<'a> TransactionInputValueOverflow<'a> {
 	}
 
 	fn check(&self) -> Result<(), TransactionError> {
-		let mut total_input = 0u64;
-
 		// inputs values are unknown at verification stage
 
 		// every value_pub_new should be within money range
 		// their sum should be within money range
 		if let Some(ref join_split) = self.transaction.raw.join_split {
-			for desc in &join_split.descriptions {
-				if desc.value_pub_new > self.max_value {
-					return Err(TransactionError::InputValueOverflow);
-				}
-
-				total_input = match total_input.checked_add(desc.value_pub_new) {
-					Some(total_input) if total_input <= self.max_value => total_input,
-					_ => return Err(TransactionError::InputValueOverflow),
-				};
+			if join_split.descriptions.iter().any(|desc| desc.value_pub_new > self.max_value) {
+				return Err(TransactionError::InputValueOverflow);
 			}
 		}
 
-		if let Some(ref sapling) = self.transaction.raw.sapling {
-			// positive sapling amount adds value to the transparent pool
-			if sapling.balancing_value > 0 {
-				match total_input.checked_add(sapling.balancing_value as u64) {
-					Some(total_input) if total_input <= self.max_value => (),
-					_ => return Err(TransactionError::InputValueOverflow),
-				};
-			}
+		let balance = self.transaction.raw.shielded_value_balance()?;
+		let total_input = balance.sprout_out;
+		if total_input > self.max_value {
+			return Err(TransactionError::InputValueOverflow);
+		}
+
+		// positive sapling amount adds value to the transparent pool
+		if balance.sapling_net > 0 {
+			match total_input.checked_add(balance.sapling_net as u64) {
+				Some(total_input) if total_input <= self.max_value => (),
+				_ => return Err(TransactionError::InputValueOverflow),
+			};
 		}
 
 		Ok(())
@@ -524,6 +644,8 @@ impl<'a> TransactionExpiry<'a> {
 	}
 
 	fn check(&self) -> Result<(), TransactionError> {
+		// up to Sapling, the same limit applies to coinbase transactions: they're never expired, but
+		// consensus doesn't require their expiry height to be zero
 		if self.transaction.raw.overwintered && self.transaction.raw.expiry_height >= self.height_threshold {
 			return Err(TransactionError::ExpiryHeightTooHigh);
 		}
@@ -545,6 +667,7 @@ impl<'a> TransactionDuplicateInputs<'a> {
 	}
 
 	fn check(&self) -> Result<(), TransactionError> {
+		// inputs are visited in order, so the reported pair doesn't depend on the map iteration order
 		let mut inputs = HashMap::new();
 		for (idx, input) in self.transaction.raw.inputs.iter().enumerate() {
 			if let Some(old_idx) = inputs.insert(&input.previous_output, idx) {
@@ -616,13 +739,17 @@ mod tests {
 	extern crate test_data;
 
 	use chain::{BTC_TX_VERSION, OVERWINTER_TX_VERSION, OVERWINTER_TX_VERSION_GROUP_ID,
-		SAPLING_TX_VERSION_GROUP_ID, Sapling, JoinSplit, JoinSplitDescription};
+		SAPLING_TX_VERSION_GROUP_ID, Sapling, JoinSplit, JoinSplitDescription, Transaction, TransactionInput,
+		IndexedTransaction};
+	use primitives::bytes::Bytes;
+	use ser::Serializable;
 	use network::{Network, ConsensusParams};
 	use error::TransactionError;
 	use super::{TransactionEmpty, TransactionVersion, TransactionNonTransparentCoinbase,
 		TransactionOutputValueOverflow, TransactionExpiry, TransactionSapling, TransactionJoinSplit,
 		TransactionInputValueOverflow, TransactionDuplicateInputs, TransactionDuplicateJoinSplitNullifiers,
-		TransactionDuplicateSaplingNullifiers};
+		TransactionDuplicateSaplingNullifiers, TransactionInputScriptSize, TransactionAncestors, AncestorsSummary,
+		TransactionReplacement, ConflictsSummary};
 
 	#[test]
 	fn transaction_empty_works() {
@@ -841,6 +968,22 @@ mod tests {
 		assert_eq!(TransactionExpiry::new(&test_data::TransactionBuilder::overwintered()
 			.set_expiry_height(consensus.transaction_expiry_height_threshold()).into(), &consensus).check(),
 			Err(TransactionError::ExpiryHeightTooHigh));
+
+		// coinbase may have any expiry height below the threshold (there are no post-Sapling rules yet)
+		assert_eq!(TransactionExpiry::new(&test_data::TransactionBuilder::coinbase()
+			.set_overwintered(true)
+			.set_expiry_height(0).into(), &consensus).check(),
+			Ok(()));
+
+		assert_eq!(TransactionExpiry::new(&test_data::TransactionBuilder::coinbase()
+			.set_overwintered(true)
+			.set_expiry_height(1).into(), &consensus).check(),
+			Ok(()));
+
+		assert_eq!(TransactionExpiry::new(&test_data::TransactionBuilder::coinbase()
+			.set_overwintered(true)
+			.set_expiry_height(consensus.transaction_expiry_height_threshold()).into(), &consensus).check(),
+			Err(TransactionError::ExpiryHeightTooHigh));
 	}
 
 	#[test]
@@ -907,6 +1050,15 @@ mod tests {
 
 		assert_eq!(TransactionDuplicateInputs::new(&test_data::TransactionBuilder::with_default_input(0)
 			.add_default_input(0).into()).check(), Err(TransactionError::DuplicateInput(0, 1)));
+
+		// the first duplicate pair is reported
+		assert_eq!(TransactionDuplicateInputs::new(&test_data::TransactionBuilder::with_default_input(0)
+			.add_default_input(0)
+			.add_default_input(0).into()).check(), Err(TransactionError::DuplicateInput(0, 1)));
+		assert_eq!(TransactionDuplicateInputs::new(&test_data::TransactionBuilder::with_default_input(0)
+			.add_default_input(1)
+			.add_default_input(1)
+			.add_default_input(0).into()).check(), Err(TransactionError::DuplicateInput(1, 2)));
 	}
 
 	#[test]
@@ -937,4 +1089,56 @@ mod tests {
 			..Default::default()
 		}).into()).check(), Err(TransactionError::DuplicateSaplingSpendNullifier(0, 1)));
 	}
+
+	#[test]
+	fn transaction_input_script_size_works() {
+		let max_script_sig_size = ConsensusParams::new(Network::Mainnet).max_script_sig_size;
+		let transaction_with_script_sig = |script_sig_len: usize| {
+			let mut tx: Transaction = test_data::TransactionBuilder::with_default_input(0)
+				.add_default_input(1)
+				.add_output(0)
+				.into();
+			tx.inputs[1].script_sig = Bytes::new_with_len(script_sig_len);
+			tx
+		};
+
+		assert_eq!(TransactionInputScriptSize::new(&transaction_with_script_sig(max_script_sig_size).into(),
+			max_script_sig_size).check(), Ok(()));
+		assert_eq!(TransactionInputScriptSize::new(&transaction_with_script_sig(max_script_sig_size + 1).into(),
+			max_script_sig_size).check(), Err(TransactionError::InputScriptTooLarge(1)));
+
+		// coinbase-like inputs are checked by other verifiers
+		let mut tx = transaction_with_script_sig(0);
+		tx.inputs[0] = TransactionInput::coinbase(Bytes::new_with_len(max_script_sig_size + 1));
+		assert!(tx.inputs[0].is_coinbase_like());
+		assert_eq!(TransactionInputScriptSize::new(&tx.into(), max_script_sig_size).check(), Ok(()));
+	}
+
+	#[test]
+	fn transaction_ancestors_works() {
+		// chain of 3 unconfirmed parents
+		let ancestors = AncestorsSummary { count: 3, size: 300 };
+
+		assert_eq!(TransactionAncestors::new(ancestors, 3, 300).check(), Ok(()));
+		assert_eq!(TransactionAncestors::new(ancestors, 2, 300).check(), Err(TransactionError::TooManyAncestors));
+		assert_eq!(TransactionAncestors::new(ancestors, 3, 299).check(), Err(TransactionError::AncestorSizeTooLarge));
+		assert_eq!(TransactionAncestors::new(AncestorsSummary::default(), 0, 0).check(), Ok(()));
+	}
+
+	#[test]
+	fn transaction_replacement_works() {
+		let tx: IndexedTransaction = test_data::TransactionBuilder::with_output(10).into();
+		let size = tx.raw.serialized_size();
+		let conflicts = ConflictsSummary { count: 1, fee: 1000, size: size };
+
+		// no conflicts => nothing to check
+		assert_eq!(TransactionReplacement::new(&tx, ConflictsSummary::default(), 0).check(), Ok(()));
+		// higher fee && fee rate => replacement is accepted
+		assert_eq!(TransactionReplacement::new(&tx, conflicts, 1001).check(), Ok(()));
+		// equal fee => replacement is rejected
+		assert_eq!(TransactionReplacement::new(&tx, conflicts, 1000).check(), Err(TransactionError::ReplacementUnderpaid));
+		// higher fee, but lower fee rate => replacement is rejected
+		let small_conflicts = ConflictsSummary { count: 1, fee: 1000, size: size / 2 };
+		assert_eq!(TransactionReplacement::new(&tx, small_conflicts, 1500).check(), Err(TransactionError::ReplacementUnderpaid));
+	}
 }
@@ -1,36 +1,85 @@
+use std::cmp;
 use primitives::compact::Compact;
 use primitives::hash::H256;
 use primitives::bigint::U256;
-use network::ConsensusParams;
+use network::{Network, ConsensusParams};
 use storage::{BlockHeaderProvider, BlockAncestors};
 use timestamp::median_timestamp_inclusive;
+use error::Error;
 
 /// Returns true if hash is lower or equal than target represented by compact bits
+///
+/// On regtest, `bits` are always equal to network maximum (see `work_required`), so any hash
+/// under the `max_bits` target is accepted there.
 pub fn is_valid_proof_of_work_hash(bits: Compact, hash: &H256) -> bool {
-	let target = match bits.to_u256() {
-		Ok(target) => target,
-		_err => return false,
+	let target = match bits.to_u256_checked() {
+		Some(target) => target,
+		None => return false,
 	};
 
+	if !has_required_leading_zero_bytes(bits, hash) {
+		return false;
+	}
+
 	let value = U256::from(&*hash.reversed() as &[u8]);
 	value <= target
 }
 
+/// Cheap pre-check for the hash that is valid for the target represented by compact bits.
+///
+/// Target is always lower than `256^size`, where `size` is the exponent of compact bits. So
+/// every hash that has non-zero bytes above `size` is above the target. Returning true doesn't
+/// mean that hash is valid - full comparison is still required.
+fn has_required_leading_zero_bytes(bits: Compact, hash: &H256) -> bool {
+	// hash is stored in little-endian => leading bytes of the value are at the end
+	let size = cmp::min(u32::from(bits) >> 24, 32) as usize;
+	hash[size..].iter().all(|byte| *byte == 0)
+}
+
 /// Returns true if hash is lower or equal than target and target is lower or equal
 /// than current network maximum
 pub fn is_valid_proof_of_work(max_work_bits: Compact, bits: Compact, hash: &H256) -> bool {
-	let maximum = match max_work_bits.to_u256() {
-		Ok(max) => max,
-		_err => return false,
-	};
+	check_proof_of_work(max_work_bits, bits, hash).is_ok()
+}
 
-	let target = match bits.to_u256() {
-		Ok(target) => target,
-		_err => return false,
-	};
+/// Same as `is_valid_proof_of_work`, but distinguishes target that is above the network
+/// maximum (`Error::DifficultyTooLow`) from hash that is above the target (`Error::Pow`).
+pub fn check_proof_of_work(max_work_bits: Compact, bits: Compact, hash: &H256) -> Result<(), Error> {
+	let maximum = max_work_bits.to_u256_checked().ok_or(Error::Pow)?;
+
+	if bits.is_overflow() {
+		return Err(Error::DifficultyTooLow);
+	}
+
+	let target = bits.to_u256_checked().ok_or(Error::Pow)?;
+	if target > maximum {
+		return Err(Error::DifficultyTooLow);
+	}
+
+	if !has_required_leading_zero_bytes(bits, hash) {
+		return Err(Error::Pow);
+	}
 
 	let value = U256::from(&*hash.reversed() as &[u8]);
-	target <= maximum && value <= target
+	if value > target {
+		return Err(Error::Pow);
+	}
+
+	Ok(())
+}
+
+/// Returns difficulty of given bits as a multiple of the minimal difficulty (represented by `max_bits`).
+///
+/// Returns 0 if difficulty can't be represented as a finite number.
+pub fn difficulty_from_bits(bits: Compact, max_bits: Compact) -> f64 {
+	bits.to_f64_checked(max_bits).unwrap_or(0.0)
+}
+
+/// Returns work, represented by the block with given bits (i.e. `2^256 / (target + 1)`).
+///
+/// Returns 0 if bits represent invalid target.
+pub fn block_work(bits: Compact) -> U256 {
+	bits.to_work()
 }
 
 /// Returns work required for given header
@@ -42,6 +91,12 @@ pub fn work_required(parent_hash: H256, time: u32, height: u32, store: &BlockHea
 		return max_bits;
 	}
 
+	// regtest (and unittests) chains have minimal difficulty and no retargeting
+	match consensus.network {
+		Network::Regtest | Network::Unitest => return max_bits,
+		Network::Mainnet | Network::Testnet | Network::Other(_) => (),
+	}
+
 	let parent_header = store.block_header(parent_hash.clone().into()).expect("self.height != 0; qed");
 
 	// Special difficulty rule for testnet:
@@ -65,8 +120,8 @@ pub fn work_required(parent_hash: H256, time: u32, height: u32, store: &BlockHea
 	}
 
 	let bits_avg = bits_total / consensus.pow_averaging_window.into();
-	let parent_mtp = median_timestamp_inclusive(parent_hash, store);
-	let oldest_mtp = median_timestamp_inclusive(oldest_hash, store);
+	let parent_mtp = median_timestamp_inclusive(parent_hash, height - 1, store);
+	let oldest_mtp = median_timestamp_inclusive(oldest_hash, (height - 1).saturating_sub(consensus.pow_averaging_window), store);
 
 	calculate_work_required(bits_avg, parent_mtp, oldest_mtp, consensus, max_bits)
 }
@@ -111,7 +166,8 @@ mod tests {
 	use chain::{BlockHeader, IndexedBlockHeader};
 	use storage::{BlockHeaderProvider, BlockRef};
 	use timestamp::median_timestamp_inclusive;
-	use super::{work_required, calculate_work_required};
+	use super::{work_required, calculate_work_required, is_valid_proof_of_work_hash, difficulty_from_bits,
+		has_required_leading_zero_bytes, block_work};
 
 	#[derive(Default)]
 	pub struct MemoryBlockHeaderProvider {
@@ -172,6 +228,17 @@ mod tests {
 		let expected = h1.block_header.bits;
 		let actual = work_required(genesis.hash(), h1.block_header.time, 1, &header_provider, &consensus);
 		assert_eq!(expected, actual);
+
+		// assert block#2 work
+		header_provider.insert(h1.block_header.clone());
+		let h2 = test_data::block_h2();
+		let expected = h2.block_header.bits;
+		let actual = work_required(h1.hash(), h2.block_header.time, 2, &header_provider, &consensus);
+		assert_eq!(expected, actual);
+
+		// until averaging window is filled, mainnet blocks have minimal difficulty
+		let max_bits: Compact = Network::Mainnet.max_bits().into();
+		assert_eq!(actual, max_bits);
 	}
 
 	// original test link:
@@ -215,8 +282,8 @@ mod tests {
 		// Result should be the same as if last difficulty was used
 		let bits_avg: U256 = header_provider.by_height[last_block as usize].bits.into();
 		let expected = calculate_work_required(bits_avg,
-			median_timestamp_inclusive(header_provider.by_height[last_block as usize].hash(), &header_provider),
-			median_timestamp_inclusive(header_provider.by_height[first_block as usize].hash(), &header_provider),
+			median_timestamp_inclusive(header_provider.by_height[last_block as usize].hash(), last_block, &header_provider),
+			median_timestamp_inclusive(header_provider.by_height[first_block as usize].hash(), first_block, &header_provider),
 			&consensus, max_bits.into());
 		let actual = work_required(header_provider.last().hash(), header_provider.next_time(), header_provider.next_height(),
 			&header_provider, &consensus);
@@ -239,8 +306,8 @@ mod tests {
 		// Result should be the same as if last difficulty was used
 		let bits_avg: U256 = header_provider.by_height[last_block as usize].bits.into();
 		let expected = calculate_work_required(bits_avg,
-			median_timestamp_inclusive(header_provider.by_height[last_block as usize].hash(), &header_provider),
-			median_timestamp_inclusive(header_provider.by_height[first_block as usize].hash(), &header_provider),
+			median_timestamp_inclusive(header_provider.by_height[last_block as usize].hash(), last_block, &header_provider),
+			median_timestamp_inclusive(header_provider.by_height[first_block as usize].hash(), first_block, &header_provider),
 			&consensus, max_bits.into());
 		let actual = work_required(header_provider.last().hash(), header_provider.next_time(), header_provider.next_height(),
 			&header_provider, &consensus);
@@ -259,8 +326,8 @@ mod tests {
 		// Result should not be the same as if last difficulty was used
 		let bits_avg = header_provider.by_height[last_block as usize].bits;
 		let expected = calculate_work_required(bits_avg.into(),
-			median_timestamp_inclusive(header_provider.by_height[last_block as usize].hash(), &header_provider),
-			median_timestamp_inclusive(header_provider.by_height[first_block as usize].hash(), &header_provider),
+			median_timestamp_inclusive(header_provider.by_height[last_block as usize].hash(), last_block, &header_provider),
+			median_timestamp_inclusive(header_provider.by_height[first_block as usize].hash(), first_block, &header_provider),
 			&consensus, max_bits.into());
 		let actual = work_required(header_provider.last().hash(), header_provider.next_time(), header_provider.next_height(),
 			&header_provider, &consensus);
@@ -269,11 +336,118 @@ mod tests {
 		// Result should be the same as if the average difficulty was used
 		let bits_avg = "0000796968696969696969696969696969696969696969696969696969696969".parse().unwrap();
 		let expected = calculate_work_required(bits_avg,
-			median_timestamp_inclusive(header_provider.by_height[last_block as usize].hash(), &header_provider),
-			median_timestamp_inclusive(header_provider.by_height[first_block as usize].hash(), &header_provider),
+			median_timestamp_inclusive(header_provider.by_height[last_block as usize].hash(), last_block, &header_provider),
+			median_timestamp_inclusive(header_provider.by_height[first_block as usize].hash(), first_block, &header_provider),
 			&consensus, max_bits.into());
 		let actual = work_required(header_provider.last().hash(), header_provider.next_time(), header_provider.next_height(),
 			&header_provider, &consensus);
 		assert_eq!(actual, expected);
 	}
+
+	#[test]
+	fn regtest_work_required_is_max_bits() {
+		let consensus = ConsensusParams::new(Network::Regtest);
+		let max_bits: Compact = Network::Regtest.max_bits().into();
+
+		// insert genesis block
+		let mut header_provider = MemoryBlockHeaderProvider::default();
+		let genesis = BlockHeader {
+			time: 1269211443,
+			bits: max_bits,
+			version: 0,
+			previous_header_hash: 0.into(),
+			merkle_root_hash: 0.into(),
+			nonce: 0.into(),
+			final_sapling_root: Default::default(),
+			solution: Default::default(),
+		};
+		header_provider.insert(genesis.clone());
+
+		// insert enough blocks to fill the averaging window, mined much faster than target spacing
+		for i in 1..2 * consensus.pow_averaging_window + 1 {
+			let header = BlockHeader {
+				time: header_provider.last().time + 1,
+				previous_header_hash: header_provider.by_height[i as usize - 1].hash(),
+				..genesis.clone()
+			};
+			header_provider.insert(header);
+		}
+
+		// next block is mined right after the previous one
+		let time = header_provider.last().time + 1;
+		assert_eq!(work_required(header_provider.last().hash(), time, header_provider.next_height(),
+			&header_provider, &consensus), max_bits);
+
+		// next block is mined a year after the previous one
+		let time = header_provider.last().time + 365 * 24 * 60 * 60;
+		assert_eq!(work_required(header_provider.last().hash(), time, header_provider.next_height(),
+			&header_provider, &consensus), max_bits);
+
+		// any hash under max_bits target is valid
+		let to_hash = |value: U256| {
+			let mut bytes = [0u8; 32];
+			value.to_big_endian(&mut bytes);
+			H256::from(bytes).reversed()
+		};
+		let max_target: U256 = max_bits.into();
+		assert!(is_valid_proof_of_work_hash(max_bits, &to_hash(max_target)));
+		assert!(!is_valid_proof_of_work_hash(max_bits, &to_hash(max_target + U256::from(1))));
+	}
+
+	#[test]
+	fn difficulty_from_bits_works() {
+		let max_bits: Compact = Network::Mainnet.max_bits().into();
+
+		// genesis block has minimal difficulty
+		assert_eq!(difficulty_from_bits(Compact::new(0x1f07ffff), max_bits), 1.0);
+		// target is 2^16 times lower than the maximal target
+		assert_eq!(difficulty_from_bits(Compact::new(0x1d07ffff), max_bits), 65536.0);
+		// target is 2^8 times lower than the maximal target, with the different mantissa
+		assert_eq!(difficulty_from_bits(Compact::new(0x1e03ffff), max_bits), 256.0 * 0x07ffff as f64 / 0x03ffff as f64);
+	}
+
+	#[test]
+	fn proof_of_work_fast_path_agrees_with_full_comparison() {
+		use rand::{thread_rng, Rng};
+
+		let mut rng = thread_rng();
+		for &bits in &[0x1f07ffff, 0x1d07ffff, 0x1c01ffff, 0x2007ffff, 0x03123456, 0x01003456] {
+			let bits = Compact::new(bits);
+			let target: U256 = bits.into();
+			let mut target_bytes = [0u8; 32];
+			target.to_big_endian(&mut target_bytes);
+
+			for _ in 0..1000 {
+				// random hash that shares random number of leading bytes with the target
+				let mut bytes = target_bytes;
+				let shared = rng.gen_range(0, 33);
+				for byte in bytes[shared..].iter_mut() {
+					*byte = rng.gen();
+				}
+				if shared < 32 && rng.gen() {
+					bytes[shared] = target_bytes[shared].wrapping_add(rng.gen_range(0, 3)).wrapping_sub(1);
+				}
+
+				let hash = H256::from(bytes).reversed();
+				let value = U256::from(&bytes as &[u8]);
+				let expected = value <= target;
+				assert_eq!(is_valid_proof_of_work_hash(bits, &hash), expected);
+				// fast path never rejects valid hash
+				if expected {
+					assert!(has_required_leading_zero_bytes(bits, &hash));
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn block_work_works() {
+		// zcash genesis block bits
+		let genesis_bits = Compact::new(0x1f07ffff);
+		assert_eq!(block_work(genesis_bits), U256::from(0x2000));
+		// cumulative work of two blocks
+		assert_eq!(block_work(genesis_bits) + block_work(genesis_bits), U256::from(0x4000));
+		assert_eq!(block_work(Compact::new(0x1d00ffff)), U256::from(0x100010001u64));
+		assert_eq!(block_work(Compact::new(0)), U256::zero());
+	}
 }